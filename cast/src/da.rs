@@ -0,0 +1,181 @@
+//! Estimates of the L1 data availability cost of calldata posted by rollups
+use ethers_core::{types::U256, utils::format_ether};
+
+/// The gas charged for a zero byte of calldata, see [EIP-2028](https://eips.ethereum.org/EIPS/eip-2028)
+pub const ZERO_BYTE_GAS: u64 = 4;
+/// The gas charged for a non-zero byte of calldata
+pub const NON_ZERO_BYTE_GAS: u64 = 16;
+
+// The linear regression over the FastLZ compressed size used by the Optimism Fjord upgrade to
+// estimate the size of a transaction in a compressed batch, scaled by 1e6
+const FJORD_MIN_SIZE: i64 = 100_000_000;
+const FJORD_INTERCEPT: i64 = -42_585_600;
+const FJORD_FASTLZ_COEF: i64 = 836_500;
+
+/// The data availability footprint of a payload under different pricing schemes
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct DaEstimate {
+    /// The length of the payload in bytes
+    pub size: u64,
+    pub zero_bytes: u64,
+    pub non_zero_bytes: u64,
+    /// The length of the payload compressed with FastLZ
+    pub fastlz_size: u64,
+}
+
+impl DaEstimate {
+    pub fn new(data: &[u8]) -> Self {
+        let zero_bytes = data.iter().filter(|b| **b == 0).count() as u64;
+        Self {
+            size: data.len() as u64,
+            zero_bytes,
+            non_zero_bytes: data.len() as u64 - zero_bytes,
+            fastlz_size: flz_compress_len(data),
+        }
+    }
+
+    /// The L1 gas of the uncompressed payload, priced per zero and non-zero byte
+    pub fn calldata_gas(&self) -> u64 {
+        self.zero_bytes * ZERO_BYTE_GAS + self.non_zero_bytes * NON_ZERO_BYTE_GAS
+    }
+
+    /// The estimated size of the payload in a compressed batch, as used by Optimism since Fjord
+    pub fn fjord_size(&self) -> u64 {
+        let scaled = FJORD_INTERCEPT + FJORD_FASTLZ_COEF * self.fastlz_size as i64;
+        (scaled.max(FJORD_MIN_SIZE) / 1_000_000) as u64
+    }
+
+    /// The L1 gas of the compressed payload, every estimated byte is priced as a non-zero byte
+    pub fn fjord_gas(&self) -> u64 {
+        self.fjord_size() * NON_ZERO_BYTE_GAS
+    }
+
+    /// Formats the estimate, including the cost in ether if the L1 gas price is known
+    pub fn pretty(&self, gas_price: Option<U256>) -> String {
+        let gas = |gas: u64| match gas_price {
+            Some(price) => format!("{gas} ({} ether)", format_ether(price * gas)),
+            None => gas.to_string(),
+        };
+        format!(
+            "size                  {}
+zero bytes            {}
+non-zero bytes        {}
+calldata gas          {}
+fastlz size           {}
+fjord estimated size  {}
+fjord gas             {}",
+            self.size,
+            self.zero_bytes,
+            self.non_zero_bytes,
+            gas(self.calldata_gas()),
+            self.fastlz_size,
+            self.fjord_size(),
+            gas(self.fjord_gas()),
+        )
+    }
+}
+
+/// Returns the length of the data compressed with FastLZ (level 1), without compressing it.
+///
+/// This is a port of `FlzCompressLen` of op-geth, which is used to price the L1 data of
+/// transactions on Optimism.
+pub fn flz_compress_len(data: &[u8]) -> u64 {
+    let len = data.len() as u32;
+    let mut size = 0u32;
+    let mut table = vec![0u32; 8192];
+
+    let u24 = |i: u32| {
+        let i = i as usize;
+        data[i] as u32 | (data[i + 1] as u32) << 8 | (data[i + 2] as u32) << 16
+    };
+    let hash = |v: u32| (2654435769u32.wrapping_mul(v) >> 19) & 0x1fff;
+    // the length of the match of `p` and `q`, plus one, up to `end`
+    let cmp = |p: u32, q: u32, end: u32| {
+        let end = end - q;
+        let mut l = 0;
+        while l < end {
+            if data[(p + l) as usize] != data[(q + l) as usize] {
+                return l + 1
+            }
+            l += 1;
+        }
+        l
+    };
+    let literals = |size: &mut u32, r: u32| {
+        *size += 0x21 * (r / 0x20);
+        if r % 0x20 != 0 {
+            *size += r % 0x20 + 1;
+        }
+    };
+    let matched = |size: &mut u32, l: u32| {
+        let l = l - 1;
+        *size += 3 * (l / 262);
+        *size += if l % 262 >= 6 { 3 } else { 2 };
+    };
+
+    let mut anchor = 0u32;
+    let ip_limit = len.saturating_sub(13);
+    let mut ip = anchor + 2;
+    while ip < ip_limit {
+        let mut reference;
+        loop {
+            let seq = u24(ip);
+            let h = hash(seq) as usize;
+            reference = table[h];
+            table[h] = ip;
+            let distance = ip - reference;
+            if ip >= ip_limit {
+                break
+            }
+            ip += 1;
+            if distance <= 0x1fff && seq == u24(reference) {
+                break
+            }
+        }
+        if ip >= ip_limit {
+            break
+        }
+        ip -= 1;
+        if ip > anchor {
+            literals(&mut size, ip - anchor);
+        }
+        let l = cmp(reference + 3, ip + 3, ip_limit + 9);
+        matched(&mut size, l);
+        ip += l;
+        table[hash(u24(ip)) as usize] = ip;
+        ip += 1;
+        table[hash(u24(ip)) as usize] = ip;
+        ip += 1;
+        anchor = ip;
+    }
+    literals(&mut size, len - anchor);
+    size as u64
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn can_estimate_compressed_len() {
+        assert_eq!(flz_compress_len(&[]), 0);
+        // short inputs are stored as a single literal run
+        assert_eq!(flz_compress_len(&[1u8; 10]), 11);
+        assert_eq!(flz_compress_len(&(0u8..40).collect::<Vec<_>>()), 42);
+        // repeated bytes compress to back references
+        assert!(flz_compress_len(&[0u8; 1000]) < 50);
+    }
+
+    #[test]
+    fn can_estimate_da() {
+        let mut data = vec![0u8; 100];
+        data[..4].copy_from_slice(&[0xa9, 0x05, 0x9c, 0xbb]);
+        let estimate = DaEstimate::new(&data);
+        assert_eq!(estimate.size, 100);
+        assert_eq!(estimate.non_zero_bytes, 4);
+        assert_eq!(estimate.calldata_gas(), 96 * 4 + 4 * 16);
+        // small payloads are charged the minimum size
+        assert_eq!(estimate.fjord_size(), 100);
+        assert_eq!(estimate.fjord_gas(), 1600);
+    }
+}
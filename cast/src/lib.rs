@@ -4,8 +4,9 @@
 use chrono::NaiveDateTime;
 use ethers_core::{
     abi::{
+        self,
         token::{LenientTokenizer, Tokenizer},
-        Abi, AbiParser, Token,
+        Abi, AbiParser, RawLog, Token,
     },
     types::{Chain, *},
     utils::{self, get_contract_address, keccak256, parse_units},
@@ -14,13 +15,15 @@ use ethers_etherscan::Client;
 use ethers_providers::{Middleware, PendingTransaction};
 use eyre::{Context, Result};
 pub use foundry_evm::*;
-use foundry_utils::{encode_args, to_table};
+use foundry_utils::{encode_args, format_token, to_table};
 use print_utils::{get_pretty_block_attr, get_pretty_tx_attr, UIfmt};
 use rustc_hex::{FromHexIter, ToHex};
+use serde_json::json;
 use std::{path::PathBuf, str::FromStr};
 pub use tx::TxBuilder;
 use tx::{TxBuilderOutput, TxBuilderPeekOutput};
 
+pub mod da;
 mod print_utils;
 mod tx;
 
@@ -81,7 +84,17 @@ where
         block: Option<BlockId>,
     ) -> Result<String> {
         let (tx, func) = builder_output;
-        let res = self.provider.call(&tx, block).await?;
+        let res = match self.provider.call(&tx, block).await {
+            Ok(res) => res,
+            Err(err) => match block {
+                Some(block) if is_missing_state_error(&err.to_string()) => eyre::bail!(
+                    "The endpoint does not have the state of block {}, calls at historical blocks require an archive node: {}",
+                    fmt_block_id(&block),
+                    err
+                ),
+                _ => return Err(err.into()),
+            },
+        };
 
         // decode args into tokens
         let func = func.expect("no valid function signature was provided.");
@@ -172,6 +185,105 @@ where
         Ok(self.provider.get_balance(who, block).await?)
     }
 
+    /// Returns the `decimals()` and `symbol()` of an ERC-20 token
+    ///
+    /// Symbols returned as `bytes32`, like the one of MKR, are supported. The symbol is empty if
+    /// the token does not implement `symbol()`.
+    ///
+    /// ```no_run
+    /// use cast::Cast;
+    /// use ethers_providers::{Provider, Http};
+    /// use std::convert::TryFrom;
+    ///
+    /// # async fn foo() -> eyre::Result<()> {
+    /// let provider = Provider::<Http>::try_from("http://localhost:8545")?;
+    /// let cast = Cast::new(provider);
+    /// let (decimals, symbol) = cast.erc20_metadata("usdc.tkn.eth", None).await?;
+    /// println!("{} has {} decimals", symbol, decimals);
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn erc20_metadata<T: Into<NameOrAddress> + Send + Sync>(
+        &self,
+        token: T,
+        block: Option<BlockId>,
+    ) -> Result<(u8, String)> {
+        let token = token.into();
+        let decimals = self
+            .erc20_call(&token, "decimals()", &[], block)
+            .await
+            .and_then(|output| decode_uint(&output))
+            .wrap_err("The token does not implement decimals()")?;
+        if decimals > U256::from(u8::MAX) {
+            eyre::bail!("The token has an invalid number of decimals: {decimals}")
+        }
+
+        let symbol = match self.erc20_call(&token, "symbol()", &[], block).await {
+            Ok(output) => match abi::decode(&[abi::ParamType::String], &output) {
+                Ok(mut tokens) => tokens.remove(0).into_string().unwrap_or_default(),
+                Err(_) if output.len() == 32 => {
+                    String::from_utf8_lossy(&output).trim_end_matches('\0').to_string()
+                }
+                Err(_) => String::new(),
+            },
+            Err(_) => String::new(),
+        };
+
+        Ok((decimals.as_u32() as u8, symbol))
+    }
+
+    /// Returns the ERC-20 token balance of `owner`, in the smallest unit of the token
+    pub async fn erc20_balance<T: Into<NameOrAddress> + Send + Sync>(
+        &self,
+        token: T,
+        owner: T,
+        block: Option<BlockId>,
+    ) -> Result<U256> {
+        let owner = self.resolve(owner.into()).await?;
+        let output =
+            self.erc20_call(&token.into(), "balanceOf(address)", &[Token::Address(owner)], block);
+        decode_uint(&output.await?)
+    }
+
+    /// Returns the amount of an ERC-20 token `spender` may transfer from `owner`, in the smallest
+    /// unit of the token
+    pub async fn erc20_allowance<T: Into<NameOrAddress> + Send + Sync>(
+        &self,
+        token: T,
+        owner: T,
+        spender: T,
+        block: Option<BlockId>,
+    ) -> Result<U256> {
+        let owner = self.resolve(owner.into()).await?;
+        let spender = self.resolve(spender.into()).await?;
+        let args = [Token::Address(owner), Token::Address(spender)];
+        let output = self.erc20_call(&token.into(), "allowance(address,address)", &args, block);
+        decode_uint(&output.await?)
+    }
+
+    /// Calls the function with the given signature of an ERC-20 token and returns the raw output
+    async fn erc20_call(
+        &self,
+        token: &NameOrAddress,
+        sig: &str,
+        args: &[Token],
+        block: Option<BlockId>,
+    ) -> Result<Bytes> {
+        let token = self.resolve(token.clone()).await?;
+        let mut data = keccak256(sig)[..4].to_vec();
+        data.extend(abi::encode(args));
+        let tx = TransactionRequest::new().to(token).data(data);
+        Ok(self.provider.call(&tx.into(), block).await?)
+    }
+
+    /// Resolves an ENS name to an address
+    async fn resolve(&self, who: NameOrAddress) -> Result<Address> {
+        Ok(match who {
+            NameOrAddress::Name(name) => self.provider.resolve_name(&name).await?,
+            NameOrAddress::Address(address) => address,
+        })
+    }
+
     /// Sends a transaction to the specified address
     ///
     /// ```no_run
@@ -455,6 +567,96 @@ where
         Ok(self.provider.get_transaction_count(who, block).await?)
     }
 
+    /// ```no_run
+    /// use cast::Cast;
+    /// use ethers_providers::{Provider, Http};
+    /// use ethers_core::types::{Address, H256};
+    /// use std::{str::FromStr, convert::TryFrom};
+    ///
+    /// # async fn foo() -> eyre::Result<()> {
+    /// let provider = Provider::<Http>::try_from("http://localhost:8545")?;
+    /// let cast = Cast::new(provider);
+    /// let addr = Address::from_str("0x7eD52863829AB99354F3a0503A622e82AcD5F7d3")?;
+    /// let value = cast.storage(addr, H256::zero(), None, false).await?;
+    /// println!("{}", value);
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn storage<T: Into<NameOrAddress> + Send + Sync>(
+        &self,
+        address: T,
+        slot: H256,
+        block: Option<BlockId>,
+        to_json: bool,
+    ) -> Result<String> {
+        let value = self.provider.get_storage_at(address, slot, block).await?;
+        Ok(if to_json { serde_json::to_string(&value)? } else { value.pretty() })
+    }
+
+    /// Returns the [EIP-1186](https://eips.ethereum.org/EIPS/eip-1186) proof of the account and
+    /// the given storage slots
+    ///
+    /// ```no_run
+    /// use cast::Cast;
+    /// use ethers_providers::{Provider, Http};
+    /// use ethers_core::types::{Address, H256};
+    /// use std::{str::FromStr, convert::TryFrom};
+    ///
+    /// # async fn foo() -> eyre::Result<()> {
+    /// let provider = Provider::<Http>::try_from("http://localhost:8545")?;
+    /// let cast = Cast::new(provider);
+    /// let addr = Address::from_str("0x7eD52863829AB99354F3a0503A622e82AcD5F7d3")?;
+    /// let proof = cast.proof(addr, vec![H256::zero()], None, false).await?;
+    /// println!("{}", proof);
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn proof<T: Into<NameOrAddress> + Send + Sync>(
+        &self,
+        address: T,
+        slots: Vec<H256>,
+        block: Option<BlockId>,
+        to_json: bool,
+    ) -> Result<String> {
+        let proof = self.provider.get_proof(address, slots, block).await?;
+        Ok(if to_json { serde_json::to_string(&proof)? } else { proof.pretty() })
+    }
+
+    /// Returns the number of pending and queued transactions in the node's mempool, using the
+    /// `txpool_status` method
+    ///
+    /// ```no_run
+    /// use cast::Cast;
+    /// use ethers_providers::{Provider, Http};
+    /// use std::convert::TryFrom;
+    ///
+    /// # async fn foo() -> eyre::Result<()> {
+    /// let provider = Provider::<Http>::try_from("http://localhost:8545")?;
+    /// let cast = Cast::new(provider);
+    /// let status = cast.txpool_status(false).await?;
+    /// println!("{}", status);
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn txpool_status(&self, to_json: bool) -> Result<String> {
+        let status = self.provider.txpool_status().await?;
+        Ok(if to_json { serde_json::to_string(&status)? } else { status.pretty() })
+    }
+
+    /// Returns the pending and queued transactions in the node's mempool, grouped by sender and
+    /// nonce, using the `txpool_content` method
+    pub async fn txpool_content(&self, to_json: bool) -> Result<String> {
+        let content = self.provider.txpool_content().await?;
+        Ok(if to_json { serde_json::to_string(&content)? } else { content.pretty() })
+    }
+
+    /// Returns a summary of the pending and queued transactions in the node's mempool, using the
+    /// `txpool_inspect` method
+    pub async fn txpool_inspect(&self, to_json: bool) -> Result<String> {
+        let inspect = self.provider.txpool_inspect().await?;
+        Ok(if to_json { serde_json::to_string(&inspect)? } else { inspect.pretty() })
+    }
+
     /// ```no_run
     /// use cast::Cast;
     /// use ethers_providers::{Provider, Http};
@@ -473,20 +675,90 @@ where
     /// # Ok(())
     /// # }
     /// ```
-    pub async fn compute_address<T: Into<Address> + Copy + Send + Sync>(
+    pub async fn compute_address<T: Into<NameOrAddress> + Send + Sync>(
         &self,
         address: T,
         nonce: Option<U256>,
     ) -> Result<Address> {
+        let address = match address.into() {
+            NameOrAddress::Name(name) => self.provider.resolve_name(&name).await?,
+            NameOrAddress::Address(address) => address,
+        };
         let unpacked = if let Some(n) = nonce {
             n
         } else {
-            self.provider.get_transaction_count(address.into(), None).await?
+            self.provider.get_transaction_count(address, None).await?
         };
 
         Ok(get_contract_address(address, unpacked))
     }
 
+    /// Resolves an ENS name to an address.
+    ///
+    /// If `verify` is true, the reverse record of the address must point back to the name.
+    ///
+    /// ```no_run
+    /// use cast::Cast;
+    /// use ethers_providers::{Provider, Http};
+    /// use std::convert::TryFrom;
+    ///
+    /// # async fn foo() -> eyre::Result<()> {
+    /// let provider = Provider::<Http>::try_from("http://localhost:8545")?;
+    /// let cast = Cast::new(provider);
+    /// let address = cast.resolve_name("vitalik.eth", true).await?;
+    /// println!("{:?}", address);
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn resolve_name(&self, name: &str, verify: bool) -> Result<Address> {
+        let address = self.provider.resolve_name(name).await?;
+        if verify {
+            let reverse = self.provider.lookup_address(address).await?;
+            eyre::ensure!(
+                reverse == name,
+                "Reverse lookup verification failed: {} resolves to {:?}, which resolves back to {}",
+                name,
+                address,
+                reverse
+            );
+        }
+        Ok(address)
+    }
+
+    /// Looks up the ENS name of an address.
+    ///
+    /// If `verify` is true, the name must resolve back to the address.
+    ///
+    /// ```no_run
+    /// use cast::Cast;
+    /// use ethers_providers::{Provider, Http};
+    /// use ethers_core::types::Address;
+    /// use std::{str::FromStr, convert::TryFrom};
+    ///
+    /// # async fn foo() -> eyre::Result<()> {
+    /// let provider = Provider::<Http>::try_from("http://localhost:8545")?;
+    /// let cast = Cast::new(provider);
+    /// let address = Address::from_str("0xd8dA6BF26964aF9D7eEd9e03E53415D37aA96045")?;
+    /// let name = cast.lookup_address(address, true).await?;
+    /// println!("{}", name);
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn lookup_address(&self, address: Address, verify: bool) -> Result<String> {
+        let name = self.provider.lookup_address(address).await?;
+        if verify {
+            let forward = self.provider.resolve_name(&name).await?;
+            eyre::ensure!(
+                forward == address,
+                "Forward lookup verification failed: {:?} resolves to {}, which resolves back to {:?}",
+                address,
+                name,
+                forward
+            );
+        }
+        Ok(name)
+    }
+
     /// ```no_run
     /// use cast::Cast;
     /// use ethers_providers::{Provider, Http};
@@ -556,6 +828,8 @@ where
         Ok(transaction)
     }
 
+    /// Returns the RLP encoding of the signed transaction, i.e. the payload a rollup posts to L1
+    ///
     /// ```no_run
     /// use cast::Cast;
     /// use ethers_providers::{Provider, Http};
@@ -565,11 +839,38 @@ where
     /// let provider = Provider::<Http>::try_from("http://localhost:8545")?;
     /// let cast = Cast::new(provider);
     /// let tx_hash = "0xf8d1713ea15a81482958fb7ddf884baee8d3bcc478c5f2f604e008dc788ee4fc";
-    /// let receipt = cast.receipt(tx_hash.to_string(), None, 1, false, false).await?;
+    /// let raw_tx = cast.raw_transaction(tx_hash.to_string()).await?;
+    /// println!("{}", raw_tx);
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn raw_transaction(&self, tx_hash: String) -> Result<Bytes> {
+        let tx = self
+            .provider
+            .get_transaction(H256::from_str(&tx_hash)?)
+            .await?
+            .ok_or_else(|| eyre::eyre!("transaction {:?} not found", tx_hash))?;
+        Ok(tx.rlp())
+    }
+
+    /// ```no_run
+    /// use cast::Cast;
+    /// use ethers_providers::{Provider, Http};
+    /// use std::convert::TryFrom;
+    ///
+    /// # async fn foo() -> eyre::Result<()> {
+    /// let provider = Provider::<Http>::try_from("http://localhost:8545")?;
+    /// let cast = Cast::new(provider);
+    /// let tx_hash = "0xf8d1713ea15a81482958fb7ddf884baee8d3bcc478c5f2f604e008dc788ee4fc";
+    /// let receipt = cast.receipt(tx_hash.to_string(), None, 1, false, false, None).await?;
     /// println!("{}", receipt);
     /// # Ok(())
     /// # }
     /// ```
+    ///
+    /// If an ABI is given, the logs that match one of its events are decoded and added to the
+    /// receipt as `decodedLogs`.
+    #[allow(clippy::too_many_arguments)]
     pub async fn receipt(
         &self,
         tx_hash: String,
@@ -577,6 +878,7 @@ where
         confs: usize,
         cast_async: bool,
         to_json: bool,
+        events: Option<&Abi>,
     ) -> Result<String> {
         let tx_hash = H256::from_str(&tx_hash)?;
 
@@ -591,9 +893,17 @@ where
                 None => return Ok("receipt not found".to_string()),
             }
         } else {
+            // an already mined transaction still has to wait for the requested confirmations
+            let confirmed = match &receipt {
+                Some(inner) if confs > 1 => {
+                    confirmations(inner, self.provider.get_block_number().await?) >= confs
+                }
+                Some(_) => true,
+                None => false,
+            };
             match receipt {
-                Some(inner) => inner,
-                None => {
+                Some(inner) if confirmed => inner,
+                _ => {
                     let tx = PendingTransaction::new(tx_hash, self.provider.provider());
                     match tx.confirmations(confs).await? {
                         Some(inner) => inner,
@@ -603,13 +913,15 @@ where
             }
         };
 
+        let mut value = serde_json::to_value(&receipt)?;
+        if let Some(abi) = events {
+            value["decodedLogs"] = decode_logs(&receipt.logs, abi).into();
+        }
+
         let receipt = if let Some(ref field) = field {
-            serde_json::to_value(&receipt)?
-                .get(field)
-                .cloned()
-                .ok_or_else(|| eyre::eyre!("field {field} not found"))?
+            value.get(field).cloned().ok_or_else(|| eyre::eyre!("field {field} not found"))?
         } else {
-            serde_json::to_value(&receipt)?
+            value
         };
 
         let receipt = if to_json { serde_json::to_string(&receipt)? } else { to_table(receipt) };
@@ -617,6 +929,44 @@ where
     }
 }
 
+/// Returns the number of confirmations of a mined transaction at the `latest` block, the block
+/// the transaction was included in counts as the first confirmation
+fn confirmations(receipt: &TransactionReceipt, latest: U64) -> usize {
+    match receipt.block_number {
+        Some(block) if block <= latest => (latest - block).as_usize() + 1,
+        _ => 0,
+    }
+}
+
+/// Decodes the logs that match an event of the ABI into JSON objects with the address of the
+/// emitter, the signature of the event and its parameters. Logs without a matching event are
+/// skipped.
+fn decode_logs(logs: &[Log], abi: &Abi) -> Vec<serde_json::Value> {
+    logs.iter()
+        .filter_map(|log| {
+            let topic = log.topics.first()?;
+            abi.events().filter(|event| !event.anonymous && event.signature() == *topic).find_map(
+                |event| {
+                    let raw = RawLog { topics: log.topics.clone(), data: log.data.to_vec() };
+                    let decoded = event.parse_log(raw).ok()?;
+                    let types =
+                        event.inputs.iter().map(|input| input.kind.to_string()).collect::<Vec<_>>();
+                    let params = decoded
+                        .params
+                        .iter()
+                        .map(|param| json!({ "name": param.name, "value": format_token(&param.value) }))
+                        .collect::<Vec<_>>();
+                    Some(json!({
+                        "address": log.address,
+                        "event": format!("{}({})", event.name, types.join(",")),
+                        "params": params,
+                    }))
+                },
+            )
+        })
+        .collect()
+}
+
 pub struct InterfaceSource {
     pub name: String,
     pub source: String,
@@ -837,6 +1187,74 @@ impl SimpleCast {
             Ok(value)
         }
     }
+    /// Parses an amount of a token with the given decimals into its smallest unit
+    ///
+    /// The amount may be followed by the symbol of the token, which is compared case-insensitively.
+    ///
+    /// ```
+    /// use cast::SimpleCast as Cast;
+    ///
+    /// fn main() -> eyre::Result<()> {
+    ///     assert_eq!(Cast::parse_token_amount("1.5", 6, "USDC")?, 1_500_000u64.into());
+    ///     assert_eq!(Cast::parse_token_amount("1.5 usdc", 6, "USDC")?, 1_500_000u64.into());
+    ///     assert_eq!(Cast::parse_token_amount("2", 0, "")?, 2u64.into());
+    ///     assert!(Cast::parse_token_amount("1.5 DAI", 6, "USDC").is_err());
+    ///     assert!(Cast::parse_token_amount("0.0000001", 6, "USDC").is_err());
+    ///
+    ///     Ok(())
+    /// }
+    /// ```
+    pub fn parse_token_amount(amount: &str, decimals: u8, symbol: &str) -> Result<U256> {
+        let mut parts = amount.split_whitespace();
+        let value = parts.next().ok_or_else(|| eyre::eyre!("The amount is empty"))?;
+        match (parts.next(), parts.next()) {
+            (None, _) => {}
+            (Some(unit), None) if unit.eq_ignore_ascii_case(symbol) => {}
+            (Some(unit), None) => {
+                eyre::bail!("The amount is in {unit}, but the symbol of the token is {symbol}")
+            }
+            _ => eyre::bail!("Invalid amount `{amount}`, expected e.g. `1.5` or `1.5 {symbol}`"),
+        }
+
+        let (integer, fraction) = value.split_once('.').unwrap_or((value, ""));
+        if fraction.len() > decimals as usize {
+            eyre::bail!("The token has only {decimals} decimals, `{value}` has more")
+        }
+        if integer.is_empty() && fraction.is_empty() ||
+            !integer.chars().chain(fraction.chars()).all(|c| c.is_ascii_digit())
+        {
+            eyre::bail!("Invalid amount `{value}`")
+        }
+        let digits = format!("{integer}{fraction:0<width$}", width = decimals as usize);
+        U256::from_dec_str(&digits).wrap_err_with(|| format!("The amount `{value}` is too large"))
+    }
+
+    /// Formats an amount in the smallest unit of a token with the given decimals, followed by the
+    /// symbol of the token
+    ///
+    /// ```
+    /// use cast::SimpleCast as Cast;
+    ///
+    /// fn main() -> eyre::Result<()> {
+    ///     assert_eq!(Cast::format_token_amount(1_500_000u64.into(), 6, "USDC"), "1.5 USDC");
+    ///     assert_eq!(Cast::format_token_amount(1_000_000u64.into(), 6, "USDC"), "1 USDC");
+    ///     assert_eq!(Cast::format_token_amount(1u64.into(), 6, "USDC"), "0.000001 USDC");
+    ///     assert_eq!(Cast::format_token_amount(10u64.into(), 0, ""), "10");
+    ///
+    ///     Ok(())
+    /// }
+    /// ```
+    pub fn format_token_amount(amount: U256, decimals: u8, symbol: &str) -> String {
+        let amount = Self::to_fix(decimals as u128, amount).expect("formatting never fails");
+        let amount = amount.trim_end_matches('0').trim_end_matches('.');
+        let amount = if amount.is_empty() { "0" } else { amount };
+        if symbol.is_empty() {
+            amount.to_string()
+        } else {
+            format!("{amount} {symbol}")
+        }
+    }
+
     /// Decodes abi-encoded hex input or output
     ///
     /// ```
@@ -1277,12 +1695,79 @@ impl SimpleCast {
         let location: String = Self::keccak(&encoded)?;
         Ok(location)
     }
+
+    /// Computes the root slot of an [ERC-7201](https://eips.ethereum.org/EIPS/eip-7201) storage
+    /// namespace: `keccak256(abi.encode(uint256(keccak256(id)) - 1)) & ~bytes32(uint256(0xff))`
+    ///
+    /// ```
+    /// # use cast::SimpleCast as Cast;
+    ///
+    /// # fn main() -> eyre::Result<()> {
+    ///    assert_eq!(Cast::index_erc7201("example.main")?, "0x183a6125c38840424c4a85fa12bab2ab606c4b6d0e7cc73c0c06ba5300eab500");
+    /// #    Ok(())
+    /// # }
+    /// ```
+    pub fn index_erc7201(id: &str) -> Result<String> {
+        let location = U256::from_big_endian(&keccak256(id)).overflowing_sub(U256::one()).0;
+        let mut encoded = [0u8; 32];
+        location.to_big_endian(&mut encoded);
+        let mut slot = keccak256(encoded);
+        slot[31] = 0;
+        Ok(format!("0x{}", slot.to_hex::<String>()))
+    }
+
+    /// Computes the slot of a diamond storage struct
+    /// ([EIP-2535](https://eips.ethereum.org/EIPS/eip-2535)), which is `keccak256(id)`
+    ///
+    /// ```
+    /// # use cast::SimpleCast as Cast;
+    ///
+    /// # fn main() -> eyre::Result<()> {
+    ///    assert_eq!(Cast::index_diamond("diamond.standard.diamond.storage")?, "0xc8fcad8db84d3cc18b4c41d551ea0ee66dd599cde068d998e57d5e09332c131c");
+    /// #    Ok(())
+    /// # }
+    /// ```
+    pub fn index_diamond(id: &str) -> Result<String> {
+        Ok(format!("0x{}", keccak256(id).to_hex::<String>()))
+    }
 }
 
 fn strip_0x(s: &str) -> &str {
     s.strip_prefix("0x").unwrap_or(s)
 }
 
+/// Decodes the `uint256` returned by a call
+fn decode_uint(output: &[u8]) -> Result<U256> {
+    match abi::decode(&[abi::ParamType::Uint(256)], output)?.pop() {
+        Some(Token::Uint(value)) => Ok(value),
+        _ => eyre::bail!("The call did not return a uint256"),
+    }
+}
+
+/// Returns whether an RPC error means that the node has pruned the state of the requested block,
+/// i.e. that it is not an archive node
+fn is_missing_state_error(err: &str) -> bool {
+    const PATTERNS: &[&str] = &[
+        "missing trie node",
+        "header not found",
+        "state not available",
+        "state is not available",
+        "historical state",
+        "old data not available",
+        "pruned",
+    ];
+    let err = err.to_lowercase();
+    PATTERNS.iter().any(|pattern| err.contains(pattern))
+}
+
+fn fmt_block_id(block: &BlockId) -> String {
+    match block {
+        BlockId::Hash(hash) => format!("{:?}", hash),
+        BlockId::Number(BlockNumber::Number(number)) => number.to_string(),
+        BlockId::Number(number) => format!("{:?}", number).to_lowercase(),
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::SimpleCast as Cast;
@@ -1303,6 +1788,59 @@ mod tests {
         );
     }
 
+    #[test]
+    fn detects_missing_state_errors() {
+        assert!(super::is_missing_state_error(
+            "(code: -32000, message: missing trie node 1c8a27ecbf1b5e7e (path ), data: None)"
+        ));
+        assert!(super::is_missing_state_error("(code: -32000, message: header not found)"));
+        assert!(!super::is_missing_state_error("(code: -32000, message: execution reverted)"));
+    }
+
+    #[test]
+    fn counts_confirmations() {
+        use ethers_core::types::{TransactionReceipt, U64};
+
+        let receipt = TransactionReceipt { block_number: Some(10u64.into()), ..Default::default() };
+        assert_eq!(super::confirmations(&receipt, U64::from(10u64)), 1);
+        assert_eq!(super::confirmations(&receipt, U64::from(12u64)), 3);
+        // the endpoint is behind the block of the receipt
+        assert_eq!(super::confirmations(&receipt, U64::from(9u64)), 0);
+        // pending transactions are not confirmed
+        assert_eq!(super::confirmations(&TransactionReceipt::default(), U64::from(10u64)), 0);
+    }
+
+    #[test]
+    fn decodes_receipt_logs() {
+        use ethers_core::{
+            abi::{parse_abi, AbiEncode},
+            types::{Address, Log, H256, U256},
+            utils::keccak256,
+        };
+
+        let abi =
+            parse_abi(&["event Transfer(address indexed from, address indexed to, uint256 value)"])
+                .unwrap();
+        let transfer = Log {
+            address: Address::from_low_u64_be(1),
+            topics: vec![
+                keccak256("Transfer(address,address,uint256)").into(),
+                H256::from(Address::from_low_u64_be(2)),
+                H256::from(Address::from_low_u64_be(3)),
+            ],
+            data: U256::from(42u64).encode().into(),
+            ..Default::default()
+        };
+        let unknown = Log { topics: vec![H256::zero()], ..Default::default() };
+
+        let decoded = super::decode_logs(&[transfer, unknown], &abi);
+        assert_eq!(decoded.len(), 1);
+        assert_eq!(decoded[0]["event"], "Transfer(address,address,uint256)");
+        assert_eq!(decoded[0]["params"][1]["name"], "to");
+        assert_eq!(decoded[0]["params"][1]["value"], "0x0000000000000000000000000000000000000003");
+        assert_eq!(decoded[0]["params"][2]["value"], "42");
+    }
+
     #[test]
     fn concat_hex() {
         assert_eq!(Cast::concat_hex(vec!["0x00".to_string(), "0x01".to_string()]), "0x0001");
@@ -1,4 +1,5 @@
 use ethers_core::types::{Block, Transaction, H256, U256, *};
+use std::collections::BTreeMap;
 
 pub fn to_bytes(uint: U256) -> Bytes {
     let mut buffer: [u8; 4 * 8] = [0; 4 * 8];
@@ -185,6 +186,96 @@ transactions:        {}",
         )
     }
 }
+impl UIfmt for EIP1186ProofResponse {
+    fn pretty(&self) -> String {
+        format!(
+            "
+address              {}
+balance              {}
+codeHash             {}
+nonce                {}
+storageHash          {}
+accountProof         {}
+storageProof         {}",
+            self.address.pretty(),
+            self.balance.pretty(),
+            self.code_hash.pretty(),
+            self.nonce.pretty(),
+            self.storage_hash.pretty(),
+            self.account_proof.pretty(),
+            self.storage_proof.pretty()
+        )
+    }
+}
+impl UIfmt for StorageProof {
+    fn pretty(&self) -> String {
+        format!(
+            "
+key                  {}
+value                {}
+proof                {}",
+            self.key.pretty(),
+            self.value.pretty(),
+            self.proof.pretty()
+        )
+    }
+}
+impl UIfmt for TxpoolStatus {
+    fn pretty(&self) -> String {
+        format!(
+            "
+pending              {}
+queued               {}",
+            self.pending.pretty(),
+            self.queued.pretty()
+        )
+    }
+}
+impl UIfmt for TxpoolContent {
+    fn pretty(&self) -> String {
+        format!(
+            "
+pending              {}
+queued               {}",
+            pretty_txpool_section(&self.pending, |tx| tab_paragraph(tx.pretty())),
+            pretty_txpool_section(&self.queued, |tx| tab_paragraph(tx.pretty()))
+        )
+    }
+}
+impl UIfmt for TxpoolInspect {
+    fn pretty(&self) -> String {
+        format!(
+            "
+pending              {}
+queued               {}",
+            pretty_txpool_section(&self.pending, |summary| format!("\t{}\n", summary.pretty())),
+            pretty_txpool_section(&self.queued, |summary| format!("\t{}\n", summary.pretty()))
+        )
+    }
+}
+impl UIfmt for TxpoolInspectSummary {
+    fn pretty(&self) -> String {
+        let to = self.to.map(|to| to.pretty()).unwrap_or_else(|| "contract creation".to_string());
+        format!("{}: {} wei + {} gas × {} wei", to, self.value, self.gas, self.gas_price)
+    }
+}
+
+/// Formats the transactions of a txpool section, grouped by sender and ordered by nonce
+fn pretty_txpool_section<T>(
+    section: &BTreeMap<Address, BTreeMap<String, T>>,
+    pretty: impl Fn(&T) -> String,
+) -> String {
+    if section.is_empty() {
+        return "{}".to_string()
+    }
+    section.iter().fold("\n".to_string(), |acc, (sender, txs)| {
+        let mut txs = txs.iter().collect::<Vec<_>>();
+        txs.sort_by_key(|(nonce, _)| nonce.parse::<u64>().unwrap_or(u64::MAX));
+        txs.into_iter().fold(acc + &format!("{}\n", sender.pretty()), |acc, (nonce, tx)| {
+            acc + &format!("  nonce {}\n", nonce) + &pretty(tx)
+        })
+    })
+}
 impl UIfmt for U64 {
     fn pretty(&self) -> String {
         self.to_string()
@@ -292,6 +383,28 @@ value                   0");
             Option::Some(H256::from_low_u64_be(100)).pretty()
         );
     }
+    #[test]
+    fn print_proof() {
+        let proof = r#"{"address":"0x7f0d15c7faae65896648c8273b6d7e43f58fa842","accountProof":["0xf8518080"],"balance":"0x0","codeHash":"0xc5d2460186f7233c927e7db2dcc703c0e500b653ca82273b7bfad8045d85a470","nonce":"0x1","storageHash":"0x56e81f171bcc55a6ff8345e692c0f86e5b48e01b996cadc001622fb5e363b421","storageProof":[{"key":"0x0000000000000000000000000000000000000000000000000000000000000000","value":"0x2a","proof":[]}]}"#;
+        let proof: EIP1186ProofResponse = serde_json::from_str(proof).unwrap();
+        let output = "
+address              0x7f0d15c7faae65896648c8273b6d7e43f58fa842
+balance              0
+codeHash             0xc5d2460186f7233c927e7db2dcc703c0e500b653ca82273b7bfad8045d85a470
+nonce                1
+storageHash          0x56e81f171bcc55a6ff8345e692c0f86e5b48e01b996cadc001622fb5e363b421
+accountProof         [
+\t0xf8518080
+]
+storageProof         [
+\t
+\tkey                  0x0000000000000000000000000000000000000000000000000000000000000000
+\tvalue                42
+\tproof                []
+]";
+        assert_eq!(proof.pretty(), output);
+    }
+
     #[test]
     fn uifmt_option_bytes() {
         let empty: Option<Bytes> = Option::None;
@@ -4,24 +4,26 @@ pub mod compile;
 mod term;
 mod utils;
 
-use cast::{Cast, SimpleCast, TxBuilder};
+use cast::{da::DaEstimate, Cast, SimpleCast, TxBuilder};
 use foundry_config::Config;
+use foundry_utils::SignaturesCache;
 mod opts;
 use cast::InterfacePath;
 use ethers::{
     contract::BaseContract,
     core::{
-        abi::parse_abi,
+        abi::{parse_abi, Abi},
         rand::thread_rng,
-        types::{BlockId, BlockNumber::Latest, H256},
+        types::{BlockId, BlockNumber, BlockNumber::Latest, H256},
     },
+    etherscan::Client,
     providers::{Middleware, Provider},
     signers::{LocalWallet, Signer},
     types::{Address, Chain, NameOrAddress, Signature, U256},
     utils::get_contract_address,
 };
 use opts::{
-    cast::{Opts, Subcommands, WalletSubcommands},
+    cast::{Erc20Subcommands, Opts, Subcommands, TxPoolSubcommands, WalletSubcommands},
     EthereumOpts, WalletType,
 };
 use rayon::prelude::*;
@@ -47,6 +49,7 @@ use eyre::WrapErr;
 #[tokio::main]
 async fn main() -> eyre::Result<()> {
     color_eyre::install()?;
+    utils::print_version_json_if_requested("cast");
 
     let opts = Opts::parse();
     match opts.sub {
@@ -166,9 +169,7 @@ async fn main() -> eyre::Result<()> {
         }
         Subcommands::AccessList { eth, address, sig, args, block, to_json } => {
             let config = Config::from(&eth);
-            let provider = Provider::try_from(
-                config.eth_rpc_url.unwrap_or_else(|| "http://localhost:8545".to_string()),
-            )?;
+            let provider = Provider::try_from(config.get_rpc_url_or_localhost_http()?)?;
             let mut builder =
                 TxBuilder::new(&provider, config.sender, address, eth.chain, false).await?;
             builder.set_args(&sig, args).await?;
@@ -177,59 +178,77 @@ async fn main() -> eyre::Result<()> {
             println!("{}", Cast::new(&provider).access_list(builder_output, block, to_json).await?);
         }
         Subcommands::Block { rpc_url, block, full, field, to_json } => {
-            let rpc_url = consume_config_rpc_url(rpc_url);
+            let rpc_url = consume_config_rpc_url(rpc_url)?;
             let provider = Provider::try_from(rpc_url)?;
             println!("{}", Cast::new(provider).block(block, full, field, to_json).await?);
         }
         Subcommands::BlockNumber { rpc_url } => {
-            let rpc_url = consume_config_rpc_url(rpc_url);
+            let rpc_url = consume_config_rpc_url(rpc_url)?;
             let provider = Provider::try_from(rpc_url)?;
             println!("{}", Cast::new(provider).block_number().await?);
         }
 
-        Subcommands::Call { address, sig, args, block, eth } => {
+        Subcommands::Call { address, sig, args, block, trace_printer, eth } => {
             let config = Config::from(&eth);
-            let provider = Provider::try_from(
-                config.eth_rpc_url.unwrap_or_else(|| "http://localhost:8545".to_string()),
-            )?;
+            let rpc_url = config.get_rpc_url_or_localhost_http()?;
+            let provider = Provider::try_from(rpc_url.as_str())?;
 
             let mut builder =
                 TxBuilder::new(&provider, config.sender, address, eth.chain, false).await?;
             builder.etherscan_api_key(eth.etherscan_api_key).set_args(&sig, args).await?;
             let builder_output = builder.build();
+
+            if trace_printer {
+                let block_number = match block {
+                    Some(BlockId::Number(BlockNumber::Number(number))) => number.as_u64(),
+                    Some(block) => provider
+                        .get_block(block)
+                        .await?
+                        .and_then(|block| block.number)
+                        .ok_or_else(|| eyre::eyre!("Block {:?} not found", block))?
+                        .as_u64(),
+                    None => provider.get_block_number().await?.as_u64(),
+                };
+                cmd::cast::call::print_opcodes(&builder_output.0, rpc_url, block_number, &config)
+                    .await?;
+            }
             println!("{}", Cast::new(provider).call(builder_output, block).await?);
         }
 
-        Subcommands::Calldata { sig, args } => {
-            println!("{}", SimpleCast::calldata(sig, &args)?);
+        Subcommands::Calldata { sig, args, pretty } => {
+            let calldata = SimpleCast::calldata(&sig, &args)?;
+            if pretty {
+                println!("{}", foundry_utils::pretty_abi_decode(&sig, &calldata, true)?);
+            } else {
+                println!("{calldata}");
+            }
         }
         Subcommands::Chain { rpc_url } => {
-            let rpc_url = consume_config_rpc_url(rpc_url);
+            let rpc_url = consume_config_rpc_url(rpc_url)?;
             let provider = Provider::try_from(rpc_url)?;
             println!("{}", Cast::new(provider).chain().await?);
         }
         Subcommands::ChainId { rpc_url } => {
-            let rpc_url = consume_config_rpc_url(rpc_url);
+            let rpc_url = consume_config_rpc_url(rpc_url)?;
 
             let provider = Provider::try_from(rpc_url)?;
             println!("{}", Cast::new(provider).chain_id().await?);
         }
         Subcommands::Client { rpc_url } => {
-            let rpc_url = consume_config_rpc_url(rpc_url);
+            let rpc_url = consume_config_rpc_url(rpc_url)?;
 
             let provider = Provider::try_from(rpc_url)?;
             println!("{}", provider.client_version().await?);
         }
         Subcommands::ComputeAddress { rpc_url, address, nonce } => {
-            let rpc_url = consume_config_rpc_url(rpc_url);
+            let rpc_url = consume_config_rpc_url(rpc_url)?;
 
-            let pubkey = Address::from_str(&address).expect("invalid pubkey provided");
             let provider = Provider::try_from(rpc_url)?;
-            let addr = Cast::new(&provider).compute_address(pubkey, nonce).await?;
+            let addr = Cast::new(&provider).compute_address(address, nonce).await?;
             println!("Computed Address: {:?}", addr);
         }
         Subcommands::Code { block, who, rpc_url } => {
-            let rpc_url = consume_config_rpc_url(rpc_url);
+            let rpc_url = consume_config_rpc_url(rpc_url)?;
             let provider = Provider::try_from(rpc_url)?;
             println!("{}", Cast::new(provider).code(who, block).await?);
         }
@@ -237,7 +256,7 @@ async fn main() -> eyre::Result<()> {
             println!("{}", SimpleCast::namehash(&name)?);
         }
         Subcommands::Tx { rpc_url, hash, field, to_json } => {
-            let rpc_url = consume_config_rpc_url(rpc_url);
+            let rpc_url = consume_config_rpc_url(rpc_url)?;
             let provider = Provider::try_from(rpc_url)?;
             println!("{}", Cast::new(&provider).transaction(hash, field, to_json).await?)
         }
@@ -250,123 +269,32 @@ async fn main() -> eyre::Result<()> {
             gas,
             gas_price,
             value,
-            mut nonce,
+            nonce,
             legacy,
             confirmations,
             to_json,
             resend,
         } => {
-            let config = Config::from(&eth);
-            let provider = Provider::try_from(
-                config.eth_rpc_url.unwrap_or_else(|| "http://localhost:8545".to_string()),
-            )?;
-            let chain_id = Cast::new(&provider).chain_id().await?;
-            let sig = sig.unwrap_or_default();
-
-            if let Ok(Some(signer)) = eth.signer_with(chain_id, provider.clone()).await {
-                let from = match &signer {
-                    WalletType::Ledger(leger) => leger.address(),
-                    WalletType::Local(local) => local.address(),
-                    WalletType::Trezor(trezor) => trezor.address(),
-                };
-
-                if resend {
-                    nonce = Some(provider.get_transaction_count(from, None).await?);
-                }
-
-                match signer {
-                    WalletType::Ledger(signer) => {
-                        cast_send(
-                            &signer,
-                            from,
-                            to,
-                            (sig, args),
-                            gas,
-                            gas_price,
-                            value,
-                            nonce,
-                            eth.chain,
-                            config.etherscan_api_key,
-                            cast_async,
-                            legacy,
-                            confirmations,
-                            to_json,
-                        )
-                        .await?;
-                    }
-                    WalletType::Local(signer) => {
-                        cast_send(
-                            &signer,
-                            from,
-                            to,
-                            (sig, args),
-                            gas,
-                            gas_price,
-                            value,
-                            nonce,
-                            eth.chain,
-                            config.etherscan_api_key,
-                            cast_async,
-                            legacy,
-                            confirmations,
-                            to_json,
-                        )
-                        .await?;
-                    }
-                    WalletType::Trezor(signer) => {
-                        cast_send(
-                            &signer,
-                            from,
-                            to,
-                            (sig, args),
-                            gas,
-                            gas_price,
-                            value,
-                            nonce,
-                            eth.chain,
-                            config.etherscan_api_key,
-                            cast_async,
-                            legacy,
-                            confirmations,
-                            to_json,
-                        )
-                        .await?;
-                    }
-                } // Checking if signer isn't the default value
-                  // 00a329c0648769A73afAc7F9381E08FB43dBEA72.
-            } else if config.sender !=
-                Address::from_str("00a329c0648769A73afAc7F9381E08FB43dBEA72").unwrap()
-            {
-                if resend {
-                    nonce = Some(provider.get_transaction_count(config.sender, None).await?);
-                }
-
-                cast_send(
-                    provider,
-                    config.sender,
-                    to,
-                    (sig, args),
-                    gas,
-                    gas_price,
-                    value,
-                    nonce,
-                    eth.chain,
-                    config.etherscan_api_key,
-                    cast_async,
-                    legacy,
-                    confirmations,
-                    to_json,
-                )
-                .await?;
-            } else {
-                eyre::bail!("No wallet or sender address provided. Consider passing it via the --from flag or setting the ETH_FROM env variable or setting in the foundry.toml file");
-            }
+            send_tx(
+                eth,
+                to,
+                sig.unwrap_or_default(),
+                args,
+                gas,
+                gas_price,
+                value,
+                nonce,
+                resend,
+                cast_async,
+                legacy,
+                confirmations,
+                to_json,
+            )
+            .await?;
         }
         Subcommands::PublishTx { eth, raw_tx, cast_async } => {
             let config = Config::from(&eth);
-            let provider = Provider::try_from(
-                config.eth_rpc_url.unwrap_or_else(|| "http://localhost:8545".to_string()),
-            )?;
+            let provider = Provider::try_from(config.get_rpc_url_or_localhost_http()?)?;
             let cast = Cast::new(&provider);
             let pending_tx = cast.publish(raw_tx).await?;
             let tx_hash = *pending_tx;
@@ -381,15 +309,13 @@ async fn main() -> eyre::Result<()> {
         }
         Subcommands::Estimate { to, sig, args, value, eth } => {
             let config = Config::from(&eth);
-            let provider = Provider::try_from(
-                config.eth_rpc_url.unwrap_or_else(|| "http://localhost:8545".to_string()),
-            )?;
+            let provider = Provider::try_from(config.get_rpc_url_or_localhost_http()?)?;
 
             let from = eth.sender().await;
 
             let mut builder = TxBuilder::new(&provider, from, to, eth.chain, false).await?;
             builder
-                .etherscan_api_key(config.etherscan_api_key)
+                .etherscan_api_key(config.get_etherscan_api_key()?)
                 .value(value)
                 .set_args(sig.as_str(), args)
                 .await?;
@@ -399,26 +325,53 @@ async fn main() -> eyre::Result<()> {
             let gas = Cast::new(&provider).estimate(builder_output).await?;
             println!("{gas}");
         }
-        Subcommands::CalldataDecode { sig, calldata } => {
-            let tokens = SimpleCast::abi_decode(&sig, &calldata, true)?;
-            let tokens = foundry_utils::format_tokens(&tokens);
-            tokens.for_each(|t| println!("{t}"));
+        Subcommands::CalldataDecode { sig, calldata, pretty } => {
+            if pretty {
+                println!("{}", foundry_utils::pretty_abi_decode(&sig, &calldata, true)?);
+            } else {
+                let tokens = SimpleCast::abi_decode(&sig, &calldata, true)?;
+                let tokens = foundry_utils::format_tokens(&tokens);
+                tokens.for_each(|t| println!("{t}"));
+            }
         }
-        Subcommands::AbiDecode { sig, calldata, input } => {
-            let tokens = SimpleCast::abi_decode(&sig, &calldata, input)?;
-            let tokens = foundry_utils::format_tokens(&tokens);
-            tokens.for_each(|t| println!("{t}"));
+        Subcommands::AbiDecode { sig, calldata, input, pretty } => {
+            if pretty {
+                println!("{}", foundry_utils::pretty_abi_decode(&sig, &calldata, input)?);
+            } else {
+                let tokens = SimpleCast::abi_decode(&sig, &calldata, input)?;
+                let tokens = foundry_utils::format_tokens(&tokens);
+                tokens.for_each(|t| println!("{t}"));
+            }
         }
-        Subcommands::AbiEncode { sig, args } => {
-            println!("{}", SimpleCast::abi_encode(&sig, &args)?);
+        Subcommands::AbiEncode { sig, args, pretty } => {
+            let encoded = SimpleCast::abi_encode(&sig, &args)?;
+            if pretty {
+                println!("{}", foundry_utils::pretty_abi_encoded_args(&sig, &encoded)?);
+            } else {
+                println!("{encoded}");
+            }
         }
         Subcommands::Index { key_type, value_type, key, slot_number } => {
             let encoded = SimpleCast::index(&key_type, &value_type, &key, &slot_number)?;
             println!("{encoded}");
         }
+        Subcommands::IndexErc7201 { id, diamond } => {
+            let slot = if diamond {
+                SimpleCast::index_diamond(&id)?
+            } else {
+                SimpleCast::index_erc7201(&id)?
+            };
+            println!("{slot}");
+        }
         Subcommands::FourByte { selector } => {
-            let sigs = foundry_utils::fourbyte(&selector).await?;
-            sigs.iter().for_each(|sig| println!("{}", sig.0));
+            let mut cache = load_signatures_cache();
+            let mut sigs = cache.functions(&selector);
+            if sigs.is_empty() {
+                sigs = foundry_utils::fourbyte(&selector).await?.into_iter().map(|s| s.0).collect();
+                sigs.iter().for_each(|sig| cache.insert_function(sig.as_str()));
+                save_signatures_cache(&cache);
+            }
+            sigs.iter().for_each(|sig| println!("{sig}"));
         }
         Subcommands::FourByteDecode { calldata, id } => {
             let sigs = foundry_utils::fourbyte_possible_sigs(&calldata, id).await?;
@@ -437,14 +390,25 @@ async fn main() -> eyre::Result<()> {
                 }
             }?;
 
+            let mut cache = load_signatures_cache();
+            cache.insert_function(sig.as_str());
+            save_signatures_cache(&cache);
+
             let tokens = SimpleCast::abi_decode(sig, &calldata, true)?;
             let tokens = foundry_utils::format_tokens(&tokens);
 
             tokens.for_each(|t| println!("{t}"));
         }
         Subcommands::FourByteEvent { topic } => {
-            let sigs = foundry_utils::fourbyte_event(&topic).await?;
-            sigs.iter().for_each(|sig| println!("{}", sig.0));
+            let mut cache = load_signatures_cache();
+            let mut sigs = cache.events(&topic);
+            if sigs.is_empty() {
+                sigs =
+                    foundry_utils::fourbyte_event(&topic).await?.into_iter().map(|s| s.0).collect();
+                sigs.iter().for_each(|sig| cache.insert_event(sig.as_str()));
+                save_signatures_cache(&cache);
+            }
+            sigs.iter().for_each(|sig| println!("{sig}"));
         }
 
         Subcommands::PrettyCalldata { calldata, offline } => {
@@ -455,8 +419,19 @@ async fn main() -> eyre::Result<()> {
             let pretty_data = foundry_utils::pretty_calldata(&calldata, offline).await?;
             println!("{pretty_data}");
         }
+        Subcommands::Da { input, gas_price, rpc_url } => {
+            let data = match input.strip_prefix("0x").unwrap_or(&input) {
+                hash if hash.len() == 64 => {
+                    let rpc_url = consume_config_rpc_url(rpc_url)?;
+                    let provider = Provider::try_from(rpc_url)?;
+                    Cast::new(&provider).raw_transaction(hash.to_string()).await?.to_vec()
+                }
+                data => hex::decode(data)?,
+            };
+            println!("{}", DaEstimate::new(&data).pretty(gas_price));
+        }
         Subcommands::Age { block, rpc_url } => {
-            let rpc_url = consume_config_rpc_url(rpc_url);
+            let rpc_url = consume_config_rpc_url(rpc_url)?;
             let provider = Provider::try_from(rpc_url)?;
             println!(
                 "{}",
@@ -464,12 +439,12 @@ async fn main() -> eyre::Result<()> {
             );
         }
         Subcommands::Balance { block, who, rpc_url } => {
-            let rpc_url = consume_config_rpc_url(rpc_url);
+            let rpc_url = consume_config_rpc_url(rpc_url)?;
             let provider = Provider::try_from(rpc_url)?;
             println!("{}", Cast::new(provider).balance(who, block).await?);
         }
         Subcommands::BaseFee { block, rpc_url } => {
-            let rpc_url = consume_config_rpc_url(rpc_url);
+            let rpc_url = consume_config_rpc_url(rpc_url)?;
 
             let provider = Provider::try_from(rpc_url)?;
             println!(
@@ -478,7 +453,7 @@ async fn main() -> eyre::Result<()> {
             );
         }
         Subcommands::GasPrice { rpc_url } => {
-            let rpc_url = consume_config_rpc_url(rpc_url);
+            let rpc_url = consume_config_rpc_url(rpc_url)?;
             let provider = Provider::try_from(rpc_url)?;
             println!("{}", Cast::new(provider).gas_price().await?);
         }
@@ -499,7 +474,7 @@ async fn main() -> eyre::Result<()> {
                 let api_key = match etherscan_api_key {
                     Some(inner) => inner,
                     _ => {
-                        if let Some(etherscan_api_key) = Config::load().etherscan_api_key {
+                        if let Some(etherscan_api_key) = Config::load().get_etherscan_api_key()? {
                             etherscan_api_key
                         } else {
                             eyre::bail!("No Etherscan API Key is set. Consider using the ETHERSCAN_API_KEY env var, or setting the -e CLI argument or etherscan-api-key in foundry.toml")
@@ -538,61 +513,56 @@ async fn main() -> eyre::Result<()> {
             }
         }
         Subcommands::ResolveName { who, rpc_url, verify } => {
-            let rpc_url = consume_config_rpc_url(rpc_url);
+            let rpc_url = consume_config_rpc_url(rpc_url)?;
             let provider = Provider::try_from(rpc_url)?;
             let who = unwrap_or_stdin(who)?;
-            let address = provider.resolve_name(&who).await?;
-            if verify {
-                let name = provider.lookup_address(address).await?;
-                assert_eq!(
-                    name, who,
-                    "forward lookup verification failed. got {}, expected {}",
-                    name, who
-                );
-            }
+            let address = Cast::new(provider).resolve_name(&who, verify).await?;
             println!("{:?}", address);
         }
         Subcommands::LookupAddress { who, rpc_url, verify } => {
-            let rpc_url = consume_config_rpc_url(rpc_url);
+            let rpc_url = consume_config_rpc_url(rpc_url)?;
             let provider = Provider::try_from(rpc_url)?;
             let who = unwrap_or_stdin(who)?;
-            let name = provider.lookup_address(who).await?;
-            if verify {
-                let address = provider.resolve_name(&name).await?;
-                assert_eq!(
-                    address, who,
-                    "forward lookup verification failed. got {}, expected {}",
-                    name, who
-                );
-            }
+            let name = Cast::new(provider).lookup_address(who, verify).await?;
             println!("{name}");
         }
-        Subcommands::Storage { address, slot, rpc_url, block } => {
-            let rpc_url = consume_config_rpc_url(rpc_url);
+        Subcommands::Storage { address, slot, rpc_url, block, to_json } => {
+            let rpc_url = consume_config_rpc_url(rpc_url)?;
 
             let provider = Provider::try_from(rpc_url)?;
-            let value = provider.get_storage_at(address, slot, block).await?;
-            println!("{:?}", value);
+            println!("{}", Cast::new(provider).storage(address, slot, block, to_json).await?);
         }
-        Subcommands::Proof { address, slots, rpc_url, block } => {
-            let rpc_url = consume_config_rpc_url(rpc_url);
+        Subcommands::Proof { address, slots, rpc_url, block, to_json } => {
+            let rpc_url = consume_config_rpc_url(rpc_url)?;
 
             let provider = Provider::try_from(rpc_url)?;
-            let value = provider.get_proof(address, slots, block).await?;
-            println!("{}", serde_json::to_string(&value)?);
+            println!("{}", Cast::new(provider).proof(address, slots, block, to_json).await?);
         }
-        Subcommands::Receipt { hash, field, to_json, rpc_url, cast_async, confirmations } => {
-            let rpc_url = consume_config_rpc_url(rpc_url);
+        Subcommands::Receipt {
+            hash,
+            field,
+            to_json,
+            abi,
+            etherscan_api_key,
+            rpc_url,
+            cast_async,
+            confirmations,
+        } => {
+            let rpc_url = consume_config_rpc_url(rpc_url)?;
             let provider = Provider::try_from(rpc_url)?;
+            let events = match abi {
+                Some(abi) => Some(load_abi(&provider, &abi, etherscan_api_key).await?),
+                None => None,
+            };
             println!(
                 "{}",
                 Cast::new(provider)
-                    .receipt(hash, field, confirmations, cast_async, to_json)
+                    .receipt(hash, field, confirmations, cast_async, to_json, events.as_ref())
                     .await?
             );
         }
         Subcommands::Nonce { block, who, rpc_url } => {
-            let rpc_url = consume_config_rpc_url(rpc_url);
+            let rpc_url = consume_config_rpc_url(rpc_url)?;
 
             let provider = Provider::try_from(rpc_url)?;
             println!("{}", Cast::new(provider).nonce(who, block).await?);
@@ -601,7 +571,7 @@ async fn main() -> eyre::Result<()> {
             let api_key = match etherscan_api_key {
                 Some(inner) => inner,
                 _ => {
-                    if let Some(etherscan_api_key) = Config::load().etherscan_api_key {
+                    if let Some(etherscan_api_key) = Config::load().get_etherscan_api_key()? {
                         etherscan_api_key
                     } else {
                         eyre::bail!("No Etherscan API Key is set. Consider using the ETHERSCAN_API_KEY env var, or setting the -e CLI argument or etherscan-api-key in foundry.toml")
@@ -632,6 +602,84 @@ async fn main() -> eyre::Result<()> {
             println!("0x{}", hex::encode(selector));
         }
         Subcommands::FindBlock(cmd) => cmd.run()?.await?,
+        Subcommands::Erc20 { command } => match command {
+            Erc20Subcommands::Balance { token, owner, block, rpc_url } => {
+                let rpc_url = consume_config_rpc_url(rpc_url)?;
+                let provider = Provider::try_from(rpc_url)?;
+                let cast = Cast::new(provider);
+                let (decimals, symbol) = cast.erc20_metadata(token.clone(), block).await?;
+                let balance = cast.erc20_balance(token, owner, block).await?;
+                println!("{}", SimpleCast::format_token_amount(balance, decimals, &symbol));
+            }
+            Erc20Subcommands::Allowance { token, owner, spender, block, rpc_url } => {
+                let rpc_url = consume_config_rpc_url(rpc_url)?;
+                let provider = Provider::try_from(rpc_url)?;
+                let cast = Cast::new(provider);
+                let (decimals, symbol) = cast.erc20_metadata(token.clone(), block).await?;
+                let allowance = cast.erc20_allowance(token, owner, spender, block).await?;
+                if allowance == U256::MAX {
+                    println!("unlimited");
+                } else {
+                    println!("{}", SimpleCast::format_token_amount(allowance, decimals, &symbol));
+                }
+            }
+            Erc20Subcommands::Transfer {
+                token,
+                to,
+                amount,
+                gas,
+                gas_price,
+                nonce,
+                cast_async,
+                eth,
+                legacy,
+                confirmations,
+                to_json,
+            } => {
+                let config = Config::from(&eth);
+                let provider = Provider::try_from(config.get_rpc_url_or_localhost_http()?)?;
+                let cast = Cast::new(&provider);
+                let (decimals, symbol) = cast.erc20_metadata(token.clone(), None).await?;
+                let amount = SimpleCast::parse_token_amount(&amount, decimals, &symbol)?;
+                let to = match to {
+                    NameOrAddress::Name(name) => provider.resolve_name(&name).await?,
+                    NameOrAddress::Address(address) => address,
+                };
+                send_tx(
+                    eth,
+                    token,
+                    "transfer(address,uint256)".to_string(),
+                    vec![format!("{to:?}"), amount.to_string()],
+                    gas,
+                    gas_price,
+                    None,
+                    nonce,
+                    false,
+                    cast_async,
+                    legacy,
+                    confirmations,
+                    to_json,
+                )
+                .await?;
+            }
+        },
+        Subcommands::TxPool { command } => match command {
+            TxPoolSubcommands::Status { rpc_url, to_json } => {
+                let rpc_url = consume_config_rpc_url(rpc_url)?;
+                let provider = Provider::try_from(rpc_url)?;
+                println!("{}", Cast::new(provider).txpool_status(to_json).await?);
+            }
+            TxPoolSubcommands::Content { rpc_url, to_json } => {
+                let rpc_url = consume_config_rpc_url(rpc_url)?;
+                let provider = Provider::try_from(rpc_url)?;
+                println!("{}", Cast::new(provider).txpool_content(to_json).await?);
+            }
+            TxPoolSubcommands::Inspect { rpc_url, to_json } => {
+                let rpc_url = consume_config_rpc_url(rpc_url)?;
+                let provider = Provider::try_from(rpc_url)?;
+                println!("{}", Cast::new(provider).txpool_inspect(to_json).await?);
+            }
+        },
         Subcommands::Wallet { command } => match command {
             WalletSubcommands::New { path, password, unsafe_password } => {
                 let mut rng = thread_rng();
@@ -668,19 +716,15 @@ async fn main() -> eyre::Result<()> {
             WalletSubcommands::Vanity { starts_with, ends_with, nonce } => {
                 let mut regexs = vec![];
                 if let Some(prefix) = starts_with {
-                    let pad_width = prefix.len() + prefix.len() % 2;
-                    hex::decode(format!("{:0>width$}", prefix, width = pad_width))
-                        .expect("invalid prefix hex provided");
+                    let prefix = vanity_pattern(&prefix).wrap_err("invalid prefix hex provided")?;
                     regexs.push(format!(r"^{}", prefix));
                 }
                 if let Some(suffix) = ends_with {
-                    let pad_width = suffix.len() + suffix.len() % 2;
-                    hex::decode(format!("{:0>width$}", suffix, width = pad_width))
-                        .expect("invalid suffix hex provided");
+                    let suffix = vanity_pattern(&suffix).wrap_err("invalid suffix hex provided")?;
                     regexs.push(format!(r"{}$", suffix));
                 }
 
-                assert!(
+                eyre::ensure!(
                     regexs.iter().map(|p| p.len() - 1).sum::<usize>() <= 40,
                     "vanity patterns length exceeded. cannot be more than 40 characters",
                 );
@@ -756,13 +800,13 @@ async fn main() -> eyre::Result<()> {
                 println!("Signature: 0x{sig}");
             }
             WalletSubcommands::Verify { message, signature, address } => {
-                let pubkey = Address::from_str(&address).expect("invalid pubkey provided");
+                let pubkey = Address::from_str(&address).wrap_err("invalid address provided")?;
                 let signature = Signature::from_str(&signature)?;
                 match signature.verify(message, pubkey) {
                     Ok(_) => {
                         println!("Validation success. Address {address} signed this message.")
                     }
-                    Err(_) => println!(
+                    Err(_) => eyre::bail!(
                         "Validation failed. Address {} did not sign this message.",
                         address
                     ),
@@ -777,6 +821,15 @@ async fn main() -> eyre::Result<()> {
     Ok(())
 }
 
+/// Validates a vanity prefix or suffix and returns it lowercased, so that checksummed patterns
+/// match the hex encoded addresses
+fn vanity_pattern(pattern: &str) -> eyre::Result<String> {
+    let pattern = pattern.strip_prefix("0x").unwrap_or(pattern).to_lowercase();
+    let pad_width = pattern.len() + pattern.len() % 2;
+    hex::decode(format!("{:0>width$}", pattern, width = pad_width))?;
+    Ok(pattern)
+}
+
 fn unwrap_or_stdin<T>(what: Option<T>) -> eyre::Result<T>
 where
     T: FromStr + Send + Sync,
@@ -793,6 +846,128 @@ where
     })
 }
 
+/// Signs and sends a transaction with the wallet or the unlocked sender of the Ethereum options
+#[allow(clippy::too_many_arguments)]
+async fn send_tx(
+    eth: EthereumOpts,
+    to: NameOrAddress,
+    sig: String,
+    args: Vec<String>,
+    gas: Option<U256>,
+    gas_price: Option<U256>,
+    value: Option<U256>,
+    mut nonce: Option<U256>,
+    resend: bool,
+    cast_async: bool,
+    legacy: bool,
+    confirmations: usize,
+    to_json: bool,
+) -> eyre::Result<()> {
+    let config = Config::from(&eth);
+    let provider = Provider::try_from(config.get_rpc_url_or_localhost_http()?)?;
+    let chain_id = Cast::new(&provider).chain_id().await?;
+
+    if let Ok(Some(signer)) = eth.signer_with(chain_id, provider.clone()).await {
+        let from = match &signer {
+            WalletType::Ledger(leger) => leger.address(),
+            WalletType::Local(local) => local.address(),
+            WalletType::Trezor(trezor) => trezor.address(),
+        };
+
+        if resend {
+            nonce = Some(provider.get_transaction_count(from, None).await?);
+        }
+
+        match signer {
+            WalletType::Ledger(signer) => {
+                cast_send(
+                    &signer,
+                    from,
+                    to,
+                    (sig, args),
+                    gas,
+                    gas_price,
+                    value,
+                    nonce,
+                    eth.chain,
+                    config.get_etherscan_api_key()?,
+                    cast_async,
+                    legacy,
+                    confirmations,
+                    to_json,
+                )
+                .await?;
+            }
+            WalletType::Local(signer) => {
+                cast_send(
+                    &signer,
+                    from,
+                    to,
+                    (sig, args),
+                    gas,
+                    gas_price,
+                    value,
+                    nonce,
+                    eth.chain,
+                    config.get_etherscan_api_key()?,
+                    cast_async,
+                    legacy,
+                    confirmations,
+                    to_json,
+                )
+                .await?;
+            }
+            WalletType::Trezor(signer) => {
+                cast_send(
+                    &signer,
+                    from,
+                    to,
+                    (sig, args),
+                    gas,
+                    gas_price,
+                    value,
+                    nonce,
+                    eth.chain,
+                    config.get_etherscan_api_key()?,
+                    cast_async,
+                    legacy,
+                    confirmations,
+                    to_json,
+                )
+                .await?;
+            }
+        } // Checking if signer isn't the default value
+          // 00a329c0648769A73afAc7F9381E08FB43dBEA72.
+    } else if config.sender !=
+        Address::from_str("00a329c0648769A73afAc7F9381E08FB43dBEA72").unwrap()
+    {
+        if resend {
+            nonce = Some(provider.get_transaction_count(config.sender, None).await?);
+        }
+
+        cast_send(
+            provider,
+            config.sender,
+            to,
+            (sig, args),
+            gas,
+            gas_price,
+            value,
+            nonce,
+            eth.chain,
+            config.get_etherscan_api_key()?,
+            cast_async,
+            legacy,
+            confirmations,
+            to_json,
+        )
+        .await?;
+    } else {
+        eyre::bail!("No wallet or sender address provided. Consider passing it via the --from flag or setting the ETH_FROM env variable or setting in the foundry.toml file");
+    }
+    Ok(())
+}
+
 #[allow(clippy::too_many_arguments)]
 async fn cast_send<M: Middleware, F: Into<NameOrAddress>, T: Into<NameOrAddress>>(
     provider: M,
@@ -835,9 +1010,54 @@ where
     if cast_async {
         println!("{:#x}", tx_hash);
     } else {
-        let receipt = cast.receipt(format!("{:#x}", tx_hash), None, confs, false, to_json).await?;
+        let receipt =
+            cast.receipt(format!("{:#x}", tx_hash), None, confs, false, to_json, None).await?;
         println!("{receipt}");
     }
 
     Ok(())
 }
+
+/// Loads the JSON ABI file at `path_or_address`, or fetches the ABI of the contract at that address
+/// from Etherscan on the chain of the provider
+async fn load_abi<M: Middleware>(
+    provider: &M,
+    path_or_address: &str,
+    etherscan_api_key: Option<String>,
+) -> eyre::Result<Abi>
+where
+    M::Error: 'static,
+{
+    if Path::new(path_or_address).exists() {
+        let file = std::fs::read_to_string(path_or_address).wrap_err("unable to read abi file")?;
+        return serde_json::from_str(&file).wrap_err("unable to parse json ABI from file")
+    }
+
+    let address = path_or_address
+        .parse::<Address>()
+        .wrap_err("Invalid ABI path or address provided. Did you make a typo?")?;
+    let etherscan_api_key = match etherscan_api_key {
+        Some(key) => Some(key),
+        None => Config::load().get_etherscan_api_key()?,
+    };
+    let api_key = match etherscan_api_key {
+        Some(api_key) => api_key,
+        None => eyre::bail!("No Etherscan API Key is set. Consider using the ETHERSCAN_API_KEY env var, or setting the --etherscan-api-key CLI argument or etherscan-api-key in foundry.toml"),
+    };
+    let chain = Chain::try_from(provider.get_chainid().await?.as_u64())?;
+    Ok(Client::new(chain, api_key)?.contract_abi(address).await?)
+}
+
+/// Loads the local signatures cache shared with `forge`
+fn load_signatures_cache() -> SignaturesCache {
+    Config::foundry_signatures_cache_file().map(SignaturesCache::load).unwrap_or_default()
+}
+
+/// Writes the local signatures cache, failing to do so is not fatal for lookups
+fn save_signatures_cache(cache: &SignaturesCache) {
+    if let Some(path) = Config::foundry_signatures_cache_file() {
+        if let Err(err) = cache.save(path) {
+            tracing::warn!("failed to update signatures cache: {}", err);
+        }
+    }
+}
@@ -0,0 +1,71 @@
+//! Helpers for `cast call`
+use crate::utils;
+use ethers::types::{transaction::eip2718::TypedTransaction, NameOrAddress};
+use forge::{
+    debug::DebugArena,
+    executor::{builder::Backend, opts::EvmOpts, ExecutorBuilder, RawCallResult},
+};
+use foundry_config::Config;
+
+/// Executes the call against a local fork of `rpc_url` at `block` and prints every opcode it
+/// executed
+pub async fn print_opcodes(
+    tx: &TypedTransaction,
+    rpc_url: String,
+    block: u64,
+    config: &Config,
+) -> eyre::Result<()> {
+    let to = match tx.to() {
+        Some(NameOrAddress::Address(to)) => *to,
+        _ => eyre::bail!("The opcodes can only be printed for calls to an address"),
+    };
+
+    let evm_opts = EvmOpts {
+        fork_url: Some(rpc_url),
+        fork_block_number: Some(block),
+        memory_limit: config.memory_limit,
+        ..Default::default()
+    };
+
+    let env = evm_opts.evm_env().await;
+    let db = Backend::new(utils::get_fork(&evm_opts, &config.rpc_storage_caching), &env).await;
+    let executor = ExecutorBuilder::new()
+        .with_config(env)
+        .with_spec(utils::evm_spec(&config.evm_version))
+        .with_debugger()
+        .build(db);
+
+    let RawCallResult { reverted, gas, debug, .. } = executor.call_raw(
+        tx.from().copied().unwrap_or_default(),
+        to,
+        tx.data().map(|data| data.0.clone()).unwrap_or_default(),
+        tx.value().copied().unwrap_or_default(),
+    )?;
+
+    print_arena(&debug.unwrap_or_default());
+    println!("{} (gas: {})", if reverted { "Reverted" } else { "Success" }, gas);
+    Ok(())
+}
+
+/// Prints the steps of every call in execution order
+fn print_arena(arena: &DebugArena) {
+    for node in arena.arena.iter().filter(|node| !node.steps.is_empty()) {
+        println!("{:?} {:?} (depth {})", node.kind, node.address, node.depth);
+        for step in &node.steps {
+            let push_bytes = step
+                .push_bytes
+                .as_ref()
+                .map(|bytes| format!(" 0x{}", hex::encode(bytes)))
+                .unwrap_or_default();
+            let stack = step.stack.iter().map(|value| format!("{:#x}", value)).collect::<Vec<_>>();
+            println!(
+                "  {:>6}: {}{} gas: {} stack: [{}]",
+                step.pc,
+                step.instruction,
+                push_bytes,
+                step.total_gas_used,
+                stack.join(", ")
+            );
+        }
+    }
+}
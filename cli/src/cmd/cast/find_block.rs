@@ -27,7 +27,7 @@ impl Cmd for FindBlockArgs {
 impl FindBlockArgs {
     async fn query_block(timestamp: u64, rpc_url: Option<String>) -> Result<()> {
         let ts_target = U256::from(timestamp);
-        let rpc_url = consume_config_rpc_url(rpc_url);
+        let rpc_url = consume_config_rpc_url(rpc_url)?;
 
         let provider = Provider::try_from(rpc_url)?;
         let last_block_num = provider.get_block_number().await?;
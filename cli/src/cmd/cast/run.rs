@@ -10,12 +10,16 @@ use ethers::{
 use forge::{
     debug::DebugArena,
     executor::{builder::Backend, opts::EvmOpts, DeployResult, ExecutorBuilder, RawCallResult},
-    trace::{identifier::EtherscanIdentifier, CallTraceArena, CallTraceDecoderBuilder, TraceKind},
+    trace::{
+        identifier::EtherscanIdentifier, CallTraceArena, CallTraceDecoderBuilder, ChromeTrace,
+        TraceKind,
+    },
 };
 use foundry_config::Config;
 use foundry_utils::RuntimeOrHandle;
 use std::{
     collections::{BTreeMap, HashMap},
+    path::{Path, PathBuf},
     str::FromStr,
     time::Duration,
 };
@@ -40,6 +44,13 @@ pub struct RunArgs {
         help = "Labels address in the trace. 0xd8dA6BF26964aF9D7eEd9e03E53415D37aA96045:vitalik.eth"
     )]
     label: Vec<String>,
+    #[clap(
+        long,
+        value_name = "PATH",
+        help = "Writes the trace in the Chrome trace event format to the given file.",
+        long_help = "Writes the trace in the Chrome trace event format to the given file, which can be opened in chrome://tracing, Perfetto or speedscope. Gas is used as the time axis."
+    )]
+    chrome_trace: Option<PathBuf>,
 }
 
 impl Cmd for RunArgs {
@@ -55,15 +66,16 @@ impl RunArgs {
         let mut evm_opts = figment.extract::<EvmOpts>()?;
         let config = Config::from_provider(figment).sanitized();
 
+        let rpc_url = config.resolve_rpc_url(&self.rpc_url)?;
         let provider =
-            Provider::try_from(self.rpc_url.as_str()).expect("could not instantiate provider");
+            Provider::try_from(rpc_url.as_str()).expect("could not instantiate provider");
 
         if let Some(tx) =
             provider.get_transaction(H256::from_str(&self.tx).expect("invalid tx hash")).await?
         {
             let tx_block_number = tx.block_number.expect("no block number").as_u64();
             let tx_hash = tx.hash();
-            evm_opts.fork_url = Some(self.rpc_url);
+            evm_opts.fork_url = Some(rpc_url);
             evm_opts.fork_block_number = Some(tx_block_number - 1);
 
             // Set up the execution environment
@@ -133,7 +145,7 @@ impl RunArgs {
 
             let etherscan_identifier = EtherscanIdentifier::new(
                 evm_opts.get_remote_chain_id(),
-                config.etherscan_api_key,
+                config.get_etherscan_api_key()?,
                 Config::foundry_etherscan_cache_dir(evm_opts.get_chain_id()),
                 Duration::from_secs(24 * 60 * 60),
             );
@@ -163,6 +175,9 @@ impl RunArgs {
                 run_debugger(result, decoder)?;
             } else {
                 print_traces(&mut result, decoder)?;
+                if let Some(path) = self.chrome_trace {
+                    write_chrome_trace(&result, &path)?;
+                }
             }
         }
         Ok(())
@@ -173,8 +188,10 @@ fn run_debugger(result: RunResult, decoder: CallTraceDecoder) -> eyre::Result<()
     // TODO Get source from etherscan
     let source_code: BTreeMap<u32, String> = BTreeMap::new();
     let calls: Vec<DebugArena> = vec![result.debug];
-    let flattened = calls.last().expect("we should have collected debug info").flatten(0);
-    let tui = Tui::new(flattened, 0, decoder.contracts, HashMap::new(), source_code)?;
+    let arena = calls.last().expect("we should have collected debug info");
+    let (call, step) = arena.revert_location(0).unwrap_or_default();
+    let tui =
+        Tui::new(arena.flatten(0), call, step, decoder.contracts, HashMap::new(), source_code)?;
     match tui.start().expect("Failed to start tui") {
         TUIExitReason::CharExit => Ok(()),
     }
@@ -202,6 +219,17 @@ fn print_traces(result: &mut RunResult, decoder: CallTraceDecoder) -> eyre::Resu
     Ok(())
 }
 
+/// Writes the decoded traces of the transaction to `path` in the Chrome trace event format
+fn write_chrome_trace(result: &RunResult, path: &Path) -> eyre::Result<()> {
+    let mut chrome_trace = ChromeTrace::default();
+    for (kind, trace) in &result.traces {
+        chrome_trace.add_arena(format!("{:?}", kind), trace);
+    }
+    std::fs::write(path, chrome_trace.to_json()?)?;
+    println!("Chrome trace written to {}", path.display());
+    Ok(())
+}
+
 struct RunResult {
     pub success: bool,
     pub traces: Vec<(TraceKind, CallTraceArena)>,
@@ -116,6 +116,16 @@ pub struct CoreBuildArgs {
     #[serde(skip)]
     pub via_ir: bool,
 
+    #[clap(
+        help_heading = "COMPILER OPTIONS",
+        help = "The maximum number of solc processes to run in parallel.",
+        long_help = "The maximum number of solc processes to run in parallel. Sources that require different compiler versions are compiled in parallel, defaults to the number of CPUs.",
+        long = "jobs",
+        value_name = "JOBS"
+    )]
+    #[serde(rename = "solc_jobs", skip_serializing_if = "Option::is_none")]
+    pub jobs: Option<usize>,
+
     #[clap(flatten, next_help_heading = "PROJECT OPTIONS")]
     #[serde(flatten)]
     pub project_paths: ProjectPathsArgs,
@@ -240,7 +250,10 @@ pub struct BuildArgs {
     #[serde(skip)]
     pub names: bool,
 
-    #[clap(help = "Print compiled contract sizes.", long = "sizes")]
+    #[clap(
+        help = "Print the deployed contract sizes and fail if any exceeds the EIP-170 limit.",
+        long = "sizes"
+    )]
     #[serde(skip)]
     pub sizes: bool,
 
@@ -252,7 +265,11 @@ pub struct BuildArgs {
 impl Cmd for BuildArgs {
     type Output = ProjectCompileOutput;
     fn run(self) -> eyre::Result<Self::Output> {
-        let project = self.project()?;
+        let config: Config = (&self).into();
+        let project = config.project()?;
+        // vyper sources are compiled first, because `--sizes` exits after printing the sizes of
+        // the solidity contracts
+        compile::compile_vyper(&project, &config, false)?;
         compile::compile(&project, self.names, self.sizes)
     }
 }
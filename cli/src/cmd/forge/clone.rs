@@ -0,0 +1,499 @@
+//! Clone command
+use crate::{compile, utils::consume_config_rpc_url};
+use ansi_term::Colour;
+use clap::{Parser, ValueHint};
+use ethers::{
+    etherscan::{contract::Metadata, Client},
+    prelude::{Http, Middleware, Provider},
+    solc::{
+        artifacts::CompactDeployedBytecode,
+        remappings::{RelativeRemapping, Remapping},
+        EvmVersion,
+    },
+    types::Address,
+};
+use eyre::Context;
+use foundry_config::{Chain, Config, SolcReq};
+use semver::Version;
+use serde::{Deserialize, Serialize};
+use std::{
+    collections::BTreeMap,
+    future::Future,
+    path::{Component, Path, PathBuf},
+    str::FromStr,
+    time::Duration,
+};
+
+/// The file the details of the cloned contract are written to, relative to the project root
+pub const CLONE_FILE_NAME: &str = "clone.json";
+
+/// Creates a project from the verified sources of a deployed contract
+#[derive(Debug, Clone, Parser)]
+pub struct CloneArgs {
+    #[clap(help = "The address of the verified contract.")]
+    address: Address,
+
+    #[clap(
+        help = "The root directory of the new project. Defaults to the name of the contract.",
+        value_hint = ValueHint::DirPath
+    )]
+    root: Option<PathBuf>,
+
+    #[clap(
+        long,
+        alias = "chain-id",
+        env = "CHAIN",
+        help = "The chain ID the contract is deployed to.",
+        default_value = "mainnet"
+    )]
+    chain: Chain,
+
+    #[clap(long, env = "ETHERSCAN_API_KEY", help = "Your Etherscan API key.")]
+    etherscan_api_key: Option<String>,
+
+    #[clap(env = "ETH_RPC_URL", long = "rpc-url", help = "The RPC endpoint.")]
+    rpc_url: Option<String>,
+
+    #[clap(
+        long,
+        help = "How often failed Etherscan requests are retried, e.g. when the rate limit is hit.",
+        default_value = "5"
+    )]
+    retries: u32,
+
+    #[clap(
+        long,
+        help = "The number of seconds to wait before retrying a failed Etherscan request.",
+        value_name = "SECONDS",
+        default_value = "1"
+    )]
+    retry_delay: u64,
+
+    #[clap(
+        long,
+        help = "Do not compile the project and compare it with the code deployed at the address."
+    )]
+    no_verify: bool,
+
+    #[clap(help = "Create the project even if the root directory is not empty.", long)]
+    force: bool,
+}
+
+impl CloneArgs {
+    pub async fn run(self) -> eyre::Result<()> {
+        let etherscan_api_key = match self.etherscan_api_key.clone() {
+            Some(key) => Some(key),
+            None => Config::load().get_etherscan_api_key()?,
+        };
+        let api_key = match etherscan_api_key {
+            Some(api_key) => api_key,
+            None => eyre::bail!("No Etherscan API Key is set. Consider using the ETHERSCAN_API_KEY env var, or setting the --etherscan-api-key CLI argument or etherscan-api-key in foundry.toml"),
+        };
+        let client = Client::new(self.chain.try_into()?, api_key)
+            .wrap_err("Failed to create etherscan client")?;
+
+        let meta = retry(self.retries, Duration::from_secs(self.retry_delay), || async {
+            client
+                .contract_source_code(self.address)
+                .await
+                .wrap_err("Failed to fetch the contract source code from Etherscan")
+        })
+        .await?;
+        let item = meta
+            .items
+            .into_iter()
+            .next()
+            .ok_or_else(|| eyre::eyre!("No contract found at {:?}", self.address))?;
+        if item.abi == "Contract source code not verified" {
+            eyre::bail!(
+                "Contract source code at {:?} on {} not verified. Maybe you have selected the wrong chain?",
+                self.address,
+                self.chain
+            )
+        }
+
+        let root = self.root.clone().unwrap_or_else(|| PathBuf::from(&item.contract_name));
+        if !self.force && root.read_dir().map(|mut i| i.next().is_some()).unwrap_or(false) {
+            eyre::bail!(
+                "`forge clone` cannot be run on a non-empty directory, run `forge clone --force` to clone regardless"
+            )
+        }
+        std::fs::create_dir_all(&root)?;
+        let root = dunce::canonicalize(root)?;
+
+        println!(
+            "Cloning {} from {:?} into {}...",
+            item.contract_name,
+            self.address,
+            root.display()
+        );
+        let project = ClonedProject::new(&item)?;
+        project.write_to(&root, self.address, &item)?;
+        println!("    {} forge project.", Colour::Green.paint("Created"));
+
+        if self.no_verify {
+            return Ok(())
+        }
+
+        let provider = Provider::<Http>::try_from(consume_config_rpc_url(self.rpc_url.clone())?)?;
+        let code = provider.get_code(self.address, None).await?;
+        if code.as_ref().is_empty() {
+            eyre::bail!("There is no code deployed at {:?}", self.address)
+        }
+
+        let config = Config::load_with_root(&root).sanitized();
+        let output = compile::suppress_compile(&config.project()?)?;
+        let matches = output
+            .into_artifacts()
+            .filter(|(id, _)| id.name == item.contract_name)
+            .filter_map(|(_, artifact)| artifact.deployed_bytecode)
+            .any(|deployed| matches_runtime_code(&deployed, code.as_ref()));
+        if !matches {
+            eyre::bail!(
+                "The rebuilt {} does not match the code deployed at {:?}",
+                item.contract_name,
+                self.address
+            )
+        }
+
+        println!(
+            "    {} the rebuilt {} matches the code deployed at {:?}.",
+            Colour::Green.paint("Verified"),
+            item.contract_name,
+            self.address
+        );
+        Ok(())
+    }
+}
+
+/// Calls `f` until it succeeds, at most `retries` more times after the first attempt
+async fn retry<T, F, Fut>(retries: u32, delay: Duration, mut f: F) -> eyre::Result<T>
+where
+    F: FnMut() -> Fut,
+    Fut: Future<Output = eyre::Result<T>>,
+{
+    let mut attempt = 0;
+    loop {
+        match f().await {
+            Err(err) if attempt < retries => {
+                attempt += 1;
+                tracing::warn!("request failed, retrying ({}/{}): {:?}", attempt, retries, err);
+                tokio::time::sleep(delay).await;
+            }
+            res => return res,
+        }
+    }
+}
+
+/// The sources and compiler settings of a verified contract
+#[derive(Debug, Default, PartialEq, Eq)]
+struct ClonedProject {
+    /// The source files, relative to the source directory of the project
+    sources: BTreeMap<PathBuf, String>,
+    /// The remappings of the project
+    remappings: Vec<String>,
+    /// The linked libraries in the form `<path>:<name>:<address>`
+    libraries: Vec<String>,
+    via_ir: bool,
+}
+
+/// The standard JSON input etherscan returns for contracts verified with it, in double braces
+#[derive(Debug, Deserialize)]
+struct StandardJsonInput {
+    sources: BTreeMap<PathBuf, SourceFile>,
+    #[serde(default)]
+    settings: StandardJsonSettings,
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(default)]
+struct StandardJsonSettings {
+    remappings: Vec<String>,
+    libraries: BTreeMap<String, BTreeMap<String, String>>,
+    #[serde(rename = "viaIR")]
+    via_ir: bool,
+}
+
+#[derive(Debug, Deserialize)]
+struct SourceFile {
+    content: String,
+}
+
+/// The details of the cloned contract that do not fit into the `foundry.toml`
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct CloneMetadata<'a> {
+    address: Address,
+    contract_name: &'a str,
+    compiler_version: &'a str,
+    constructor_arguments: String,
+}
+
+impl ClonedProject {
+    /// The directory the sources are written to
+    const SRC: &'static str = "src";
+
+    fn new(item: &Metadata) -> eyre::Result<Self> {
+        let source_code = item.source_code.trim();
+
+        // contracts verified with standard JSON input are wrapped in double braces, contracts
+        // verified as multiple files are a JSON object of files
+        if let Some(input) = source_code.strip_prefix("{{").and_then(|s| s.strip_suffix("}}")) {
+            let input: StandardJsonInput = serde_json::from_str(&format!("{{{}}}", input))
+                .wrap_err("Failed to parse the standard JSON input of the verified contract")?;
+            Self::from_sources(input.sources, input.settings)
+        } else if source_code.starts_with('{') {
+            let sources: BTreeMap<PathBuf, SourceFile> = serde_json::from_str(source_code)
+                .wrap_err("Failed to parse the sources of the verified contract")?;
+            Self::from_sources(sources, Default::default())
+        } else {
+            let file = PathBuf::from(format!("{}.sol", item.contract_name));
+            let libraries = item
+                .library
+                .split(';')
+                .filter(|library| !library.trim().is_empty())
+                .map(|library| {
+                    let (name, address) = library.split_once(':').ok_or_else(|| {
+                        eyre::eyre!("Invalid library `{}` of the verified contract", library)
+                    })?;
+                    Ok(format!(
+                        "{}/{}:{}:0x{}",
+                        Self::SRC,
+                        file.display(),
+                        name.trim(),
+                        address.trim().trim_start_matches("0x")
+                    ))
+                })
+                .collect::<eyre::Result<_>>()?;
+            Ok(Self {
+                sources: [(file, item.source_code.clone())].into(),
+                libraries,
+                ..Default::default()
+            })
+        }
+    }
+
+    /// Places the sources under the source directory and remaps their original paths to it
+    fn from_sources(
+        sources: BTreeMap<PathBuf, SourceFile>,
+        settings: StandardJsonSettings,
+    ) -> eyre::Result<Self> {
+        let sources = sources
+            .into_iter()
+            .map(|(path, file)| Ok((sanitize_path(&path)?, file.content)))
+            .collect::<eyre::Result<BTreeMap<_, _>>>()?;
+
+        // original remappings point to the original paths, which are now in the source directory
+        let mut remappings = settings
+            .remappings
+            .iter()
+            .map(|remapping| {
+                let (name, path) = remapping
+                    .split_once('=')
+                    .ok_or_else(|| eyre::eyre!("Invalid remapping `{}`", remapping))?;
+                Ok(format!("{}={}/{}", name, Self::SRC, path))
+            })
+            .collect::<eyre::Result<Vec<_>>>()?;
+        let mut roots = sources
+            .keys()
+            .filter(|path| path.components().count() > 1)
+            .filter_map(|path| path.components().next())
+            .map(|root| root.as_os_str().to_string_lossy().into_owned())
+            .collect::<Vec<_>>();
+        roots.dedup();
+        remappings.extend(roots.into_iter().map(|root| format!("{0}/={1}/{0}/", root, Self::SRC)));
+
+        let libraries = settings
+            .libraries
+            .into_iter()
+            .flat_map(|(file, libraries)| {
+                libraries.into_iter().map(move |(name, address)| {
+                    format!("{}/{}:{}:{}", Self::SRC, file, name, address)
+                })
+            })
+            .collect();
+
+        Ok(Self { sources, remappings, libraries, via_ir: settings.via_ir })
+    }
+
+    /// Writes the sources, the `foundry.toml` and the [`CLONE_FILE_NAME`] to `root`
+    fn write_to(&self, root: &Path, address: Address, item: &Metadata) -> eyre::Result<()> {
+        for (path, content) in &self.sources {
+            let path = root.join(Self::SRC).join(path);
+            if let Some(parent) = path.parent() {
+                std::fs::create_dir_all(parent)?;
+            }
+            std::fs::write(path, content)?;
+        }
+
+        let mut config = Config::load_with_root(root);
+        config.src = Self::SRC.into();
+        config.solc = Some(SolcReq::Version(compiler_version(&item.compiler_version)?));
+        config.optimizer = item.optimization_used == "1";
+        if let Ok(runs) = item.runs.parse() {
+            config.optimizer_runs = runs;
+        }
+        if !item.evm_version.is_empty() && !item.evm_version.eq_ignore_ascii_case("default") {
+            config.evm_version = EvmVersion::from_str(&item.evm_version.to_lowercase())
+                .map_err(|err| eyre::eyre!("Invalid EVM version: {}", err))?;
+        }
+        config.via_ir = self.via_ir;
+        config.libraries = self.libraries.clone();
+        config.remappings = self
+            .remappings
+            .iter()
+            .map(|remapping| {
+                let remapping = Remapping::from_str(remapping)
+                    .map_err(|err| eyre::eyre!("Invalid remapping `{}`: {}", remapping, err))?;
+                Ok(RelativeRemapping::new(remapping, root))
+            })
+            .collect::<eyre::Result<_>>()?;
+        std::fs::write(root.join(Config::FILE_NAME), config.to_string_pretty()?)?;
+
+        let metadata = CloneMetadata {
+            address,
+            contract_name: &item.contract_name,
+            compiler_version: &item.compiler_version,
+            constructor_arguments: format!(
+                "0x{}",
+                item.constructor_arguments.trim_start_matches("0x")
+            ),
+        };
+        std::fs::write(root.join(CLONE_FILE_NAME), serde_json::to_string_pretty(&metadata)?)?;
+        Ok(())
+    }
+}
+
+/// Returns the version of a compiler version like `v0.8.7+commit.e28d00a7`
+fn compiler_version(version: &str) -> eyre::Result<Version> {
+    let version: Version = version
+        .trim_start_matches('v')
+        .parse()
+        .wrap_err_with(|| format!("Invalid compiler version `{}`", version))?;
+    Ok(Version::new(version.major, version.minor, version.patch))
+}
+
+/// Returns the path relative to the source directory, paths can not leave it
+fn sanitize_path(path: &Path) -> eyre::Result<PathBuf> {
+    let mut sanitized = PathBuf::new();
+    for component in path.components() {
+        match component {
+            Component::Normal(component) => sanitized.push(component),
+            Component::CurDir | Component::RootDir | Component::Prefix(_) => {}
+            Component::ParentDir => eyre::bail!("Invalid source path `{}`", path.display()),
+        }
+    }
+    Ok(sanitized)
+}
+
+/// Returns `true` if the locally compiled runtime code matches the deployed `code`.
+///
+/// The metadata appended by solc is ignored, since the paths of the sources differ from the
+/// original paths, and the values of immutables are ignored in the deployed code.
+fn matches_runtime_code(local: &CompactDeployedBytecode, code: &[u8]) -> bool {
+    let bytecode = match local.bytecode.as_ref().and_then(|b| b.object.as_bytes()) {
+        Some(bytecode) => bytecode.as_ref(),
+        None => return false,
+    };
+
+    let mut code = code.to_vec();
+    for offsets in local.immutable_references.values().flatten() {
+        let (start, end) = (offsets.start as usize, (offsets.start + offsets.length) as usize);
+        if end <= code.len() {
+            code[start..end].iter_mut().for_each(|byte| *byte = 0);
+        }
+    }
+
+    strip_metadata(bytecode) == strip_metadata(&code)
+}
+
+/// Strips the CBOR encoded metadata solc appends to the bytecode, its length is stored in the last
+/// two bytes
+fn strip_metadata(code: &[u8]) -> &[u8] {
+    if code.len() < 2 {
+        return code
+    }
+    let len = u16::from_be_bytes([code[code.len() - 2], code[code.len() - 1]]) as usize + 2;
+    if len > code.len() {
+        return code
+    }
+    &code[..code.len() - len]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn metadata(contract_name: &str, source_code: &str) -> Metadata {
+        serde_json::from_value(serde_json::json!({
+            "SourceCode": source_code,
+            "ABI": "[]",
+            "ContractName": contract_name,
+            "CompilerVersion": "v0.8.10+commit.fc410830",
+            "OptimizationUsed": "1",
+            "Runs": "200",
+            "ConstructorArguments": "",
+            "EVMVersion": "Default",
+            "Library": "",
+            "LicenseType": "MIT",
+            "Proxy": "0",
+            "Implementation": "",
+            "SwarmSource": "",
+        }))
+        .unwrap()
+    }
+
+    #[test]
+    fn can_clone_single_file() {
+        let project = ClonedProject::new(&metadata("Token", "contract Token {}")).unwrap();
+        assert_eq!(
+            project,
+            ClonedProject {
+                sources: [(PathBuf::from("Token.sol"), "contract Token {}".to_string())].into(),
+                ..Default::default()
+            }
+        );
+    }
+
+    #[test]
+    fn can_clone_standard_json() {
+        let source_code = r#"{{
+            "language": "Solidity",
+            "sources": {
+                "contracts/Token.sol": { "content": "import \"@oz/ERC20.sol\";" },
+                "lib/oz/ERC20.sol": { "content": "contract ERC20 {}" }
+            },
+            "settings": {
+                "remappings": ["@oz/=lib/oz/"],
+                "libraries": { "contracts/Math.sol": { "Math": "0x0000000000000000000000000000000000000001" } },
+                "viaIR": true
+            }
+        }}"#;
+        let project = ClonedProject::new(&metadata("Token", source_code)).unwrap();
+        assert_eq!(project.sources.len(), 2);
+        assert_eq!(project.sources[Path::new("lib/oz/ERC20.sol")], "contract ERC20 {}");
+        assert_eq!(
+            project.remappings,
+            vec!["@oz/=src/lib/oz/", "contracts/=src/contracts/", "lib/=src/lib/"]
+        );
+        assert_eq!(
+            project.libraries,
+            vec!["src/contracts/Math.sol:Math:0x0000000000000000000000000000000000000001"]
+        );
+        assert!(project.via_ir);
+    }
+
+    #[test]
+    fn rejects_sources_outside_the_project() {
+        let source_code = r#"{ "../Token.sol": { "content": "contract Token {}" } }"#;
+        assert!(ClonedProject::new(&metadata("Token", source_code)).is_err());
+    }
+
+    #[test]
+    fn can_strip_metadata() {
+        let code = [0x60, 0x80, 0xa1, 0x00, 0x01];
+        assert_eq!(strip_metadata(&code), &[0x60, 0x80]);
+        assert_eq!(strip_metadata(&[0x60, 0xff, 0xff]), &[0x60, 0xff, 0xff]);
+        assert_eq!(compiler_version("v0.8.10+commit.fc410830").unwrap(), Version::new(0, 8, 10));
+    }
+}
@@ -0,0 +1,249 @@
+//! Constructor args command
+use crate::{
+    cmd::{forge::build::CoreBuildArgs, utils::read_artifact},
+    compile,
+    opts::forge::ContractInfo,
+    utils::consume_config_rpc_url,
+};
+use clap::Parser;
+use ethers::{
+    abi::{Abi, Address},
+    etherscan::Client,
+    prelude::{Http, Middleware, Provider},
+    types::{Bytes, H256},
+    utils::get_contract_address,
+};
+use eyre::Context;
+use foundry_config::Chain;
+use foundry_utils::format_tokens;
+
+/// Extracts and decodes the constructor arguments a contract was deployed with
+#[derive(Debug, Clone, Parser)]
+pub struct ConstructorArgsArgs {
+    #[clap(help = "The address of the deployed contract.")]
+    address: Address,
+
+    #[clap(
+        long,
+        help = "The local contract to decode the arguments with, in the form `<path>:<contractname>` or `<contractname>`.",
+        long_help = "The local contract to decode the arguments with, in the form `<path>:<contractname>` or `<contractname>`. The constructor arguments are sliced from the creation code of the transaction that created the contract. If omitted, the ABI and the constructor arguments of the verified contract are fetched from Etherscan."
+    )]
+    contract: Option<ContractInfo>,
+
+    #[clap(
+        long = "tx",
+        help = "The transaction that created the contract.",
+        long_help = "The transaction that created the contract. If omitted, the transaction is looked up by searching for the block the code of the contract first appeared in, which requires an archive node.",
+        requires = "contract"
+    )]
+    tx_hash: Option<H256>,
+
+    #[clap(env = "ETH_RPC_URL", long = "rpc-url", help = "The RPC endpoint.")]
+    rpc_url: Option<String>,
+
+    #[clap(
+        long,
+        alias = "chain-id",
+        env = "CHAIN",
+        help = "The chain ID the contract is deployed to.",
+        default_value = "mainnet"
+    )]
+    chain: Chain,
+
+    #[clap(long, env = "ETHERSCAN_API_KEY", help = "Your Etherscan API key.")]
+    etherscan_api_key: Option<String>,
+
+    #[clap(long, help = "Print the ABI-encoded constructor arguments instead of decoding them.")]
+    raw: bool,
+
+    #[clap(flatten, next_help_heading = "BUILD OPTIONS")]
+    opts: CoreBuildArgs,
+}
+
+impl ConstructorArgsArgs {
+    pub async fn run(self) -> eyre::Result<()> {
+        let local = match self.contract {
+            Some(ref contract) => {
+                let project = self.opts.project()?;
+                let compiled = compile::suppress_compile(&project)?;
+                let (abi, bytecode, _) = read_artifact(&project, compiled, contract.clone())?;
+                let bytecode = bytecode.object.into_bytes().ok_or_else(|| {
+                    eyre::eyre!("Contract `{}` has unlinked bytecode", contract.name)
+                })?;
+                Some((abi, bytecode))
+            }
+            None => None,
+        };
+
+        let (abi, args) = match local {
+            Some((abi, bytecode)) => {
+                let provider = Provider::<Http>::try_from(
+                    consume_config_rpc_url(self.rpc_url.clone())?.as_str(),
+                )?;
+                let tx_hash = match self.tx_hash {
+                    Some(tx_hash) => tx_hash,
+                    None => self.find_creation_tx(&provider).await?,
+                };
+                (abi, self.creation_args(&provider, tx_hash, &bytecode).await?)
+            }
+            None => self.etherscan_args().await?,
+        };
+
+        if self.raw {
+            println!("0x{}", hex::encode(&args));
+            return Ok(())
+        }
+
+        let constructor = abi
+            .constructor
+            .as_ref()
+            .ok_or_else(|| eyre::eyre!("The contract does not have a constructor"))?;
+        let types = constructor.inputs.iter().map(|input| input.kind.clone()).collect::<Vec<_>>();
+        let tokens = ethers::abi::decode(&types, &args)
+            .wrap_err("Failed to decode the constructor arguments")?;
+
+        for (input, token) in constructor.inputs.iter().zip(format_tokens(&tokens)) {
+            if input.name.is_empty() {
+                println!("{}: {}", input.kind, token);
+            } else {
+                println!("{} {}: {}", input.kind, input.name, token);
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Finds the transaction that created the contract by searching for the first block that has
+    /// its code
+    async fn find_creation_tx(&self, provider: &Provider<Http>) -> eyre::Result<H256> {
+        let mut high = provider.get_block_number().await?.as_u64();
+        if !has_code(provider, self.address, high).await? {
+            eyre::bail!("No contract deployed at {:?}", self.address)
+        }
+
+        let mut low = 0;
+        while low < high {
+            let mid = low + (high - low) / 2;
+            if has_code(provider, self.address, mid).await? {
+                high = mid;
+            } else {
+                low = mid + 1;
+            }
+        }
+
+        let block = provider
+            .get_block_with_txs(low)
+            .await?
+            .ok_or_else(|| eyre::eyre!("Block {} not found", low))?;
+        block
+            .transactions
+            .into_iter()
+            .find(|tx| tx.to.is_none() && get_contract_address(tx.from, tx.nonce) == self.address)
+            .map(|tx| tx.hash)
+            .ok_or_else(|| {
+                eyre::eyre!(
+                    "{:?} was not created by a transaction in block {}. Contracts deployed by other contracts are not supported.",
+                    self.address,
+                    low
+                )
+            })
+    }
+
+    /// Slices the constructor arguments from the creation code of the given transaction
+    async fn creation_args(
+        &self,
+        provider: &Provider<Http>,
+        tx_hash: H256,
+        bytecode: &Bytes,
+    ) -> eyre::Result<Vec<u8>> {
+        let receipt = provider
+            .get_transaction_receipt(tx_hash)
+            .await?
+            .ok_or_else(|| eyre::eyre!("Transaction {:?} not found", tx_hash))?;
+        if receipt.contract_address != Some(self.address) {
+            eyre::bail!(
+                "Transaction {:?} did not create {:?}. Contracts deployed by other contracts are not supported.",
+                tx_hash,
+                self.address
+            )
+        }
+
+        let tx = provider
+            .get_transaction(tx_hash)
+            .await?
+            .ok_or_else(|| eyre::eyre!("Transaction {:?} not found", tx_hash))?;
+
+        let args = strip_bytecode(tx.input.as_ref(), bytecode.as_ref()).ok_or_else(|| {
+            eyre::eyre!(
+                "The creation code of {:?} does not match the bytecode of the local contract",
+                tx_hash
+            )
+        })?;
+        Ok(args.to_vec())
+    }
+
+    /// Fetches the constructor arguments and the ABI of the verified contract from Etherscan
+    async fn etherscan_args(&self) -> eyre::Result<(Abi, Vec<u8>)> {
+        let key = self.etherscan_api_key.as_deref().ok_or_else(|| {
+            eyre::eyre!("An Etherscan API key is required if `--contract` is not provided")
+        })?;
+        let client = Client::new(self.chain.try_into()?, key)
+            .wrap_err("Failed to create etherscan client")?;
+
+        let meta = client
+            .contract_source_code(self.address)
+            .await
+            .wrap_err("Failed to fetch the contract source code from Etherscan")?;
+        let item = meta
+            .items
+            .first()
+            .ok_or_else(|| eyre::eyre!("No contract found at {:?}", self.address))?;
+        if item.abi == "Contract source code not verified" {
+            eyre::bail!(
+                "Contract source code at {:?} on {} not verified. Maybe you have selected the wrong chain?",
+                self.address,
+                self.chain
+            )
+        }
+
+        let abi: Abi = serde_json::from_str(&item.abi).wrap_err("Failed to parse Etherscan ABI")?;
+        let args = hex::decode(item.constructor_arguments.trim_start_matches("0x"))
+            .wrap_err("Failed to decode Etherscan constructor arguments")?;
+        Ok((abi, args))
+    }
+}
+
+/// Returns whether there is code at the address in the given block
+async fn has_code(provider: &Provider<Http>, address: Address, block: u64) -> eyre::Result<bool> {
+    Ok(!provider.get_code(address, Some(block.into())).await?.as_ref().is_empty())
+}
+
+/// Returns the constructor arguments appended to the bytecode in the creation code, or `None` if
+/// the creation code does not start with the bytecode
+fn strip_bytecode<'a>(code: &'a [u8], bytecode: &[u8]) -> Option<&'a [u8]> {
+    code.strip_prefix(bytecode)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn can_strip_bytecode() {
+        let bytecode = [0x60, 0x80, 0x60, 0x40];
+        assert_eq!(
+            strip_bytecode(&[0x60, 0x80, 0x60, 0x40, 0x01, 0x02], &bytecode),
+            Some(&[0x01, 0x02][..])
+        );
+        assert_eq!(strip_bytecode(&bytecode, &bytecode), Some(&[][..]));
+    }
+
+    #[test]
+    fn rejects_mismatching_creation_code() {
+        let bytecode = [0x60, 0x80, 0x60, 0x40];
+        // same length, different code
+        assert_eq!(strip_bytecode(&[0x60, 0x80, 0x60, 0x41, 0x01], &bytecode), None);
+        // shorter than the bytecode
+        assert_eq!(strip_bytecode(&[0x60, 0x80], &bytecode), None);
+    }
+}
@@ -1,6 +1,13 @@
 //! Create command
 use crate::{
-    cmd::{forge::build::CoreBuildArgs, Cmd},
+    cmd::{
+        forge::{
+            build::CoreBuildArgs,
+            deployments::{is_successful, Deployment, DeploymentRegistry},
+            verify::{provider::VerificationProviderType, VerifyArgs},
+        },
+        Cmd,
+    },
     compile,
     opts::{forge::ContractInfo, EthereumOpts, WalletType},
     utils::{parse_ether_value, parse_u256},
@@ -12,10 +19,16 @@ use ethers::{
     types::{transaction::eip2718::TypedTransaction, Chain, U256},
 };
 use eyre::{Context, Result};
+use foundry_config::Config;
 use foundry_utils::parse_tokens;
+use semver::Version;
 use serde_json::json;
 use std::{fs, path::PathBuf, sync::Arc};
 
+/// How often the verification of a contract deployed with `--verify` is retried while Etherscan
+/// has not indexed the contract, waiting 155 seconds in total
+const VERIFY_RETRIES: u32 = 5;
+
 #[derive(Debug, Clone, Parser)]
 pub struct CreateArgs {
     #[clap(help = "The contract identifier in the form `<path>:<contractname>`.")]
@@ -25,6 +38,9 @@ pub struct CreateArgs {
         long,
         multiple_values = true,
         help = "The constructor arguments.",
+        long_help = r#"The constructor arguments.
+
+Struct (tuple) and array arguments can be passed as JSON arrays of their components, e.g. `[1,["0x..",true]]`."#,
         name = "constructor_args",
         conflicts_with = "constructor_args_path"
     )]
@@ -33,6 +49,9 @@ pub struct CreateArgs {
     #[clap(
         long,
         help = "The path to a file containing the constructor arguments.",
+        long_help = r#"The path to a file containing the constructor arguments.
+
+The file either contains the space separated arguments or a JSON array with one element per argument."#,
         value_hint = ValueHint::FilePath,
         name = "constructor_args_path",
         conflicts_with = "constructor_args",
@@ -97,20 +116,46 @@ Examples: 1ether, 10gwei, 0.01ether"#,
         help = "Print the deployment information as JSON."
     )]
     json: bool,
+
+    #[clap(
+        long,
+        help = "Verify the contract on Etherscan after it was deployed.",
+        long_help = "Verify the contract on Etherscan after it was deployed. Requires the contract in the form `<path>:<contractname>` and an Etherscan API key."
+    )]
+    verify: bool,
 }
 
 impl Cmd for CreateArgs {
     type Output = ();
 
     fn run(self) -> Result<Self::Output> {
+        if self.verify && self.contract.path.is_none() {
+            eyre::bail!("`--verify` requires the contract in the form `<path>:<contractname>`")
+        }
+
         // Find Project & Compile
-        let project = self.opts.project()?;
+        let config = Config::from(&self.opts);
+        let project = config.project()?;
         let compiled = if self.json {
             // Supress compile stdout messages when printing json output
             compile::suppress_compile(&project)?
         } else {
             compile::compile(&project, false, false)?
         };
+        compile::compile_vyper(&project, &config, self.json)?;
+
+        // the compiler version is required for verification
+        let version = if self.verify {
+            let path = self.contract.path.as_ref().expect("`--verify` requires the path; qed");
+            let path = dunce::canonicalize(path)?;
+            compiled
+                .clone()
+                .into_artifacts()
+                .find(|(id, _)| id.name == self.contract.name && id.source == path)
+                .map(|(id, _)| id.version)
+        } else {
+            None
+        };
 
         // Get ABI and BIN
         let (abi, bin, _) =
@@ -122,7 +167,7 @@ impl Cmd for CreateArgs {
         };
 
         // Add arguments to constructor
-        let provider = Provider::<Http>::try_from(self.eth.rpc_url()?)?;
+        let provider = Provider::<Http>::try_from(self.eth.rpc_url()?.as_str())?;
         let params = match abi.constructor {
             Some(ref v) => {
                 let constructor_args =
@@ -131,7 +176,7 @@ impl Cmd for CreateArgs {
                             eyre::bail!("constructor args path not found");
                         }
                         let file = fs::read_to_string(constructor_args_path)?;
-                        file.split(' ').map(|s| s.to_string()).collect::<Vec<String>>()
+                        read_constructor_args_file(&file)
                     } else {
                         self.constructor_args.clone()
                     };
@@ -146,13 +191,13 @@ impl Cmd for CreateArgs {
         if let Some(signer) = rt.block_on(self.eth.signer_with(chain_id, provider))? {
             match signer {
                 WalletType::Ledger(signer) => {
-                    rt.block_on(self.deploy(abi, bin, params, signer))?;
+                    rt.block_on(self.deploy(abi, bin, params, signer, version))?;
                 }
                 WalletType::Local(signer) => {
-                    rt.block_on(self.deploy(abi, bin, params, signer))?;
+                    rt.block_on(self.deploy(abi, bin, params, signer, version))?;
                 }
                 WalletType::Trezor(signer) => {
-                    rt.block_on(self.deploy(abi, bin, params, signer))?;
+                    rt.block_on(self.deploy(abi, bin, params, signer, version))?;
                 }
             }
         } else {
@@ -170,6 +215,7 @@ impl CreateArgs {
         bin: BytecodeObject,
         args: Vec<Token>,
         provider: M,
+        version: Option<Version>,
     ) -> Result<()> {
         let chain = provider.get_chainid().await?.as_u64();
         let deployer_address =
@@ -180,6 +226,7 @@ impl CreateArgs {
         let provider = Arc::new(provider);
         let factory = ContractFactory::new(abi, bin, provider.clone());
 
+        let encoded_args = ethers::abi::encode(&args);
         let is_args_empty = args.is_empty();
         let deployer =
             factory.deploy_tokens(args).context("Failed to deploy contract").map_err(|e| {
@@ -241,6 +288,44 @@ impl CreateArgs {
             println!("Transaction hash: {:?}", receipt.transaction_hash);
         }
 
+        if is_successful(&receipt) {
+            let broadcast = Config::from(&self.opts).__root.0.join("broadcast");
+            let mut registry = DeploymentRegistry::load(&broadcast, chain)?;
+            registry.add(Deployment::new(
+                self.contract.path.as_ref().map_or_else(
+                    || self.contract.name.clone(),
+                    |path| format!("{path}:{}", self.contract.name),
+                ),
+                deployed_contract.address(),
+                &receipt,
+            ));
+            registry.save()?;
+        }
+
+        if self.verify {
+            let version = version.ok_or_else(|| {
+                eyre::eyre!("Could not find the compiler version of {}", self.contract.name)
+            })?;
+            let verify = VerifyArgs {
+                address: deployed_contract.address(),
+                contract: self.contract,
+                constructor_args: (!encoded_args.is_empty()).then(|| hex::encode(&encoded_args)),
+                compiler_version: etherscan_compiler_version(&version),
+                num_of_optimizations: None,
+                chain: chain.into(),
+                etherscan_key: self.eth.etherscan_api_key,
+                flatten: false,
+                force: false,
+                verifier: VerificationProviderType::Etherscan,
+                // the contract was just deployed, so Etherscan most likely did not index it yet
+                retries: VERIFY_RETRIES,
+                project_paths: self.opts.project_paths,
+            };
+            verify.run().await.wrap_err(
+                "Failed to verify the deployed contract, consider retrying with `forge verify-contract`",
+            )?;
+        }
+
         Ok(())
     }
 
@@ -259,3 +344,31 @@ impl CreateArgs {
         parse_tokens(params, true)
     }
 }
+
+/// Reads the constructor arguments from the content of a constructor args file
+///
+/// If the content is a JSON array, every element is a separate argument, so that struct arguments
+/// can be given as nested JSON values.
+fn read_constructor_args_file(content: &str) -> Vec<String> {
+    match serde_json::from_str::<serde_json::Value>(content) {
+        Ok(serde_json::Value::Array(args)) => args
+            .into_iter()
+            .map(|arg| match arg {
+                serde_json::Value::String(s) => s,
+                arg => arg.to_string(),
+            })
+            .collect(),
+        _ => content.split(' ').map(|s| s.to_string()).collect(),
+    }
+}
+
+/// Returns the compiler version in the format etherscan expects, `v0.8.10+commit.fc410830`
+fn etherscan_compiler_version(version: &Version) -> String {
+    let v = format!("v{}.{}.{}", version.major, version.minor, version.patch);
+    // the build metadata of the version reported by solc also contains the platform, e.g.
+    // `commit.fc410830.Linux.g++`
+    match version.build.as_str().split('.').take(2).collect::<Vec<_>>().as_slice() {
+        ["commit", hash] => format!("{}+commit.{}", v, hash),
+        _ => v,
+    }
+}
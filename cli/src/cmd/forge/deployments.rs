@@ -0,0 +1,160 @@
+//! The registry of the contracts deployed with `forge create` and `forge script --broadcast`
+use ethers::{
+    solc::artifacts::StorageLayout,
+    types::{Address, TransactionReceipt, TxHash},
+};
+use serde::{Deserialize, Serialize};
+use std::{
+    collections::BTreeMap,
+    io::BufWriter,
+    path::{Path, PathBuf},
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+/// A deployed contract
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Deployment {
+    /// The deployed contract in the form `<path>:<contractname>`, or `<contractname>` if the path
+    /// is not known
+    pub contract: String,
+    pub address: Address,
+    pub transaction_hash: TxHash,
+    pub block_number: Option<u64>,
+    /// The unix timestamp of the deployment
+    pub timestamp: u64,
+    /// The storage layout of the contract, used to check the safety of upgrades to a new
+    /// implementation
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub storage_layout: Option<StorageLayout>,
+}
+
+impl Deployment {
+    /// Creates the deployment of `contract` from the receipt of its creation transaction
+    pub fn new(
+        contract: impl Into<String>,
+        address: Address,
+        receipt: &TransactionReceipt,
+    ) -> Self {
+        Self {
+            contract: contract.into(),
+            address,
+            transaction_hash: receipt.transaction_hash,
+            block_number: receipt.block_number.map(|number| number.as_u64()),
+            timestamp: now(),
+            storage_layout: None,
+        }
+    }
+
+    /// The name of the deployed contract
+    pub fn name(&self) -> &str {
+        self.contract.rsplit(':').next().unwrap_or(&self.contract)
+    }
+}
+
+/// Returns `true` if the transaction of the receipt succeeded, contracts of reverted creation
+/// transactions were not deployed and are not recorded
+pub fn is_successful(receipt: &TransactionReceipt) -> bool {
+    receipt.status == Some(1u64.into())
+}
+
+/// The latest deployment of every contract on a chain, written to
+/// `<broadcast>/deployments/<chain id>.json`.
+///
+/// The registry is the single place where tools, e.g. frontends of a local development stack, can
+/// look up the addresses of the project's contracts.
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DeploymentRegistry {
+    pub chain_id: u64,
+    /// The latest deployment of each contract, by contract name
+    pub contracts: BTreeMap<String, Deployment>,
+    #[serde(skip)]
+    pub path: PathBuf,
+}
+
+impl DeploymentRegistry {
+    /// Loads the registry of the chain, which is empty if nothing was deployed to the chain yet
+    pub fn load(broadcast: &Path, chain_id: u64) -> eyre::Result<Self> {
+        let path = broadcast.join("deployments").join(format!("{chain_id}.json"));
+        let mut registry = if path.exists() {
+            serde_json::from_reader(std::fs::File::open(&path)?)?
+        } else {
+            DeploymentRegistry { chain_id, ..Default::default() }
+        };
+        registry.path = path;
+        Ok(registry)
+    }
+
+    /// Records the deployment, replacing the previous deployment of a contract with the same name
+    pub fn add(&mut self, deployment: Deployment) {
+        self.contracts.insert(deployment.name().to_string(), deployment);
+    }
+
+    /// Returns the latest deployment at the address
+    pub fn find_by_address(&self, address: Address) -> Option<&Deployment> {
+        self.contracts.values().find(|deployment| deployment.address == address)
+    }
+
+    /// Returns the names of the deployed contracts by address
+    pub fn names(&self) -> BTreeMap<Address, &str> {
+        self.contracts
+            .iter()
+            .map(|(name, deployment)| (deployment.address, name.as_str()))
+            .collect()
+    }
+
+    pub fn save(&self) -> eyre::Result<()> {
+        if let Some(parent) = self.path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        let writer = BufWriter::new(std::fs::File::create(&self.path)?);
+        serde_json::to_writer_pretty(writer, self)?;
+        Ok(())
+    }
+}
+
+/// Returns the current unix timestamp in seconds
+fn now() -> u64 {
+    SystemTime::now().duration_since(UNIX_EPOCH).expect("time went backwards").as_secs()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn can_save_and_load_registry() {
+        let tmp = tempfile::tempdir().unwrap();
+        let mut registry = DeploymentRegistry::load(tmp.path(), 31337).unwrap();
+        assert!(registry.contracts.is_empty());
+
+        let receipt = TransactionReceipt::default();
+        registry.add(Deployment::new(
+            "src/Counter.sol:Counter",
+            Address::from_low_u64_be(1),
+            &receipt,
+        ));
+        registry.add(Deployment::new("Counter", Address::from_low_u64_be(2), &receipt));
+        registry.add(Deployment::new("src/Token.sol:Token", Address::from_low_u64_be(3), &receipt));
+        registry.save().unwrap();
+
+        let loaded = DeploymentRegistry::load(tmp.path(), 31337).unwrap();
+        assert_eq!(loaded, registry);
+        assert_eq!(loaded.path, tmp.path().join("deployments/31337.json"));
+        assert_eq!(loaded.contracts["Counter"].address, Address::from_low_u64_be(2));
+        assert_eq!(loaded.names()[&Address::from_low_u64_be(3)], "Token");
+    }
+
+    #[test]
+    fn only_successful_receipts_are_deployments() {
+        let receipt = |status: Option<u64>| TransactionReceipt {
+            status: status.map(Into::into),
+            ..Default::default()
+        };
+        assert!(is_successful(&receipt(Some(1))));
+        assert!(!is_successful(&receipt(Some(0))));
+        // pre-byzantium receipts have no status
+        assert!(!is_successful(&receipt(None)));
+    }
+}
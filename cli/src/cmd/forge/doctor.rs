@@ -0,0 +1,547 @@
+//! doctor command
+
+use crate::cmd::forge::build::ProjectPathsArgs;
+use ansi_term::Colour;
+use clap::Parser;
+use ethers::{
+    providers::{Http, Middleware, Provider},
+    solc::{remappings::Remapping, Solc},
+};
+use foundry_config::{figment::Figment, remappings_from_newline, Config, SolcReq};
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    fmt,
+    path::Path,
+    process::Command,
+    time::Duration,
+};
+
+foundry_config::impl_figment_convert!(DoctorArgs, opts);
+
+/// How long to wait for an RPC endpoint to return its chain id
+const RPC_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Command to check the environment of the project and suggest fixes for the problems it finds
+#[derive(Debug, Clone, Parser)]
+pub struct DoctorArgs {
+    #[clap(long, help = "Do not check whether the configured RPC endpoints are reachable.")]
+    no_rpc: bool,
+
+    #[clap(flatten, next_help_heading = "PROJECT OPTIONS")]
+    opts: ProjectPathsArgs,
+}
+
+impl DoctorArgs {
+    pub async fn run(self) -> eyre::Result<()> {
+        let figment: Figment = From::from(&self);
+        let mut diagnostics = Vec::new();
+
+        match Config::try_from(figment) {
+            Ok(config) => {
+                let config = config.sanitized();
+                diagnostics.extend(check_config_file(&config));
+                diagnostics.extend(check_solc(&config));
+                diagnostics.extend(check_submodules(&config.__root.0));
+                diagnostics.extend(check_remappings(&config));
+                if !self.no_rpc && !config.offline {
+                    diagnostics.extend(check_rpc_endpoints(&config).await);
+                }
+            }
+            Err(err) => diagnostics.push(Diagnostic::error(
+                format!("The config could not be loaded: {err}"),
+                "Fix the reported value in foundry.toml or the FOUNDRY_ environment variables",
+            )),
+        }
+
+        for diagnostic in diagnostics.iter() {
+            println!("{diagnostic}");
+        }
+
+        let errors = diagnostics.iter().filter(|d| d.severity == Severity::Error).count();
+        let warnings = diagnostics.iter().filter(|d| d.severity == Severity::Warning).count();
+        println!();
+        if errors + warnings == 0 {
+            println!("{}", Colour::Green.paint("No problems found"));
+        } else {
+            println!("Found {errors} errors and {warnings} warnings");
+        }
+        if errors > 0 {
+            std::process::exit(1);
+        }
+        Ok(())
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Severity {
+    Ok,
+    Warning,
+    Error,
+}
+
+/// The result of a single check, with a suggested fix for problems
+#[derive(Debug, Clone)]
+struct Diagnostic {
+    severity: Severity,
+    message: String,
+    fix: Option<String>,
+}
+
+impl Diagnostic {
+    fn ok(message: impl Into<String>) -> Self {
+        Self { severity: Severity::Ok, message: message.into(), fix: None }
+    }
+
+    fn warning(message: impl Into<String>, fix: impl Into<String>) -> Self {
+        Self { severity: Severity::Warning, message: message.into(), fix: Some(fix.into()) }
+    }
+
+    fn error(message: impl Into<String>, fix: impl Into<String>) -> Self {
+        Self { severity: Severity::Error, message: message.into(), fix: Some(fix.into()) }
+    }
+}
+
+impl fmt::Display for Diagnostic {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let status = match self.severity {
+            Severity::Ok => Colour::Green.paint("[OK]"),
+            Severity::Warning => Colour::Yellow.paint("[WARN]"),
+            Severity::Error => Colour::Red.paint("[ERROR]"),
+        };
+        write!(f, "{status} {}", self.message)?;
+        if let Some(ref fix) = self.fix {
+            write!(f, "\n       fix: {fix}")?;
+        }
+        Ok(())
+    }
+}
+
+/// Checks that the project has a `foundry.toml` which defines the selected profile
+fn check_config_file(config: &Config) -> Vec<Diagnostic> {
+    let path = config.__root.0.join(Config::FILE_NAME);
+    let content = match std::fs::read_to_string(&path) {
+        Ok(content) => content,
+        Err(_) => {
+            return vec![Diagnostic::warning(
+                format!("No {} found in {}", Config::FILE_NAME, config.__root.0.display()),
+                "Run `forge init` or add a foundry.toml, otherwise the defaults are used",
+            )]
+        }
+    };
+    let toml = match content.parse::<toml::Value>() {
+        Ok(toml) => toml,
+        Err(err) => {
+            return vec![Diagnostic::error(
+                format!("{} is not valid TOML: {err}", path.display()),
+                "Fix the syntax error",
+            )]
+        }
+    };
+
+    let profile = config.profile.as_str();
+    if profile_section(&toml, profile).is_some() || profile == Config::DEFAULT_PROFILE.as_str() {
+        vec![Diagnostic::ok(format!("{} uses the profile `{profile}`", path.display()))]
+    } else {
+        vec![Diagnostic::warning(
+            format!("The selected profile `{profile}` is not defined in {}", path.display()),
+            format!("Add a `[profile.{profile}]` section or unset FOUNDRY_PROFILE"),
+        )]
+    }
+}
+
+/// The section of the profile in foundry.toml, either `[<profile>]` or `[profile.<profile>]`
+fn profile_section<'a>(toml: &'a toml::Value, profile: &str) -> Option<&'a toml::Value> {
+    toml.get(profile)
+        .or_else(|| toml.get(Config::PROFILE_SECTION).and_then(|profiles| profiles.get(profile)))
+}
+
+/// Checks that the installed solc versions can be run and the configured solc is available
+fn check_solc(config: &Config) -> Vec<Diagnostic> {
+    let mut diagnostics = Vec::new();
+
+    let mut installed = Vec::new();
+    for version in Solc::installed_versions() {
+        let version = version.as_ref().to_string();
+        match Solc::find_svm_installed_version(&version) {
+            Ok(Some(solc)) if solc.version().is_ok() => installed.push(version),
+            _ => {
+                let dir = Solc::svm_home()
+                    .map(|home| home.join(&version).display().to_string())
+                    .unwrap_or_else(|| version.clone());
+                diagnostics.push(Diagnostic::error(
+                    format!("The installed solc {version} can not be run"),
+                    format!("Remove {dir}, it will be installed again when it's needed"),
+                ));
+            }
+        }
+    }
+    if installed.is_empty() {
+        diagnostics.push(Diagnostic::warning(
+            "No solc versions are installed",
+            "Run `forge build`, the required versions are installed automatically",
+        ));
+    } else {
+        diagnostics
+            .push(Diagnostic::ok(format!("Installed solc versions: {}", installed.join(", "))));
+    }
+
+    match config.solc {
+        Some(SolcReq::Version(ref version)) => {
+            if installed.contains(&version.to_string()) {
+                diagnostics
+                    .push(Diagnostic::ok(format!("The configured solc {version} is installed")));
+            } else if config.offline {
+                diagnostics.push(Diagnostic::error(
+                    format!("The configured solc {version} is not installed and `offline` is set"),
+                    "Build once without `offline` to install it",
+                ));
+            } else {
+                diagnostics.push(Diagnostic::warning(
+                    format!("The configured solc {version} is not installed"),
+                    "Run `forge build` to install it",
+                ));
+            }
+        }
+        Some(SolcReq::Local(ref path)) => match Solc::new(path).version() {
+            Ok(version) => diagnostics.push(Diagnostic::ok(format!(
+                "The configured solc {} is {version}",
+                path.display()
+            ))),
+            Err(err) => diagnostics.push(Diagnostic::error(
+                format!("The configured solc {} can not be run: {err}", path.display()),
+                "Point `solc` in foundry.toml to a solc binary or to a version",
+            )),
+        },
+        None => {}
+    }
+
+    diagnostics
+}
+
+/// Checks that all git submodules of the project are initialized and checked out at the recorded
+/// commits
+fn check_submodules(root: &Path) -> Vec<Diagnostic> {
+    let output = match Command::new("git").args(&["submodule", "status"]).current_dir(root).output()
+    {
+        Ok(output) if output.status.success() => output,
+        Ok(_) => {
+            return vec![Diagnostic::warning(
+                format!("{} is not a git repository", root.display()),
+                "Run `git init`, dependencies are installed as git submodules",
+            )]
+        }
+        Err(err) => {
+            return vec![Diagnostic::error(
+                format!("Failed to run git: {err}"),
+                "Install git, it's needed to install dependencies",
+            )]
+        }
+    };
+
+    let mut diagnostics = Vec::new();
+    let status = String::from_utf8_lossy(&output.stdout);
+    let submodules: Vec<_> = status.lines().filter(|line| !line.is_empty()).collect();
+    for line in submodules.iter() {
+        let path = line[1..].split_whitespace().nth(1).unwrap_or_default();
+        match line.chars().next() {
+            Some('-') => diagnostics.push(Diagnostic::error(
+                format!("The submodule {path} is not initialized"),
+                "Run `git submodule update --init --recursive`",
+            )),
+            Some('+') => diagnostics.push(Diagnostic::warning(
+                format!("The submodule {path} is not checked out at the recorded commit"),
+                format!(
+                    "Run `git submodule update {path}`, or `git add {path}` to record the commit"
+                ),
+            )),
+            Some('U') => diagnostics.push(Diagnostic::error(
+                format!("The submodule {path} has merge conflicts"),
+                "Resolve the conflicts and commit the submodule",
+            )),
+            _ => {}
+        }
+    }
+    if diagnostics.is_empty() {
+        diagnostics
+            .push(Diagnostic::ok(format!("{} git submodules are up to date", submodules.len())));
+    }
+    diagnostics
+}
+
+/// Where a remapping is declared, in the order in which they take precedence
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+enum RemappingSource {
+    File,
+    Toml,
+    Lib,
+}
+
+impl fmt::Display for RemappingSource {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            RemappingSource::File => f.write_str("remappings.txt"),
+            RemappingSource::Toml => f.write_str(Config::FILE_NAME),
+            RemappingSource::Lib => f.write_str("auto-detected"),
+        }
+    }
+}
+
+/// Collects the remappings of remappings.txt, foundry.toml and the libraries separately, unlike
+/// [Config::get_all_remappings] which only keeps the remapping that takes precedence.
+///
+/// The paths are relative to the root of the project.
+fn declared_remappings(config: &Config) -> Result<Vec<(RemappingSource, Remapping)>, Diagnostic> {
+    let root = &config.__root.0;
+    let invalid = |source: RemappingSource, err: String| {
+        Diagnostic::error(
+            format!("{source} contains an invalid remapping: {err}"),
+            "Fix or remove it",
+        )
+    };
+    let mut remappings = Vec::new();
+
+    if let Ok(content) = std::fs::read_to_string(root.join("remappings.txt")) {
+        for remapping in remappings_from_newline(&content) {
+            let remapping =
+                remapping.map_err(|err| invalid(RemappingSource::File, err.to_string()))?;
+            remappings.push((RemappingSource::File, remapping));
+        }
+    }
+
+    let toml = std::fs::read_to_string(root.join(Config::FILE_NAME))
+        .ok()
+        .and_then(|content| content.parse::<toml::Value>().ok());
+    let declared = toml.as_ref().and_then(|toml| {
+        profile_section(toml, config.profile.as_str())?.get("remappings")?.as_array()
+    });
+    for remapping in declared.into_iter().flatten() {
+        let remapping = remapping
+            .as_str()
+            .unwrap_or_default()
+            .parse::<Remapping>()
+            .map_err(|err| invalid(RemappingSource::Toml, err.to_string()))?;
+        remappings.push((RemappingSource::Toml, remapping));
+    }
+
+    for lib in config.libs.iter() {
+        remappings.extend(
+            Remapping::find_many(root.join(lib)).into_iter().map(|r| (RemappingSource::Lib, r)),
+        );
+    }
+
+    for (_, remapping) in remappings.iter_mut() {
+        let path = Path::new(&remapping.path);
+        let path = path.strip_prefix(root).unwrap_or(path);
+        let path = path.strip_prefix(".").unwrap_or(path);
+        remapping.path = path.display().to_string().trim_end_matches('/').to_string();
+    }
+    Ok(remappings)
+}
+
+/// Checks that no remapping is declared with several paths, across remappings.txt, foundry.toml
+/// and the auto-detected remappings of the libraries, and that all remapped paths exist
+fn check_remappings(config: &Config) -> Vec<Diagnostic> {
+    let remappings = match declared_remappings(config) {
+        Ok(remappings) => remappings,
+        Err(diagnostic) => return vec![diagnostic],
+    };
+
+    // the sources that declare each path of a remapping
+    let mut declared: BTreeMap<&str, BTreeMap<&str, BTreeSet<RemappingSource>>> = BTreeMap::new();
+    for (source, Remapping { name, path }) in remappings.iter() {
+        declared.entry(name).or_default().entry(path).or_default().insert(*source);
+    }
+
+    let mut diagnostics = Vec::new();
+    for (name, paths) in declared.iter() {
+        if paths.len() > 1 {
+            let list = paths
+                .iter()
+                .map(|(path, sources)| {
+                    let sources = sources.iter().map(|s| s.to_string()).collect::<Vec<_>>();
+                    format!("{path} ({})", sources.join(", "))
+                })
+                .collect::<Vec<_>>()
+                .join(", ");
+            let explicit = paths
+                .values()
+                .filter(|sources| sources.iter().any(|s| *s != RemappingSource::Lib))
+                .count();
+            if explicit > 1 {
+                diagnostics.push(Diagnostic::error(
+                    format!("The remapping `{name}` is declared with several paths: {list}"),
+                    "Keep only one of them, remappings.txt takes precedence over foundry.toml",
+                ));
+            } else {
+                // the path of the source with the highest precedence is used
+                let used = paths
+                    .iter()
+                    .min_by_key(|(_, sources)| sources.iter().next())
+                    .map(|(path, _)| *path)
+                    .unwrap_or_default();
+                diagnostics.push(Diagnostic::warning(
+                    format!("The remapping `{name}` points to several paths: {list}, {used} is used"),
+                    format!(
+                        "Make sure {used} is the intended path, or declare `{name}` in remappings.txt"
+                    ),
+                ));
+            }
+        }
+        for path in paths.keys() {
+            if !config.__root.0.join(path).exists() {
+                diagnostics.push(Diagnostic::warning(
+                    format!("The remapping `{name}` points to {path}, which does not exist"),
+                    "Fix the remapping or install the missing dependency with `forge install`",
+                ));
+            }
+        }
+    }
+    if diagnostics.is_empty() {
+        diagnostics.push(Diagnostic::ok(format!("{} remappings are valid", declared.len())));
+    }
+    diagnostics
+}
+
+/// Checks that `eth_rpc_url` and all `[rpc_endpoints]` can be resolved and return a chain id
+async fn check_rpc_endpoints(config: &Config) -> Vec<Diagnostic> {
+    let mut endpoints: Vec<_> = config
+        .eth_rpc_url
+        .iter()
+        .map(|url| ("eth_rpc_url".to_string(), config.resolve_rpc_url(url)))
+        .collect();
+    endpoints.extend(
+        config
+            .rpc_endpoints
+            .iter()
+            .map(|(alias, endpoint)| (format!("rpc_endpoints.{alias}"), endpoint.resolve())),
+    );
+
+    let mut diagnostics = Vec::new();
+    for (name, url) in endpoints {
+        let url = match url {
+            Ok(url) => url,
+            Err(err) => {
+                diagnostics.push(Diagnostic::error(
+                    format!("The RPC endpoint `{name}` can not be resolved: {err}"),
+                    format!("Set the environment variable `{}`", err.var),
+                ));
+                continue
+            }
+        };
+        let provider = match Provider::<Http>::try_from(url.as_str()) {
+            Ok(provider) => provider,
+            Err(err) => {
+                diagnostics.push(Diagnostic::error(
+                    format!("The RPC endpoint `{name}` is not a valid URL: {err}"),
+                    "Fix the URL in foundry.toml",
+                ));
+                continue
+            }
+        };
+        // the URL isn't printed since it usually contains an API key
+        match tokio::time::timeout(RPC_TIMEOUT, provider.get_chainid()).await {
+            Ok(Ok(chain_id)) => diagnostics.push(Diagnostic::ok(format!(
+                "The RPC endpoint `{name}` is reachable (chain id {chain_id})"
+            ))),
+            Ok(Err(err)) => diagnostics.push(Diagnostic::error(
+                format!("The RPC endpoint `{name}` is not reachable: {err}"),
+                "Check the URL, the API key and your network connection",
+            )),
+            Err(_) => diagnostics.push(Diagnostic::error(
+                format!("The RPC endpoint `{name}` did not respond within {RPC_TIMEOUT:?}"),
+                "Check the URL and your network connection",
+            )),
+        }
+    }
+    diagnostics
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use foundry_config::{figment::Profile, RootPath};
+    use std::fs;
+
+    fn config(root: &Path) -> Config {
+        Config {
+            __root: RootPath(root.to_path_buf()),
+            libs: vec!["lib".into()],
+            ..Default::default()
+        }
+    }
+
+    fn severities(diagnostics: &[Diagnostic]) -> Vec<Severity> {
+        diagnostics.iter().map(|d| d.severity).collect()
+    }
+
+    #[test]
+    fn can_check_config_file() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut config = config(dir.path());
+        assert_eq!(severities(&check_config_file(&config)), vec![Severity::Warning]);
+
+        fs::write(dir.path().join(Config::FILE_NAME), "[profile.default\n").unwrap();
+        assert_eq!(severities(&check_config_file(&config)), vec![Severity::Error]);
+
+        fs::write(dir.path().join(Config::FILE_NAME), "[profile.default]\nsrc = 'src'\n").unwrap();
+        assert_eq!(severities(&check_config_file(&config)), vec![Severity::Ok]);
+
+        config.profile = Profile::new("ci");
+        assert_eq!(severities(&check_config_file(&config)), vec![Severity::Warning]);
+
+        fs::write(dir.path().join(Config::FILE_NAME), "[ci]\nsrc = 'src'\n").unwrap();
+        assert_eq!(severities(&check_config_file(&config)), vec![Severity::Ok]);
+    }
+
+    #[test]
+    fn can_check_remappings() {
+        let dir = tempfile::tempdir().unwrap();
+        let root = dir.path();
+        fs::create_dir_all(root.join("lib/ds-test/src")).unwrap();
+        fs::create_dir_all(root.join("lib/forge-std/lib/ds-test/src")).unwrap();
+        // only the declared remappings are checked
+        let config = Config { libs: vec![], ..config(root) };
+
+        fs::write(root.join("remappings.txt"), "ds-test/=lib/ds-test/src/\n").unwrap();
+        fs::write(
+            root.join(Config::FILE_NAME),
+            "[default]\nremappings = ['ds-test/=./lib/ds-test/src/']\n",
+        )
+        .unwrap();
+        assert_eq!(severities(&check_remappings(&config)), vec![Severity::Ok]);
+
+        // remappings.txt and foundry.toml disagree
+        fs::write(
+            root.join(Config::FILE_NAME),
+            "[default]\nremappings = ['ds-test/=lib/forge-std/lib/ds-test/src/']\n",
+        )
+        .unwrap();
+        let diagnostics = check_remappings(&config);
+        assert_eq!(severities(&diagnostics), vec![Severity::Error]);
+        assert!(diagnostics[0].message.contains("lib/ds-test/src (remappings.txt)"));
+        assert!(diagnostics[0].message.contains("lib/forge-std/lib/ds-test/src (foundry.toml)"));
+
+        // missing paths are reported
+        fs::write(root.join(Config::FILE_NAME), "[default]\n").unwrap();
+        fs::write(root.join("remappings.txt"), "solmate/=lib/solmate/src/\n").unwrap();
+        assert_eq!(severities(&check_remappings(&config)), vec![Severity::Warning]);
+
+        fs::write(root.join("remappings.txt"), "ds-test/=lib/ds-test/src\nds-test\n").unwrap();
+        assert_eq!(severities(&check_remappings(&config)), vec![Severity::Error]);
+    }
+
+    #[test]
+    fn warns_about_overridden_lib_remappings() {
+        let dir = tempfile::tempdir().unwrap();
+        let root = dir.path();
+        fs::create_dir_all(root.join("lib/solmate/src")).unwrap();
+        fs::write(root.join("lib/solmate/src/Token.sol"), "contract Token {}").unwrap();
+        let config = config(root);
+
+        fs::write(root.join("remappings.txt"), "solmate/=lib/solmate/\n").unwrap();
+        let diagnostics = check_remappings(&config);
+        assert_eq!(severities(&diagnostics), vec![Severity::Warning]);
+        assert!(diagnostics[0].message.contains("lib/solmate/src (auto-detected)"));
+        assert!(diagnostics[0].message.ends_with("lib/solmate is used"));
+    }
+}
@@ -39,6 +39,7 @@ impl Cmd for FlattenArgs {
             force: false,
             libraries: vec![],
             via_ir: false,
+            jobs: None,
             revert_strings: None,
         };
 
@@ -12,7 +12,8 @@ use ethers::solc::ProjectPathsConfig;
 use rayon::prelude::*;
 use similar::{ChangeTag, TextDiff};
 
-use forge_fmt::{Formatter, FormatterConfig, Visitable};
+use forge_fmt::{Formatter, Visitable};
+use foundry_config::Config;
 
 use crate::cmd::Cmd;
 
@@ -67,6 +68,11 @@ impl Cmd for FmtArgs {
     type Output = ();
 
     fn run(self) -> eyre::Result<Self::Output> {
+        let config = match self.root {
+            Some(ref root) => Config::load_with_root(root),
+            None => Config::load(),
+        };
+
         let root = if let Some(path) = self.path {
             path
         } else {
@@ -110,7 +116,7 @@ impl Cmd for FmtArgs {
 
                 let mut output = String::new();
                 let mut formatter =
-                    Formatter::new(&mut output, &source, FormatterConfig::default());
+                    Formatter::new(&mut output, &source, config.fmt);
 
                 source_unit.visit(&mut formatter).unwrap();
 
@@ -168,7 +174,11 @@ impl Cmd for FmtArgs {
                         return Ok(Some(diff_summary))
                     }
                 } else if let Input::Path(path) = input {
-                    std::fs::write(path, output)?;
+                    // unchanged files are not written, so they don't trigger watchers like
+                    // `forge test --watch`
+                    if source != output {
+                        std::fs::write(path, output)?;
+                    }
                 }
 
                 Ok(None)
@@ -52,13 +52,22 @@ pub struct InitArgs {
         long
     )]
     vscode: bool,
+    #[clap(
+        help = "Use the Hardhat-style project layout.",
+        long_help = "Use the Hardhat-style project layout. Contracts are placed in `contracts` and `node_modules` is added to the library paths.",
+        conflicts_with = "template",
+        long,
+        alias = "hh"
+    )]
+    hardhat: bool,
 }
 
 impl Cmd for InitArgs {
     type Output = ();
 
     fn run(self) -> eyre::Result<Self::Output> {
-        let InitArgs { root, template, no_git, no_commit, quiet, offline, force, vscode } = self;
+        let InitArgs { root, template, no_git, no_commit, quiet, offline, force, vscode, hardhat } =
+            self;
 
         let root = root.unwrap_or_else(|| std::env::current_dir().unwrap());
         // create the root dir if it does not exist
@@ -96,7 +105,8 @@ impl Cmd for InitArgs {
             p_println!(!quiet => "Initializing {}...", root.display());
 
             // make the dirs
-            let src = root.join("src");
+            let src_dir = if hardhat { "contracts" } else { "src" };
+            let src = root.join(src_dir);
             std::fs::create_dir_all(&src)?;
 
             let test = root.join("test");
@@ -112,7 +122,11 @@ impl Cmd for InitArgs {
             let dest = root.join(Config::FILE_NAME);
             if !dest.exists() {
                 // write foundry.toml
-                let config = Config::load_with_root(&root).into_basic();
+                let mut config = Config::load_with_root(&root).into_basic();
+                if hardhat {
+                    config.src = src_dir.into();
+                    config.libs = vec!["node_modules".into(), "lib".into()];
+                }
                 std::fs::write(dest, config.to_string_pretty()?)?;
             }
 
@@ -134,7 +148,7 @@ impl Cmd for InitArgs {
             }
             // vscode init
             if vscode {
-                init_vscode(&root)?;
+                init_vscode(&root, src_dir)?;
             }
         }
 
@@ -187,7 +201,7 @@ fn init_git_repo(root: &Path, no_commit: bool) -> eyre::Result<()> {
 }
 
 /// initializes the `.vscode/settings.json` file
-fn init_vscode(root: &Path) -> eyre::Result<()> {
+fn init_vscode(root: &Path, src: &str) -> eyre::Result<()> {
     let remappings_file = root.join("remappings.txt");
     if !remappings_file.exists() {
         let remappings = remappings::relative_remappings(&root.join("lib"), root)
@@ -215,7 +229,7 @@ fn init_vscode(root: &Path) -> eyre::Result<()> {
     // insert [vscode-solidity settings](https://github.com/juanfranblanco/vscode-solidity)
     let src_key = "solidity.packageDefaultDependenciesContractsDirectory";
     if !obj.contains_key(src_key) {
-        obj.insert(src_key.to_string(), serde_json::Value::String("src".to_string()));
+        obj.insert(src_key.to_string(), serde_json::Value::String(src.to_string()));
     }
     let lib_key = "solidity.packageDefaultDependenciesDirectory";
     if !obj.contains_key(lib_key) {
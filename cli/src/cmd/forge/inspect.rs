@@ -95,6 +95,9 @@ pub struct InspectArgs {
     #[clap(help = "The contract artifact field to inspect.")]
     pub field: ContractArtifactFields,
 
+    #[clap(help = "Print JSON fields on a single line instead of pretty printing them.", long)]
+    pub raw: bool,
+
     /// All build arguments are supported
     #[clap(flatten)]
     build: build::CoreBuildArgs,
@@ -103,7 +106,7 @@ pub struct InspectArgs {
 impl Cmd for InspectArgs {
     type Output = ();
     fn run(self) -> eyre::Result<Self::Output> {
-        let InspectArgs { contract, field, build } = self;
+        let InspectArgs { contract, field, raw, build } = self;
 
         // Map field to ContractOutputSelection
         let mut cos = build.compiler.extra_output.unwrap_or_default();
@@ -169,7 +172,7 @@ impl Cmd for InspectArgs {
         // Match on ContractArtifactFields and Pretty Print
         match field {
             ContractArtifactFields::Abi => {
-                println!("{}", serde_json::to_string_pretty(&to_value(&artifact.abi)?)?);
+                println!("{}", format_json(&artifact.abi, raw)?);
             }
             ContractArtifactFields::Bytecode => {
                 let tval: Value = to_value(&artifact.bytecode)?;
@@ -198,19 +201,16 @@ impl Cmd for InspectArgs {
                 );
             }
             ContractArtifactFields::MethodIdentifiers => {
-                println!(
-                    "{}",
-                    serde_json::to_string_pretty(&to_value(&artifact.method_identifiers)?)?
-                );
+                println!("{}", format_json(&artifact.method_identifiers, raw)?);
             }
             ContractArtifactFields::GasEstimates => {
-                println!("{}", serde_json::to_string_pretty(&to_value(&artifact.gas_estimates)?)?);
+                println!("{}", format_json(&artifact.gas_estimates, raw)?);
             }
             ContractArtifactFields::StorageLayout => {
-                println!("{}", serde_json::to_string_pretty(&to_value(&artifact.storage_layout)?)?);
+                println!("{}", format_json(&artifact.storage_layout, raw)?);
             }
             ContractArtifactFields::DevDoc => {
-                println!("{}", serde_json::to_string_pretty(&to_value(&artifact.devdoc)?)?);
+                println!("{}", format_json(&artifact.devdoc, raw)?);
             }
             ContractArtifactFields::Ir => {
                 println!(
@@ -229,10 +229,10 @@ impl Cmd for InspectArgs {
                 );
             }
             ContractArtifactFields::Metadata => {
-                println!("{}", serde_json::to_string_pretty(&to_value(&artifact.metadata)?)?);
+                println!("{}", format_json(&artifact.metadata, raw)?);
             }
             ContractArtifactFields::UserDoc => {
-                println!("{}", serde_json::to_string_pretty(&to_value(&artifact.userdoc)?)?);
+                println!("{}", format_json(&artifact.userdoc, raw)?);
             }
             ContractArtifactFields::Ewasm => {
                 println!(
@@ -247,3 +247,9 @@ impl Cmd for InspectArgs {
         Ok(())
     }
 }
+
+/// Returns the field as pretty printed JSON, or on a single line if `raw` is set
+fn format_json(field: &impl serde::Serialize, raw: bool) -> eyre::Result<String> {
+    let value = to_value(field)?;
+    Ok(if raw { serde_json::to_string(&value)? } else { serde_json::to_string_pretty(&value)? })
+}
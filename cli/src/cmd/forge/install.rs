@@ -1,7 +1,11 @@
 //! Create command
 use std::{path::PathBuf, str};
 
-use crate::{cmd::Cmd, opts::forge::Dependency, utils::p_println};
+use crate::{
+    cmd::{forge::remappings::add_lib_remappings, Cmd},
+    opts::forge::Dependency,
+    utils::p_println,
+};
 use ansi_term::Colour;
 use clap::{Parser, ValueHint};
 use foundry_config::find_project_root_path;
@@ -98,6 +102,10 @@ pub(crate) fn install(
         }
 
         p_println!(!quiet => "    {} {}",    Colour::Green.paint("Installed"), dep.name);
+
+        for remapping in add_lib_remappings(root, &libs.join(target_dir))? {
+            p_println!(!quiet => "    {} remapping {}", Colour::Green.paint("Added"), remapping);
+        }
     }
     Ok(())
 }
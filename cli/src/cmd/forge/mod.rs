@@ -6,7 +6,7 @@
 //! [`foundry_config::Config`].
 //!
 //! See [`BuildArgs`] for a reference implementation.
-//! And [`RunArgs`] for how to merge `Providers`.
+//! And [`ScriptArgs`] for how to merge `Providers`.
 //!
 //! # Example
 //!
@@ -39,17 +39,23 @@
 
 pub mod bind;
 pub mod build;
+pub mod clone;
 pub mod config;
+pub mod constructor_args;
 pub mod create;
+pub mod deployments;
+pub mod doctor;
 pub mod flatten;
 pub mod fmt;
 pub mod init;
 pub mod inspect;
 pub mod install;
 pub mod remappings;
-pub mod run;
+pub mod script;
+pub mod self_update;
 pub mod snapshot;
 pub mod test;
 pub mod tree;
+pub mod upload_selectors;
 pub mod verify;
 pub mod watch;
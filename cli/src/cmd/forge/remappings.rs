@@ -49,3 +49,113 @@ pub fn relative_remappings(lib: &Path, root: &Path) -> Vec<Remapping> {
         .map(Into::into)
         .collect()
 }
+
+/// Adds the remappings of the library at `lib` to the project's `remappings.txt`, if the project
+/// has one.
+///
+/// Returns the remappings that were added, remappings whose name is already mapped are skipped.
+pub(crate) fn add_lib_remappings(root: &Path, lib: &Path) -> eyre::Result<Vec<String>> {
+    let file = root.join("remappings.txt");
+    if !file.exists() {
+        return Ok(vec![])
+    }
+    let content = std::fs::read_to_string(&file)?;
+    let mut lines = content.lines().map(str::to_string).collect::<Vec<_>>();
+
+    // remappings are detected for all libraries of the libs folder, only the ones of `lib` are
+    // added
+    let libs = lib.parent().unwrap_or(root);
+    let relative_lib = lib.strip_prefix(root).unwrap_or(lib);
+    let remappings = relative_remappings(libs, root)
+        .into_iter()
+        .filter(|remapping| Path::new(&remapping.path).starts_with(relative_lib));
+
+    let mut added = vec![];
+    for remapping in remappings {
+        if lines.iter().any(|line| remapping_name(line) == remapping.name) {
+            continue
+        }
+        let remapping = remapping.to_string();
+        lines.push(remapping.clone());
+        added.push(remapping);
+    }
+
+    if !added.is_empty() {
+        std::fs::write(&file, lines.join("\n") + "\n")?;
+    }
+    Ok(added)
+}
+
+/// Removes all remappings that point into the library at `lib` from the project's
+/// `remappings.txt`, if the project has one.
+///
+/// `lib` is expected to be relative to the `root`, e.g. `lib/forge-std`.
+pub(crate) fn remove_lib_remappings(root: &Path, lib: &Path) -> eyre::Result<Vec<String>> {
+    let file = root.join("remappings.txt");
+    if !file.exists() {
+        return Ok(vec![])
+    }
+    let content = std::fs::read_to_string(&file)?;
+    let (removed, kept): (Vec<_>, Vec<_>) = content.lines().partition(|line| {
+        line.split_once('=')
+            .map(|(_, path)| Path::new(path.trim()).starts_with(lib))
+            .unwrap_or(false)
+    });
+
+    if !removed.is_empty() {
+        std::fs::write(&file, kept.join("\n") + "\n")?;
+    }
+    Ok(removed.into_iter().map(str::to_string).collect())
+}
+
+/// Returns the name of a `name=path` remapping line
+fn remapping_name(line: &str) -> &str {
+    line.split_once('=').map(|(name, _)| name.trim()).unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+
+    /// Creates a library with a single source file at `lib/<name>/src`
+    fn add_lib(root: &Path, name: &str) {
+        let src = root.join("lib").join(name).join("src");
+        fs::create_dir_all(&src).unwrap();
+        fs::write(src.join("Lib.sol"), "contract Lib {}").unwrap();
+    }
+
+    #[test]
+    fn can_add_and_remove_lib_remappings() {
+        let tmp = tempfile::tempdir().unwrap();
+        let root = tmp.path();
+        add_lib(root, "forge-std");
+        add_lib(root, "solmate");
+        let file = root.join("remappings.txt");
+        fs::write(&file, "forge-std/=lib/forge-std/src/\n").unwrap();
+
+        let added = add_lib_remappings(root, &root.join("lib/solmate")).unwrap();
+        assert_eq!(added, ["solmate/=lib/solmate/src/"]);
+        // libraries that are already mapped are skipped
+        assert!(add_lib_remappings(root, &root.join("lib/forge-std")).unwrap().is_empty());
+        assert_eq!(
+            fs::read_to_string(&file).unwrap(),
+            "forge-std/=lib/forge-std/src/\nsolmate/=lib/solmate/src/\n"
+        );
+
+        let removed = remove_lib_remappings(root, Path::new("lib/solmate")).unwrap();
+        assert_eq!(removed, ["solmate/=lib/solmate/src/"]);
+        assert_eq!(fs::read_to_string(&file).unwrap(), "forge-std/=lib/forge-std/src/\n");
+    }
+
+    #[test]
+    fn ignores_projects_without_remappings_file() {
+        let tmp = tempfile::tempdir().unwrap();
+        let root = tmp.path();
+        add_lib(root, "solmate");
+
+        assert!(add_lib_remappings(root, &root.join("lib/solmate")).unwrap().is_empty());
+        assert!(remove_lib_remappings(root, Path::new("lib/solmate")).unwrap().is_empty());
+        assert!(!root.join("remappings.txt").exists());
+    }
+}
@@ -0,0 +1,140 @@
+use super::{sequence::ScriptSequence, ScriptArgs};
+use crate::opts::WalletType;
+use ethers::{
+    prelude::{Http, Middleware, PendingTransaction, Provider, Signer, SignerMiddleware},
+    types::{transaction::eip2718::TypedTransaction, Chain, Eip1559TransactionRequest},
+};
+
+/// Returns the chain id of the RPC endpoint the transactions are broadcasted to
+pub async fn remote_chain_id(fork_url: &str) -> eyre::Result<u64> {
+    let provider = Provider::<Http>::try_from(fork_url)?;
+    Ok(provider.get_chainid().await?.as_u64())
+}
+
+impl ScriptArgs {
+    /// Signs and sends all transactions of the sequence that have not been broadcasted yet.
+    ///
+    /// The journal is saved after every state change of a transaction so the broadcast can be
+    /// resumed with `--resume`.
+    pub async fn send_transactions(
+        &self,
+        sequence: &mut ScriptSequence,
+        fork_url: &str,
+    ) -> eyre::Result<()> {
+        let provider = Provider::<Http>::try_from(fork_url)?;
+        let chain_id = provider.get_chainid().await?;
+
+        match self.wallet.to_wallet().signer(provider, chain_id).await? {
+            WalletType::Local(signer) => self.broadcast(signer, sequence).await,
+            WalletType::Ledger(signer) => self.broadcast(signer, sequence).await,
+            WalletType::Trezor(signer) => self.broadcast(signer, sequence).await,
+        }
+    }
+
+    async fn broadcast<S: Signer + 'static>(
+        &self,
+        signer: SignerMiddleware<Provider<Http>, S>,
+        sequence: &mut ScriptSequence,
+    ) -> eyre::Result<()> {
+        let sender = signer.address();
+        if let Some(tx) = sequence.transactions.iter().find(|tx| tx.from() != Some(&sender)) {
+            eyre::bail!(
+                "The script requires the sender {:?} but the provided wallet is {sender:?}.",
+                tx.from().expect("recorded transactions have a sender")
+            )
+        }
+
+        let chain = signer.get_chainid().await?.as_u64();
+        let is_legacy =
+            self.legacy || Chain::try_from(chain).map(|x| Chain::is_legacy(&x)).unwrap_or_default();
+
+        // Transactions of a previous run might still be pending
+        for tx_hash in sequence.pending.clone() {
+            let receipt = match signer.get_transaction_receipt(tx_hash).await? {
+                Some(receipt) => Some(receipt),
+                // the transaction is still known to the node, so we wait for it instead of
+                // sending it again
+                None if signer.get_transaction(tx_hash).await?.is_some() => {
+                    println!("Waiting for pending transaction {tx_hash:?}.");
+                    PendingTransaction::new(tx_hash, signer.inner()).await?
+                }
+                None => None,
+            };
+
+            match receipt {
+                Some(receipt) => sequence.add_receipt(receipt),
+                None => {
+                    // only a transaction whose nonce is still unused was dropped and can be sent
+                    // again, otherwise another transaction took its place
+                    let nonce = signer.get_transaction_count(sender, None).await?;
+                    if let Some(expected) = sequence
+                        .transactions
+                        .get(sequence.receipts.len())
+                        .and_then(|tx| tx.nonce())
+                        .filter(|expected| nonce > **expected)
+                    {
+                        eyre::bail!(
+                            "Transaction {tx_hash:?} was not mined, but the nonce {expected} of {sender:?} has already been used by another transaction."
+                        )
+                    }
+                }
+            }
+            sequence.remove_pending(tx_hash);
+        }
+        sequence.save()?;
+
+        let already_broadcasted = sequence.receipts.len();
+        let total = sequence.transactions.len();
+        if already_broadcasted > 0 {
+            println!("Skipping {already_broadcasted} already broadcasted transaction(s).");
+        }
+
+        let transactions: Vec<_> =
+            sequence.transactions.iter().skip(already_broadcasted).cloned().collect();
+        for (idx, tx) in transactions.into_iter().enumerate() {
+            let mut tx = if is_legacy { tx } else { into_eip1559(tx) };
+            signer.fill_transaction(&mut tx, None).await?;
+
+            let pending = signer.send_transaction(tx, None).await?;
+            let tx_hash = *pending;
+            sequence.add_pending(tx_hash);
+            sequence.save()?;
+
+            let receipt = pending.await?.ok_or_else(|| {
+                eyre::eyre!("Transaction {tx_hash:?} was dropped. Run the script again with `--resume` to retry.")
+            })?;
+            sequence.remove_pending(tx_hash);
+
+            let status = if receipt.status.unwrap_or_default().is_zero() { "failed" } else { "ok" };
+            println!(
+                "[{}/{total}] {tx_hash:?} ({status}){}",
+                already_broadcasted + idx + 1,
+                receipt
+                    .contract_address
+                    .map(|addr| format!(" deployed at {addr:?}"))
+                    .unwrap_or_default()
+            );
+            sequence.add_receipt(receipt);
+            sequence.save()?;
+        }
+
+        println!("\nTransactions saved to: {}", sequence.path.display());
+        Ok(())
+    }
+}
+
+/// Converts a recorded legacy transaction into an EIP1559 transaction
+fn into_eip1559(tx: TypedTransaction) -> TypedTransaction {
+    match tx {
+        TypedTransaction::Legacy(tx) => TypedTransaction::Eip1559(Eip1559TransactionRequest {
+            from: tx.from,
+            to: tx.to,
+            gas: tx.gas,
+            value: tx.value,
+            data: tx.data,
+            nonce: tx.nonce,
+            ..Default::default()
+        }),
+        tx => tx,
+    }
+}
@@ -0,0 +1,184 @@
+use super::ScriptArgs;
+use crate::compile;
+use ethers::{
+    prelude::ArtifactId,
+    solc::{
+        artifacts::{
+            output_selection::ContractOutputSelection, CompactContractBytecode, ContractBytecode,
+            ContractBytecodeSome, StorageLayout,
+        },
+        Project,
+    },
+};
+use forge::executor::opts::EvmOpts;
+use foundry_config::Config;
+use foundry_utils::PostLinkInput;
+use std::collections::BTreeMap;
+
+struct ExtraLinkingInfo<'a> {
+    no_target_name: bool,
+    target_fname: String,
+    contract: &'a mut CompactContractBytecode,
+    dependencies: &'a mut Vec<ethers::types::Bytes>,
+    matched: bool,
+}
+
+pub struct BuildOutput {
+    pub project: Project,
+    pub contract: CompactContractBytecode,
+    pub highlevel_known_contracts: BTreeMap<ArtifactId, ContractBytecodeSome>,
+    pub storage_layouts: BTreeMap<ArtifactId, StorageLayout>,
+    pub sources: BTreeMap<u32, String>,
+    pub predeploy_libraries: Vec<ethers::types::Bytes>,
+}
+
+impl ScriptArgs {
+    /// Compiles the file with auto-detection and compiler params.
+    pub fn build(&self, config: &Config, evm_opts: &EvmOpts) -> eyre::Result<BuildOutput> {
+        let target_contract = dunce::canonicalize(&self.path)?;
+        // the storage layouts are needed to check the upgrades made by the script
+        let mut config = config.clone();
+        if !config.extra_output.contains(&ContractOutputSelection::StorageLayout) {
+            config.extra_output.push(ContractOutputSelection::StorageLayout);
+        }
+        let project = config.ephemeral_no_artifacts_project()?;
+        let output = compile::compile_files(&project, vec![target_contract])?;
+
+        let (contracts, sources) = output.into_artifacts_with_sources();
+        let mut storage_layouts = BTreeMap::new();
+        let contracts: BTreeMap<ArtifactId, CompactContractBytecode> = contracts
+            .into_iter()
+            .map(|(id, artifact)| {
+                if let Some(layout) = artifact.storage_layout.clone() {
+                    storage_layouts.insert(id.clone(), layout);
+                }
+                (id, artifact.into())
+            })
+            .collect();
+
+        let mut run_dependencies = vec![];
+        let mut contract =
+            CompactContractBytecode { abi: None, bytecode: None, deployed_bytecode: None };
+        let mut highlevel_known_contracts = BTreeMap::new();
+
+        let mut target_fname = dunce::canonicalize(&self.path)
+            .expect("Couldn't convert contract path to absolute path")
+            .to_str()
+            .expect("Bad path to string")
+            .to_string();
+
+        let no_target_name = if let Some(target_name) = &self.target_contract {
+            target_fname = target_fname + ":" + target_name;
+            false
+        } else {
+            true
+        };
+
+        foundry_utils::link(
+            contracts,
+            &mut highlevel_known_contracts,
+            evm_opts.sender,
+            &mut ExtraLinkingInfo {
+                no_target_name,
+                target_fname,
+                contract: &mut contract,
+                dependencies: &mut run_dependencies,
+                matched: false,
+            },
+            |file, key| (format!("{file}:{key}"), file, key),
+            |post_link_input| {
+                let PostLinkInput {
+                    contract,
+                    known_contracts: highlevel_known_contracts,
+                    id,
+                    extra,
+                    dependencies,
+                } = post_link_input;
+
+                // if it's the target contract, grab the info
+                if extra.no_target_name {
+                    if id.source == std::path::Path::new(&extra.target_fname) {
+                        if extra.matched {
+                            eyre::bail!("Multiple contracts in the target path. Please specify the contract name with `-t ContractName`")
+                        }
+                        *extra.dependencies = dependencies;
+                        *extra.contract = contract.clone();
+                        extra.matched = true;
+                    }
+                } else {
+                    let split: Vec<&str> = extra.target_fname.split(':').collect();
+                    let path = std::path::Path::new(split[0]);
+                    let name = split[1];
+                    if path == id.source && name == id.name {
+                        *extra.dependencies = dependencies;
+                        *extra.contract = contract.clone();
+                        extra.matched = true;
+                    }
+                }
+
+                let tc: ContractBytecode = contract.into();
+                highlevel_known_contracts.insert(id, tc.unwrap());
+                Ok(())
+            },
+        )?;
+
+        Ok(BuildOutput {
+            project,
+            contract,
+            highlevel_known_contracts,
+            storage_layouts,
+            sources: sources.into_ids().collect(),
+            predeploy_libraries: run_dependencies,
+        })
+    }
+}
+
+/// Returns the contract whose creation code the data starts with, the constructor arguments are
+/// appended to it
+pub fn find_by_creation_code<'a>(
+    contracts: &'a BTreeMap<ArtifactId, ContractBytecodeSome>,
+    data: &[u8],
+) -> Option<&'a ArtifactId> {
+    contracts
+        .iter()
+        .filter_map(|(id, contract)| {
+            let code = contract.bytecode.object.as_bytes()?;
+            (!code.is_empty() && data.starts_with(code.as_ref())).then(|| (id, code.len()))
+        })
+        .max_by_key(|(_, len)| *len)
+        .map(|(id, _)| id)
+}
+
+/// Returns the contract with the given runtime code, ignoring the metadata hash
+pub fn find_by_runtime_code<'a>(
+    contracts: &'a BTreeMap<ArtifactId, ContractBytecodeSome>,
+    code: &[u8],
+) -> Option<&'a ArtifactId> {
+    if code.is_empty() {
+        return None
+    }
+    let code = strip_metadata(code);
+    contracts
+        .iter()
+        .find(|(_, contract)| {
+            contract
+                .deployed_bytecode
+                .bytecode
+                .as_ref()
+                .and_then(|bytecode| bytecode.object.as_bytes())
+                .map_or(false, |deployed| strip_metadata(deployed.as_ref()) == code)
+        })
+        .map(|(id, _)| id)
+}
+
+/// Strips the CBOR encoded metadata solc appends to the runtime code, its length is stored in the
+/// last two bytes
+fn strip_metadata(code: &[u8]) -> &[u8] {
+    match code.len().checked_sub(2) {
+        Some(end) => {
+            let len = u16::from_be_bytes([code[end], code[end + 1]]) as usize;
+            end.checked_sub(len).map_or(code, |start| &code[..start])
+        }
+        None => code,
+    }
+}
@@ -0,0 +1,534 @@
+//! script command
+use crate::{
+    cmd::{
+        forge::{build::CoreBuildArgs, deployments::DeploymentRegistry, watch::WatchArgs},
+        Cmd,
+    },
+    opts::Wallet,
+    utils,
+};
+use ansi_term::Colour;
+use clap::{Parser, ValueHint};
+use ethers::{
+    abi::{Abi, RawLog},
+    prelude::ArtifactId,
+    solc::artifacts::CompactContractBytecode,
+    types::{transaction::eip2718::TypedTransaction, Address, NameOrAddress, U256},
+};
+use forge::{
+    debug::DebugArena,
+    decode::decode_console_logs,
+    executor::{builder::Backend, load_allocs, opts::EvmOpts, ExecutorBuilder},
+    trace::{identifier::LocalTraceIdentifier, CallTraceArena, CallTraceDecoderBuilder, TraceKind},
+};
+use foundry_common::evm::EvmArgs;
+use foundry_config::{figment::Figment, Config};
+use foundry_utils::{encode_args, IntoFunction, RuntimeOrHandle};
+use std::{
+    collections::{BTreeMap, VecDeque},
+    path::PathBuf,
+};
+use ui::{TUIExitReason, Tui, Ui};
+use watchexec::config::{InitConfig, RuntimeConfig};
+
+mod broadcast;
+mod build;
+use build::BuildOutput;
+mod runner;
+use runner::Runner;
+mod sequence;
+use sequence::ScriptSequence;
+mod upgrade;
+
+// Loads project's figment and merges the build cli arguments into it
+foundry_config::impl_figment_convert!(ScriptArgs, opts, evm_opts);
+
+#[derive(Debug, Clone, Parser)]
+pub struct ScriptArgs {
+    /// The path of the contract to run.
+    ///
+    /// If multiple contracts exist in the same file you must specify the target contract with
+    /// --target-contract.
+    #[clap(value_hint = ValueHint::FilePath)]
+    pub path: PathBuf,
+
+    /// Arguments to pass to the script function.
+    pub args: Vec<String>,
+
+    /// The name of the contract you want to run.
+    #[clap(long, short, value_name = "CONTRACT_NAME")]
+    pub target_contract: Option<String>,
+
+    /// The signature of the function you want to call in the contract, or raw calldata.
+    #[clap(long, short, default_value = "run()", value_name = "SIGNATURE")]
+    pub sig: String,
+
+    /// Open the script in the debugger.
+    #[clap(long, conflicts_with = "watch")]
+    pub debug: bool,
+
+    /// Broadcasts the transactions recorded by the script.
+    ///
+    /// The transactions are signed with the provided wallet and sent to the endpoint given by
+    /// --fork-url.
+    #[clap(long)]
+    pub broadcast: bool,
+
+    /// Resumes broadcasting the transactions of a previous run that failed or were interrupted.
+    #[clap(long, requires = "broadcast", conflicts_with = "watch")]
+    pub resume: bool,
+
+    /// Send legacy transactions instead of EIP1559 transactions.
+    ///
+    /// This is automatically enabled for common networks without EIP1559.
+    #[clap(long)]
+    pub legacy: bool,
+
+    /// Relative percentage to multiply the gas used in the simulation by to get the gas limit of
+    /// each transaction.
+    #[clap(long, default_value = "130", value_name = "PERCENTAGE")]
+    pub gas_estimate_multiplier: u64,
+
+    /// Broadcasts upgrades of proxies even if the storage layout of the new implementation is
+    /// incompatible with the current one.
+    #[clap(long, requires = "broadcast")]
+    pub force_upgrade: bool,
+
+    #[clap(flatten, next_help_heading = "BUILD OPTIONS")]
+    pub opts: CoreBuildArgs,
+
+    #[clap(flatten, next_help_heading = "EVM OPTIONS")]
+    pub evm_opts: EvmArgs,
+
+    #[clap(flatten, next_help_heading = "WALLET OPTIONS")]
+    pub wallet: ScriptWallet,
+
+    /// Runs the script again whenever the sources change.
+    ///
+    /// Together with --broadcast, every contract of the script is deployed again to the endpoint
+    /// of --fork-url, e.g. a local node, even if only one of them changed, and the journal of the
+    /// run is rewritten with the new addresses.
+    #[clap(flatten, next_help_heading = "WATCH OPTIONS")]
+    pub watch: WatchArgs,
+}
+
+impl Cmd for ScriptArgs {
+    type Output = ();
+    fn run(self) -> eyre::Result<Self::Output> {
+        let figment: Figment = From::from(&self);
+        let mut evm_opts = figment.extract::<EvmOpts>()?;
+        let verbosity = evm_opts.verbosity;
+        let mut config = Config::from_provider(figment).sanitized();
+        utils::resolve_rpc_endpoints(&mut config, &mut evm_opts)?;
+
+        let fork_url = evm_opts.fork_url.clone();
+        if self.broadcast && fork_url.is_none() {
+            eyre::bail!("You need to provide an RPC URL with --fork-url to broadcast transactions.")
+        }
+
+        if self.resume {
+            let fork_url = fork_url.expect("is checked above");
+            let chain_id = utils::block_on(broadcast::remote_chain_id(&fork_url))?;
+            let mut sequence = ScriptSequence::load(
+                &self.sig,
+                &self.path,
+                &self.broadcast_dir(&config),
+                chain_id,
+            )?;
+            return utils::block_on(self.send_transactions(&mut sequence, &fork_url))
+        }
+
+        let BuildOutput {
+            project,
+            contract,
+            highlevel_known_contracts,
+            storage_layouts,
+            sources,
+            predeploy_libraries,
+        } = self.build(&config, &evm_opts)?;
+
+        if self.broadcast && !predeploy_libraries.is_empty() {
+            eyre::bail!("Broadcasting scripts that require library linking is not supported. Deploy the libraries first and provide their addresses via the `libraries` config.")
+        }
+
+        let known_contracts = highlevel_known_contracts
+            .iter()
+            .map(|(id, c)| {
+                (
+                    id.clone(),
+                    (
+                        c.abi.clone(),
+                        c.deployed_bytecode.clone().into_bytes().expect("not bytecode").to_vec(),
+                    ),
+                )
+            })
+            .collect::<BTreeMap<ArtifactId, (Abi, Vec<u8>)>>();
+
+        let mut result = self.execute(contract, &evm_opts, &config, &predeploy_libraries)?;
+
+        // Identify addresses in each trace
+        // TODO: Could we use the Etherscan identifier here? Main issue: Pulling source code and
+        // bytecode. Might be better to wait for an interactive debugger where we can do this on
+        // the fly while retaining access to the database?
+        let local_identifier = LocalTraceIdentifier::new(&known_contracts);
+        let mut decoder = CallTraceDecoderBuilder::new()
+            .with_labels(result.labeled_addresses.clone())
+            .with_events(local_identifier.events())
+            .build();
+        for (_, trace) in &mut result.traces {
+            decoder.identify(trace, &local_identifier);
+        }
+
+        if self.debug {
+            let source_code: BTreeMap<u32, String> = sources
+                .iter()
+                .map(|(id, path)| {
+                    let resolved = project
+                        .paths
+                        .resolve_library_import(&PathBuf::from(path))
+                        .unwrap_or_else(|| PathBuf::from(path));
+                    (
+                        *id,
+                        std::fs::read_to_string(resolved).expect(&*format!(
+                            "Something went wrong reading the source file: {:?}",
+                            path
+                        )),
+                    )
+                })
+                .collect();
+
+            let calls: Vec<DebugArena> = result.debug.expect("we should have collected debug info");
+            let arena = calls.last().expect("we should have collected debug info");
+            // Start at the revert if the execution failed, the steps before it can still be
+            // stepped through backwards
+            let (call, step) = arena.revert_location(0).unwrap_or_default();
+            let tui = Tui::new(
+                arena.flatten(0),
+                call,
+                step,
+                decoder.contracts,
+                highlevel_known_contracts
+                    .into_iter()
+                    .map(|(id, artifact)| (id.name, artifact))
+                    .collect(),
+                source_code,
+            )?;
+            match tui.start().expect("Failed to start tui") {
+                TUIExitReason::CharExit => return Ok(()),
+            }
+        }
+
+        if verbosity >= 3 {
+            if result.traces.is_empty() {
+                eyre::bail!("Unexpected error: No traces despite verbosity level. Please report this as a bug: https://github.com/foundry-rs/foundry/issues/new?assignees=&labels=T-bug&template=BUG-FORM.yml");
+            }
+
+            if !result.success && verbosity == 3 || verbosity > 3 {
+                println!("Traces:");
+                for (kind, trace) in &mut result.traces {
+                    let should_include = match kind {
+                        TraceKind::Setup => (verbosity >= 5) || (verbosity == 4 && !result.success),
+                        TraceKind::Execution => verbosity > 3 || !result.success,
+                        _ => false,
+                    };
+
+                    if should_include {
+                        decoder.decode(trace);
+                        println!("{trace}");
+                    }
+                }
+                println!();
+            }
+        }
+
+        if result.success {
+            println!("{}", Colour::Green.paint("Script ran successfully."));
+        } else {
+            println!("{}", Colour::Red.paint("Script failed."));
+        }
+
+        println!("Gas used: {}", result.gas);
+        println!("== Logs ==");
+        let console_logs = decode_console_logs(&result.logs);
+        if !console_logs.is_empty() {
+            for log in console_logs {
+                println!("  {log}");
+            }
+        }
+
+        if !result.success {
+            return Ok(())
+        }
+
+        if let Some(transactions) = result.transactions {
+            if let Some(fork_url) = fork_url {
+                let transactions = self.simulate(transactions, &evm_opts, &config)?;
+
+                if self.broadcast {
+                    let chain_id = utils::block_on(broadcast::remote_chain_id(&fork_url))?;
+                    let mut registry =
+                        DeploymentRegistry::load(&self.broadcast_dir(&config), chain_id)?;
+                    utils::block_on(self.check_upgrades(
+                        &transactions,
+                        &highlevel_known_contracts,
+                        &storage_layouts,
+                        &registry,
+                        &fork_url,
+                    ))?;
+
+                    let mut sequence = ScriptSequence::new(
+                        transactions,
+                        &self.sig,
+                        &self.path,
+                        &self.broadcast_dir(&config),
+                        chain_id,
+                    )?;
+                    sequence.save()?;
+                    utils::block_on(self.send_transactions(&mut sequence, &fork_url))?;
+
+                    sequence
+                        .deployments(
+                            &highlevel_known_contracts,
+                            &storage_layouts,
+                            &project.paths.root,
+                        )
+                        .into_iter()
+                        .for_each(|deployment| registry.add(deployment));
+                    registry.save()?;
+                } else {
+                    println!("\nSIMULATION COMPLETE. To broadcast these transactions, add --broadcast and wallet configuration to the previous command.");
+                }
+            } else {
+                println!("\nIf you wish to simulate on-chain transactions pass a RPC URL with --fork-url.");
+            }
+        }
+
+        Ok(())
+    }
+}
+
+impl ScriptArgs {
+    /// Deploys the script contract and calls its `setUp()` and the script function
+    fn execute(
+        &self,
+        contract: CompactContractBytecode,
+        evm_opts: &EvmOpts,
+        config: &Config,
+        predeploy_libraries: &[ethers::types::Bytes],
+    ) -> eyre::Result<ScriptResult> {
+        let CompactContractBytecode { abi, bytecode, .. } = contract;
+        let abi = abi.expect("no ABI for contract");
+        let bytecode = bytecode.expect("no bytecode for contract").object.into_bytes().unwrap();
+        let setup_fns: Vec<_> =
+            abi.functions().filter(|func| func.name.to_lowercase() == "setup").collect();
+
+        let needs_setup = setup_fns.len() == 1 && setup_fns[0].name == "setUp";
+
+        for setup_fn in setup_fns.iter() {
+            if setup_fn.name != "setUp" {
+                println!(
+                    "{} Found invalid setup function \"{}\" did you mean \"setUp()\"?",
+                    Colour::Yellow.bold().paint("Warning:"),
+                    setup_fn.signature()
+                );
+            }
+        }
+
+        let runtime = RuntimeOrHandle::new();
+        let env = runtime.block_on(evm_opts.evm_env());
+        // the db backend that serves all the data
+        let db = runtime
+            .block_on(Backend::new(utils::get_fork(evm_opts, &config.rpc_storage_caching), &env));
+
+        let mut builder = ExecutorBuilder::new()
+            .with_cheatcodes(evm_opts.ffi)
+            .with_fs_permissions(config.fs_permissions.clone(), &config.__root.0)
+            .with_config(env)
+            .with_spec(crate::utils::evm_spec(&config.evm_version))
+            .with_gas_limit(evm_opts.gas_limit());
+
+        if evm_opts.verbosity >= 3 {
+            builder = builder.with_tracing();
+        }
+        if self.debug {
+            builder = builder.with_tracing().with_debugger();
+        }
+
+        let mut executor = builder.build(db);
+        if let Some(ref allocs) = config.allocs {
+            executor.load_allocs(&load_allocs(allocs)?);
+        }
+
+        let mut runner = Runner::new(executor, evm_opts.initial_balance, evm_opts.sender);
+        let (address, mut result) = runner.setup(predeploy_libraries, bytecode, needs_setup)?;
+
+        let ScriptResult {
+            success,
+            gas,
+            logs,
+            traces,
+            debug: run_debug,
+            labeled_addresses,
+            transactions,
+        } = runner.run(
+            address,
+            if let Some(calldata) = self.sig.strip_prefix("0x") {
+                hex::decode(calldata)?.into()
+            } else {
+                encode_args(&IntoFunction::into(self.sig.clone()), &self.args)?.into()
+            },
+        )?;
+
+        result.success &= success;
+
+        result.gas = gas;
+        result.logs.extend(logs);
+        result.traces.extend(traces);
+        result.debug = run_debug;
+        result.labeled_addresses.extend(labeled_addresses);
+        result.transactions = transactions;
+
+        Ok(result)
+    }
+
+    /// Executes the recorded transactions one after another against a fresh fork of the target
+    /// chain and sets their gas limit based on the gas they used.
+    fn simulate(
+        &self,
+        transactions: VecDeque<TypedTransaction>,
+        evm_opts: &EvmOpts,
+        config: &Config,
+    ) -> eyre::Result<VecDeque<TypedTransaction>> {
+        let runtime = RuntimeOrHandle::new();
+        let env = runtime.block_on(evm_opts.evm_env());
+        let db = runtime
+            .block_on(Backend::new(utils::get_fork(evm_opts, &config.rpc_storage_caching), &env));
+
+        let mut executor = ExecutorBuilder::new()
+            .with_config(env)
+            .with_spec(crate::utils::evm_spec(&config.evm_version))
+            .with_gas_limit(evm_opts.gas_limit())
+            .build(db);
+        // the transactions were recorded on top of the allocs
+        if let Some(ref allocs) = config.allocs {
+            executor.load_allocs(&load_allocs(allocs)?);
+        }
+        let mut runner = Runner::new(executor, evm_opts.initial_balance, evm_opts.sender);
+
+        let mut total_gas = U256::zero();
+        let transactions = transactions
+            .into_iter()
+            .map(|mut tx| {
+                let from = *tx.from().expect("recorded transactions have a sender");
+                let to = match tx.to() {
+                    Some(NameOrAddress::Address(to)) => Some(*to),
+                    Some(NameOrAddress::Name(_)) => {
+                        unreachable!("recorded transactions always have an address")
+                    }
+                    None => None,
+                };
+                let data = tx.data().cloned().unwrap_or_default();
+                let value = tx.value().cloned().unwrap_or_default();
+
+                let gas = runner.simulate(from, to, data, value)?;
+                let gas_limit = U256::from(gas * self.gas_estimate_multiplier / 100);
+                total_gas += gas_limit;
+                tx.set_gas(gas_limit);
+
+                Ok(tx)
+            })
+            .collect::<eyre::Result<VecDeque<_>>>()?;
+
+        println!(
+            "\n{} transaction(s) simulated successfully. Estimated total gas used: {total_gas}",
+            transactions.len()
+        );
+
+        Ok(transactions)
+    }
+
+    /// The directory the transaction journals are written to
+    fn broadcast_dir(&self, config: &Config) -> PathBuf {
+        config.__root.0.join("broadcast")
+    }
+
+    /// Returns whether `ScriptArgs` was configured with `--watch`
+    pub fn is_watch(&self) -> bool {
+        self.watch.watch.is_some()
+    }
+
+    /// Returns the [`watchexec::InitConfig`] and [`watchexec::RuntimeConfig`] necessary to
+    /// bootstrap a new [`watchexe::Watchexec`] loop.
+    pub(crate) fn watchexec_config(&self) -> eyre::Result<(InitConfig, RuntimeConfig)> {
+        self.watch.watchexec_config(|| {
+            let config = Config::from(self);
+            vec![config.src, self.path.clone()]
+        })
+    }
+}
+
+pub struct ScriptResult {
+    pub success: bool,
+    pub logs: Vec<RawLog>,
+    pub traces: Vec<(TraceKind, CallTraceArena)>,
+    pub debug: Option<Vec<DebugArena>>,
+    pub gas: u64,
+    pub labeled_addresses: BTreeMap<Address, String>,
+    pub transactions: Option<VecDeque<TypedTransaction>>,
+}
+
+/// The wallet used to sign the broadcasted transactions.
+///
+/// These are a subset of the [`Wallet`] options without short flags, which would otherwise clash
+/// with the script and EVM options.
+#[derive(Debug, Clone, Default, Parser)]
+pub struct ScriptWallet {
+    /// Use the provided private key.
+    #[clap(long, value_name = "RAW_PRIVATE_KEY")]
+    pub private_key: Option<String>,
+
+    /// Use the mnemonic file at the specified path.
+    #[clap(long, value_name = "PATH")]
+    pub mnemonic_path: Option<String>,
+
+    /// Use the private key from the given mnemonic index. Used with --mnemonic-path.
+    #[clap(long, default_value = "0", value_name = "INDEX")]
+    pub mnemonic_index: u32,
+
+    /// Use the keystore in the given folder or file.
+    #[clap(long = "keystore", env = "ETH_KEYSTORE", value_name = "PATH")]
+    pub keystore_path: Option<String>,
+
+    /// The keystore password. Used with --keystore.
+    #[clap(long = "password", requires = "keystore-path", value_name = "PASSWORD")]
+    pub keystore_password: Option<String>,
+
+    /// Use a Ledger hardware wallet.
+    #[clap(long)]
+    pub ledger: bool,
+
+    /// Use a Trezor hardware wallet.
+    #[clap(long)]
+    pub trezor: bool,
+
+    /// The derivation path to use with hardware wallets.
+    #[clap(long, value_name = "PATH")]
+    pub hd_path: Option<String>,
+}
+
+impl ScriptWallet {
+    pub fn to_wallet(&self) -> Wallet {
+        Wallet {
+            interactive: false,
+            private_key: self.private_key.clone(),
+            mnemonic_path: self.mnemonic_path.clone(),
+            mnemonic_index: self.mnemonic_index,
+            keystore_path: self.keystore_path.clone(),
+            keystore_password: self.keystore_password.clone(),
+            ledger: self.ledger,
+            trezor: self.trezor,
+            hd_path: self.hd_path.clone(),
+            from: None,
+        }
+    }
+}
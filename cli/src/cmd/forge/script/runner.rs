@@ -0,0 +1,161 @@
+use super::ScriptResult;
+use ethers::types::{Address, Bytes, U256};
+use forge::{
+    executor::{CallResult, DatabaseRef, DeployResult, EvmError, Executor, RawCallResult},
+    trace::{CallTraceArena, TraceKind},
+    CALLER,
+};
+
+/// Drives the execution of a script contract
+pub struct Runner<DB: DatabaseRef> {
+    pub executor: Executor<DB>,
+    pub initial_balance: U256,
+    pub sender: Address,
+}
+
+impl<DB: DatabaseRef> Runner<DB> {
+    pub fn new(executor: Executor<DB>, initial_balance: U256, sender: Address) -> Self {
+        Self { executor, initial_balance, sender }
+    }
+
+    pub fn setup(
+        &mut self,
+        libraries: &[Bytes],
+        code: Bytes,
+        setup: bool,
+    ) -> eyre::Result<(Address, ScriptResult)> {
+        // Transactions recorded by the script are sent from the sender, so they need to use its
+        // actual nonce
+        let sender_nonce = self.executor.get_nonce(self.sender);
+
+        // We max out their balance so that they can deploy and make calls.
+        self.executor.set_balance(self.sender, U256::MAX);
+        self.executor.set_balance(*CALLER, U256::MAX);
+
+        // We set the nonce of the deployer accounts to 1 to get the same addresses as DappTools
+        self.executor.set_nonce(self.sender, 1);
+
+        // Deploy libraries
+        let mut traces: Vec<(TraceKind, CallTraceArena)> = libraries
+            .iter()
+            .filter_map(|code| {
+                let DeployResult { traces, .. } = self
+                    .executor
+                    .deploy(self.sender, code.0.clone(), 0u32.into())
+                    .expect("couldn't deploy library");
+
+                traces
+            })
+            .map(|traces| (TraceKind::Deployment, traces))
+            .collect();
+
+        // Restore the nonce of the sender, the script contract itself is deployed by the default
+        // caller so it does not affect the nonces of any recorded transactions
+        self.executor.set_nonce(self.sender, sender_nonce);
+
+        // Deploy an instance of the contract
+        let DeployResult {
+            address,
+            mut logs,
+            traces: constructor_traces,
+            debug: constructor_debug,
+            ..
+        } = self.executor.deploy(*CALLER, code.0, 0u32.into()).expect("couldn't deploy");
+        traces.extend(constructor_traces.map(|traces| (TraceKind::Deployment, traces)).into_iter());
+        self.executor.set_balance(address, self.initial_balance);
+
+        // Optionally call the `setUp` function
+        Ok(if setup {
+            match self.executor.setup(address) {
+                Ok(CallResult {
+                    reverted,
+                    traces: setup_traces,
+                    labels,
+                    logs: setup_logs,
+                    debug,
+                    gas,
+                    ..
+                }) |
+                Err(EvmError::Execution {
+                    reverted,
+                    traces: setup_traces,
+                    labels,
+                    logs: setup_logs,
+                    debug,
+                    gas,
+                    ..
+                }) => {
+                    traces
+                        .extend(setup_traces.map(|traces| (TraceKind::Setup, traces)).into_iter());
+                    logs.extend_from_slice(&setup_logs);
+
+                    (
+                        address,
+                        ScriptResult {
+                            logs,
+                            traces,
+                            labeled_addresses: labels,
+                            success: !reverted,
+                            debug: vec![constructor_debug, debug].into_iter().collect(),
+                            gas,
+                            transactions: None,
+                        },
+                    )
+                }
+                Err(e) => return Err(e.into()),
+            }
+        } else {
+            (
+                address,
+                ScriptResult {
+                    logs,
+                    traces,
+                    success: true,
+                    debug: vec![constructor_debug].into_iter().collect(),
+                    gas: 0,
+                    labeled_addresses: Default::default(),
+                    transactions: None,
+                },
+            )
+        })
+    }
+
+    pub fn run(&mut self, address: Address, calldata: Bytes) -> eyre::Result<ScriptResult> {
+        let RawCallResult {
+            reverted, gas, stipend, logs, traces, labels, debug, transactions, ..
+        } = self.executor.call_raw(self.sender, address, calldata.0, 0.into())?;
+        Ok(ScriptResult {
+            success: !reverted,
+            gas: gas.overflowing_sub(stipend).0,
+            logs,
+            traces: traces.map(|traces| vec![(TraceKind::Execution, traces)]).unwrap_or_default(),
+            debug: vec![debug].into_iter().collect(),
+            labeled_addresses: labels,
+            transactions,
+        })
+    }
+
+    /// Executes a recorded transaction on top of the current state and returns the gas it used.
+    ///
+    /// The state after the transaction is persisted so that subsequent transactions are executed
+    /// on top of it.
+    pub fn simulate(
+        &mut self,
+        from: Address,
+        to: Option<Address>,
+        calldata: Bytes,
+        value: U256,
+    ) -> eyre::Result<u64> {
+        if let Some(to) = to {
+            let RawCallResult { reverted, gas, .. } =
+                self.executor.call_raw_committing(from, to, calldata.0, value)?;
+            if reverted {
+                eyre::bail!("Simulated execution of a transaction to {to:?} reverted.")
+            }
+            Ok(gas)
+        } else {
+            let DeployResult { gas, .. } = self.executor.deploy(from, calldata.0, value)?;
+            Ok(gas)
+        }
+    }
+}
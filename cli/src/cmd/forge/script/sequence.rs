@@ -0,0 +1,145 @@
+use super::build::find_by_creation_code;
+use crate::cmd::forge::deployments::{is_successful, Deployment};
+use ethers::{
+    prelude::ArtifactId,
+    solc::artifacts::{ContractBytecodeSome, StorageLayout},
+    types::{transaction::eip2718::TypedTransaction, TransactionReceipt, TxHash},
+};
+use eyre::Context;
+use serde::{Deserialize, Serialize};
+use std::{
+    collections::{BTreeMap, VecDeque},
+    io::BufWriter,
+    path::{Path, PathBuf},
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+/// The journal of a broadcasted script.
+///
+/// Keeps track of all transactions recorded by the script and which of them have already been
+/// sent, so an interrupted broadcast can be resumed.
+#[derive(Deserialize, Serialize, Clone, Debug)]
+pub struct ScriptSequence {
+    pub transactions: VecDeque<TypedTransaction>,
+    pub receipts: Vec<TransactionReceipt>,
+    pub pending: Vec<TxHash>,
+    #[serde(skip)]
+    pub path: PathBuf,
+    pub timestamp: u64,
+}
+
+impl ScriptSequence {
+    pub fn new(
+        transactions: VecDeque<TypedTransaction>,
+        sig: &str,
+        target: &Path,
+        broadcast: &Path,
+        chain_id: u64,
+    ) -> eyre::Result<Self> {
+        let path = ScriptSequence::get_path(broadcast, sig, target, chain_id)?;
+
+        Ok(ScriptSequence {
+            transactions,
+            receipts: vec![],
+            pending: vec![],
+            path,
+            timestamp: now(),
+        })
+    }
+
+    /// Loads the journal of a previous run of the same script
+    pub fn load(sig: &str, target: &Path, broadcast: &Path, chain_id: u64) -> eyre::Result<Self> {
+        let path = ScriptSequence::get_path(broadcast, sig, target, chain_id)?;
+        let file = std::fs::File::open(&path)
+            .wrap_err_with(|| format!("Failed to read the transactions of a previous run from {path:?}. Did you run the script with `--broadcast` before?"))?;
+        let mut sequence: ScriptSequence = serde_json::from_reader(file)?;
+        sequence.path = path;
+        Ok(sequence)
+    }
+
+    /// Writes the journal to `<sig>-latest.json` and a copy of it to `<sig>-<timestamp>.json`
+    pub fn save(&mut self) -> eyre::Result<()> {
+        if self.transactions.is_empty() {
+            return Ok(())
+        }
+
+        self.timestamp = now();
+
+        let writer = BufWriter::new(std::fs::File::create(&self.path)?);
+        serde_json::to_writer_pretty(writer, &self)?;
+
+        let timestamped = self.path.with_file_name(
+            self.path
+                .file_name()
+                .expect("journal path has a file name")
+                .to_string_lossy()
+                .replace("latest", &self.timestamp.to_string()),
+        );
+        std::fs::copy(&self.path, timestamped)?;
+
+        Ok(())
+    }
+
+    pub fn add_receipt(&mut self, receipt: TransactionReceipt) {
+        self.receipts.push(receipt);
+    }
+
+    pub fn add_pending(&mut self, tx_hash: TxHash) {
+        if !self.pending.contains(&tx_hash) {
+            self.pending.push(tx_hash);
+        }
+    }
+
+    pub fn remove_pending(&mut self, tx_hash: TxHash) {
+        self.pending.retain(|element| element != &tx_hash);
+    }
+
+    /// Returns the contracts created by the broadcasted transactions, which are identified by their
+    /// creation code
+    pub fn deployments(
+        &self,
+        contracts: &BTreeMap<ArtifactId, ContractBytecodeSome>,
+        storage_layouts: &BTreeMap<ArtifactId, StorageLayout>,
+        root: &Path,
+    ) -> Vec<Deployment> {
+        self.transactions
+            .iter()
+            .zip(&self.receipts)
+            .filter(|(_, receipt)| is_successful(receipt))
+            .filter_map(|(tx, receipt)| {
+                let address = receipt.contract_address?;
+                let id = find_by_creation_code(contracts, tx.data()?)?;
+                let path = id.source.strip_prefix(root).unwrap_or(&id.source);
+                Some(Deployment {
+                    storage_layout: storage_layouts.get(id).cloned(),
+                    ..Deployment::new(format!("{}:{}", path.display(), id.name), address, receipt)
+                })
+            })
+            .collect()
+    }
+
+    /// Returns the path of the journal:
+    /// `<broadcast>/<script file name>/<chain id>/<sig>-latest.json`
+    pub fn get_path(
+        broadcast: &Path,
+        sig: &str,
+        target: &Path,
+        chain_id: u64,
+    ) -> eyre::Result<PathBuf> {
+        let target_fname =
+            target.file_name().ok_or_else(|| eyre::eyre!("No file name for {target:?}"))?;
+
+        let mut out = broadcast.join(target_fname).join(chain_id.to_string());
+        std::fs::create_dir_all(&out)?;
+
+        // only keep the function name of the signature
+        let filename = sig.split_once('(').map(|(name, _)| name).unwrap_or(sig);
+        out.push(format!("{filename}-latest.json"));
+        Ok(out)
+    }
+}
+
+/// Returns the current unix timestamp in seconds
+fn now() -> u64 {
+    SystemTime::now().duration_since(UNIX_EPOCH).expect("time went backwards").as_secs()
+}
@@ -0,0 +1,388 @@
+//! Storage layout checks of the proxy upgrades made by a script
+use super::{
+    build::{find_by_creation_code, find_by_runtime_code},
+    ScriptArgs,
+};
+use crate::cmd::forge::deployments::DeploymentRegistry;
+use ansi_term::Colour;
+use ethers::{
+    prelude::{ArtifactId, Http, Middleware, Provider},
+    solc::artifacts::{ContractBytecodeSome, StorageLayout},
+    types::{transaction::eip2718::TypedTransaction, Address, NameOrAddress, H256, U256},
+    utils::get_contract_address,
+};
+use std::{
+    collections::{BTreeMap, VecDeque},
+    fmt,
+    str::FromStr,
+};
+
+/// The EIP-1967 slot of the implementation of a proxy,
+/// `bytes32(uint256(keccak256('eip1967.proxy.implementation')) - 1)`
+const IMPLEMENTATION_SLOT: &str =
+    "0x360894a13ba1a3210667c828492db98dca3e2076cc3735a920a3ca505d382bbc";
+
+/// `upgradeTo(address)` of UUPS and transparent proxies
+const UPGRADE_TO: [u8; 4] = [0x36, 0x59, 0xcf, 0xe6];
+/// `upgradeToAndCall(address,bytes)` of UUPS and transparent proxies
+const UPGRADE_TO_AND_CALL: [u8; 4] = [0x4f, 0x1e, 0xf2, 0x86];
+/// `upgrade(address,address)` of a proxy admin
+const UPGRADE: [u8; 4] = [0x99, 0xa8, 0x8e, 0xc4];
+/// `upgradeAndCall(address,address,bytes)` of a proxy admin
+const UPGRADE_AND_CALL: [u8; 4] = [0x96, 0x23, 0x60, 0x9d];
+
+/// An upgrade of a proxy to a new implementation
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Upgrade {
+    pub proxy: Address,
+    pub implementation: Address,
+}
+
+impl Upgrade {
+    /// Decodes the upgrade made by a call to a proxy or to the admin of a proxy, returns `None` if
+    /// the call is not an upgrade
+    pub fn decode(to: Address, data: &[u8]) -> Option<Self> {
+        let address = |word: usize| {
+            let word = data.get(4 + word * 32..4 + (word + 1) * 32)?;
+            Some(Address::from_slice(&word[12..]))
+        };
+        match data.get(..4)? {
+            selector if selector == UPGRADE_TO || selector == UPGRADE_TO_AND_CALL => {
+                Some(Upgrade { proxy: to, implementation: address(0)? })
+            }
+            selector if selector == UPGRADE || selector == UPGRADE_AND_CALL => {
+                Some(Upgrade { proxy: address(0)?, implementation: address(1)? })
+            }
+            _ => None,
+        }
+    }
+}
+
+/// A change of a storage variable between two layouts
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum LayoutChange {
+    /// The variable was removed, or moved to another slot or offset
+    Removed { label: String, slot: U256, offset: i64 },
+    /// The type of the variable changed
+    TypeChanged { label: String, from: String, to: String },
+    /// The variable was renamed, which keeps the layout intact but might be a mistake
+    Renamed { from: String, to: String },
+    /// The variables that replace a storage gap do not fit into it
+    GapOverflow { label: String },
+}
+
+impl LayoutChange {
+    /// Whether the change keeps the existing storage intact
+    pub fn is_compatible(&self) -> bool {
+        matches!(self, LayoutChange::Renamed { .. })
+    }
+}
+
+impl fmt::Display for LayoutChange {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            LayoutChange::Removed { label, slot, offset } => {
+                write!(f, "`{label}` (slot {slot}, offset {offset}) was removed or moved")
+            }
+            LayoutChange::TypeChanged { label, from, to } => {
+                write!(f, "the type of `{label}` changed from `{from}` to `{to}`")
+            }
+            LayoutChange::Renamed { from, to } => write!(f, "`{from}` was renamed to `{to}`"),
+            LayoutChange::GapOverflow { label } => {
+                write!(f, "the variables added in place of `{label}` exceed the gap")
+            }
+        }
+    }
+}
+
+/// A storage variable with its type resolved
+#[derive(Debug)]
+struct Variable<'a> {
+    label: &'a str,
+    slot: U256,
+    offset: i64,
+    ty: &'a str,
+    /// The number of bytes the variable occupies
+    size: U256,
+}
+
+impl<'a> Variable<'a> {
+    /// The slot after the last slot occupied by the variable
+    fn end(&self) -> U256 {
+        self.slot + (self.size + 31) / 32
+    }
+}
+
+fn variables(layout: &StorageLayout) -> Vec<Variable<'_>> {
+    layout
+        .storage
+        .iter()
+        .map(|var| {
+            let ty = layout.types.get(&var.storage_type);
+            Variable {
+                label: &var.label,
+                slot: U256::from_dec_str(&var.slot).unwrap_or_default(),
+                offset: var.offset,
+                ty: ty.map(|ty| ty.label.as_str()).unwrap_or(&var.storage_type),
+                size: ty
+                    .and_then(|ty| U256::from_dec_str(&ty.number_of_bytes).ok())
+                    .unwrap_or_default(),
+            }
+        })
+        .collect()
+}
+
+/// Compares the storage layout of the current implementation of a proxy with the layout of the
+/// implementation it is upgraded to.
+///
+/// Every variable of the old layout must keep its slot, offset and type. New variables may only be
+/// appended, or take the place of a storage gap, i.e. a variable whose name starts with `__gap`, as
+/// long as they fit into it.
+pub fn diff_layouts(old: &StorageLayout, new: &StorageLayout) -> Vec<LayoutChange> {
+    let new = variables(new);
+    let mut changes = Vec::new();
+    for var in variables(old) {
+        if var.label.starts_with("__gap") {
+            let overflows = new
+                .iter()
+                .filter(|new| new.slot >= var.slot && new.slot < var.end())
+                .any(|new| new.end() > var.end());
+            if overflows {
+                changes.push(LayoutChange::GapOverflow { label: var.label.to_string() });
+            }
+            continue
+        }
+
+        match new.iter().find(|new| new.slot == var.slot && new.offset == var.offset) {
+            None => changes.push(LayoutChange::Removed {
+                label: var.label.to_string(),
+                slot: var.slot,
+                offset: var.offset,
+            }),
+            Some(new) if new.ty != var.ty || new.size != var.size => {
+                changes.push(LayoutChange::TypeChanged {
+                    label: var.label.to_string(),
+                    from: var.ty.to_string(),
+                    to: new.ty.to_string(),
+                })
+            }
+            Some(new) if new.label != var.label => changes.push(LayoutChange::Renamed {
+                from: var.label.to_string(),
+                to: new.label.to_string(),
+            }),
+            Some(_) => {}
+        }
+    }
+    changes
+}
+
+impl ScriptArgs {
+    /// Checks that the implementations the script upgrades proxies to keep the storage layout of
+    /// the current implementations.
+    ///
+    /// The layout of the current implementation is taken from the deployment registry, or from
+    /// the compiled contract with the same runtime code. Incompatible upgrades abort the broadcast
+    /// unless `--force-upgrade` is set.
+    pub async fn check_upgrades(
+        &self,
+        transactions: &VecDeque<TypedTransaction>,
+        contracts: &BTreeMap<ArtifactId, ContractBytecodeSome>,
+        storage_layouts: &BTreeMap<ArtifactId, StorageLayout>,
+        registry: &DeploymentRegistry,
+        fork_url: &str,
+    ) -> eyre::Result<()> {
+        let provider = Provider::<Http>::try_from(fork_url)?;
+
+        // the creation code of the contracts deployed by the script, by their address
+        let created: BTreeMap<Address, &[u8]> = transactions
+            .iter()
+            .filter(|tx| tx.to().is_none())
+            .filter_map(|tx| {
+                let address = get_contract_address(*tx.from()?, *tx.nonce()?);
+                Some((address, tx.data()?.as_ref()))
+            })
+            .collect();
+
+        // the artifact of the contract deployed at the address, either by the script or before
+        let find_artifact = |address: Address| {
+            let provider = &provider;
+            let created = &created;
+            async move {
+                if let Some(code) = created.get(&address) {
+                    return Ok::<_, eyre::Report>(find_by_creation_code(contracts, code))
+                }
+                let code = provider.get_code(address, None).await?;
+                Ok(find_by_runtime_code(contracts, code.as_ref()))
+            }
+        };
+
+        let mut incompatible = false;
+        for tx in transactions {
+            let (to, data) = match (tx.to(), tx.data()) {
+                (Some(NameOrAddress::Address(to)), Some(data)) => (*to, data),
+                _ => continue,
+            };
+            let upgrade = match Upgrade::decode(to, data) {
+                Some(upgrade) => upgrade,
+                None => continue,
+            };
+
+            let slot = provider
+                .get_storage_at(upgrade.proxy, H256::from_str(IMPLEMENTATION_SLOT)?, None)
+                .await?;
+            let current = Address::from_slice(&slot.as_bytes()[12..]);
+            if current.is_zero() {
+                println!(
+                    "{} {:?} is not an EIP-1967 proxy, skipping the storage layout check of its upgrade.",
+                    Colour::Yellow.bold().paint("Warning:"),
+                    upgrade.proxy
+                );
+                continue
+            }
+
+            let old = match registry
+                .find_by_address(current)
+                .and_then(|deployment| deployment.storage_layout.as_ref())
+            {
+                Some(layout) => Some(layout),
+                None => find_artifact(current).await?.and_then(|id| storage_layouts.get(id)),
+            };
+            let new =
+                find_artifact(upgrade.implementation).await?.and_then(|id| storage_layouts.get(id));
+            let (old, new) = match (old, new) {
+                (Some(old), Some(new)) => (old, new),
+                _ => {
+                    println!(
+                        "{} Could not find the storage layouts of the upgrade of {:?} from {current:?} to {:?}, skipping the check.",
+                        Colour::Yellow.bold().paint("Warning:"),
+                        upgrade.proxy,
+                        upgrade.implementation
+                    );
+                    continue
+                }
+            };
+
+            for change in diff_layouts(old, new) {
+                if change.is_compatible() {
+                    println!("{} {change}", Colour::Yellow.bold().paint("Warning:"));
+                } else {
+                    incompatible = true;
+                    println!(
+                        "{} upgrade of {:?}: {change}",
+                        Colour::Red.bold().paint("Error:"),
+                        upgrade.proxy
+                    );
+                }
+            }
+        }
+
+        if incompatible && !self.force_upgrade {
+            eyre::bail!("The script upgrades proxies to implementations with an incompatible storage layout. Pass --force-upgrade to broadcast the transactions anyway.")
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn layout(vars: &[(&str, u64, i64, &str)]) -> StorageLayout {
+        let storage: Vec<_> = vars
+            .iter()
+            .map(|(label, slot, offset, ty)| {
+                json!({
+                    "astId": 1,
+                    "contract": "src/Counter.sol:Counter",
+                    "label": label,
+                    "offset": offset,
+                    "slot": slot.to_string(),
+                    "type": ty,
+                })
+            })
+            .collect();
+        serde_json::from_value(json!({
+            "storage": storage,
+            "types": {
+                "t_uint256": { "encoding": "inplace", "label": "uint256", "numberOfBytes": "32" },
+                "t_uint128": { "encoding": "inplace", "label": "uint128", "numberOfBytes": "16" },
+                "t_address": { "encoding": "inplace", "label": "address", "numberOfBytes": "20" },
+                "t_array(t_uint256)3_storage": {
+                    "base": "t_uint256",
+                    "encoding": "inplace",
+                    "label": "uint256[3]",
+                    "numberOfBytes": "96"
+                },
+                "t_array(t_uint256)2_storage": {
+                    "base": "t_uint256",
+                    "encoding": "inplace",
+                    "label": "uint256[2]",
+                    "numberOfBytes": "64"
+                }
+            }
+        }))
+        .unwrap()
+    }
+
+    #[test]
+    fn can_decode_upgrades() {
+        let proxy = Address::from_low_u64_be(1);
+        let implementation = Address::from_low_u64_be(2);
+        let word = |address: Address| H256::from(address).as_bytes().to_vec();
+
+        let data = [UPGRADE_TO.to_vec(), word(implementation)].concat();
+        assert_eq!(Upgrade::decode(proxy, &data), Some(Upgrade { proxy, implementation }));
+
+        let admin = Address::from_low_u64_be(3);
+        let data = [UPGRADE_AND_CALL.to_vec(), word(proxy), word(implementation)].concat();
+        assert_eq!(Upgrade::decode(admin, &data), Some(Upgrade { proxy, implementation }));
+
+        assert_eq!(Upgrade::decode(proxy, &UPGRADE_TO), None);
+        assert_eq!(Upgrade::decode(proxy, &[0xa9, 0x05, 0x9c, 0xbb]), None);
+    }
+
+    #[test]
+    fn can_diff_layouts() {
+        let old = layout(&[
+            ("owner", 0, 0, "t_address"),
+            ("count", 1, 0, "t_uint256"),
+            ("__gap", 2, 0, "t_array(t_uint256)3_storage"),
+        ]);
+
+        // appending variables and using the gap is compatible
+        let new = layout(&[
+            ("owner", 0, 0, "t_address"),
+            ("count", 1, 0, "t_uint256"),
+            ("limit", 2, 0, "t_uint256"),
+            ("__gap", 3, 0, "t_array(t_uint256)2_storage"),
+            ("extra", 5, 0, "t_uint256"),
+        ]);
+        assert!(diff_layouts(&old, &new).is_empty());
+
+        let new = layout(&[
+            ("admin", 0, 0, "t_address"),
+            ("count", 1, 0, "t_uint128"),
+            ("limit", 2, 0, "t_uint256"),
+            ("values", 3, 0, "t_array(t_uint256)3_storage"),
+        ]);
+        assert_eq!(
+            diff_layouts(&old, &new),
+            vec![
+                LayoutChange::Renamed { from: "owner".to_string(), to: "admin".to_string() },
+                LayoutChange::TypeChanged {
+                    label: "count".to_string(),
+                    from: "uint256".to_string(),
+                    to: "uint128".to_string()
+                },
+                LayoutChange::GapOverflow { label: "__gap".to_string() },
+            ]
+        );
+
+        let new = layout(&[("count", 1, 0, "t_uint256")]);
+        assert_eq!(
+            diff_layouts(&old, &new),
+            vec![LayoutChange::Removed { label: "owner".to_string(), slot: 0.into(), offset: 0 }]
+        );
+    }
+}
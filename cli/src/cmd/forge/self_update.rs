@@ -0,0 +1,275 @@
+//! Self update command
+use clap::Parser;
+use eyre::{Context, ContextCompat};
+use flate2::read::GzDecoder;
+use serde::Deserialize;
+use sha2::{Digest, Sha256};
+use std::{
+    fs,
+    io::Read,
+    path::{Path, PathBuf},
+};
+
+/// The repository releases are downloaded from
+const RELEASE_REPO: &str = "foundry-rs/foundry";
+
+/// The binaries of a release that are installed, other files of the release are ignored
+const BINARIES: &[&str] = &["forge", "cast", "anvil"];
+
+/// Replaces the installed binaries with a released version, like `foundryup` does
+#[derive(Debug, Clone, Parser)]
+pub struct SelfUpdateArgs {
+    #[clap(
+        long = "version",
+        short = 'v',
+        help = "The version to install, either `latest`, `nightly`, a nightly tag like `nightly-<sha>` or a release like `v0.2.0`.",
+        default_value = "latest",
+        value_name = "TAG"
+    )]
+    version: String,
+
+    #[clap(long, help = "Do not verify the checksum of the downloaded release.")]
+    no_verify: bool,
+}
+
+impl SelfUpdateArgs {
+    pub async fn run(self) -> eyre::Result<()> {
+        let client = reqwest::Client::builder().user_agent("forge").build()?;
+        let url = release_url(&self.version);
+        let release: Release = client
+            .get(&url)
+            .send()
+            .await?
+            .error_for_status()
+            .wrap_err_with(|| format!("Failed to look up the release {}", self.version))?
+            .json()
+            .await?;
+        let asset = release.asset(platform()?, architecture()?)?;
+
+        println!("Downloading foundry {} ({})", release.tag_name, asset.name);
+        let tarball = download(&client, &asset.browser_download_url).await?;
+
+        if self.no_verify {
+            println!("Skipping checksum verification");
+        } else {
+            let digest = asset.digest.as_deref().wrap_err_with(|| {
+                format!(
+                    "The release does not publish a checksum for {}, use `--no-verify` to install it anyway",
+                    asset.name
+                )
+            })?;
+            verify_checksum(&tarball, digest)?;
+        }
+
+        let current_exe = std::env::current_exe()?;
+        let bin_dir = current_exe.parent().wrap_err("Executable has no parent directory")?;
+        for path in install(&tarball, bin_dir)? {
+            println!("Installed {}", path.display());
+        }
+        Ok(())
+    }
+}
+
+/// A release as returned by the GitHub API
+#[derive(Debug, Deserialize)]
+struct Release {
+    tag_name: String,
+    assets: Vec<Asset>,
+}
+
+/// A file of a release
+#[derive(Debug, Deserialize)]
+struct Asset {
+    name: String,
+    browser_download_url: String,
+    /// The digest GitHub computed for the file, `sha256:<hex digest>`
+    digest: Option<String>,
+}
+
+impl Release {
+    /// Returns the tarball of the release for the platform and architecture
+    fn asset(&self, platform: &str, arch: &str) -> eyre::Result<&Asset> {
+        let suffix = format!("_{}_{}.tar.gz", platform, arch);
+        self.assets.iter().find(|asset| asset.name.ends_with(&suffix)).wrap_err_with(|| {
+            format!("Release {} has no binaries for {} {}", self.tag_name, platform, arch)
+        })
+    }
+}
+
+/// The GitHub API endpoint of the requested version, `latest` is the latest tagged release
+fn release_url(version: &str) -> String {
+    let version = version.trim();
+    let api = format!("https://api.github.com/repos/{}/releases", RELEASE_REPO);
+    if version == "latest" {
+        format!("{}/latest", api)
+    } else if version.starts_with(|c: char| c.is_ascii_digit()) {
+        format!("{}/tags/v{}", api, version)
+    } else {
+        format!("{}/tags/{}", api, version)
+    }
+}
+
+/// The platform as named in the release assets
+fn platform() -> eyre::Result<&'static str> {
+    match std::env::consts::OS {
+        "linux" => Ok("linux"),
+        "macos" => Ok("darwin"),
+        os => eyre::bail!("Unsupported platform: {}", os),
+    }
+}
+
+/// The architecture as named in the release assets
+fn architecture() -> eyre::Result<&'static str> {
+    match std::env::consts::ARCH {
+        "x86_64" => Ok("amd64"),
+        "aarch64" => Ok("arm64"),
+        arch => eyre::bail!("Unsupported architecture: {}", arch),
+    }
+}
+
+async fn download(client: &reqwest::Client, url: &str) -> eyre::Result<Vec<u8>> {
+    let res = client
+        .get(url)
+        .send()
+        .await?
+        .error_for_status()
+        .wrap_err_with(|| format!("Failed to download {}", url))?;
+    Ok(res.bytes().await?.to_vec())
+}
+
+/// Checks the content against the digest of a release asset, `sha256:<hex digest>`
+fn verify_checksum(content: &[u8], digest: &str) -> eyre::Result<()> {
+    let expected = digest
+        .strip_prefix("sha256:")
+        .wrap_err_with(|| format!("Unsupported checksum {}", digest))?;
+    let actual = hex::encode(Sha256::digest(content));
+    if !expected.eq_ignore_ascii_case(&actual) {
+        eyre::bail!("Checksum mismatch: expected {}, got {}", expected, actual)
+    }
+    Ok(())
+}
+
+/// Unpacks the [BINARIES] of the tarball into `bin_dir`, replacing the existing binaries.
+///
+/// Binaries are written next to their destination first and then renamed, so that the running
+/// executable is replaced atomically.
+fn install(tarball: &[u8], bin_dir: &Path) -> eyre::Result<Vec<PathBuf>> {
+    let mut installed = Vec::new();
+    let mut archive = tar::Archive::new(GzDecoder::new(tarball));
+    for entry in archive.entries()? {
+        let mut entry = entry?;
+        if !entry.header().entry_type().is_file() {
+            continue
+        }
+        let path = entry.path()?;
+        let name = match path.file_name().and_then(|name| name.to_str()) {
+            Some(name) if BINARIES.contains(&name) => name.to_string(),
+            _ => continue,
+        };
+        let mut content = Vec::new();
+        entry.read_to_end(&mut content)?;
+
+        let dest = bin_dir.join(&name);
+        let tmp = bin_dir.join(format!(".{}.tmp", name));
+        fs::write(&tmp, content).wrap_err_with(|| format!("Failed to write {:?}", tmp))?;
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            fs::set_permissions(&tmp, fs::Permissions::from_mode(0o755))?;
+        }
+        fs::rename(&tmp, &dest).wrap_err_with(|| format!("Failed to replace {:?}", dest))?;
+        installed.push(dest);
+    }
+
+    if installed.is_empty() {
+        eyre::bail!("The release does not contain any binaries")
+    }
+    Ok(installed)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn tarball(files: &[(&str, &[u8])]) -> Vec<u8> {
+        let mut builder = tar::Builder::new(flate2::write::GzEncoder::new(
+            Vec::new(),
+            flate2::Compression::default(),
+        ));
+        for (path, content) in files {
+            let mut header = tar::Header::new_gnu();
+            header.set_size(content.len() as u64);
+            header.set_mode(0o755);
+            header.set_cksum();
+            builder.append_data(&mut header, path, *content).unwrap();
+        }
+        builder.into_inner().unwrap().finish().unwrap()
+    }
+
+    #[test]
+    fn can_verify_checksum() {
+        let digest = "sha256:2cf24dba5fb0a30e26e83b2ac5b9e29e1b161e5c1fa7425e73043362938b9824";
+        verify_checksum(b"hello", digest).unwrap();
+        assert!(verify_checksum(b"hello!", digest).is_err());
+        assert!(verify_checksum(b"hello", "md5:5d41402abc4b2a76b9719d911017c592").is_err());
+    }
+
+    #[test]
+    fn resolves_release_urls() {
+        let api = "https://api.github.com/repos/foundry-rs/foundry/releases";
+        assert_eq!(release_url("latest"), format!("{}/latest", api));
+        assert_eq!(release_url("nightly"), format!("{}/tags/nightly", api));
+        assert_eq!(release_url("nightly-abc"), format!("{}/tags/nightly-abc", api));
+        assert_eq!(release_url("0.2.0"), format!("{}/tags/v0.2.0", api));
+        assert_eq!(release_url("v0.2.0"), format!("{}/tags/v0.2.0", api));
+    }
+
+    #[test]
+    fn can_find_release_asset() {
+        let release: Release = serde_json::from_str(
+            r#"{
+                "tag_name": "v0.2.0",
+                "assets": [
+                    {
+                        "name": "foundry_v0.2.0_darwin_amd64.tar.gz",
+                        "browser_download_url": "https://github.com/foundry-rs/foundry/releases/download/v0.2.0/foundry_v0.2.0_darwin_amd64.tar.gz",
+                        "digest": null
+                    },
+                    {
+                        "name": "foundry_v0.2.0_linux_amd64.tar.gz",
+                        "browser_download_url": "https://github.com/foundry-rs/foundry/releases/download/v0.2.0/foundry_v0.2.0_linux_amd64.tar.gz",
+                        "digest": "sha256:2cf24dba5fb0a30e26e83b2ac5b9e29e1b161e5c1fa7425e73043362938b9824"
+                    }
+                ]
+            }"#,
+        )
+        .unwrap();
+
+        let asset = release.asset("linux", "amd64").unwrap();
+        assert_eq!(asset.name, "foundry_v0.2.0_linux_amd64.tar.gz");
+        assert!(asset.digest.is_some());
+        assert!(release.asset("darwin", "amd64").unwrap().digest.is_none());
+        assert!(release.asset("linux", "arm64").is_err());
+    }
+
+    #[test]
+    fn can_install_from_tarball() {
+        let tarball = tarball(&[("forge", b"new!"), ("README.md", b"docs"), ("bin/cast", b"cast")]);
+
+        let dir = tempfile::tempdir().unwrap();
+        fs::write(dir.path().join("forge"), "old").unwrap();
+
+        let installed = install(&tarball, dir.path()).unwrap();
+        assert_eq!(installed, vec![dir.path().join("forge"), dir.path().join("cast")]);
+        assert_eq!(fs::read_to_string(dir.path().join("forge")).unwrap(), "new!");
+        // only the binaries of the release are installed
+        assert!(!dir.path().join("README.md").exists());
+    }
+
+    #[test]
+    fn fails_without_binaries() {
+        let tarball = tarball(&[("README.md", b"docs")]);
+        let dir = tempfile::tempdir().unwrap();
+        assert!(install(&tarball, dir.path()).is_err());
+    }
+}
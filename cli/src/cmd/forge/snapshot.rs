@@ -27,7 +27,7 @@ use watchexec::config::{InitConfig, RuntimeConfig};
 /// A regex that matches a basic snapshot entry like
 /// `Test:testDeposit() (gas: 58804)`
 pub static RE_BASIC_SNAPSHOT_ENTRY: Lazy<Regex> = Lazy::new(|| {
-    Regex::new(r"(?P<file>(.*?)):(?P<sig>(\w+)\s*\((.*?)\))\s*\(((gas:)?\s*(?P<gas>\d+)|(runs:\s*(?P<runs>\d+),\s*μ:\s*(?P<avg>\d+),\s*~:\s*(?P<med>\d+)))\)").unwrap()
+    Regex::new(r"(?P<file>(.*?)):(?P<sig>(\w+)\s*\((.*?)\)(\s*\[[^\]]*\])?)\s*\(((gas:)?\s*(?P<gas>\d+)|(runs:\s*(?P<runs>\d+),\s*μ:\s*(?P<avg>\d+),\s*~:\s*(?P<med>\d+)))\)").unwrap()
 });
 
 #[derive(Debug, Clone, Parser)]
@@ -105,7 +105,7 @@ impl Cmd for SnapshotArgs {
     fn run(self) -> eyre::Result<()> {
         let outcome = custom_run(self.test, self.include_fuzz_tests)?;
         outcome.ensure_ok()?;
-        let tests = self.config.apply(outcome);
+        let tests = snapshot_entries(self.config.apply(outcome));
 
         if let Some(path) = self.diff {
             let snap = path.as_ref().unwrap_or(&self.snap);
@@ -250,21 +250,37 @@ fn read_snapshot(path: impl AsRef<Path>) -> eyre::Result<Vec<SnapshotEntry>> {
     Ok(entries)
 }
 
-/// Writes a series of tests to a snapshot file
+/// Returns the snapshot entries of the tests.
+///
+/// Every test is followed by the code regions it measured with `startSnapshotGas` and
+/// `stopSnapshotGas`, e.g. `Test:testDeposit() [transfer] (gas: 21000)`.
+fn snapshot_entries(tests: Vec<Test>) -> Vec<SnapshotEntry> {
+    let mut entries = Vec::with_capacity(tests.len());
+    for test in tests {
+        let contract_name = test.contract_name().to_string();
+        entries.push(SnapshotEntry {
+            contract_name: contract_name.clone(),
+            signature: test.signature.clone(),
+            gas_used: test.result.kind.gas_used(),
+        });
+        entries.extend(test.result.gas_snapshots.iter().map(|(name, gas)| SnapshotEntry {
+            contract_name: contract_name.clone(),
+            signature: format!("{} [{}]", test.signature, name),
+            gas_used: TestKindGas::Standard(*gas),
+        }));
+    }
+    entries
+}
+
+/// Writes a series of snapshot entries to a snapshot file
 fn write_to_snapshot_file(
-    tests: &[Test],
+    tests: &[SnapshotEntry],
     path: impl AsRef<Path>,
     _format: Option<Format>,
 ) -> eyre::Result<()> {
     let mut out = String::new();
     for test in tests {
-        writeln!(
-            out,
-            "{}:{} {}",
-            test.contract_name(),
-            test.signature,
-            test.result.kind.gas_used()
-        )?;
+        writeln!(out, "{}:{} {}", test.contract_name, test.signature, test.gas_used)?;
     }
     Ok(fs::write(path, out)?)
 }
@@ -295,7 +311,7 @@ impl SnapshotDiff {
 /// Compares the set of tests with an existing snapshot
 ///
 /// Returns true all tests match
-fn check(tests: Vec<Test>, snaps: Vec<SnapshotEntry>) -> bool {
+fn check(tests: Vec<SnapshotEntry>, snaps: Vec<SnapshotEntry>) -> bool {
     let snaps = snaps
         .into_iter()
         .map(|s| ((s.contract_name, s.signature), s.gas_used))
@@ -303,24 +319,20 @@ fn check(tests: Vec<Test>, snaps: Vec<SnapshotEntry>) -> bool {
     let mut has_diff = false;
     for test in tests {
         if let Some(target_gas) =
-            snaps.get(&(test.contract_name().to_string(), test.signature.clone())).cloned()
+            snaps.get(&(test.contract_name.clone(), test.signature.clone())).cloned()
         {
-            let source_gas = test.result.kind.gas_used();
+            let source_gas = test.gas_used;
             if source_gas.gas() != target_gas.gas() {
                 eprintln!(
                     "Diff in \"{}::{}\": consumed \"{}\" gas, expected \"{}\" gas ",
-                    test.contract_name(),
-                    test.signature,
-                    source_gas,
-                    target_gas
+                    test.contract_name, test.signature, source_gas, target_gas
                 );
                 has_diff = true;
             }
         } else {
             eprintln!(
                 "No matching snapshot entry found for \"{}::{}\" in snapshot file",
-                test.contract_name(),
-                test.signature
+                test.contract_name, test.signature
             );
             has_diff = true;
         }
@@ -329,17 +341,15 @@ fn check(tests: Vec<Test>, snaps: Vec<SnapshotEntry>) -> bool {
 }
 
 /// Compare the set of tests with an existing snapshot
-fn diff(tests: Vec<Test>, snaps: Vec<SnapshotEntry>) -> eyre::Result<()> {
+fn diff(tests: Vec<SnapshotEntry>, snaps: Vec<SnapshotEntry>) -> eyre::Result<()> {
     let snaps = snaps
         .into_iter()
         .map(|s| ((s.contract_name, s.signature), s.gas_used))
         .collect::<HashMap<_, _>>();
     let mut diffs = Vec::with_capacity(tests.len());
     for test in tests.into_iter() {
-        let target_gas_used = snaps
-            .get(&(test.contract_name().to_string(), test.signature.clone()))
-            .cloned()
-            .ok_or_else(|| {
+        let target_gas_used =
+            snaps.get(&(test.contract_name, test.signature.clone())).cloned().ok_or_else(|| {
                 eyre::eyre!(
                     "No matching snapshot entry found for \"{}\" in snapshot file",
                     test.signature
@@ -347,7 +357,7 @@ fn diff(tests: Vec<Test>, snaps: Vec<SnapshotEntry>) -> eyre::Result<()> {
             })?;
 
         diffs.push(SnapshotDiff {
-            source_gas_used: test.result.kind.gas_used(),
+            source_gas_used: test.gas_used,
             signature: test.signature,
             target_gas_used,
         });
@@ -418,6 +428,20 @@ mod tests {
         );
     }
 
+    #[test]
+    fn can_parse_gas_snapshot_section_entry() {
+        let s = "Test:testDeposit() [transfer] (gas: 2100)";
+        let entry = SnapshotEntry::from_str(s).unwrap();
+        assert_eq!(
+            entry,
+            SnapshotEntry {
+                contract_name: "Test".to_string(),
+                signature: "testDeposit() [transfer]".to_string(),
+                gas_used: TestKindGas::Standard(2100)
+            }
+        );
+    }
+
     #[test]
     fn can_parse_fuzz_snapshot_entry() {
         let s = "Test:deposit() (runs: 256, μ: 100, ~:200)";
@@ -1,35 +1,56 @@
 //! Test command
 use crate::{
     cmd::{
-        forge::{build::CoreBuildArgs, run::RunArgs, watch::WatchArgs},
+        forge::{
+            build::CoreBuildArgs,
+            script::{ScriptArgs, ScriptWallet},
+            watch::WatchArgs,
+        },
         Cmd,
     },
-    compile::ProjectCompiler,
+    compile::{self, ProjectCompiler},
     utils,
     utils::FoundryPathExt,
 };
 use ansi_term::Colour;
-use clap::{AppSettings, Parser};
-use ethers::solc::FileFilter;
+use clap::{AppSettings, ArgEnum, Parser};
+use comfy_table::{
+    modifiers::UTF8_ROUND_CORNERS, presets::UTF8_FULL, Attribute, Cell, Color, Table,
+};
+use ethers::{
+    abi::Abi,
+    solc::{
+        artifacts::{CompactContractBytecode, ContractBytecode},
+        FileFilter, Project, ProjectCompileOutput,
+    },
+};
 use forge::{
     decode::decode_console_logs,
     executor::opts::EvmOpts,
+    fuzz::DifferentialReference,
+    gas_lines::LineGasReport,
     gas_report::GasReport,
     trace::{
         identifier::{EtherscanIdentifier, LocalTraceIdentifier},
-        CallTraceDecoderBuilder, TraceKind,
+        parse_signatures, CallTraceDecoderBuilder, TraceKind,
     },
-    MultiContractRunner, MultiContractRunnerBuilder, SuiteResult, TestFilter, TestKind,
+    MultiContractRunner, MultiContractRunnerBuilder, SuiteResult, TestEvent, TestFilter, TestKind,
+    TestProgress,
 };
 use foundry_common::evm::EvmArgs;
-use foundry_config::{figment::Figment, Config};
+use foundry_config::{
+    figment::{providers::Serialized, value::Dict, Figment, Provider},
+    Config,
+};
+use foundry_utils::SignaturesCache;
 use regex::Regex;
+use serde::Serialize;
 use std::{
     collections::BTreeMap,
     path::{Path, PathBuf},
     sync::mpsc::channel,
     thread,
-    time::Duration,
+    time::{Duration, Instant},
 };
 use watchexec::config::{InitConfig, RuntimeConfig};
 
@@ -73,45 +94,52 @@ pub struct Filter {
 
 impl Filter {
     pub fn with_merged_config(&self) -> Self {
-        let config = Config::load();
+        self.merged_with(&Config::load())
+    }
+
+    /// Returns the filter with the patterns of the `config` that are not set by the arguments
+    pub fn merged_with(&self, config: &Config) -> Self {
         let mut filter = self.clone();
         if filter.test_pattern.is_none() {
-            filter.test_pattern = config.test_pattern.map(|p| p.into());
+            filter.test_pattern = config.test_pattern.clone().map(|p| p.into());
         }
         if filter.test_pattern_inverse.is_none() {
-            filter.test_pattern_inverse = config.test_pattern_inverse.map(|p| p.into());
+            filter.test_pattern_inverse = config.test_pattern_inverse.clone().map(|p| p.into());
         }
         if filter.contract_pattern.is_none() {
-            filter.contract_pattern = config.contract_pattern.map(|p| p.into());
+            filter.contract_pattern = config.contract_pattern.clone().map(|p| p.into());
         }
         if filter.contract_pattern_inverse.is_none() {
-            filter.contract_pattern_inverse = config.contract_pattern_inverse.map(|p| p.into());
+            filter.contract_pattern_inverse =
+                config.contract_pattern_inverse.clone().map(|p| p.into());
         }
         if filter.path_pattern.is_none() {
-            filter.path_pattern = config.path_pattern;
+            filter.path_pattern = config.path_pattern.clone();
         }
         if filter.path_pattern_inverse.is_none() {
-            filter.path_pattern_inverse = config.path_pattern_inverse;
+            filter.path_pattern_inverse = config.path_pattern_inverse.clone();
         }
         filter
     }
 }
 
 impl FileFilter for Filter {
-    /// Returns true if the file regex pattern match the `file`
+    /// Returns true if the file glob pattern matches the `file` and the inverse pattern doesn't
     ///
-    /// If no file regex is set this returns true if the file ends with `.t.sol`, see
+    /// If no file glob is set this returns true if the file ends with `.t.sol`, see
     /// [FoundryPathExr::is_sol_test()]
     fn is_match(&self, file: &Path) -> bool {
-        if let Some(file) = file.as_os_str().to_str() {
-            if let Some(ref glob) = self.path_pattern {
-                return glob.compile_matcher().is_match(file)
-            }
-            if let Some(ref glob) = self.path_pattern_inverse {
-                return !glob.compile_matcher().is_match(file)
-            }
-        }
-        file.is_sol_test()
+        let path = file.as_os_str().to_str().unwrap_or_default();
+        let included = match self.path_pattern {
+            Some(ref glob) => glob.compile_matcher().is_match(path),
+            None => file.is_sol_test(),
+        };
+        // the inverse pattern also applies to the `--match-path` injected by `forge test --watch`
+        let excluded = match self.path_pattern_inverse {
+            Some(ref glob) => glob.compile_matcher().is_match(path),
+            None => false,
+        };
+        included && !excluded
     }
 }
 
@@ -188,14 +216,62 @@ pub struct TestArgs {
     #[clap(long, env = "FORGE_GAS_REPORT")]
     gas_report: bool,
 
+    /// Print the gas used by each source line of the called contracts in the standard tests.
+    ///
+    /// The most expensive lines of every function are listed in a table per source file. With
+    /// `json`, the gas of all lines is printed as JSON, by source path and line number.
+    #[clap(
+        long,
+        arg_enum,
+        value_name = "FORMAT",
+        min_values = 0,
+        default_missing_value = "text",
+        conflicts_with_all = &["debug", "json"]
+    )]
+    gas_lines: Option<GasLinesFormat>,
+
     /// Exit with code 0 even if a test fails.
     #[clap(long, env = "FORGE_ALLOW_FAILURE")]
     allow_failure: bool,
 
+    /// Run the tests once for every given config profile and compare the results.
+    ///
+    /// The profiles are selected the same way as with `FOUNDRY_PROFILE`.
+    #[clap(
+        long,
+        value_name = "PROFILES",
+        use_value_delimiter = true,
+        conflicts_with_all = &["debug", "watch"]
+    )]
+    profiles: Vec<String>,
+
+    /// The total number of worker threads to run the tests on, defaults to the number of CPUs.
+    ///
+    /// Test contracts and the tests within them share these threads, so this limits the number of
+    /// tests that run in parallel, not the number of test contracts.
+    ///
+    /// The solc processes that compile the project are limited with `--jobs`.
+    #[clap(long, value_name = "JOBS")]
+    test_jobs: Option<usize>,
+
     /// Output test results in JSON format.
     #[clap(long, short, help_heading = "DISPLAY OPTIONS")]
     json: bool,
 
+    /// Print a summary table of the test results grouped by test suite.
+    #[clap(long, help_heading = "DISPLAY OPTIONS")]
+    summary: bool,
+
+    /// Also list the tests with the highest gas usage and the slowest tests in the summary.
+    #[clap(long, requires = "summary", help_heading = "DISPLAY OPTIONS")]
+    detailed: bool,
+
+    /// Stream progress events to stderr while compiling and running the tests.
+    ///
+    /// With `json`, every event is printed as a JSON object on its own line.
+    #[clap(long, arg_enum, value_name = "FORMAT", help_heading = "DISPLAY OPTIONS")]
+    progress: Option<ProgressFormat>,
+
     #[clap(flatten, next_help_heading = "EVM OPTIONS")]
     evm_opts: EvmArgs,
 
@@ -211,6 +287,11 @@ pub struct TestArgs {
 
     #[clap(flatten, next_help_heading = "WATCH OPTIONS")]
     pub watch: WatchArgs,
+
+    /// The config profile of a run of `--profiles`, which replaces the profile selected by
+    /// `FOUNDRY_PROFILE`
+    #[clap(skip)]
+    profile: Option<String>,
 }
 
 impl TestArgs {
@@ -227,9 +308,22 @@ impl TestArgs {
     /// Returns the currently configured [Config] and the extracted [EvmOpts] from that config
     pub fn config_and_evm_opts(&self) -> eyre::Result<(Config, EvmOpts)> {
         // merge all configs
-        let figment: Figment = self.into();
-        let evm_opts = figment.extract()?;
+        let mut figment: Figment = self.into();
+        if let Some(ref profile) = self.profile {
+            // the arguments are merged again as global values, so that they still take precedence
+            // over the values of the selected profile
+            figment = figment
+                .select(profile.as_str())
+                .merge(Serialized::globals(selected_profile_data(&self.opts)?))
+                .merge(Serialized::globals(selected_profile_data(&self.evm_opts)?));
+        }
+        let mut evm_opts = figment.extract()?;
         let mut config = Config::from_provider(figment).sanitized();
+        utils::resolve_rpc_endpoints(&mut config, &mut evm_opts)?;
+
+        // the allocs path is relative to the project root
+        evm_opts.allocs = config.allocs.clone();
+        evm_opts.fs_permissions = config.fs_permissions.clone();
 
         // merging etherscan api key into Config
         if let Some(etherscan_api_key) = &self.etherscan_api_key {
@@ -257,10 +351,111 @@ impl Cmd for TestArgs {
     type Output = TestOutcome;
 
     fn run(self) -> eyre::Result<Self::Output> {
-        custom_run(self, true)
+        if self.profiles.is_empty() {
+            custom_run(self, true)
+        } else {
+            run_profiles(self)
+        }
     }
 }
 
+/// Runs the tests once for every profile of `--profiles` and prints how the results compare
+///
+/// The returned outcome contains the suites of all runs, named `<suite> [<profile>]`
+fn run_profiles(args: TestArgs) -> eyre::Result<TestOutcome> {
+    let mut outcomes = Vec::with_capacity(args.profiles.len());
+    for profile in args.profiles.iter() {
+        if !args.json {
+            println!("{}", Colour::Cyan.paint(format!("Running tests with profile `{profile}`")));
+        }
+        let mut args = args.clone();
+        args.profiles.clear();
+        args.profile = Some(profile.clone());
+        outcomes.push((profile.clone(), custom_run(args, true)?));
+    }
+
+    if !args.json {
+        println!("\nProfile summary:");
+        println!("{}", profiles_summary_table(&outcomes));
+
+        let diverging = diverging_tests(&outcomes);
+        if !diverging.is_empty() {
+            println!("\nTests with different results across profiles:");
+            let mut header = vec!["Test Suite", "Test"];
+            header.extend(outcomes.iter().map(|(profile, _)| profile.as_str()));
+            let mut table = summary_table(header);
+            for ((suite, test), statuses) in diverging {
+                let mut row =
+                    vec![Cell::new(suite), Cell::new(test).add_attribute(Attribute::Bold)];
+                row.extend(statuses.into_iter().map(|status| match status {
+                    Some(TestStatus::Pass) => Cell::new("pass").fg(Color::Green),
+                    Some(TestStatus::Fail) => Cell::new("fail").fg(Color::Red),
+                    Some(TestStatus::Skip) => Cell::new("skip").fg(Color::Yellow),
+                    None => Cell::new("-"),
+                }));
+                table.add_row(row);
+            }
+            println!("{table}");
+        }
+    }
+
+    let results = outcomes
+        .into_iter()
+        .flat_map(|(profile, outcome)| {
+            outcome
+                .results
+                .into_iter()
+                .map(move |(suite, result)| (format!("{suite} [{profile}]"), result))
+        })
+        .collect();
+    Ok(TestOutcome::new(results, args.allow_failure))
+}
+
+/// Returns the values of the arguments, which are keyed by the profile selected by
+/// `FOUNDRY_PROFILE`
+fn selected_profile_data(args: &impl Provider) -> eyre::Result<Dict> {
+    Ok(args.data()?.remove(&Config::selected_profile()).unwrap_or_default())
+}
+
+/// Returns a table with the number of passed, failed and skipped tests and the duration of the
+/// run of each profile
+fn profiles_summary_table(outcomes: &[(String, TestOutcome)]) -> Table {
+    let mut table = summary_table(["Profile", "Passed", "Failed", "Skipped", "Duration"]);
+    for (profile, outcome) in outcomes {
+        let failed = outcome.failures().count();
+        table.add_row(vec![
+            Cell::new(profile).add_attribute(Attribute::Bold),
+            Cell::new(outcome.successes().count()).fg(Color::Green),
+            Cell::new(failed).fg(if failed > 0 { Color::Red } else { Color::Reset }),
+            Cell::new(outcome.skips().count()).fg(Color::Yellow),
+            Cell::new(format!("{:.2?}", outcome.duration())),
+        ]);
+    }
+    table
+}
+
+/// Returns the status of every `(suite, test)` in each profile run, for the tests whose status
+/// isn't the same in all runs
+///
+/// A status is `None` if the test wasn't run with that profile
+fn diverging_tests(
+    outcomes: &[(String, TestOutcome)],
+) -> BTreeMap<(String, String), Vec<Option<TestStatus>>> {
+    let mut statuses: BTreeMap<(String, String), Vec<Option<TestStatus>>> = BTreeMap::new();
+    for (idx, (_, outcome)) in outcomes.iter().enumerate() {
+        for (suite, SuiteResult { test_results, .. }) in outcome.results.iter() {
+            for (test, result) in test_results.iter() {
+                statuses
+                    .entry((suite.clone(), test.clone()))
+                    .or_insert_with(|| vec![None; outcomes.len()])[idx] =
+                    Some(TestStatus::new(result));
+            }
+        }
+    }
+    statuses.retain(|_, statuses| statuses.windows(2).any(|pair| pair[0] != pair[1]));
+    statuses
+}
+
 /// The result of a single test
 #[derive(Debug, Clone)]
 pub struct Test {
@@ -304,7 +499,12 @@ impl TestOutcome {
 
     /// Iterator over all succeeding tests and their names
     pub fn successes(&self) -> impl Iterator<Item = (&String, &forge::TestResult)> {
-        self.tests().filter(|(_, t)| t.success)
+        self.tests().filter(|(_, t)| t.success && !t.skipped)
+    }
+
+    /// Iterator over all skipped tests and their names
+    pub fn skips(&self) -> impl Iterator<Item = (&String, &forge::TestResult)> {
+        self.tests().filter(|(_, t)| t.skipped)
     }
 
     /// Iterator over all failing tests and their names
@@ -362,17 +562,188 @@ impl TestOutcome {
         let result =
             if failed == 0 { Colour::Green.paint("ok") } else { Colour::Red.paint("FAILED") };
         format!(
-            "Test result: {}. {} passed; {} failed; finished in {:.2?}",
+            "Test result: {}. {} passed; {} failed; {} skipped; finished in {:.2?}",
             result,
             self.successes().count(),
             failed,
+            self.skips().count(),
             self.duration()
         )
     }
+
+    /// Returns a table with the number of passed and failed tests, the total gas and the duration
+    /// of each test suite.
+    pub fn summary_table(&self) -> Table {
+        let mut table =
+            summary_table(["Test Suite", "Passed", "Failed", "Skipped", "Total Gas", "Duration"]);
+        for (name, suite) in self.results.iter() {
+            let skipped = suite.test_results.values().filter(|t| t.skipped).count();
+            let passed = suite.test_results.values().filter(|t| t.success).count() - skipped;
+            let failed = suite.len() - passed - skipped;
+            let gas: u64 = suite.test_results.values().map(|t| t.kind.gas_used().gas()).sum();
+            table.add_row(vec![
+                Cell::new(name).add_attribute(Attribute::Bold),
+                Cell::new(passed).fg(Color::Green),
+                Cell::new(failed).fg(if failed > 0 { Color::Red } else { Color::Reset }),
+                Cell::new(skipped).fg(Color::Yellow),
+                Cell::new(gas),
+                Cell::new(format!("{:.2?}", suite.duration)),
+            ]);
+        }
+        table
+    }
+
+    /// Returns tables of the `n` tests with the highest gas usage and the `n` slowest tests
+    pub fn detailed_tables(&self, n: usize) -> (Table, Table) {
+        let mut tests: Vec<_> = self
+            .results
+            .iter()
+            .flat_map(|(suite, SuiteResult { test_results, .. })| {
+                test_results.iter().map(move |(name, result)| (suite, name, result))
+            })
+            .collect();
+
+        let mut gas_table = summary_table(["Test Suite", "Test", "Gas"]);
+        tests.sort_by_key(|(_, _, result)| std::cmp::Reverse(result.kind.gas_used().gas()));
+        for (suite, name, result) in tests.iter().take(n) {
+            gas_table.add_row(vec![
+                Cell::new(suite),
+                Cell::new(name).add_attribute(Attribute::Bold),
+                Cell::new(result.kind.gas_used().gas()),
+            ]);
+        }
+
+        let mut duration_table = summary_table(["Test Suite", "Test", "Duration"]);
+        tests.sort_by_key(|(_, _, result)| std::cmp::Reverse(result.duration));
+        for (suite, name, result) in tests.iter().take(n) {
+            duration_table.add_row(vec![
+                Cell::new(suite),
+                Cell::new(name).add_attribute(Attribute::Bold),
+                Cell::new(format!("{:.2?}", result.duration)),
+            ]);
+        }
+
+        (gas_table, duration_table)
+    }
+}
+
+/// The formats of the report of `forge test --gas-lines`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ArgEnum)]
+pub enum GasLinesFormat {
+    Text,
+    Json,
+}
+
+/// The formats of the progress events of `forge test --progress`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ArgEnum)]
+pub enum ProgressFormat {
+    Json,
+}
+
+/// A progress event of `forge test --progress json`, printed to stderr as a single line of JSON
+#[derive(Debug, Serialize)]
+#[serde(tag = "event", rename_all = "snake_case")]
+enum ProgressEvent<'a> {
+    CompilationStarted,
+    CompilationFinished {
+        success: bool,
+        duration_ms: u64,
+    },
+    SuiteStarted {
+        suite: &'a str,
+    },
+    TestStarted {
+        suite: &'a str,
+        test: &'a str,
+    },
+    TestFinished {
+        suite: &'a str,
+        test: &'a str,
+        status: TestStatus,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        reason: Option<&'a str>,
+        gas: u64,
+        duration_ms: u64,
+    },
+    SuiteFinished {
+        suite: &'a str,
+        passed: usize,
+        failed: usize,
+        skipped: usize,
+        duration_ms: u64,
+    },
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+enum TestStatus {
+    Pass,
+    Fail,
+    Skip,
+}
+
+impl TestStatus {
+    fn new(result: &forge::TestResult) -> Self {
+        if result.skipped {
+            TestStatus::Skip
+        } else if result.success {
+            TestStatus::Pass
+        } else {
+            TestStatus::Fail
+        }
+    }
+}
+
+impl<'a> ProgressEvent<'a> {
+    fn from_test_event(suite: &'a str, event: TestEvent<'a>) -> Self {
+        match event {
+            TestEvent::SuiteStarted => ProgressEvent::SuiteStarted { suite },
+            TestEvent::TestStarted { test } => ProgressEvent::TestStarted { suite, test },
+            TestEvent::TestFinished { test, result } => ProgressEvent::TestFinished {
+                suite,
+                test,
+                status: TestStatus::new(result),
+                reason: result.reason.as_deref(),
+                gas: result.kind.gas_used().gas(),
+                duration_ms: result.duration.as_millis() as u64,
+            },
+            TestEvent::SuiteFinished { result } => {
+                let count = |status| {
+                    result.test_results.values().filter(|t| TestStatus::new(t) == status).count()
+                };
+                ProgressEvent::SuiteFinished {
+                    suite,
+                    passed: count(TestStatus::Pass),
+                    failed: count(TestStatus::Fail),
+                    skipped: count(TestStatus::Skip),
+                    duration_ms: result.duration.as_millis() as u64,
+                }
+            }
+        }
+    }
+
+    /// Prints the event to stderr, each event is printed on a single line
+    fn emit(&self) {
+        if let Ok(line) = serde_json::to_string(self) {
+            eprintln!("{line}");
+        }
+    }
+}
+
+/// Returns an empty table with the given bold header
+fn summary_table<'a>(header: impl IntoIterator<Item = &'a str>) -> Table {
+    let mut table = Table::new();
+    table.load_preset(UTF8_FULL).apply_modifier(UTF8_ROUND_CORNERS);
+    table.set_header(
+        header.into_iter().map(|title| Cell::new(title).add_attribute(Attribute::Bold)),
+    );
+    table
 }
 
 fn short_test_result(name: &str, result: &forge::TestResult) {
-    let status = if result.success {
+    let status = if result.skipped {
+        Colour::Yellow.paint("[SKIP]")
+    } else if result.success {
         Colour::Green.paint("[PASS]")
     } else {
         let txt = match (&result.reason, &result.counterexample) {
@@ -391,7 +762,10 @@ fn short_test_result(name: &str, result: &forge::TestResult) {
         Colour::Red.paint(txt)
     };
 
-    println!("{} {} {}", status, name, result.kind.gas_used());
+    match result.differential {
+        Some(ref stats) => println!("{} {} {} {}", status, name, result.kind.gas_used(), stats),
+        None => println!("{} {} {}", status, name, result.kind.gas_used()),
+    }
 }
 
 pub fn custom_run(args: TestArgs, include_fuzz_tests: bool) -> eyre::Result<TestOutcome> {
@@ -408,16 +782,32 @@ pub fn custom_run(args: TestArgs, include_fuzz_tests: bool) -> eyre::Result<Test
         ..Default::default()
     };
     let fuzzer = proptest::test_runner::TestRunner::new(cfg);
-    let mut filter = args.filter();
+    let mut filter = args.filter.merged_with(&config);
 
     // Set up the project
     let project = config.project()?;
     let compiler = ProjectCompiler::default();
+    let progress = args.progress.is_some();
+    if progress {
+        ProgressEvent::CompilationStarted.emit();
+    }
+    let start = Instant::now();
     let output = if config.sparse_mode {
         compiler.compile_sparse(&project, filter.clone())
     } else {
         compiler.compile(&project)
-    }?;
+    };
+    if progress {
+        ProgressEvent::CompilationFinished {
+            success: output.is_ok(),
+            duration_ms: start.elapsed().as_millis() as u64,
+        }
+        .emit();
+    }
+    let output = output?;
+    compile::compile_vyper(&project, &config, args.json)?;
+    let mut line_gas_report =
+        args.gas_lines.is_some().then(|| collect_line_gas_report(&project, &output)).transpose()?;
 
     // Determine print verbosity and executor verbosity
     let verbosity = evm_opts.verbosity;
@@ -427,7 +817,20 @@ pub fn custom_run(args: TestArgs, include_fuzz_tests: bool) -> eyre::Result<Test
 
     // Prepare the test builder
     let evm_spec = crate::utils::evm_spec(&config.evm_version);
-    let mut runner = MultiContractRunnerBuilder::default()
+    let mut builder = MultiContractRunnerBuilder::default();
+    if let Some(jobs) = args.test_jobs {
+        builder = builder.jobs(jobs);
+    }
+    builder = builder.gas_profiling(line_gas_report.is_some()).count_precompiles(verbosity >= 2);
+    for (function, command) in config.differential.iter() {
+        builder = builder.differential(function, DifferentialReference::command(command));
+    }
+    if progress {
+        builder = builder.progress(TestProgress::new(|suite, event| {
+            ProgressEvent::from_test_event(suite, event).emit()
+        }));
+    }
+    let mut runner = builder
         .fuzzer(fuzzer)
         .initial_balance(evm_opts.initial_balance)
         .evm_spec(evm_spec)
@@ -462,14 +865,19 @@ pub fn custom_run(args: TestArgs, include_fuzz_tests: bool) -> eyre::Result<Test
                     };
 
                     // Run the debugger
-                    let debugger = RunArgs {
+                    let debugger = ScriptArgs {
                         path: PathBuf::from(runner.source_paths.get(&id).unwrap()),
                         target_contract: Some(utils::get_contract_name(&id).to_string()),
                         sig,
                         args: Vec::new(),
                         debug: true,
+                        broadcast: false,
+                        resume: false,
+                        legacy: false,
+                        gas_estimate_multiplier: 130,
                         opts: args.opts,
                         evm_opts: args.evm_opts,
+                        wallet: ScriptWallet::default(),
                     };
                     debugger.run()?;
 
@@ -482,7 +890,9 @@ pub fn custom_run(args: TestArgs, include_fuzz_tests: bool) -> eyre::Result<Test
                         Use --match-contract and --match-path to further limit the search."))
             }
     } else {
-        test(
+        let local_identifier =
+            line_gas_report.is_some().then(|| LocalTraceIdentifier::new(&runner.known_contracts));
+        let outcome = test(
             config,
             runner,
             verbosity,
@@ -491,8 +901,70 @@ pub fn custom_run(args: TestArgs, include_fuzz_tests: bool) -> eyre::Result<Test
             args.allow_failure,
             include_fuzz_tests,
             args.gas_report,
-        )
+        )?;
+
+        if let (Some(report), Some(identifier)) = (&mut line_gas_report, &local_identifier) {
+            for result in outcome.results.values().flat_map(|suite| suite.test_results.values()) {
+                if let Some(ref profile) = result.gas_profile {
+                    report.analyze(profile, identifier);
+                }
+            }
+            match args.gas_lines {
+                Some(GasLinesFormat::Json) => {
+                    println!("{}", serde_json::to_string(&report.lines())?)
+                }
+                _ => {
+                    println!("\nGas by source line:");
+                    print!("{report}");
+                }
+            }
+        }
+
+        if args.summary && !args.json {
+            println!("\nTest summary:");
+            println!("{}", outcome.summary_table());
+            if args.detailed {
+                let (gas_table, duration_table) = outcome.detailed_tables(10);
+                println!("\nHighest gas usage:");
+                println!("{gas_table}");
+                println!("\nSlowest tests:");
+                println!("{duration_table}");
+            }
+        }
+
+        Ok(outcome)
+    }
+}
+
+/// Collects the runtime source maps and the sources of the compiled contracts for `--gas-lines`
+fn collect_line_gas_report(
+    project: &Project,
+    output: &ProjectCompileOutput,
+) -> eyre::Result<LineGasReport> {
+    let (contracts, sources) = output.clone().into_artifacts_with_sources();
+
+    let mut source_maps = BTreeMap::new();
+    for (id, artifact) in contracts {
+        let contract: ContractBytecode = CompactContractBytecode::from(artifact).into();
+        let source_map = contract
+            .deployed_bytecode
+            .and_then(|deployed_bytecode| deployed_bytecode.bytecode?.source_map());
+        if let Some(source_map) = source_map {
+            let source_map = source_map
+                .map_err(|err| eyre::eyre!("Invalid source map of {}: {}", id.identifier(), err))?;
+            source_maps.insert(id, source_map);
+        }
     }
+
+    // sources that can not be read are not part of the report
+    let sources = sources.into_ids().filter_map(|(id, path)| {
+        let path = project.root().join(path);
+        let content = std::fs::read_to_string(&path).ok()?;
+        let path = path.strip_prefix(project.root()).unwrap_or(&path).display().to_string();
+        Some((id, path, content))
+    });
+
+    Ok(LineGasReport::new(source_maps, sources))
 }
 
 /// Runs all the tests
@@ -514,13 +986,16 @@ fn test(
     } else {
         // Set up identifiers
         let local_identifier = LocalTraceIdentifier::new(&runner.known_contracts);
+        let signatures = parse_signatures(&update_signatures_cache(
+            runner.known_contracts.values().map(|(abi, _)| abi),
+        ));
         let remote_chain_id = runner.evm_opts.get_remote_chain_id();
         // Do not re-query etherscan for contracts that you've already queried today.
         // TODO: Make this configurable.
         let cache_ttl = Duration::from_secs(24 * 60 * 60);
         let etherscan_identifier = EtherscanIdentifier::new(
             remote_chain_id,
-            config.etherscan_api_key,
+            config.get_etherscan_api_key()?,
             remote_chain_id.and_then(Config::foundry_etherscan_cache_dir),
             cache_ttl,
         );
@@ -547,8 +1022,20 @@ fn test(
             for (name, result) in &mut tests {
                 short_test_result(name, result);
 
-                // We only display logs at level 2 and above
+                // We only display logs and the precompile calls of fuzz tests at level 2 and
+                // above
                 if verbosity >= 2 {
+                    if let TestKind::Fuzz(ref cases) = result.kind {
+                        let precompile_stats = cases.precompile_stats();
+                        if !precompile_stats.is_empty() {
+                            println!("Precompile calls:");
+                            for (name, stats) in precompile_stats {
+                                println!("  {name}: {stats}");
+                            }
+                            println!();
+                        }
+                    }
+
                     // We only decode logs from Hardhat and DS-style console events
                     let console_logs = decode_console_logs(&result.logs);
                     if !console_logs.is_empty() {
@@ -565,6 +1052,7 @@ fn test(
                     let mut decoder = CallTraceDecoderBuilder::new()
                         .with_labels(result.labeled_addresses.clone())
                         .with_events(local_identifier.events())
+                        .with_signatures(signatures.clone())
                         .build();
 
                     // Decode the traces
@@ -626,3 +1114,116 @@ fn test(
         Ok(TestOutcome::new(results, allow_failure))
     }
 }
+
+/// Adds the signatures of the project's ABIs to the local signatures cache, which is shared with
+/// `cast`, and returns the cache
+fn update_signatures_cache<'a>(abis: impl IntoIterator<Item = &'a Abi>) -> SignaturesCache {
+    let path = match Config::foundry_signatures_cache_file() {
+        Some(path) => path,
+        None => return Default::default(),
+    };
+    let mut cache = SignaturesCache::load(&path);
+    let previous = cache.clone();
+    abis.into_iter().for_each(|abi| cache.extend_from_abi(abi));
+    if cache != previous {
+        if let Err(err) = cache.save(&path) {
+            tracing::warn!("failed to update signatures cache: {}", err);
+        }
+    }
+    cache
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn result(success: bool, skipped: bool) -> forge::TestResult {
+        forge::TestResult {
+            success,
+            skipped,
+            reason: None,
+            counterexample: None,
+            logs: vec![],
+            kind: TestKind::Standard(0),
+            traces: vec![],
+            labeled_addresses: BTreeMap::new(),
+            duration: Duration::default(),
+            differential: None,
+            gas_snapshots: BTreeMap::new(),
+            gas_profile: None,
+        }
+    }
+
+    fn outcome(tests: &[(&str, forge::TestResult)]) -> TestOutcome {
+        let results =
+            tests.iter().map(|(name, result)| (name.to_string(), result.clone())).collect();
+        let suite = SuiteResult::new(Duration::from_millis(10), results, vec![]);
+        TestOutcome::new(BTreeMap::from([("Suite".to_string(), suite)]), false)
+    }
+
+    /// Returns the cells of the row of the table that starts with `first`
+    fn row(table: &str, first: &str) -> Vec<String> {
+        table
+            .lines()
+            .map(|line| {
+                line.split('│')
+                    .map(|cell| cell.trim().to_string())
+                    .filter(|cell| !cell.is_empty())
+                    .collect::<Vec<_>>()
+            })
+            .find(|cells| cells.first().map(String::as_str) == Some(first))
+            .unwrap()
+    }
+
+    #[test]
+    fn finds_diverging_tests() {
+        let outcomes = vec![
+            (
+                "default".to_string(),
+                outcome(&[
+                    ("testA()", result(true, false)),
+                    ("testB()", result(true, false)),
+                    ("testC()", result(true, false)),
+                ]),
+            ),
+            (
+                "ci".to_string(),
+                outcome(&[("testA()", result(true, false)), ("testB()", result(false, false))]),
+            ),
+        ];
+
+        let diverging = diverging_tests(&outcomes);
+        assert_eq!(diverging.len(), 2);
+        assert_eq!(
+            diverging[&("Suite".to_string(), "testB()".to_string())],
+            vec![Some(TestStatus::Pass), Some(TestStatus::Fail)]
+        );
+        // a test that was not run with every profile diverges as well
+        assert_eq!(
+            diverging[&("Suite".to_string(), "testC()".to_string())],
+            vec![Some(TestStatus::Pass), None]
+        );
+    }
+
+    #[test]
+    fn summarizes_profiles() {
+        let outcomes = vec![
+            (
+                "default".to_string(),
+                outcome(&[
+                    ("testA()", result(true, false)),
+                    ("testB()", result(false, false)),
+                    ("testC()", result(true, true)),
+                ]),
+            ),
+            (
+                "ci".to_string(),
+                outcome(&[("testA()", result(true, false)), ("testB()", result(true, false))]),
+            ),
+        ];
+
+        let table = profiles_summary_table(&outcomes).to_string();
+        assert_eq!(row(&table, "default"), ["default", "1", "1", "1", "10.00ms"]);
+        assert_eq!(row(&table, "ci"), ["ci", "2", "0", "0", "10.00ms"]);
+    }
+}
@@ -0,0 +1,85 @@
+//! Upload selectors command
+use crate::{cmd::forge::build::CoreBuildArgs, compile};
+use clap::Parser;
+use ethers::abi::Abi;
+use foundry_config::Config;
+use foundry_utils::{import_selectors, SelectorImportResult, SignaturesCache};
+use std::collections::BTreeSet;
+
+/// Uploads the function, error and event signatures of the project to the public signature
+/// database
+#[derive(Debug, Clone, Parser)]
+pub struct UploadSelectorsArgs {
+    #[clap(
+        help = "The name of the contract to upload the selectors of.",
+        long_help = "The name of the contract to upload the selectors of. If omitted, the selectors of all contracts of the project are uploaded."
+    )]
+    contract: Option<String>,
+
+    #[clap(flatten, next_help_heading = "BUILD OPTIONS")]
+    opts: CoreBuildArgs,
+}
+
+impl UploadSelectorsArgs {
+    pub async fn run(self) -> eyre::Result<()> {
+        let project = self.opts.project()?;
+        let compiled = compile::suppress_compile(&project)?;
+
+        let abis = compiled
+            .into_artifacts()
+            .filter(|(id, _)| self.contract.as_ref().map(|name| &id.name == name).unwrap_or(true))
+            .filter_map(|(_, artifact)| artifact.abi.map(Abi::from))
+            .collect::<Vec<_>>();
+
+        if abis.is_empty() {
+            match self.contract {
+                Some(name) => eyre::bail!("Could not find artifact `{}`", name),
+                None => eyre::bail!("No contracts found in the project"),
+            }
+        }
+
+        // keep the local signatures cache in sync with what is shared upstream
+        if let Some(path) = Config::foundry_signatures_cache_file() {
+            let mut local = SignaturesCache::load(&path);
+            abis.iter().for_each(|abi| local.extend_from_abi(abi));
+            local.save(&path)?;
+        }
+
+        let functions = abis
+            .iter()
+            .flat_map(|abi| {
+                abi.functions()
+                    .map(foundry_utils::function_signature)
+                    .chain(abi.errors().map(foundry_utils::error_signature))
+                    .collect::<Vec<_>>()
+            })
+            .collect::<BTreeSet<_>>();
+        let events = abis
+            .iter()
+            .flat_map(|abi| abi.events().map(foundry_utils::event_signature).collect::<Vec<_>>())
+            .collect::<BTreeSet<_>>();
+
+        let response =
+            import_selectors(functions.into_iter().collect(), events.into_iter().collect()).await?;
+        print_import_result("function", &response.function);
+        print_import_result("event", &response.event);
+
+        Ok(())
+    }
+}
+
+fn print_import_result(kind: &str, result: &SelectorImportResult) {
+    for (signature, selector) in &result.imported {
+        println!("Imported {} {} {}", kind, selector, signature);
+    }
+    for (signature, selector) in &result.duplicated {
+        println!("Duplicated {} {} {}", kind, selector, signature);
+    }
+    println!(
+        "Uploaded {} {} signatures: {} imported, {} already known",
+        result.imported.len() + result.duplicated.len(),
+        kind,
+        result.imported.len(),
+        result.duplicated.len()
+    );
+}
@@ -1,10 +1,9 @@
 //! Verify contract source on etherscan
 
-use super::build::{CoreBuildArgs, ProjectPathsArgs};
-use crate::opts::forge::ContractInfo;
-use clap::Parser;
+use super::{provider::VerificationProvider, VerifyArgs, VerifyCheckArgs};
+use crate::{cmd::forge::build::CoreBuildArgs, opts::forge::CompilerArgs};
+use async_trait::async_trait;
 use ethers::{
-    abi::Address,
     etherscan::{
         contract::{CodeFormat, VerifyContract},
         Client,
@@ -15,77 +14,45 @@ use ethers::{
     },
 };
 use eyre::Context;
-use foundry_config::Chain;
 use semver::Version;
-use std::{collections::BTreeMap, path::Path};
+use std::{collections::BTreeMap, path::Path, time::Duration};
 use tracing::{trace, warn};
 
-/// Verification arguments
-#[derive(Debug, Clone, Parser)]
-pub struct VerifyArgs {
-    #[clap(help = "The address of the contract to verify.")]
-    address: Address,
-
-    #[clap(help = "The contract identifier in the form `<path>:<contractname>`.")]
-    contract: ContractInfo,
-
-    #[clap(long, help = "the encoded constructor arguments")]
-    constructor_args: Option<String>,
-
-    #[clap(long, help = "The compiler version used to build the smart contract.")]
-    compiler_version: String,
-
-    #[clap(
-        alias = "optimizer-runs",
-        long,
-        help = "The number of optimization runs used to build the smart contract."
-    )]
-    num_of_optimizations: Option<u32>,
-
-    #[clap(
-        long,
-        alias = "chain-id",
-        env = "CHAIN",
-        help = "The chain ID the contract is deployed to.",
-        default_value = "mainnet"
-    )]
-    chain: Chain,
-
-    #[clap(help = "Your Etherscan API key.", env = "ETHERSCAN_API_KEY")]
-    etherscan_key: String,
-
-    #[clap(help = "Flatten the source code before verifying.", long = "flatten")]
-    flatten: bool,
-
-    #[clap(
-        short,
-        long,
-        help = "Do not compile the flattened smart contract before verifying (if --flatten is passed)."
-    )]
-    force: bool,
-
-    #[clap(flatten, next_help_heading = "PROJECT OPTIONS")]
-    project_paths: ProjectPathsArgs,
-}
-
-impl VerifyArgs {
-    /// Run the verify command to submit the contract's source code for verification on etherscan
-    pub async fn run(&self) -> eyre::Result<()> {
-        if self.contract.path.is_none() {
-            eyre::bail!("Contract info must be provided in the format <path>:<name>")
-        }
+#[derive(Debug, Clone, Copy)]
+pub struct EtherscanVerificationProvider;
 
-        let etherscan = Client::new(self.chain.try_into()?, &self.etherscan_key)
+#[async_trait]
+impl VerificationProvider for EtherscanVerificationProvider {
+    async fn verify(&self, args: VerifyArgs) -> eyre::Result<()> {
+        let etherscan = Client::new(args.chain.try_into()?, etherscan_key(&args.etherscan_key)?)
             .wrap_err("Failed to create etherscan client")?;
 
-        let verify_args = self.create_verify_request()?;
+        let verify_args = self.create_verify_request(&args)?;
 
         trace!("submitting verification request {:?}", verify_args);
 
-        let resp = etherscan
-            .submit_contract_verification(&verify_args)
-            .await
-            .wrap_err("Failed to submit contract verification")?;
+        let mut retry = 0;
+        let resp = loop {
+            let resp = etherscan
+                .submit_contract_verification(&verify_args)
+                .await
+                .wrap_err("Failed to submit contract verification")?;
+
+            // etherscan rejects the submission until it indexed the deployed contract
+            if resp.status == "0" && is_not_indexed(&resp.result) && retry < args.retries {
+                let delay = retry_delay(retry);
+                retry += 1;
+                eprintln!(
+                    "Contract is not indexed by Etherscan yet, retrying in {}s ({}/{})",
+                    delay.as_secs(),
+                    retry,
+                    args.retries
+                );
+                tokio::time::sleep(delay).await;
+                continue
+            }
+            break resp
+        };
 
         if resp.status == "0" {
             if resp.message == "Contract source code already verified" {
@@ -114,20 +81,64 @@ impl VerifyArgs {
     url: {}#code"#,
             resp.message,
             resp.result,
-            etherscan.address_url(self.address)
+            etherscan.address_url(args.address)
         );
         Ok(())
     }
 
+    /// Executes the command to check verification status on Etherscan
+    async fn check(&self, args: VerifyCheckArgs) -> eyre::Result<()> {
+        let etherscan = Client::new(args.chain.try_into()?, etherscan_key(&args.etherscan_key)?)
+            .wrap_err("Failed to create etherscan client")?;
+
+        let resp = etherscan
+            .check_contract_verification_status(args.guid.clone())
+            .await
+            .wrap_err("Failed to request verification status")?;
+
+        if resp.status == "0" {
+            if resp.result == "Pending in queue" {
+                println!("Verification is pending...");
+                return Ok(())
+            }
+
+            if resp.result == "Already Verified" {
+                println!("Contract source code already verified");
+                return Ok(())
+            }
+
+            warn!("Failed verification: {:?}", resp);
+
+            eprintln!(
+                "Contract verification failed:\nResponse: `{}`\nDetails: `{}`",
+                resp.message, resp.result
+            );
+
+            std::process::exit(1);
+        }
+
+        println!("Contract successfully verified.");
+        Ok(())
+    }
+}
+
+impl EtherscanVerificationProvider {
     /// Creates the `VerifyContract` etherescan request in order to verify the contract
     ///
     /// If `--flatten` is set to `true` then this will send with [`CodeFormat::SingleFile`]
-    /// otherwise this will use the [`CodeFormat::StandardJsonInput`]
-    fn create_verify_request(&self) -> eyre::Result<VerifyContract> {
+    /// otherwise this will use the [`CodeFormat::StandardJsonInput`], which contains all sources
+    /// of the contract with their original paths and the project's compiler settings, like the
+    /// optimizer and `via_ir`.
+    fn create_verify_request(&self, args: &VerifyArgs) -> eyre::Result<VerifyContract> {
         let build_args = CoreBuildArgs {
-            project_paths: self.project_paths.clone(),
+            project_paths: args.project_paths.clone(),
             out_path: Default::default(),
-            compiler: Default::default(),
+            compiler: CompilerArgs {
+                // `--num-of-optimizations` takes precedence over the configured optimizer settings
+                optimize: args.num_of_optimizations.is_some(),
+                optimizer_runs: args.num_of_optimizations.map(|runs| runs as usize),
+                ..Default::default()
+            },
             ignored_error_codes: vec![],
             no_auto_detect: false,
             use_solc: None,
@@ -135,6 +146,7 @@ impl VerifyArgs {
             force: false,
             libraries: vec![],
             via_ir: false,
+            jobs: None,
             revert_strings: None,
         };
 
@@ -142,7 +154,7 @@ impl VerifyArgs {
 
         // check that the provided contract is part of the source dir
         let contract_path =
-            project.root().join(self.contract.path.as_ref().expect("Is present; qed"));
+            project.root().join(args.contract.path.as_ref().expect("Is present; qed"));
 
         if !contract_path.exists() {
             eyre::bail!("Contract {:?} does not exist.", contract_path);
@@ -152,19 +164,21 @@ impl VerifyArgs {
             eyre::bail!("Contract {:?} is outside of project source directory", contract_path);
         }
 
-        let (source, contract_name, code_format) = if self.flatten {
-            flattened_source(self, &project, &contract_path)?
+        let (source, contract_name, code_format) = if args.flatten {
+            flattened_source(self, args, &project, &contract_path)?
         } else {
-            standard_json_source(self, &project, &contract_path)?
+            standard_json_source(self, args, &project, &contract_path)?
         };
 
         let mut verify_args =
-            VerifyContract::new(self.address, contract_name, source, self.compiler_version.clone())
-                .constructor_arguments(self.constructor_args.clone())
+            VerifyContract::new(args.address, contract_name, source, args.compiler_version.clone())
+                .constructor_arguments(args.constructor_args.clone())
                 .code_format(code_format);
 
-        verify_args = if let Some(optimizations) = self.num_of_optimizations {
-            verify_args.optimization(true).runs(optimizations)
+        // the optimizer settings of the project, these are also part of the standard json input
+        let optimizer = &project.solc_config.settings.optimizer;
+        verify_args = if optimizer.enabled.unwrap_or_default() {
+            verify_args.optimization(true).runs(optimizer.runs.unwrap_or(200) as u32)
         } else {
             verify_args.optimization(false)
         };
@@ -183,8 +197,8 @@ impl VerifyArgs {
     /// # Example
     ///
     /// the `compiler_version` `v0.8.7+commit.e28d00a7` will be returned as `0.8.7`
-    fn sanitized_solc_version(&self) -> eyre::Result<Version> {
-        let v: Version = self.compiler_version.trim_start_matches('v').parse()?;
+    fn sanitized_solc_version(&self, args: &VerifyArgs) -> eyre::Result<Version> {
+        let v: Version = args.compiler_version.trim_start_matches('v').parse()?;
         Ok(Version::new(v.major, v.minor, v.patch))
     }
 
@@ -202,8 +216,8 @@ impl VerifyArgs {
     /// If the solc compiler output contains errors, this could either be due to a bug in the
     /// flattening code or could to conflict in the flattened code, for example if there are
     /// multiple interfaces with the same name.
-    fn check_flattened(&self, content: impl Into<String>) -> eyre::Result<()> {
-        let version: Version = self.sanitized_solc_version()?;
+    fn check_flattened(&self, args: &VerifyArgs, content: impl Into<String>) -> eyre::Result<()> {
+        let version: Version = self.sanitized_solc_version(args)?;
         let solc = if let Some(solc) = Solc::find_svm_installed_version(version.to_string())? {
             solc
         } else {
@@ -226,7 +240,7 @@ impl VerifyArgs {
 This could be a bug, please inspect the outout of `forge flatten {}` and report an issue.
 To skip this solc dry, pass `--force`.
 "#,
-                self.contract.path.as_ref().expect("Path is some;")
+                args.contract.path.as_ref().expect("Path is some;")
             );
             std::process::exit(1)
         }
@@ -235,63 +249,8 @@ To skip this solc dry, pass `--force`.
     }
 }
 
-/// Check verification status arguments
-#[derive(Debug, Clone, Parser)]
-pub struct VerifyCheckArgs {
-    #[clap(help = "The verification GUID.")]
-    guid: String,
-
-    #[clap(
-        long,
-        alias = "chain-id",
-        env = "CHAIN",
-        help = "The chain ID the contract is deployed to.",
-        default_value = "mainnet"
-    )]
-    chain: Chain,
-
-    #[clap(help = "Your Etherscan API key.", env = "ETHERSCAN_API_KEY")]
-    etherscan_key: String,
-}
-
-impl VerifyCheckArgs {
-    /// Executes the command to check verification status on Etherscan
-    pub async fn run(&self) -> eyre::Result<()> {
-        let etherscan = Client::new(self.chain.try_into()?, &self.etherscan_key)
-            .wrap_err("Failed to create etherscan client")?;
-
-        let resp = etherscan
-            .check_contract_verification_status(self.guid.clone())
-            .await
-            .wrap_err("Failed to request verification status")?;
-
-        if resp.status == "0" {
-            if resp.result == "Pending in queue" {
-                println!("Verification is pending...");
-                return Ok(())
-            }
-
-            if resp.result == "Already Verified" {
-                println!("Contract source code already verified");
-                return Ok(())
-            }
-
-            warn!("Failed verification: {:?}", resp);
-
-            eprintln!(
-                "Contract verification failed:\nResponse: `{}`\nDetails: `{}`",
-                resp.message, resp.result
-            );
-
-            std::process::exit(1);
-        }
-
-        println!("Contract successfully verified.");
-        Ok(())
-    }
-}
-
 fn flattened_source(
+    provider: &EtherscanVerificationProvider,
     args: &VerifyArgs,
     project: &Project,
     target: &Path,
@@ -314,7 +273,7 @@ fn flattened_source(
 
     if !args.force {
         // solc dry run of flattened code
-        args.check_flattened(source.clone()).map_err(|err| {
+        provider.check_flattened(args, source.clone()).map_err(|err| {
             eyre::eyre!(
                 "Failed to compile the flattened code locally: `{}`\
 To skip this solc dry, have a look at the  `--force` flag of this command.",
@@ -328,6 +287,7 @@ To skip this solc dry, have a look at the  `--force` flag of this command.",
 }
 
 fn standard_json_source(
+    provider: &EtherscanVerificationProvider,
     args: &VerifyArgs,
     project: &Project,
     target: &Path,
@@ -335,7 +295,7 @@ fn standard_json_source(
     let input = project
         .standard_json_input(target)
         .wrap_err("Failed to get standard json input")?
-        .normalize_evm_version(&args.sanitized_solc_version()?);
+        .normalize_evm_version(&provider.sanitized_solc_version(args)?);
 
     let source = serde_json::to_string(&input).wrap_err("Failed to parse standard json input")?;
     let name = format!(
@@ -345,3 +305,45 @@ fn standard_json_source(
     );
     Ok((source, name, CodeFormat::StandardJsonInput))
 }
+
+/// Returns the etherscan API key or an error if it is missing
+fn etherscan_key(key: &Option<String>) -> eyre::Result<&str> {
+    key.as_deref().ok_or_else(|| {
+        eyre::eyre!(
+            "An Etherscan API key is required for Etherscan verification. Pass it as argument or set `ETHERSCAN_API_KEY`."
+        )
+    })
+}
+
+/// Returns `true` if the verification was rejected because Etherscan did not index the contract
+/// yet, which is the case for a while after it was deployed
+fn is_not_indexed(result: &str) -> bool {
+    result.contains("Unable to locate ContractCode")
+}
+
+/// The time to wait before the `retry`-th retry of a verification submission, doubling with every
+/// retry
+fn retry_delay(retry: u32) -> Duration {
+    Duration::from_secs(5 << retry.min(6))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn detects_unindexed_contracts() {
+        assert!(is_not_indexed(
+            "Unable to locate ContractCode at 0x9c2c15d0b6d3a5a4f2d3c3d1f3f2a1d4c3f2e1d0"
+        ));
+        assert!(!is_not_indexed("Fail - Unable to verify"));
+    }
+
+    #[test]
+    fn backs_off_exponentially() {
+        assert_eq!(retry_delay(0), Duration::from_secs(5));
+        assert_eq!(retry_delay(1), Duration::from_secs(10));
+        assert_eq!(retry_delay(2), Duration::from_secs(20));
+        assert_eq!(retry_delay(10), retry_delay(6));
+    }
+}
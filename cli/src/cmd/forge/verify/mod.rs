@@ -0,0 +1,119 @@
+//! Verify contract source on etherscan or sourcify
+
+use super::build::ProjectPathsArgs;
+use crate::opts::forge::ContractInfo;
+use clap::Parser;
+use ethers::abi::Address;
+use foundry_config::Chain;
+use provider::VerificationProviderType;
+
+mod etherscan;
+pub mod provider;
+mod sourcify;
+
+/// Verification arguments
+#[derive(Debug, Clone, Parser)]
+pub struct VerifyArgs {
+    #[clap(help = "The address of the contract to verify.")]
+    pub address: Address,
+
+    #[clap(help = "The contract identifier in the form `<path>:<contractname>`.")]
+    pub contract: ContractInfo,
+
+    #[clap(long, help = "the encoded constructor arguments")]
+    pub constructor_args: Option<String>,
+
+    #[clap(long, help = "The compiler version used to build the smart contract.")]
+    pub compiler_version: String,
+
+    #[clap(
+        alias = "optimizer-runs",
+        long,
+        help = "The number of optimization runs used to build the smart contract.",
+        long_help = "The number of optimization runs used to build the smart contract. Enables the optimizer. Defaults to the optimizer settings of the project."
+    )]
+    pub num_of_optimizations: Option<u32>,
+
+    #[clap(
+        long,
+        alias = "chain-id",
+        env = "CHAIN",
+        help = "The chain ID the contract is deployed to.",
+        default_value = "mainnet"
+    )]
+    pub chain: Chain,
+
+    #[clap(help = "Your Etherscan API key.", env = "ETHERSCAN_API_KEY")]
+    pub etherscan_key: Option<String>,
+
+    #[clap(
+        help = "Flatten the source code before verifying.",
+        long_help = "Flatten the source code before verifying. By default, the contract is verified with the solc standard json input of the project, which preserves the original file layout.",
+        long = "flatten"
+    )]
+    pub flatten: bool,
+
+    #[clap(
+        short,
+        long,
+        help = "Do not compile the flattened smart contract before verifying (if --flatten is passed)."
+    )]
+    pub force: bool,
+
+    #[clap(arg_enum, long, help = "The verification provider.", default_value = "etherscan")]
+    pub verifier: VerificationProviderType,
+
+    #[clap(
+        long,
+        help = "Number of times to retry the submission while Etherscan has not indexed the contract.",
+        long_help = "Number of times to retry the submission while Etherscan has not indexed the contract. The delay between retries starts at 5 seconds and doubles with every retry.",
+        default_value = "0",
+        value_name = "RETRIES"
+    )]
+    pub retries: u32,
+
+    #[clap(flatten, next_help_heading = "PROJECT OPTIONS")]
+    pub project_paths: ProjectPathsArgs,
+}
+
+impl VerifyArgs {
+    /// Run the verify command to submit the contract's source code for verification
+    pub async fn run(self) -> eyre::Result<()> {
+        if self.contract.path.is_none() {
+            eyre::bail!("Contract info must be provided in the format <path>:<name>")
+        }
+
+        self.verifier.client().verify(self).await
+    }
+}
+
+/// Check verification status arguments
+#[derive(Debug, Clone, Parser)]
+pub struct VerifyCheckArgs {
+    #[clap(
+        help = "The verification GUID. For sourcify, this is the address of the contract instead."
+    )]
+    guid: String,
+
+    #[clap(
+        long,
+        alias = "chain-id",
+        env = "CHAIN",
+        help = "The chain ID the contract is deployed to.",
+        default_value = "mainnet"
+    )]
+    chain: Chain,
+
+    #[clap(help = "Your Etherscan API key.", env = "ETHERSCAN_API_KEY")]
+    etherscan_key: Option<String>,
+
+    #[clap(arg_enum, long, help = "The verification provider.", default_value = "etherscan")]
+    verifier: VerificationProviderType,
+}
+
+impl VerifyCheckArgs {
+    /// Executes the command to check the verification status
+    pub async fn run(self) -> eyre::Result<()> {
+        self.verifier.client().check(self).await
+    }
+}
@@ -0,0 +1,33 @@
+use super::{
+    etherscan::EtherscanVerificationProvider, sourcify::SourcifyVerificationProvider, VerifyArgs,
+    VerifyCheckArgs,
+};
+use async_trait::async_trait;
+use clap::ArgEnum;
+
+/// An abstraction for various verification providers such as etherscan, sourcify, blockscout
+#[async_trait]
+pub trait VerificationProvider {
+    /// Submits the contract's source code for verification
+    async fn verify(&self, args: VerifyArgs) -> eyre::Result<()>;
+
+    /// Checks whether the contract is verified
+    async fn check(&self, args: VerifyCheckArgs) -> eyre::Result<()>;
+}
+
+/// The supported verification backends
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ArgEnum)]
+pub enum VerificationProviderType {
+    Etherscan,
+    Sourcify,
+}
+
+impl VerificationProviderType {
+    /// Returns the client for this provider
+    pub fn client(&self) -> Box<dyn VerificationProvider> {
+        match self {
+            VerificationProviderType::Etherscan => Box::new(EtherscanVerificationProvider),
+            VerificationProviderType::Sourcify => Box::new(SourcifyVerificationProvider),
+        }
+    }
+}
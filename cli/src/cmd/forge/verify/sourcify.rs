@@ -0,0 +1,230 @@
+//! Verify contract source on sourcify
+
+use super::{provider::VerificationProvider, VerifyArgs, VerifyCheckArgs};
+use crate::{cmd::forge::build::CoreBuildArgs, compile, opts::forge::CompilerArgs};
+use async_trait::async_trait;
+use ethers::solc::artifacts::output_selection::ContractOutputSelection;
+use eyre::Context;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use tracing::{trace, warn};
+
+pub static SOURCIFY_URL: &str = "https://sourcify.dev/server/";
+
+/// The type that can verify a contract on `sourcify`
+#[derive(Debug, Clone, Copy)]
+pub struct SourcifyVerificationProvider;
+
+#[async_trait]
+impl VerificationProvider for SourcifyVerificationProvider {
+    async fn verify(&self, args: VerifyArgs) -> eyre::Result<()> {
+        let body = self.create_verify_request(&args)?;
+
+        trace!("submitting verification request {:?}", body);
+
+        let client = reqwest::Client::new();
+        let resp = client
+            .post(format!("{}verify", SOURCIFY_URL))
+            .json(&body)
+            .send()
+            .await
+            .wrap_err("Failed to submit contract verification")?;
+
+        let status = resp.status();
+        let text = resp.text().await?;
+        if !status.is_success() {
+            warn!("Failed verify submission: {}", text);
+            let error: serde_json::Value = serde_json::from_str(&text).unwrap_or_default();
+            eprintln!(
+                "Encountered an error verifying this contract:\nResponse: `{}`\nDetails: `{}`",
+                status,
+                error.get("error").and_then(|e| e.as_str()).unwrap_or(&text)
+            );
+            std::process::exit(1)
+        }
+
+        let resp: SourcifyVerificationResponse = serde_json::from_str(&text)
+            .wrap_err_with(|| format!("Failed to parse sourcify response: {}", text))?;
+        self.process_sourcify_response(resp.result);
+        Ok(())
+    }
+
+    async fn check(&self, args: VerifyCheckArgs) -> eyre::Result<()> {
+        let url = format!(
+            "{}check-by-addresses?addresses={}&chainIds={}",
+            SOURCIFY_URL,
+            args.guid,
+            args.chain.id(),
+        );
+
+        let resp = reqwest::get(url).await.wrap_err("Failed to request verification status")?;
+        let status = resp.status();
+        if !status.is_success() {
+            eprintln!("Failed to request verification status with status code {}", status);
+            std::process::exit(1)
+        }
+
+        let resp: Vec<SourcifyResponseElement> =
+            resp.json().await.wrap_err("Failed to parse sourcify response")?;
+        self.process_sourcify_response(resp);
+        Ok(())
+    }
+}
+
+impl SourcifyVerificationProvider {
+    /// Compiles the project with metadata output and collects the contract's metadata and all of
+    /// its sources into a sourcify verification request
+    fn create_verify_request(&self, args: &VerifyArgs) -> eyre::Result<SourcifyVerifyRequest> {
+        let build_args = CoreBuildArgs {
+            project_paths: args.project_paths.clone(),
+            out_path: Default::default(),
+            compiler: CompilerArgs {
+                extra_output: Some(vec![ContractOutputSelection::Metadata]),
+                ..Default::default()
+            },
+            ignored_error_codes: vec![],
+            no_auto_detect: false,
+            use_solc: None,
+            offline: false,
+            force: false,
+            libraries: vec![],
+            via_ir: false,
+            jobs: None,
+            revert_strings: None,
+        };
+
+        let project = build_args.project()?;
+
+        // check that the provided contract is part of the source dir
+        let contract_path =
+            project.root().join(args.contract.path.as_ref().expect("Is present; qed"));
+
+        if !contract_path.exists() {
+            eyre::bail!("Contract {:?} does not exist.", contract_path);
+        }
+
+        let output = compile::suppress_compile(&project)?;
+        let artifact = output.find(&args.contract.name).ok_or_else(|| {
+            eyre::eyre!(
+                "Could not find artifact `{}` in the compiled artifacts",
+                args.contract.name
+            )
+        })?;
+        let metadata = artifact.metadata.as_ref().ok_or_else(|| {
+            eyre::eyre!("Contract `{}` was compiled without metadata", args.contract.name)
+        })?;
+        let metadata = serde_json::to_value(metadata)?;
+
+        let mut files = BTreeMap::new();
+        if let Some(sources) = metadata.get("sources").and_then(|s| s.as_object()) {
+            for source in sources.keys() {
+                let path = project.root().join(source);
+                let content = std::fs::read_to_string(&path)
+                    .wrap_err_with(|| format!("Failed to read source file {:?}", path))?;
+                files.insert(source.clone(), content);
+            }
+        }
+        files.insert("metadata.json".to_string(), serde_json::to_string_pretty(&metadata)?);
+
+        Ok(SourcifyVerifyRequest {
+            address: format!("{:?}", args.address),
+            chain: args.chain.id().to_string(),
+            files,
+        })
+    }
+
+    /// Prints the verification status of the first element of the response
+    fn process_sourcify_response(&self, response: Vec<SourcifyResponseElement>) {
+        match response.first() {
+            Some(SourcifyResponseElement { status, .. }) if status == "perfect" => {
+                println!("Contract source code fully verified")
+            }
+            Some(SourcifyResponseElement { status, .. }) if status == "partial" => {
+                println!("Contract source code partially verified")
+            }
+            Some(SourcifyResponseElement { status, address }) if status != "false" => {
+                println!("Verification status of `{}`: `{}`", address, status)
+            }
+            _ => println!("Contract source code is not verified"),
+        }
+    }
+}
+
+#[derive(Debug, Serialize)]
+pub struct SourcifyVerifyRequest {
+    address: String,
+    chain: String,
+    files: BTreeMap<String, String>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct SourcifyVerificationResponse {
+    result: Vec<SourcifyResponseElement>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct SourcifyResponseElement {
+    status: String,
+    address: String,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use clap::Parser;
+
+    #[test]
+    fn can_create_verify_request() {
+        let root = tempfile::tempdir().unwrap();
+        let source = r#"// SPDX-License-Identifier: UNLICENSED
+pragma solidity 0.8.10;
+
+contract Counter {
+    uint256 public count;
+}
+"#;
+        std::fs::create_dir_all(root.path().join("src")).unwrap();
+        std::fs::write(root.path().join("src/Counter.sol"), source).unwrap();
+
+        let args = VerifyArgs::parse_from([
+            "foundry-cli",
+            "0x0000000000000000000000000000000000000001",
+            "src/Counter.sol:Counter",
+            "--compiler-version",
+            "0.8.10",
+            "--verifier",
+            "sourcify",
+            "--root",
+            root.path().to_str().unwrap(),
+        ]);
+        let request = SourcifyVerificationProvider.create_verify_request(&args).unwrap();
+
+        assert_eq!(request.address, "0x0000000000000000000000000000000000000001");
+        assert_eq!(request.chain, "1");
+        // the metadata and every source it references
+        assert_eq!(request.files.len(), 2);
+        let (_, content) =
+            request.files.iter().find(|(path, _)| path.ends_with("src/Counter.sol")).unwrap();
+        assert_eq!(content, source);
+        let metadata: serde_json::Value =
+            serde_json::from_str(&request.files["metadata.json"]).unwrap();
+        assert_eq!(metadata["compiler"]["version"], "0.8.10+commit.fc410830");
+    }
+
+    #[test]
+    fn can_parse_responses() {
+        let resp: SourcifyVerificationResponse = serde_json::from_str(
+            r#"{"result":[{"address":"0x0000000000000000000000000000000000000001","status":"perfect"}]}"#,
+        )
+        .unwrap();
+        assert_eq!(resp.result[0].status, "perfect");
+
+        // `check-by-addresses` responds with the elements only
+        let resp: Vec<SourcifyResponseElement> = serde_json::from_str(
+            r#"[{"address":"0x0000000000000000000000000000000000000001","status":"false"}]"#,
+        )
+        .unwrap();
+        assert_eq!(resp[0].address, "0x0000000000000000000000000000000000000001");
+        assert_eq!(resp[0].status, "false");
+    }
+}
@@ -1,7 +1,7 @@
 //! Watch mode support
 
 use crate::{
-    cmd::forge::{build::BuildArgs, snapshot::SnapshotArgs, test::TestArgs},
+    cmd::forge::{build::BuildArgs, script::ScriptArgs, snapshot::SnapshotArgs, test::TestArgs},
     utils::{self, FoundryPathExt},
 };
 use clap::Parser;
@@ -104,6 +104,25 @@ pub async fn watch_build(args: BuildArgs) -> eyre::Result<()> {
     Ok(())
 }
 
+/// Executes a [`Watchexec`] that listens for changes in the project's src dir and the script and
+/// reruns `forge script`
+pub async fn watch_script(args: ScriptArgs) -> eyre::Result<()> {
+    let (init, mut runtime) = args.watchexec_config()?;
+    let cmd = cmd_args(args.watch.watch.as_ref().map(|paths| paths.len()).unwrap_or_default());
+
+    trace!("watch script cmd={:?}", cmd);
+    runtime.command(cmd.clone());
+    let wx = Watchexec::new(init, runtime.clone())?;
+
+    on_action(args.watch, runtime, Arc::clone(&wx), cmd, (), |_| {});
+
+    // start executing the command immediately
+    wx.send_event(Event::default()).await?;
+    wx.main().await??;
+
+    Ok(())
+}
+
 /// Executes a [`Watchexec`] that listens for changes in the project's src dir and reruns `forge
 /// snapshot`
 pub async fn watch_snapshot(args: SnapshotArgs) -> eyre::Result<()> {
@@ -188,11 +207,8 @@ fn on_test(action: OnActionState<WatchTestState>) {
         .map(str::to_string)
         .collect();
 
-    // replace `--match-path` | `-mp` argument
-    if let Some(pos) = cmd.iter().position(|arg| arg == "--match-path" || arg == "-mp") {
-        // --match-path requires 1 argument
-        cmd.drain(pos..=(pos + 1));
-    }
+    // replace `--match-path` | `--mp` argument
+    remove_match_path_args(&mut cmd);
 
     if changed_sol_test_files.len() > 1 ||
         (changed_sol_test_files.is_empty() && last_test_files.is_empty())
@@ -253,10 +269,28 @@ fn on_test(action: OnActionState<WatchTestState>) {
     );
 }
 
+/// Removes all `--match-path <glob>`, `--mp <glob>`, `--match-path=<glob>` and `--mp=<glob>`
+/// arguments
+fn remove_match_path_args(cmd: &mut Vec<String>) {
+    let mut args = std::mem::take(cmd).into_iter();
+    while let Some(arg) = args.next() {
+        if arg == "--match-path" || arg == "--mp" {
+            // skip the glob
+            args.next();
+        } else if !arg.starts_with("--match-path=") && !arg.starts_with("--mp=") {
+            cmd.push(arg);
+        }
+    }
+}
+
 /// Returns the env args without the `--watch` flag from the args for the Watchexec command
 fn cmd_args(num: usize) -> Vec<String> {
     // all the forge arguments including path to forge bin
-    let mut cmd_args: Vec<_> = std::env::args().collect();
+    remove_watch_args(std::env::args().collect(), num)
+}
+
+/// Removes the `--watch` flag and its `num` paths from the arguments
+fn remove_watch_args(mut cmd_args: Vec<String>, num: usize) -> Vec<String> {
     if let Some(pos) = cmd_args.iter().position(|arg| arg == "--watch" || arg == "-w") {
         cmd_args.drain(pos..=(pos + num));
     }
@@ -416,3 +450,61 @@ fn default_shell() -> Shell {
 fn default_shell() -> Shell {
     Shell::default()
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn can_remove_match_path_args() {
+        let mut cmd: Vec<String> = [
+            "forge",
+            "test",
+            "--match-path",
+            "test/A.t.sol",
+            "--no-match-path",
+            "test/B.t.sol",
+            "--mp=test/C.t.sol",
+            "--mp",
+            "test/D.t.sol",
+            "-vvv",
+        ]
+        .into_iter()
+        .map(String::from)
+        .collect();
+        remove_match_path_args(&mut cmd);
+        assert_eq!(cmd, ["forge", "test", "--no-match-path", "test/B.t.sol", "-vvv"]);
+    }
+
+    #[test]
+    fn can_remove_script_watch_args() {
+        let cmd: Vec<String> = [
+            "forge",
+            "script",
+            "script/Deploy.s.sol",
+            "--broadcast",
+            "--watch",
+            "src",
+            "script/Deploy.s.sol",
+            "-vvv",
+        ]
+        .into_iter()
+        .map(String::from)
+        .collect();
+
+        let args = ScriptArgs::parse_from(&cmd[1..]);
+        assert!(args.is_watch());
+        let num = args.watch.watch.as_ref().map(|paths| paths.len()).unwrap_or_default();
+        assert_eq!(num, 2);
+        assert_eq!(
+            remove_watch_args(cmd, num),
+            ["forge", "script", "script/Deploy.s.sol", "--broadcast", "-vvv"]
+        );
+    }
+
+    #[test]
+    fn script_is_not_watched_by_default() {
+        let args = ScriptArgs::parse_from(["script", "script/Deploy.s.sol", "--broadcast"]);
+        assert!(!args.is_watch());
+    }
+}
@@ -75,10 +75,18 @@ fn get_artifact_from_path(
     // Get sources from the requested location
     let abs_path = dunce::canonicalize(PathBuf::from(contract_path))?;
 
-    let cache = SolFilesCache::read_joined(&project.paths)?;
-
-    // Read the artifact from disk
-    let artifact: CompactContractBytecode = cache.read_artifact(abs_path, &contract_name)?;
+    // Read the artifact from disk, Vyper sources are not tracked by the solc cache
+    let artifact: CompactContractBytecode = if abs_path.extension() == Some("vy".as_ref()) {
+        let file_name = abs_path.file_name().unwrap();
+        let artifact =
+            project.paths.artifacts.join(file_name).join(format!("{contract_name}.json"));
+        serde_json::from_str(&std::fs::read_to_string(&artifact).map_err(|err| {
+            eyre::eyre!("failed to read artifact {}: {}", artifact.display(), err)
+        })?)?
+    } else {
+        let cache = SolFilesCache::read_joined(&project.paths)?;
+        cache.read_artifact(abs_path, &contract_name)?
+    };
 
     Ok((
         artifact
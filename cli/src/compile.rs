@@ -3,7 +3,15 @@
 use crate::term;
 use comfy_table::{modifiers::UTF8_ROUND_CORNERS, presets::UTF8_FULL, *};
 use ethers::solc::{report::NoReporter, Artifact, FileFilter, Project, ProjectCompileOutput};
-use std::{collections::BTreeMap, fmt::Display, path::PathBuf};
+use eyre::WrapErr;
+use foundry_config::Config;
+use std::{
+    collections::BTreeMap,
+    fmt::Display,
+    fs, io,
+    path::{Path, PathBuf},
+    process::Command,
+};
 
 /// Compiles the provided [`Project`], throws if there's any compiler error and logs whether
 /// compilation was successful or if there was a cache hit.
@@ -43,6 +51,14 @@ impl SizeReport {
     pub fn exceeds_size_limit(&self) -> bool {
         self.max_size() > CONTRACT_SIZE_LIMIT
     }
+
+    /// Returns the names of the contracts above the size limit, excluding test contracts.
+    pub fn oversized_contracts(&self) -> impl Iterator<Item = &String> {
+        self.contracts
+            .iter()
+            .filter(|(_, c)| !c.is_test_contract && c.size > CONTRACT_SIZE_LIMIT)
+            .map(|(name, _)| name)
+    }
 }
 
 impl Display for SizeReport {
@@ -175,8 +191,9 @@ If you are in a subdirectory in a Git repository, try adding `--root .`"#,
                 let mut size_report = SizeReport { contracts: BTreeMap::new() };
                 for (_, contracts) in compiled_contracts.into_iter() {
                     for (name, contract) in contracts {
+                        // EIP-170 limits the size of the deployed code, not the init code
                         let size = contract
-                            .get_bytecode_bytes()
+                            .get_deployed_bytecode_bytes()
                             .map(|bytes| bytes.0.len())
                             .unwrap_or_default();
 
@@ -193,8 +210,15 @@ If you are in a subdirectory in a Git repository, try adding `--root .`"#,
                 println!("{size_report}");
 
                 // exit with error if any contract exceeds the size limit, excluding test contracts.
-                let exit_status = if size_report.exceeds_size_limit() { 1 } else { 0 };
-                std::process::exit(exit_status);
+                if size_report.exceeds_size_limit() {
+                    for name in size_report.oversized_contracts() {
+                        eprintln!(
+                            "{name} exceeds the contract size limit of {CONTRACT_SIZE_LIMIT} bytes (EIP-170)"
+                        );
+                    }
+                    std::process::exit(1);
+                }
+                std::process::exit(0);
             }
         }
 
@@ -239,3 +263,173 @@ pub fn compile_files(project: &Project, files: Vec<PathBuf>) -> eyre::Result<Pro
     println!("{output}");
     Ok(output)
 }
+
+/// Compiles the Vyper sources of the project with the configured `vyper` binary and writes their
+/// artifacts next to the Solidity artifacts, e.g. `out/Token.vy/Token.json`, so they can be
+/// deployed with `getCode` or `forge create`.
+///
+/// Sources are only recompiled if their artifact is missing or older than the source. Returns the
+/// paths of the artifacts.
+///
+/// If `quiet` is true, e.g. if the output of the command is JSON, nothing is printed to stdout.
+pub fn compile_vyper(
+    project: &Project,
+    config: &Config,
+    quiet: bool,
+) -> eyre::Result<Vec<PathBuf>> {
+    let sources = walkdir::WalkDir::new(&project.paths.sources)
+        .into_iter()
+        .filter_map(Result::ok)
+        .filter(|entry| entry.path().extension().map_or(false, |ext| ext == "vy"))
+        .map(|entry| entry.into_path())
+        .collect::<Vec<_>>();
+    if sources.is_empty() {
+        return Ok(Vec::new())
+    }
+
+    let vyper = config.vyper.clone().unwrap_or_else(|| PathBuf::from("vyper"));
+    let mut artifacts = Vec::with_capacity(sources.len());
+    for source in sources {
+        let file_name = source.file_name().unwrap().to_string_lossy();
+        let name = source.file_stem().unwrap().to_string_lossy();
+        let artifact = project.paths.artifacts.join(&*file_name).join(format!("{name}.json"));
+        if !is_stale(&source, &artifact) {
+            artifacts.push(artifact);
+            continue
+        }
+
+        let mut cmd = Command::new(&vyper);
+        cmd.args(["-f", "abi,bytecode,bytecode_runtime"]);
+        if !config.vyper_optimize {
+            cmd.arg("--no-optimize");
+        }
+        let output = cmd.arg(&source).output().map_err(|err| {
+            if err.kind() == io::ErrorKind::NotFound {
+                eyre::eyre!(
+                    "found Vyper sources but `{}` is not installed, install vyper or set `vyper` in foundry.toml",
+                    vyper.display()
+                )
+            } else {
+                eyre::eyre!("failed to run `{}`: {}", vyper.display(), err)
+            }
+        })?;
+        if !output.status.success() {
+            eyre::bail!(
+                "failed to compile {}:\n{}",
+                source.display(),
+                String::from_utf8_lossy(&output.stderr).trim()
+            )
+        }
+
+        // every requested output format is printed on its own line
+        let stdout = String::from_utf8_lossy(&output.stdout);
+        let mut lines = stdout.lines();
+        let (abi, bytecode, deployed_bytecode) = match (lines.next(), lines.next(), lines.next()) {
+            (Some(abi), Some(bytecode), Some(deployed_bytecode)) => {
+                (abi, bytecode, deployed_bytecode)
+            }
+            _ => eyre::bail!("unexpected output of `{}`: {}", vyper.display(), stdout),
+        };
+        let abi: serde_json::Value =
+            serde_json::from_str(abi).wrap_err("vyper did not print a valid ABI")?;
+        let json = serde_json::json!({
+            "abi": abi,
+            "bytecode": { "object": bytecode.trim() },
+            "deployedBytecode": { "object": deployed_bytecode.trim() },
+        });
+
+        fs::create_dir_all(artifact.parent().unwrap())?;
+        fs::write(&artifact, serde_json::to_string_pretty(&json)?)?;
+        if !quiet {
+            println!("Compiled {}", source.display());
+        }
+        artifacts.push(artifact);
+    }
+    Ok(artifacts)
+}
+
+/// Returns `true` if the artifact is missing or older than the source
+fn is_stale(source: &Path, artifact: &Path) -> bool {
+    let modified = |path: &Path| fs::metadata(path).and_then(|meta| meta.modified()).ok();
+    match (modified(source), modified(artifact)) {
+        (Some(source), Some(artifact)) => source > artifact,
+        _ => true,
+    }
+}
+
+#[cfg(all(test, unix))]
+mod tests {
+    use super::*;
+    use std::os::unix::fs::PermissionsExt;
+
+    /// Writes a fake `vyper` to the root, which records its invocations in `<root>/runs`
+    fn fake_vyper(root: &Path) -> PathBuf {
+        let vyper = root.join("vyper");
+        fs::write(
+            &vyper,
+            format!(
+                r#"#!/bin/sh
+echo "$@" >> {}
+echo '[{{"type":"function","name":"total","inputs":[],"outputs":[],"stateMutability":"view"}}]'
+echo 0x6001
+echo 0x6002
+"#,
+                root.join("runs").display()
+            ),
+        )
+        .unwrap();
+        fs::set_permissions(&vyper, fs::Permissions::from_mode(0o755)).unwrap();
+        vyper
+    }
+
+    fn project(root: &Path, vyper: PathBuf) -> (Project, Config) {
+        fs::create_dir_all(root.join("src")).unwrap();
+        let config = Config { vyper: Some(vyper), ..Config::with_root(root) };
+        (config.project().unwrap(), config)
+    }
+
+    #[test]
+    fn can_compile_vyper_sources() {
+        let tmp = tempfile::tempdir().unwrap();
+        let root = tmp.path();
+        let (project, config) = project(root, fake_vyper(root));
+        fs::write(root.join("src/Token.vy"), "# @version 0.3.4").unwrap();
+
+        let artifacts = compile_vyper(&project, &config, true).unwrap();
+        let artifact = project.paths.artifacts.join("Token.vy/Token.json");
+        assert_eq!(artifacts, vec![artifact.clone()]);
+
+        let json: serde_json::Value =
+            serde_json::from_str(&fs::read_to_string(&artifact).unwrap()).unwrap();
+        assert_eq!(json["abi"][0]["name"], "total");
+        assert_eq!(json["bytecode"]["object"], "0x6001");
+        assert_eq!(json["deployedBytecode"]["object"], "0x6002");
+
+        // the artifact is up to date, so vyper is not run again
+        assert_eq!(compile_vyper(&project, &config, true).unwrap(), vec![artifact]);
+        let runs = fs::read_to_string(root.join("runs")).unwrap();
+        assert_eq!(runs.lines().count(), 1);
+        assert!(runs.contains("--no-optimize"));
+    }
+
+    #[test]
+    fn skips_projects_without_vyper_sources() {
+        let tmp = tempfile::tempdir().unwrap();
+        let root = tmp.path();
+        let (project, config) = project(root, root.join("missing-vyper"));
+        fs::write(root.join("src/Token.sol"), "contract Token {}").unwrap();
+
+        assert!(compile_vyper(&project, &config, true).unwrap().is_empty());
+    }
+
+    #[test]
+    fn fails_without_vyper() {
+        let tmp = tempfile::tempdir().unwrap();
+        let root = tmp.path();
+        let (project, config) = project(root, root.join("missing-vyper"));
+        fs::write(root.join("src/Token.vy"), "# @version 0.3.4").unwrap();
+
+        let err = compile_vyper(&project, &config, true).unwrap_err();
+        assert!(err.to_string().contains("is not installed"), "{}", err);
+    }
+}
@@ -4,7 +4,10 @@ mod opts;
 mod term;
 mod utils;
 
-use crate::cmd::{forge::watch, Cmd};
+use crate::cmd::{
+    forge::{remappings::remove_lib_remappings, watch},
+    Cmd,
+};
 use opts::forge::{Dependency, Opts, Subcommands};
 use std::process::Command;
 
@@ -14,6 +17,7 @@ use clap_complete::generate;
 fn main() -> eyre::Result<()> {
     color_eyre::install()?;
     utils::subscriber();
+    utils::print_version_json_if_requested("forge");
 
     let opts = Opts::parse();
     match opts.sub {
@@ -35,8 +39,12 @@ fn main() -> eyre::Result<()> {
                 cmd.run()?;
             }
         }
-        Subcommands::Run(cmd) => {
-            cmd.run()?;
+        Subcommands::Script(cmd) => {
+            if cmd.is_watch() {
+                utils::block_on(watch::watch_script(cmd))?;
+            } else {
+                cmd.run()?;
+            }
         }
         Subcommands::VerifyContract(args) => {
             utils::block_on(args.run())?;
@@ -44,6 +52,15 @@ fn main() -> eyre::Result<()> {
         Subcommands::VerifyCheck(args) => {
             utils::block_on(args.run())?;
         }
+        Subcommands::ConstructorArgs(args) => {
+            utils::block_on(args.run())?;
+        }
+        Subcommands::UploadSelectors(args) => {
+            utils::block_on(args.run())?;
+        }
+        Subcommands::SelfUpdate(args) => {
+            utils::block_on(args.run())?;
+        }
         Subcommands::Create(cmd) => {
             cmd.run()?;
         }
@@ -72,6 +89,9 @@ fn main() -> eyre::Result<()> {
         Subcommands::Init(cmd) => {
             cmd.run()?;
         }
+        Subcommands::Clone(args) => {
+            utils::block_on(args.run())?;
+        }
         Subcommands::Completions { shell } => {
             generate(shell, &mut Opts::command(), "forge", &mut std::io::stdout())
         }
@@ -101,6 +121,9 @@ fn main() -> eyre::Result<()> {
         Subcommands::Tree(cmd) => {
             cmd.run()?;
         }
+        Subcommands::Doctor(args) => {
+            utils::block_on(args.run())?;
+        }
     }
 
     Ok(())
@@ -137,6 +160,10 @@ fn remove(root: impl AsRef<std::path::Path>, dependencies: Vec<Dependency>) -> e
             .spawn()?
             .wait()?;
 
+        for remapping in remove_lib_remappings(root.as_ref(), &path)? {
+            println!("Removed remapping {}", remapping);
+        }
+
         Ok(())
     })
 }
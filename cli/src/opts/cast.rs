@@ -173,6 +173,12 @@ Examples:
         args: Vec<String>,
         #[clap(long, short, help = "the block you want to query, can also be earliest/latest/pending", parse(try_from_str = parse_block_id))]
         block: Option<BlockId>,
+        #[clap(
+            long,
+            help = "Print the opcodes of the call.",
+            long_help = "Print the opcodes of the call, executed against a local fork at the queried block."
+        )]
+        trace_printer: bool,
         #[clap(flatten)]
         eth: EthereumOpts,
     },
@@ -185,6 +191,11 @@ Examples:
         sig: String,
         #[clap(allow_hyphen_values = true)]
         args: Vec<String>,
+        #[clap(
+            long,
+            help = "Print every argument with its type and the 32-byte words of the encoding."
+        )]
+        pretty: bool,
     },
     #[clap(name = "chain")]
     #[clap(about = "Get the symbolic name of the current chain.")]
@@ -209,8 +220,8 @@ Examples:
     ComputeAddress {
         #[clap(long, env = "ETH_RPC_URL")]
         rpc_url: Option<String>,
-        #[clap(help = "The deployer address.")]
-        address: String,
+        #[clap(help = "The deployer address.", parse(try_from_str = parse_name_or_address))]
+        address: NameOrAddress,
         #[clap(long, help = "The nonce of the deployer address.", parse(try_from_str = parse_u256))]
         nonce: Option<U256>,
     },
@@ -244,6 +255,17 @@ Examples:
         cast_async: bool,
         #[clap(long = "json", short = 'j', help_heading = "DISPLAY OPTIONS")]
         to_json: bool,
+        #[clap(
+            long,
+            value_name = "PATH_OR_ADDRESS",
+            help = "Decode the logs with the events of an ABI.",
+            long_help = r#"Decode the logs with the events of an ABI.
+
+Either the path to a JSON ABI file, or the address of a contract whose ABI is fetched from Etherscan."#
+        )]
+        abi: Option<String>,
+        #[clap(long, env = "ETHERSCAN_API_KEY", help = "etherscan API key")]
+        etherscan_api_key: Option<String>,
         #[clap(long, env = "ETH_RPC_URL")]
         rpc_url: Option<String>,
     },
@@ -340,15 +362,20 @@ Examples: 1ether, 10gwei, 0.01ether"#,
         // TODO: We only need RPC URL and Etherscan API key here.
         eth: EthereumOpts,
     },
-    #[clap(name = "--calldata-decode")]
+    #[clap(name = "--calldata-decode", alias = "calldata-decode")]
     #[clap(about = "Decode ABI-encoded input data.")]
     CalldataDecode {
         #[clap(help = "The function signature in the format `<name>(<in-types>)(<out-types>)`.")]
         sig: String,
         #[clap(help = "The ABI-encoded calldata.")]
         calldata: String,
+        #[clap(
+            long,
+            help = "Print every decoded argument with its type and the 32-byte words of the encoding."
+        )]
+        pretty: bool,
     },
-    #[clap(name = "--abi-decode")]
+    #[clap(name = "--abi-decode", alias = "abi-decode")]
     #[clap(
         about = "Decode ABI-encoded input or output data",
         long_about = r#"Decode ABI-encoded input or output data.
@@ -362,6 +389,11 @@ Defaults to decoding output data. To decode input data pass --input or use cast
         calldata: String,
         #[clap(long, short, help = "Decode input data.")]
         input: bool,
+        #[clap(
+            long,
+            help = "Print every decoded value with its type and the 32-byte words of the encoding."
+        )]
+        pretty: bool,
     },
     #[clap(name = "abi-encode")]
     #[clap(about = "ABI encode the given function argument, excluding the selector.")]
@@ -371,6 +403,11 @@ Defaults to decoding output data. To decode input data pass --input or use cast
         #[clap(help = "The arguments of the function.")]
         #[clap(allow_hyphen_values = true)]
         args: Vec<String>,
+        #[clap(
+            long,
+            help = "Print every argument with its type and the 32-byte words of the encoding."
+        )]
+        pretty: bool,
     },
     #[clap(name = "index")]
     #[clap(about = "Compute the storage slot for an entry in a mapping.")]
@@ -384,6 +421,17 @@ Defaults to decoding output data. To decode input data pass --input or use cast
         #[clap(help = "The storage slot of the mapping.")]
         slot_number: String,
     },
+    #[clap(name = "index-erc7201")]
+    #[clap(about = "Compute the root slot of an ERC-7201 storage namespace.")]
+    IndexErc7201 {
+        #[clap(help = "The namespace id, e.g. `example.main`.")]
+        id: String,
+        #[clap(
+            long,
+            help = "Compute the slot of a diamond storage struct, `keccak256(id)`, instead of the ERC-7201 root."
+        )]
+        diamond: bool,
+    },
     #[clap(name = "4byte")]
     #[clap(about = "Get the function signatures for the given selector from 4byte.directory.")]
     FourByte {
@@ -425,6 +473,30 @@ Tries to decode the calldata using 4byte.directory unless --offline is passed."#
         #[clap(long, short, help = "Skip the 4byte directory lookup.")]
         offline: bool,
     },
+    #[clap(name = "da")]
+    #[clap(
+        about = "Estimate the L1 data availability cost of calldata or a transaction.",
+        long_about = r#"Estimate the L1 data availability cost of calldata or a transaction.
+
+Prints the L1 gas of the data priced per zero and non-zero byte, and the FastLZ compressed size and gas used by Optimism since the Fjord upgrade.
+
+A 32 byte input is treated as a transaction hash, and the signed transaction is fetched from the RPC endpoint."#
+    )]
+    Da {
+        #[clap(help = "The calldata or the transaction hash.", value_name = "DATA_OR_TX_HASH")]
+        input: String,
+        #[clap(
+            long,
+            help = "The L1 gas price, to print the cost in ether.",
+            long_help = r#"The L1 gas price, to print the cost in ether, either specified in wei, or as a string with a unit type.
+
+Examples: 30gwei, 1000000000"#,
+            parse(try_from_str = parse_ether_value)
+        )]
+        gas_price: Option<U256>,
+        #[clap(long, env = "ETH_RPC_URL")]
+        rpc_url: Option<String>,
+    },
     #[clap(name = "age")]
     #[clap(about = "Get the timestamp of a block.")]
     Age {
@@ -534,8 +606,13 @@ Tries to decode the calldata using 4byte.directory unless --offline is passed."#
             parse(try_from_str = parse_block_id)
         )]
         block: Option<BlockId>,
+        #[clap(long = "json", short = 'j', help_heading = "DISPLAY OPTIONS")]
+        to_json: bool,
     },
-    #[clap(name = "proof", about = "Generate a storage proof for a given storage slot.")]
+    #[clap(
+        name = "proof",
+        about = "Generate an EIP-1186 proof of an account and the given storage slots."
+    )]
     Proof {
         #[clap(help = "The contract address.", parse(try_from_str = parse_name_or_address))]
         address: NameOrAddress,
@@ -551,6 +628,8 @@ Tries to decode the calldata using 4byte.directory unless --offline is passed."#
             parse(try_from_str = parse_block_id)
         )]
         block: Option<BlockId>,
+        #[clap(long = "json", short = 'j', help_heading = "DISPLAY OPTIONS")]
+        to_json: bool,
     },
     #[clap(name = "nonce")]
     #[clap(about = "Get the nonce for an account.")]
@@ -585,6 +664,23 @@ Tries to decode the calldata using 4byte.directory unless --offline is passed."#
         #[clap(subcommand)]
         command: WalletSubcommands,
     },
+    #[clap(
+        name = "erc20",
+        about = "Query and transfer ERC-20 tokens with amounts in the units of the token."
+    )]
+    Erc20 {
+        #[clap(subcommand)]
+        command: Erc20Subcommands,
+    },
+    #[clap(
+        name = "tx-pool",
+        visible_alias = "txpool",
+        about = "Inspect the mempool of a node that exposes the txpool API, e.g. geth or anvil."
+    )]
+    TxPool {
+        #[clap(subcommand)]
+        command: TxPoolSubcommands,
+    },
     #[clap(
         name = "interface",
         about = "Generate a Solidity interface from a given ABI.",
@@ -691,8 +787,125 @@ pub enum WalletSubcommands {
     },
 }
 
+#[derive(Debug, Parser)]
+pub enum Erc20Subcommands {
+    #[clap(name = "balance", about = "Get the token balance of an account.")]
+    Balance {
+        #[clap(help = "The token contract.", parse(try_from_str = parse_name_or_address))]
+        token: NameOrAddress,
+        #[clap(help = "The account you want to query.", parse(try_from_str = parse_name_or_address))]
+        owner: NameOrAddress,
+        #[clap(
+            long,
+            short = 'B',
+            help = "The block height you want to query at.",
+            long_help = "The block height you want to query at. Can also be the tags earliest, latest, or pending.",
+            parse(try_from_str = parse_block_id)
+        )]
+        block: Option<BlockId>,
+        #[clap(short, long, env = "ETH_RPC_URL")]
+        rpc_url: Option<String>,
+    },
+    #[clap(
+        name = "allowance",
+        about = "Get the amount of tokens a spender may transfer from an account."
+    )]
+    Allowance {
+        #[clap(help = "The token contract.", parse(try_from_str = parse_name_or_address))]
+        token: NameOrAddress,
+        #[clap(help = "The account that owns the tokens.", parse(try_from_str = parse_name_or_address))]
+        owner: NameOrAddress,
+        #[clap(help = "The approved spender.", parse(try_from_str = parse_name_or_address))]
+        spender: NameOrAddress,
+        #[clap(
+            long,
+            short = 'B',
+            help = "The block height you want to query at.",
+            long_help = "The block height you want to query at. Can also be the tags earliest, latest, or pending.",
+            parse(try_from_str = parse_block_id)
+        )]
+        block: Option<BlockId>,
+        #[clap(short, long, env = "ETH_RPC_URL")]
+        rpc_url: Option<String>,
+    },
+    #[clap(name = "transfer", about = "Sign and publish a transfer of tokens.")]
+    Transfer {
+        #[clap(help = "The token contract.", parse(try_from_str = parse_name_or_address))]
+        token: NameOrAddress,
+        #[clap(help = "The recipient.", parse(try_from_str = parse_name_or_address))]
+        to: NameOrAddress,
+        #[clap(
+            help = "The amount of tokens to transfer.",
+            long_help = r#"The amount of tokens to transfer, in the units of the token and optionally followed by its symbol.
+
+Examples: 1.5, "1.5 USDC""#
+        )]
+        amount: String,
+        #[clap(long, help = "Gas limit for the transaction.", parse(try_from_str = parse_u256))]
+        gas: Option<U256>,
+        #[clap(
+            long = "gas-price",
+            help = "Gas price for legacy transactions, or max fee per gas for EIP1559 transactions.",
+            env = "ETH_GAS_PRICE",
+            parse(try_from_str = parse_ether_value)
+        )]
+        gas_price: Option<U256>,
+        #[clap(long, help = "nonce for the transaction", parse(try_from_str = parse_u256))]
+        nonce: Option<U256>,
+        #[clap(long, env = "CAST_ASYNC")]
+        cast_async: bool,
+        #[clap(flatten)]
+        eth: EthereumOpts,
+        #[clap(
+            long,
+            help = "Send a legacy transaction instead of an EIP1559 transaction.",
+            long_help = r#"Send a legacy transaction instead of an EIP1559 transaction.
+
+This is automatically enabled for common networks without EIP1559."#
+        )]
+        legacy: bool,
+        #[clap(
+            short,
+            long,
+            help = "The number of confirmations until the receipt is fetched.",
+            default_value = "1"
+        )]
+        confirmations: usize,
+        #[clap(long = "json", short = 'j', help_heading = "DISPLAY OPTIONS")]
+        to_json: bool,
+    },
+}
+
+#[derive(Debug, Parser)]
+pub enum TxPoolSubcommands {
+    #[clap(name = "status", about = "Get the number of pending and queued transactions.")]
+    Status {
+        #[clap(short, long, env = "ETH_RPC_URL")]
+        rpc_url: Option<String>,
+        #[clap(long = "json", short = 'j', help_heading = "DISPLAY OPTIONS")]
+        to_json: bool,
+    },
+    #[clap(name = "content", about = "Get the pending and queued transactions.")]
+    Content {
+        #[clap(short, long, env = "ETH_RPC_URL")]
+        rpc_url: Option<String>,
+        #[clap(long = "json", short = 'j', help_heading = "DISPLAY OPTIONS")]
+        to_json: bool,
+    },
+    #[clap(name = "inspect", about = "Get a summary of the pending and queued transactions.")]
+    Inspect {
+        #[clap(short, long, env = "ETH_RPC_URL")]
+        rpc_url: Option<String>,
+        #[clap(long = "json", short = 'j', help_heading = "DISPLAY OPTIONS")]
+        to_json: bool,
+    },
+}
+
+/// Parses an address, with or without the `0x` prefix, or an ENS name that is resolved with the
+/// provider of the command
 pub fn parse_name_or_address(s: &str) -> eyre::Result<NameOrAddress> {
-    Ok(if s.starts_with("0x") {
+    let is_unprefixed_address = s.len() == 40 && s.chars().all(|c| c.is_ascii_hexdigit());
+    Ok(if s.starts_with("0x") || is_unprefixed_address {
         NameOrAddress::Address(s.parse::<Address>()?)
     } else {
         NameOrAddress::Name(s.into())
@@ -719,7 +932,11 @@ fn parse_slot(s: &str) -> eyre::Result<H256> {
 }
 
 #[derive(Debug, Parser)]
-#[clap(name = "cast", version = crate::utils::VERSION_MESSAGE)]
+#[clap(
+    name = "cast",
+    version = crate::utils::VERSION_MESSAGE,
+    long_version = crate::utils::LONG_VERSION_MESSAGE
+)]
 pub struct Opts {
     #[clap(subcommand)]
     pub sub: Subcommands,
@@ -6,15 +6,20 @@ use std::{path::PathBuf, str::FromStr};
 use crate::cmd::forge::{
     bind::BindArgs,
     build::BuildArgs,
+    clone::CloneArgs,
     config,
+    constructor_args::ConstructorArgsArgs,
     create::CreateArgs,
+    doctor::DoctorArgs,
     flatten,
     init::InitArgs,
     inspect,
     install::InstallArgs,
     remappings::RemappingArgs,
-    run::RunArgs,
+    script::ScriptArgs,
+    self_update::SelfUpdateArgs,
     snapshot, test, tree,
+    upload_selectors::UploadSelectorsArgs,
     verify::{VerifyArgs, VerifyCheckArgs},
 };
 use serde::Serialize;
@@ -31,7 +36,11 @@ static GH_REPO_PREFIX_REGEX: Lazy<Regex> = Lazy::new(|| {
 });
 
 #[derive(Debug, Parser)]
-#[clap(name = "forge", version = crate::utils::VERSION_MESSAGE)]
+#[clap(
+    name = "forge",
+    version = crate::utils::VERSION_MESSAGE,
+    long_version = crate::utils::LONG_VERSION_MESSAGE
+)]
 pub struct Opts {
     #[clap(subcommand)]
     pub sub: Subcommands,
@@ -55,9 +64,11 @@ pub enum Subcommands {
     #[clap(alias = "b")]
     Build(BuildArgs),
 
-    #[clap(about = "Run a single smart contract as a script.")]
-    #[clap(alias = "r")]
-    Run(RunArgs),
+    #[clap(
+        about = "Run a smart contract as a script, building transactions that can be sent onchain."
+    )]
+    #[clap(alias = "run", alias = "r")]
+    Script(ScriptArgs),
 
     #[clap(
         alias = "u",
@@ -88,23 +99,47 @@ pub enum Subcommands {
     Remappings(RemappingArgs),
 
     #[clap(
-        about = "Verify smart contracts on Etherscan.",
-        long_about = "Verify smart contracts on Etherscan."
+        about = "Verify smart contracts on Etherscan or Sourcify.",
+        long_about = "Verify smart contracts on Etherscan or Sourcify."
     )]
     VerifyContract(VerifyArgs),
 
     #[clap(
-        about = "Check verification status on Etherscan.",
-        long_about = "Check verification status on Etherscan."
+        about = "Check verification status on Etherscan or Sourcify.",
+        long_about = "Check verification status on Etherscan or Sourcify."
     )]
     VerifyCheck(VerifyCheckArgs),
 
+    #[clap(
+        about = "Extract and decode the constructor arguments of a deployed contract.",
+        long_about = "Extract and decode the constructor arguments of a deployed contract, either from its creation transaction or from Etherscan."
+    )]
+    ConstructorArgs(ConstructorArgsArgs),
+
+    #[clap(
+        about = "Upload the function, error and event selectors of the project.",
+        long_about = "Upload the function, error and event selectors of the project to the public signature database, so that they can be decoded by others. The selectors are also added to the local signatures cache."
+    )]
+    UploadSelectors(UploadSelectorsArgs),
+
     #[clap(alias = "c", about = "Deploy a smart contract.")]
     Create(CreateArgs),
 
     #[clap(alias = "i", about = "Create a new Forge project.")]
     Init(InitArgs),
 
+    #[clap(
+        about = "Create a project from the verified sources of a deployed contract.",
+        long_about = "Create a project from the verified sources of a deployed contract. The sources, compiler settings and constructor arguments are fetched from Etherscan, and the rebuilt contract is compared with the code deployed at the address."
+    )]
+    Clone(CloneArgs),
+
+    #[clap(
+        about = "Update forge and cast to the latest release or a specific version.",
+        long_about = "Update forge and cast to the latest release or a specific version. The binaries next to the running executable are replaced with the ones of the release."
+    )]
+    SelfUpdate(SelfUpdateArgs),
+
     #[clap(about = "Generate shell completions script")]
     Completions {
         #[clap(arg_enum)]
@@ -135,6 +170,11 @@ pub enum Subcommands {
     Inspect(inspect::InspectArgs),
     #[clap(about = "Display a tree visualization of the project's dependency graph.")]
     Tree(tree::TreeArgs),
+    #[clap(
+        about = "Check the project's environment for common problems.",
+        long_about = "Check the project's environment for common problems: the config, the installed solc versions, the git submodules, the remappings and the reachability of the configured RPC endpoints. A fix is suggested for every problem that is found."
+    )]
+    Doctor(DoctorArgs),
 }
 
 // A set of solc compiler settings that can be set via command line arguments, which are intended
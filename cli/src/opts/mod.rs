@@ -97,7 +97,7 @@ impl EthereumOpts {
 
     #[allow(unused)]
     pub async fn signer(&self, chain_id: U256) -> eyre::Result<Option<WalletType>> {
-        self.signer_with(chain_id, Provider::try_from(self.rpc_url()?)?).await
+        self.signer_with(chain_id, Provider::try_from(self.rpc_url()?.as_str())?).await
     }
 
     /// Returns a [`SignerMiddleware`] corresponding to the provided private key, mnemonic or hw
@@ -107,46 +107,19 @@ impl EthereumOpts {
         chain_id: U256,
         provider: Provider<Http>,
     ) -> eyre::Result<Option<WalletType>> {
-        if self.wallet.ledger {
-            let derivation = match &self.wallet.hd_path {
-                Some(hd_path) => LedgerHDPath::Other(hd_path.clone()),
-                None => LedgerHDPath::LedgerLive(self.wallet.mnemonic_index as usize),
-            };
-            let ledger = Ledger::new(derivation, chain_id.as_u64()).await?;
-
-            Ok(Some(WalletType::Ledger(SignerMiddleware::new(provider, ledger))))
-        } else if self.wallet.trezor {
-            let derivation = match &self.wallet.hd_path {
-                Some(hd_path) => TrezorHDPath::Other(hd_path.clone()),
-                None => TrezorHDPath::TrezorLive(self.wallet.mnemonic_index as usize),
-            };
-
-            // cached to ~/.ethers-rs/trezor/cache/trezor.session
-            let trezor = Trezor::new(derivation, chain_id.as_u64(), None).await?;
-
-            Ok(Some(WalletType::Trezor(SignerMiddleware::new(provider, trezor))))
-        } else {
-            let local = self
-                .wallet
-                .private_key()
-                .transpose()
-                .or_else(|| self.wallet.interactive().transpose())
-                .or_else(|| self.wallet.mnemonic().transpose())
-                .or_else(|| self.wallet.keystore().transpose())
-                .transpose()?
-                .ok_or_else(|| eyre::eyre!("error accessing local wallet, did you set a private key, mnemonic or keystore? Run `cast send --help` or `forge create --help` and use the corresponding CLI flag to set your key via --private-key, --mnemonic-path, --interactive, --trezor or --ledger. Alternatively, if you're using a local node with unlocked accounts, set the `ETH_FROM` environment variable to the address of the account you want to use"))?;
-
-            let local = local.with_chain_id(chain_id.as_u64());
+        self.wallet.signer(provider, chain_id).await.map(Some)
+    }
 
-            Ok(Some(WalletType::Local(SignerMiddleware::new(provider, local))))
-        }
+    /// Returns the RPC endpoint, with `rpc_endpoints` aliases and env vars resolved
+    pub fn rpc_url(&self) -> Result<String> {
+        Ok(Config::load().resolve_rpc_url(self.unresolved_rpc_url())?)
     }
 
-    pub fn rpc_url(&self) -> Result<&str> {
+    fn unresolved_rpc_url(&self) -> &str {
         if self.flashbots {
-            Ok(FLASHBOTS_URL)
+            FLASHBOTS_URL
         } else {
-            Ok(self.rpc_url.as_deref().unwrap_or("http://localhost:8545"))
+            self.rpc_url.as_deref().unwrap_or("http://localhost:8545")
         }
     }
 }
@@ -161,7 +134,7 @@ impl figment::Provider for EthereumOpts {
         let value = Value::serialize(self)?;
         let mut dict = value.into_dict().unwrap();
 
-        let rpc_url = self.rpc_url().map_err(|err| err.to_string())?;
+        let rpc_url = self.unresolved_rpc_url();
         if rpc_url != "http://localhost:8545" {
             dict.insert("eth_rpc_url".to_string(), rpc_url.to_string().into());
         }
@@ -280,6 +253,47 @@ pub struct Wallet {
 }
 
 impl Wallet {
+    /// Returns a [`SignerMiddleware`] corresponding to the provided private key, mnemonic or hw
+    /// signer
+    pub async fn signer(
+        &self,
+        provider: Provider<Http>,
+        chain_id: U256,
+    ) -> eyre::Result<WalletType> {
+        if self.ledger {
+            let derivation = match &self.hd_path {
+                Some(hd_path) => LedgerHDPath::Other(hd_path.clone()),
+                None => LedgerHDPath::LedgerLive(self.mnemonic_index as usize),
+            };
+            let ledger = Ledger::new(derivation, chain_id.as_u64()).await?;
+
+            Ok(WalletType::Ledger(SignerMiddleware::new(provider, ledger)))
+        } else if self.trezor {
+            let derivation = match &self.hd_path {
+                Some(hd_path) => TrezorHDPath::Other(hd_path.clone()),
+                None => TrezorHDPath::TrezorLive(self.mnemonic_index as usize),
+            };
+
+            // cached to ~/.ethers-rs/trezor/cache/trezor.session
+            let trezor = Trezor::new(derivation, chain_id.as_u64(), None).await?;
+
+            Ok(WalletType::Trezor(SignerMiddleware::new(provider, trezor)))
+        } else {
+            let local = self
+                .private_key()
+                .transpose()
+                .or_else(|| self.interactive().transpose())
+                .or_else(|| self.mnemonic().transpose())
+                .or_else(|| self.keystore().transpose())
+                .transpose()?
+                .ok_or_else(|| eyre::eyre!("error accessing local wallet, did you set a private key, mnemonic or keystore? Run `cast send --help` or `forge create --help` and use the corresponding CLI flag to set your key via --private-key, --mnemonic-path, --interactive, --trezor or --ledger. Alternatively, if you're using a local node with unlocked accounts, set the `ETH_FROM` environment variable to the address of the account you want to use"))?;
+
+            let local = local.with_chain_id(chain_id.as_u64());
+
+            Ok(WalletType::Local(SignerMiddleware::new(provider, local)))
+        }
+    }
+
     fn interactive(&self) -> Result<Option<LocalWallet>> {
         Ok(if self.interactive {
             println!("Insert private key:");
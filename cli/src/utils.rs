@@ -29,6 +29,59 @@ pub(crate) const VERSION_MESSAGE: &str = concat!(
     ")"
 );
 
+/// The long version message for the current program, printed with `--version`, which also contains
+/// the build provenance, like
+///
+/// ```text
+/// forge 0.1.0 (f01b232bc 2022-01-22T23:28:39.493201+00:00)
+/// target: x86_64-unknown-linux-gnu
+/// profile: release
+/// features: default,rustls
+/// ```
+pub(crate) const LONG_VERSION_MESSAGE: &str = concat!(
+    env!("CARGO_PKG_VERSION"),
+    " (",
+    env!("VERGEN_GIT_SHA_SHORT"),
+    " ",
+    env!("VERGEN_BUILD_TIMESTAMP"),
+    ")",
+    "\ntarget: ",
+    env!("VERGEN_CARGO_TARGET_TRIPLE"),
+    "\nprofile: ",
+    env!("VERGEN_CARGO_PROFILE"),
+    "\nfeatures: ",
+    env!("VERGEN_CARGO_FEATURES"),
+);
+
+/// Prints the version information as JSON and exits, if the program was invoked with
+/// `--version --json`.
+///
+/// This needs to be checked before the arguments are parsed, because clap handles `--version`
+/// itself.
+pub(crate) fn print_version_json_if_requested(name: &str) {
+    let args = std::env::args().skip(1).collect::<Vec<_>>();
+    let is_version = args.iter().any(|arg| arg == "--version" || arg == "-V");
+    if args.len() != 2 || !is_version || !args.iter().any(|arg| arg == "--json") {
+        return
+    }
+
+    let features = env!("VERGEN_CARGO_FEATURES")
+        .split(',')
+        .filter(|feature| !feature.is_empty())
+        .collect::<Vec<_>>();
+    let version = serde_json::json!({
+        "name": name,
+        "version": env!("CARGO_PKG_VERSION"),
+        "commit": env!("VERGEN_GIT_SHA_SHORT"),
+        "buildTimestamp": env!("VERGEN_BUILD_TIMESTAMP"),
+        "target": env!("VERGEN_CARGO_TARGET_TRIPLE"),
+        "profile": env!("VERGEN_CARGO_PROFILE"),
+        "features": features,
+    });
+    println!("{}", version);
+    std::process::exit(0)
+}
+
 /// Useful extensions to [`std::path::Path`].
 pub trait FoundryPathExt {
     /// Returns true if the [`Path`] ends with `.t.sol`
@@ -69,12 +122,18 @@ pub fn subscriber() {
         .init()
 }
 
+/// Returns the hardfork the EVM executes with for the `evm_version` of the config
 pub fn evm_spec(evm: &EvmVersion) -> SpecId {
     match evm {
+        EvmVersion::Homestead => SpecId::HOMESTEAD,
+        EvmVersion::TangerineWhistle => SpecId::TANGERINE,
+        EvmVersion::SpuriousDragon => SpecId::SPURIOUS_DRAGON,
+        EvmVersion::Byzantium => SpecId::BYZANTIUM,
+        EvmVersion::Constantinople => SpecId::CONSTANTINOPLE,
+        EvmVersion::Petersburg => SpecId::PETERSBURG,
         EvmVersion::Istanbul => SpecId::ISTANBUL,
         EvmVersion::Berlin => SpecId::BERLIN,
         EvmVersion::London => SpecId::LONDON,
-        _ => panic!("Unsupported EVM version"),
     }
 }
 
@@ -131,12 +190,14 @@ pub fn parse_u256(s: &str) -> eyre::Result<U256> {
 
 /// Return `rpc-url` cli argument if given, or consume `eth-rpc-url` from foundry.toml. Default to
 /// `localhost:8545`
-pub fn consume_config_rpc_url(rpc_url: Option<String>) -> String {
-    if let Some(rpc_url) = rpc_url {
-        rpc_url
-    } else {
-        Config::load().eth_rpc_url.unwrap_or_else(|| "http://localhost:8545".to_string())
-    }
+///
+/// The url can also be an alias of an endpoint in the `[rpc_endpoints]` table of foundry.toml.
+pub fn consume_config_rpc_url(rpc_url: Option<String>) -> eyre::Result<String> {
+    let config = Config::load();
+    let rpc_url = rpc_url
+        .or_else(|| config.eth_rpc_url.clone())
+        .unwrap_or_else(|| "http://localhost:8545".to_string());
+    Ok(config.resolve_rpc_url(&rpc_url)?)
 }
 
 /// Parses an ether value from a string.
@@ -177,6 +238,18 @@ pub fn block_on<F: Future>(future: F) -> F::Output {
     rt.block_on(future)
 }
 
+/// Resolves the `fork_url` of the [EvmOpts] and the `eth_rpc_url` of the [Config] if they are
+/// aliases of an endpoint in the config's `[rpc_endpoints]`, like `--fork-url mainnet`
+pub fn resolve_rpc_endpoints(config: &mut Config, evm_opts: &mut EvmOpts) -> eyre::Result<()> {
+    if let Some(ref fork_url) = evm_opts.fork_url {
+        evm_opts.fork_url = Some(config.resolve_rpc_url(fork_url)?);
+    }
+    if let Some(ref eth_rpc_url) = config.eth_rpc_url {
+        config.eth_rpc_url = Some(config.resolve_rpc_url(eth_rpc_url)?);
+    }
+    Ok(())
+}
+
 /// Helper function that returns the [Fork] to use, if any.
 ///
 /// storage caching for the [Fork] will be enabled if
@@ -224,6 +297,7 @@ pub fn get_fork(evm_opts: &EvmOpts, config: &StorageCachingConfig) -> Option<For
             pin_block: evm_opts.fork_block_number,
             cache_path: cache_storage,
             chain_id,
+            retry_backoff: evm_opts.fork_retry_backoff,
         };
         return Some(fork)
     }
@@ -260,4 +334,12 @@ mod tests {
         let p = Path::new("contracts/Greeter.sol");
         assert!(!p.is_sol_test());
     }
+
+    #[test]
+    fn can_map_evm_versions() {
+        assert_eq!(evm_spec(&EvmVersion::Homestead), SpecId::HOMESTEAD);
+        assert_eq!(evm_spec(&EvmVersion::Byzantium), SpecId::BYZANTIUM);
+        assert_eq!(evm_spec(&EvmVersion::Petersburg), SpecId::PETERSBURG);
+        assert_eq!(evm_spec(&EvmVersion::London), SpecId::LONDON);
+    }
 }
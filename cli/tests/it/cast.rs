@@ -29,3 +29,49 @@ casttest!(finds_block, |_: TestProject, mut cmd: TestCommand| {
     // Output block time: Mar 21 2022 06:20:09 UTC
     assert!(output.contains("14428082"), "{}", output);
 });
+
+// tests that `cast wallet verify` fails if the signature was not created by the address
+casttest!(wallet_sign_and_verify, |_: TestProject, mut cmd: TestCommand| {
+    let private_key = "0x0000000000000000000000000000000000000000000000000000000000000001";
+    let address = "0x7E5F4552091A69125d5DfCb7b8C2659029395Bdf";
+
+    cmd.args(["wallet", "sign", "--private-key", private_key, "hello"]);
+    let output = cmd.stdout_lossy();
+    let signature = output.trim().strip_prefix("Signature: ").unwrap().to_string();
+
+    cmd.cast_fuse().args(["wallet", "verify", "--address", address, "hello", &signature]);
+    assert!(cmd.stdout_lossy().contains("Validation success"));
+
+    cmd.cast_fuse().args([
+        "wallet",
+        "verify",
+        "--address",
+        "0x0000000000000000000000000000000000000001",
+        "hello",
+        &signature,
+    ]);
+    cmd.assert_err();
+});
+
+// tests that checksummed vanity patterns are matched case insensitively
+casttest!(wallet_vanity_checksummed_prefix, |_: TestProject, mut cmd: TestCommand| {
+    cmd.args(["wallet", "vanity", "--starts-with", "0xAb"]);
+    let output = cmd.stdout_lossy().to_lowercase();
+    assert!(output.contains("address: 0xab"), "{}", output);
+});
+
+// tests that the pretty printed encoding of `abi-encode` has no selector
+casttest!(abi_encode_pretty, |_: TestProject, mut cmd: TestCommand| {
+    cmd.args([
+        "abi-encode",
+        "--pretty",
+        "transfer(address,uint256)",
+        "0x00000000000000000000000000000000000000aa",
+        "42",
+    ]);
+    let output = cmd.stdout_lossy();
+    assert!(!output.contains("Selector"), "{}", output);
+    assert!(output.contains("[1] (uint256): 42"), "{}", output);
+    assert!(output.contains("[0x20]: "), "{}", output);
+    assert!(!output.contains("[0x40]: "), "{}", output);
+});
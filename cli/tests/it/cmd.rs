@@ -23,6 +23,16 @@ forgetest!(print_help, |_: TestProject, mut cmd: TestCommand| {
     cmd.assert_non_empty_stdout();
 });
 
+// tests that `--version --json` prints the build information as json
+forgetest!(print_version_json, |_: TestProject, mut cmd: TestCommand| {
+    cmd.args(["--version", "--json"]);
+    let version: serde_json::Value = serde_json::from_str(&cmd.stdout_lossy()).unwrap();
+    assert_eq!(version["name"], "forge");
+    assert_eq!(version["version"], env!("CARGO_PKG_VERSION"));
+    assert!(version["target"].is_string());
+    assert!(version["features"].is_array());
+});
+
 // checks that `clean` can be invoked even if out and cache don't exist
 forgetest!(can_clean_non_existing, |prj: TestProject, mut cmd: TestCommand| {
     cmd.arg("clean");
@@ -131,6 +141,22 @@ forgetest!(can_init_vscode, |prj: TestProject, mut cmd: TestCommand| {
     assert_eq!(content, "ds-test/=lib/ds-test/src/");
 });
 
+// checks that init can create a hardhat style project
+forgetest!(can_init_hardhat, |prj: TestProject, mut cmd: TestCommand| {
+    prj.wipe();
+
+    cmd.arg("init").arg(prj.root()).args(["--hardhat", "--no-git"]);
+    cmd.assert_non_empty_stdout();
+
+    assert!(prj.root().join("contracts/Contract.sol").is_file());
+    assert!(!prj.root().join("src").exists());
+
+    let s = read_string(prj.root().join(Config::FILE_NAME));
+    let config: BasicConfig = parse_with_profile(&s).unwrap().unwrap().1;
+    assert_eq!(config.src, std::path::PathBuf::from("contracts"));
+    assert_eq!(config.libs, vec![std::path::PathBuf::from("node_modules"), "lib".into()]);
+});
+
 // checks that `clean` removes dapptools style paths
 forgetest!(can_clean, |prj: TestProject, mut cmd: TestCommand| {
     prj.assert_create_dirs_exists();
@@ -366,6 +392,49 @@ Gas used: 3957
     ),));
 });
 
+// Tests that the `script` command records broadcasted transactions
+forgetest!(can_execute_script_command_with_broadcast, |prj: TestProject, mut cmd: TestCommand| {
+    let script = prj
+        .inner()
+        .add_source(
+            "Foo",
+            r#"
+// SPDX-License-Identifier: UNLICENSED
+pragma solidity 0.8.10;
+
+interface Vm {
+    function broadcast() external;
+}
+
+contract Counter {
+    uint256 public count;
+    function increment() external {
+        count++;
+    }
+}
+
+contract Demo {
+    Vm constant vm = Vm(address(bytes20(uint160(uint256(keccak256('hevm cheat code'))))));
+    function run() external {
+        Counter counter = new Counter();
+        vm.broadcast();
+        counter.increment();
+    }
+}
+   "#,
+        )
+        .unwrap();
+
+    cmd.arg("script").arg(script).args(["--target-contract", "Demo"]);
+    let output = cmd.stdout_lossy();
+    assert!(output.contains(&Colour::Green.paint("Script ran successfully.").to_string()));
+    assert!(output.ends_with(
+        "
+If you wish to simulate on-chain transactions pass a RPC URL with --fork-url.
+"
+    ));
+});
+
 // tests that the `inspect` command works correctly
 forgetest!(can_execute_inspect_command, |prj: TestProject, mut cmd: TestCommand| {
     // explicitly set to include the ipfs bytecode hash
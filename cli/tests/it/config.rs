@@ -12,9 +12,9 @@ use foundry_cli_test_utils::{
 };
 use foundry_config::{
     caching::{CachedChains, CachedEndpoints, StorageCachingConfig},
-    Config, OptimizerDetails, SolcReq,
+    Config, FormatterConfig, OptimizerDetails, QuoteStyle, RpcEndpoint, RpcEndpoints, SolcReq,
 };
-use std::{fs, path::PathBuf, str::FromStr};
+use std::{collections::BTreeMap, fs, path::PathBuf, str::FromStr};
 
 // import forge utils as mod
 #[allow(unused)]
@@ -40,6 +40,9 @@ forgetest!(can_extract_config_values, |prj: TestProject, mut cmd: TestCommand| {
         solc: Some(SolcReq::Local(PathBuf::from("custom-solc"))),
         auto_detect_solc: false,
         offline: true,
+        solc_jobs: Some(3),
+        vyper: Some("vyper-0.3.4".into()),
+        vyper_optimize: false,
         optimizer: false,
         optimizer_runs: 1000,
         optimizer_details: Some(OptimizerDetails {
@@ -66,6 +69,7 @@ forgetest!(can_extract_config_values, |prj: TestProject, mut cmd: TestCommand| {
         initial_balance: U256::from(0xffffffffffffffffffffffffu128),
         block_number: 10,
         fork_block_number: Some(200),
+        fork_retry_backoff: Some(500),
         chain_id: Some(9999.into()),
         gas_limit: 99_000_000.into(),
         gas_price: 999,
@@ -76,6 +80,7 @@ forgetest!(can_extract_config_values, |prj: TestProject, mut cmd: TestCommand| {
         block_gas_limit: Some(100.into()),
         memory_limit: 2u64.pow(25),
         eth_rpc_url: Some("localhost".to_string()),
+        rpc_endpoints: RpcEndpoints::new([("local", RpcEndpoint::from("http://localhost:8545"))]),
         etherscan_api_key: None,
         verbosity: 4,
         remappings: vec![Remapping::from_str("ds-test=lib/ds-test/").unwrap().into()],
@@ -92,6 +97,22 @@ forgetest!(can_extract_config_values, |prj: TestProject, mut cmd: TestCommand| {
         bytecode_hash: Default::default(),
         revert_strings: Some(RevertStrings::Strip),
         sparse_mode: true,
+        fmt: FormatterConfig {
+            line_length: 120,
+            tab_width: 2,
+            bracket_spacing: true,
+            quote_style: QuoteStyle::Single,
+        },
+        fs_permissions: Default::default(),
+        sandbox: Default::default(),
+        allocs: None,
+        deterministic_addresses: false,
+        isolate: true,
+        deny_reserved_addresses: false,
+        differential: BTreeMap::from([(
+            "average".to_string(),
+            vec!["python3".to_string(), "average.py".to_string()],
+        )]),
         __non_exhaustive: (),
     };
     prj.write_config(input.clone());
@@ -344,3 +365,22 @@ forgetest!(can_set_optimizer_runs, |prj: TestProject, mut cmd: TestCommand| {
     let config = prj.config_from_output(["--optimizer-runs", "300"]);
     assert_eq!(config.optimizer_runs, 300);
 });
+
+// test that differential references can be set on the command line
+forgetest!(can_set_differential_references, |prj: TestProject, mut cmd: TestCommand| {
+    cmd.set_current_dir(prj.root());
+
+    let config = prj.config_from_output([
+        "--differential",
+        "average=python3 average.py",
+        "--differential",
+        "answer=echo 0x2a",
+    ]);
+    assert_eq!(
+        config.differential,
+        BTreeMap::from([
+            ("answer".to_string(), vec!["echo".to_string(), "0x2a".to_string()]),
+            ("average".to_string(), vec!["python3".to_string(), "average.py".to_string()]),
+        ])
+    );
+});
@@ -128,3 +128,72 @@ contract FailTest is DSTest {
     cmd.args(["test", "--match-path", "*src/ATest.t.sol"]);
     cmd.stdout().contains("[PASS]") && !cmd.stdout().contains("[FAIL]")
 });
+
+// tests that `--summary --detailed` prints the summary tables
+forgetest!(can_print_test_summary, |prj: TestProject, mut cmd: TestCommand| {
+    prj.insert_ds_test();
+
+    prj.inner()
+        .add_source(
+            "ATest.t.sol",
+            r#"
+// SPDX-License-Identifier: UNLICENSED
+pragma solidity 0.8.10;
+import "./test.sol";
+contract ATest is DSTest {
+    function testPass() external {
+        assertTrue(true);
+    }
+}
+   "#,
+        )
+        .unwrap();
+
+    cmd.args(["test", "--summary", "--detailed"]);
+    let stdout = cmd.stdout();
+    assert!(stdout.contains("Test summary:"));
+    assert!(stdout.contains("Highest gas usage:"));
+    assert!(stdout.contains("Slowest tests:"));
+    assert!(stdout.contains("testPass()"));
+});
+
+// tests that `--profiles` runs the tests with the config of every profile, and that the arguments
+// take precedence over the config of each profile
+forgetest!(can_run_tests_with_profiles, |prj: TestProject, mut cmd: TestCommand| {
+    prj.insert_ds_test();
+
+    prj.inner()
+        .add_source(
+            "ChainTest.t.sol",
+            r#"
+// SPDX-License-Identifier: UNLICENSED
+pragma solidity 0.8.10;
+import "./test.sol";
+contract ChainTest is DSTest {
+    function testChainId() external {
+        assertEq(block.chainid, 99);
+    }
+}
+   "#,
+        )
+        .unwrap();
+    std::fs::write(
+        prj.root().join(Config::FILE_NAME),
+        r#"
+[profile.default]
+chain_id = 1
+
+[profile.ci]
+chain_id = 99
+"#,
+    )
+    .unwrap();
+
+    cmd.args(["test", "--profiles", "default,ci", "--allow-failure"]);
+    let out = cmd.stdout_lossy();
+    assert!(out.contains("Tests with different results across profiles"), "{}", out);
+
+    cmd.forge_fuse().args(["test", "--profiles", "default,ci", "--chain-id", "99"]);
+    let out = cmd.stdout_lossy();
+    assert!(!out.contains("Tests with different results across profiles"), "{}", out);
+});
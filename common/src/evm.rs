@@ -37,6 +37,8 @@ use serde::Serialize;
 pub struct EvmArgs {
     /// Fetch state over a remote endpoint instead of starting from an empty state.
     ///
+    /// This can also be the name of an endpoint in the `rpc_endpoints` table of the config.
+    ///
     /// If you want to fetch state from a specific block number, see --fork-block-number.
     #[clap(long, short, alias = "rpc-url")]
     #[serde(rename = "eth_rpc_url", skip_serializing_if = "Option::is_none")]
@@ -49,6 +51,16 @@ pub struct EvmArgs {
     #[serde(skip_serializing_if = "Option::is_none")]
     pub fork_block_number: Option<u64>,
 
+    /// The milliseconds to wait before retrying requests the endpoint rejected because of its
+    /// rate limit.
+    ///
+    /// The backoff doubles with every retry.
+    ///
+    /// See --fork-url.
+    #[clap(long, requires = "fork-url", value_name = "BACKOFF")]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub fork_retry_backoff: Option<u64>,
+
     /// Explicitly disables the use of RPC caching.
     ///
     /// All storage slots are read entirely from the endpoint.
@@ -75,6 +87,22 @@ pub struct EvmArgs {
     #[serde(skip)]
     pub ffi: bool,
 
+    /// Execute every call made by the test contract as a transaction of its own.
+    ///
+    /// Every call starts with cold accounts and storage slots and pays the base cost of a
+    /// transaction, so the measured gas matches the gas of the calls on-chain.
+    #[clap(long)]
+    #[serde(skip)]
+    pub isolate: bool,
+
+    /// Fuzz the functions with the given name differentially against a command.
+    ///
+    /// The command is run with the ABI-encoded inputs as its last argument and prints the
+    /// ABI-encoded outputs, e.g. `--differential "average=python3 scripts/average.py"`.
+    #[clap(long, value_name = "FUNCTION=COMMAND", multiple_occurrences = true)]
+    #[serde(skip)]
+    pub differential: Vec<String>,
+
     /// Verbosity of the EVM.
     ///
     /// Pass multiple times to increase the verbosity (e.g. -v, -vv, -vvv).
@@ -114,6 +142,24 @@ impl Provider for EvmArgs {
             dict.insert("ffi".to_string(), self.ffi.into());
         }
 
+        if self.isolate {
+            dict.insert("isolate".to_string(), self.isolate.into());
+        }
+
+        if !self.differential.is_empty() {
+            let mut references = Dict::new();
+            for reference in self.differential.iter() {
+                let (function, command) = reference.split_once('=').ok_or_else(|| {
+                    format!("Invalid differential reference `{reference}`, expected `<FUNCTION>=<COMMAND>`")
+                })?;
+                references.insert(
+                    function.trim().to_string(),
+                    command.split_whitespace().collect::<Vec<_>>().into(),
+                );
+            }
+            dict.insert("differential".to_string(), references.into());
+        }
+
         if self.no_storage_caching {
             dict.insert("no_storage_caching".to_string(), self.no_storage_caching.into());
         }
@@ -0,0 +1,93 @@
+//! Support for named RPC endpoints
+
+use crate::resolve::{interpolate, UnresolvedEnvVarError};
+use serde::{Deserialize, Serialize};
+use std::{collections::BTreeMap, fmt};
+
+/// Named RPC endpoints, configured in the `[rpc_endpoints]` table:
+///
+/// ```toml
+/// [default.rpc_endpoints]
+/// mainnet = "https://eth-mainnet.alchemyapi.io/v2/${ALCHEMY_API_KEY}"
+/// local = "http://localhost:8545"
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct RpcEndpoints {
+    endpoints: BTreeMap<String, RpcEndpoint>,
+}
+
+impl RpcEndpoints {
+    /// Creates a new set of endpoints from `alias -> url` pairs
+    pub fn new(endpoints: impl IntoIterator<Item = (impl Into<String>, RpcEndpoint)>) -> Self {
+        Self { endpoints: endpoints.into_iter().map(|(alias, url)| (alias.into(), url)).collect() }
+    }
+
+    /// Returns `true` if no endpoints are configured
+    pub fn is_empty(&self) -> bool {
+        self.endpoints.is_empty()
+    }
+
+    /// Returns the endpoint of the given alias, if any
+    pub fn get(&self, alias: &str) -> Option<&RpcEndpoint> {
+        self.endpoints.get(alias)
+    }
+
+    /// Returns an iterator over all `alias -> endpoint` pairs
+    pub fn iter(&self) -> impl Iterator<Item = (&String, &RpcEndpoint)> {
+        self.endpoints.iter()
+    }
+}
+
+/// A single RPC endpoint URL which may contain `${ENV_VAR}` placeholders.
+///
+/// The placeholders are kept as is when the config is serialized, so secrets don't end up in
+/// `forge config` outputs, and are only substituted by [`RpcEndpoint::resolve`].
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct RpcEndpoint(pub String);
+
+impl RpcEndpoint {
+    /// Returns the URL with all `${ENV_VAR}` placeholders replaced by the value of the environment
+    /// variable, see [`interpolate`]
+    pub fn resolve(&self) -> Result<String, UnresolvedEnvVarError> {
+        interpolate(&self.0)
+    }
+}
+
+impl From<String> for RpcEndpoint {
+    fn from(url: String) -> Self {
+        Self(url)
+    }
+}
+
+impl From<&str> for RpcEndpoint {
+    fn from(url: &str) -> Self {
+        Self(url.to_string())
+    }
+}
+
+impl fmt::Display for RpcEndpoint {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn can_resolve_placeholders() {
+        figment::Jail::expect_with(|jail| {
+            jail.set_env("_FOUNDRY_RPC_KEY", "123456");
+            let endpoint = RpcEndpoint::from("https://rpc.example/v2/${_FOUNDRY_RPC_KEY}");
+            assert_eq!(endpoint.resolve().unwrap(), "https://rpc.example/v2/123456");
+
+            let endpoint = RpcEndpoint::from("https://rpc.example/v2/${ _FOUNDRY_MISSING }");
+            let err = endpoint.resolve().unwrap_err();
+            assert_eq!(err.var, "_FOUNDRY_MISSING");
+            Ok(())
+        });
+    }
+}
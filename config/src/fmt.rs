@@ -0,0 +1,57 @@
+//! Configuration of the Solidity formatter, `forge fmt`
+
+use serde::{Deserialize, Serialize};
+
+/// Settings of `forge fmt`:
+///
+/// ```toml
+/// [default.fmt]
+/// line_length = 100
+/// tab_width = 2
+/// bracket_spacing = true
+/// quote_style = "single"
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct FormatterConfig {
+    /// Maximum line length where the formatter will try to wrap the line
+    pub line_length: usize,
+    /// Number of spaces per indentation level
+    pub tab_width: usize,
+    /// Print spaces between brackets
+    pub bracket_spacing: bool,
+    /// The quotes around import paths, other string literals keep the quotes they were written
+    /// with
+    pub quote_style: QuoteStyle,
+}
+
+impl Default for FormatterConfig {
+    fn default() -> Self {
+        Self {
+            line_length: 80,
+            tab_width: 4,
+            bracket_spacing: false,
+            quote_style: QuoteStyle::Double,
+        }
+    }
+}
+
+/// The quotes the formatter uses for strings
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum QuoteStyle {
+    /// `"`
+    Double,
+    /// `'`
+    Single,
+}
+
+impl QuoteStyle {
+    /// Returns the quote character
+    pub fn quote(self) -> char {
+        match self {
+            QuoteStyle::Double => '"',
+            QuoteStyle::Single => '\'',
+        }
+    }
+}
@@ -0,0 +1,92 @@
+//! Support for restricting the filesystem access of cheatcodes
+
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+
+/// The paths cheatcodes like `readFile` and `writeFile` may access, configured as a list of
+/// `fs_permissions` entries:
+///
+/// ```toml
+/// [default]
+/// fs_permissions = [{ access = "read", path = "./fixtures" }, { access = "read-write", path = "./out" }]
+/// ```
+///
+/// No path can be accessed unless it is listed.
+#[derive(Debug, Clone, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct FsPermissions {
+    permissions: Vec<PathPermission>,
+}
+
+impl FsPermissions {
+    /// Creates a new set of permissions
+    pub fn new(permissions: impl IntoIterator<Item = PathPermission>) -> Self {
+        Self { permissions: permissions.into_iter().collect() }
+    }
+
+    /// Returns `true` if `path` may be accessed with the given kind of access.
+    ///
+    /// The `path` is expected to be absolute and normalized, as are the paths of a canonicalized
+    /// [`crate::Config`]
+    pub fn is_allowed(&self, path: &Path, access: FsAccess) -> bool {
+        self.permissions.iter().any(|permission| {
+            permission.access.allows(access) && path.starts_with(&permission.path)
+        })
+    }
+
+    /// Joins all relative paths with `root` and normalizes them
+    pub fn join_all(&mut self, root: impl AsRef<Path>) {
+        for permission in &mut self.permissions {
+            permission.path = crate::canonic(root.as_ref().join(&permission.path));
+        }
+    }
+}
+
+/// Grants access to a file or to everything inside a directory
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PathPermission {
+    pub access: FsAccess,
+    pub path: PathBuf,
+}
+
+impl PathPermission {
+    pub fn new(access: FsAccess, path: impl Into<PathBuf>) -> Self {
+        Self { access, path: path.into() }
+    }
+}
+
+/// The kind of filesystem access
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum FsAccess {
+    Read,
+    Write,
+    ReadWrite,
+}
+
+impl FsAccess {
+    /// Returns `true` if this access grants the `requested` access
+    pub fn allows(&self, requested: FsAccess) -> bool {
+        *self == FsAccess::ReadWrite || *self == requested
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn can_check_permissions() {
+        let permissions = FsPermissions::new([
+            PathPermission::new(FsAccess::Read, "/project/fixtures"),
+            PathPermission::new(FsAccess::ReadWrite, "/project/out"),
+        ]);
+
+        assert!(permissions.is_allowed(Path::new("/project/fixtures/a.json"), FsAccess::Read));
+        assert!(!permissions.is_allowed(Path::new("/project/fixtures/a.json"), FsAccess::Write));
+        assert!(permissions.is_allowed(Path::new("/project/out/b.txt"), FsAccess::Write));
+        assert!(!permissions.is_allowed(Path::new("/project/src/C.sol"), FsAccess::Read));
+        assert!(!permissions.is_allowed(Path::new("/project/fixtures-2/a.json"), FsAccess::Read));
+        assert!(!FsPermissions::default().is_allowed(Path::new("/"), FsAccess::Read));
+    }
+}
@@ -3,6 +3,7 @@
 
 use std::{
     borrow::Cow,
+    collections::BTreeMap,
     path::{Path, PathBuf},
     str::FromStr,
 };
@@ -22,7 +23,7 @@ use ethers_solc::{
 };
 use eyre::{ContextCompat, WrapErr};
 use figment::{
-    providers::{Data, Env, Format, Serialized, Toml},
+    providers::{Env, Format, Serialized, Toml},
     value::{Dict, Map, Value},
     Error, Figment, Metadata, Profile, Provider,
 };
@@ -41,6 +42,21 @@ pub mod caching;
 mod chain;
 pub use chain::Chain;
 
+mod endpoints;
+pub use endpoints::{RpcEndpoint, RpcEndpoints};
+
+mod resolve;
+pub use resolve::{interpolate, UnresolvedEnvVarError};
+
+mod fs_permissions;
+pub use fs_permissions::{FsAccess, FsPermissions, PathPermission};
+
+mod sandbox;
+pub use sandbox::{CheatcodeGroup, Sandbox};
+
+pub mod fmt;
+pub use fmt::{FormatterConfig, QuoteStyle};
+
 // reexport so cli types can implement `figment::Provider` to easily merge compiler arguments
 pub use figment;
 use regex::Regex;
@@ -125,7 +141,17 @@ pub struct Config {
     ///    - if `auto_detect_solc = true` and `offline = true`, the required solc version(s) will
     ///      be auto detected but if the solc version is not installed, it will _not_ try to
     ///      install it
+    ///    - if `solc_version` is set and `offline = true`, the build fails if that version is not
+    ///      installed
     pub offline: bool,
+    /// The maximum number of solc processes that compile different compiler versions in
+    /// parallel, defaults to the number of CPUs
+    pub solc_jobs: Option<usize>,
+    /// The `vyper` binary that compiles the project's `.vy` sources, defaults to `vyper` on the
+    /// `PATH`. Vyper is only required if the project has Vyper sources.
+    pub vyper: Option<PathBuf>,
+    /// Whether to optimize the Vyper sources
+    pub vyper_optimize: bool,
     /// Whether to activate optimizer
     pub optimizer: bool,
     /// Sets the optimizer runs
@@ -136,9 +162,12 @@ pub struct Config {
     pub optimizer_details: Option<OptimizerDetails>,
     /// verbosity to use
     pub verbosity: u8,
-    /// url of the rpc server that should be used for any rpc calls
+    /// url of the rpc server that should be used for any rpc calls, see
+    /// [`Config::get_rpc_url_or_localhost_http`]
     pub eth_rpc_url: Option<String>,
-    /// etherscan API key
+    /// named rpc endpoints that can be used instead of a url, see [`Config::get_rpc_url`]
+    pub rpc_endpoints: RpcEndpoints,
+    /// etherscan API key, see [`Config::get_etherscan_api_key`]
     pub etherscan_api_key: Option<String>,
     /// list of solidity error codes to always silence in the compiler output
     pub ignored_error_codes: Vec<SolidityErrorCode>,
@@ -164,6 +193,11 @@ pub struct Config {
     pub fuzz_runs: u32,
     /// Whether to allow ffi cheatcodes in test
     pub ffi: bool,
+    /// The paths the `readFile`, `writeFile` and `loadAllocs` cheatcodes and `getCode` with a JSON
+    /// artifact path may access, see [`FsPermissions`]
+    pub fs_permissions: FsPermissions,
+    /// The cheatcodes tests in third-party sources can not use, see [`Sandbox`]
+    pub sandbox: Sandbox,
     /// The address which will be executing all tests
     pub sender: Address,
     /// The tx.origin value during EVM execution
@@ -174,6 +208,28 @@ pub struct Config {
     pub block_number: u64,
     /// pins the block number for the state fork
     pub fork_block_number: Option<u64>,
+    /// The milliseconds to wait before retrying requests the fork endpoint rejected because of
+    /// its rate limit, doubled with every retry
+    pub fork_retry_backoff: Option<u64>,
+    /// A JSON file of account allocations, in the format of a geth genesis `alloc`, that is
+    /// loaded into the state of every test contract before it is deployed
+    pub allocs: Option<PathBuf>,
+    /// Whether contracts created in tests are moved to deterministic, incrementing addresses
+    /// (`0x...10001`, `0x...10002`, ...) instead of their nonce-derived addresses
+    pub deterministic_addresses: bool,
+    /// Whether every call made by a test contract is executed as a transaction of its own, which
+    /// starts with cold accounts and storage slots and pays the intrinsic gas of a transaction
+    pub isolate: bool,
+    /// Whether `prank` and `etch` fail if they are used with a reserved address: the cheatcode
+    /// address, the console address or a precompile
+    pub deny_reserved_addresses: bool,
+    /// The commands that are the reference implementations of the functions that are fuzzed
+    /// differentially, by function name.
+    ///
+    /// A command is run with the ABI-encoded inputs as its last argument and prints the
+    /// ABI-encoded outputs, like the commands of the `ffi` cheatcode. Like these, the commands
+    /// are only run if `ffi` is enabled and the test is not denied `ffi` by the `sandbox`.
+    pub differential: BTreeMap<String, Vec<String>>,
     /// the chainid opcode value
     pub chain_id: Option<Chain>,
     /// Block gas limit
@@ -252,6 +308,8 @@ pub struct Config {
     /// included in solc's output selection, see also
     /// [OutputSelection](ethers_solc::artifacts::output_selection::OutputSelection)
     pub sparse_mode: bool,
+    /// The settings of `forge fmt`, see [`FormatterConfig`]
+    pub fmt: FormatterConfig,
     /// The root path where the config detection started from, `Config::with_root`
     #[doc(hidden)]
     //  We're skipping serialization here, so it won't be included in the [`Config::to_string()`]
@@ -282,6 +340,9 @@ impl Config {
     /// The hardhat profile: "hardhat"
     pub const HARDHAT_PROFILE: Profile = Profile::const_new("hardhat");
 
+    /// The toml table that contains all profiles: `[profile.<name>]`
+    pub const PROFILE_SECTION: &'static str = "profile";
+
     /// File name of config toml file
     pub const FILE_NAME: &'static str = "foundry.toml";
 
@@ -393,6 +454,8 @@ impl Config {
             self.remappings.into_iter().map(|r| RelativeRemapping::new(r.into(), &root)).collect();
 
         self.cache_path = p(&root, &self.cache_path);
+        self.allocs = self.allocs.map(|allocs| p(&root, &allocs));
+        self.fs_permissions.join_all(&root);
 
         self
     }
@@ -439,7 +502,7 @@ impl Config {
     }
 
     fn create_project(&self, cached: bool, no_artifacts: bool) -> Result<Project, SolcError> {
-        let mut project = Project::builder()
+        let mut builder = Project::builder()
             .artifacts(self.configured_artifacts_handler())
             .paths(self.project_paths())
             .allowed_path(&self.__root.0)
@@ -449,8 +512,11 @@ impl Config {
             .set_auto_detect(self.is_auto_detect())
             .set_offline(self.offline)
             .set_cached(cached)
-            .set_no_artifacts(no_artifacts)
-            .build()?;
+            .set_no_artifacts(no_artifacts);
+        if let Some(jobs) = self.solc_jobs {
+            builder = builder.solc_jobs(jobs.max(1));
+        }
+        let mut project = builder.build()?;
 
         if self.force {
             project.cleanup()?;
@@ -476,6 +542,12 @@ impl Config {
                     let v = version.to_string();
                     let mut solc = Solc::find_svm_installed_version(&v)?;
                     if solc.is_none() {
+                        if self.offline {
+                            return Err(SolcError::msg(format!(
+                                "can't install missing solc {} in offline mode",
+                                version
+                            )))
+                        }
                         Solc::blocking_install(version)?;
                         solc = Solc::find_svm_installed_version(&v)?;
                     }
@@ -732,6 +804,10 @@ impl Config {
                 );
         }
         s = s.replace("[rpc_storage_caching]", &format!("[{}.rpc_storage_caching]", self.profile));
+        s = s.replace("[rpc_endpoints]", &format!("[{}.rpc_endpoints]", self.profile));
+        s = s.replace("[[fs_permissions]]", &format!("[[{}.fs_permissions]]", self.profile));
+        s = s.replace("[sandbox]", &format!("[{}.sandbox]", self.profile));
+        s = s.replace("[fmt]", &format!("[{}.fmt]", self.profile));
 
         Ok(format!(
             r#"[{}]
@@ -740,6 +816,48 @@ impl Config {
         ))
     }
 
+    /// Returns the url of the `[rpc_endpoints]` entry with the given alias, with all `${ENV_VAR}`
+    /// placeholders resolved.
+    ///
+    /// Returns `None` if there is no endpoint with that alias.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use foundry_config::Config;
+    /// # fn t() {
+    /// let config = Config::load();
+    /// let url = config.get_rpc_url("mainnet");
+    /// # }
+    /// ```
+    pub fn get_rpc_url(
+        &self,
+        alias: impl AsRef<str>,
+    ) -> Option<Result<String, UnresolvedEnvVarError>> {
+        self.rpc_endpoints.get(alias.as_ref()).map(RpcEndpoint::resolve)
+    }
+
+    /// Returns the url of the endpoint if `url_or_alias` is an alias in `[rpc_endpoints]`,
+    /// otherwise `url_or_alias` is returned with its `${ENV_VAR}` placeholders resolved.
+    ///
+    /// Placeholders are only resolved when the value is used, so that their secrets don't end up in
+    /// the serialized config.
+    pub fn resolve_rpc_url(&self, url_or_alias: &str) -> Result<String, UnresolvedEnvVarError> {
+        self.get_rpc_url(url_or_alias).unwrap_or_else(|| interpolate(url_or_alias))
+    }
+
+    /// Returns the `eth_rpc_url`, or `http://localhost:8545` if it is not set, resolved with
+    /// [`Config::resolve_rpc_url`]
+    pub fn get_rpc_url_or_localhost_http(&self) -> Result<String, UnresolvedEnvVarError> {
+        self.resolve_rpc_url(self.eth_rpc_url.as_deref().unwrap_or("http://localhost:8545"))
+    }
+
+    /// Returns the `etherscan_api_key` with its `${ENV_VAR}` placeholders resolved, see
+    /// [`interpolate`]
+    pub fn get_etherscan_api_key(&self) -> Result<Option<String>, UnresolvedEnvVarError> {
+        self.etherscan_api_key.as_deref().map(interpolate).transpose()
+    }
+
     /// Returns the selected profile
     ///
     /// If the `FOUNDRY_PROFILE` env variable is not set, this returns the `DEFAULT_PROFILE`
@@ -767,6 +885,11 @@ impl Config {
         Some(Self::foundry_cache_dir()?.join(chain_id.into().to_string()).join("etherscan"))
     }
 
+    /// Returns the path to foundry's local signatures cache `~/.foundry/cache/signatures`
+    pub fn foundry_signatures_cache_file() -> Option<PathBuf> {
+        Some(Self::foundry_cache_dir()?.join("signatures"))
+    }
+
     /// Returns the path to the cache file of the `block` on the `chain`
     /// `~/.foundry/cache/<chain>/<block>/storage.json`
     pub fn foundry_block_cache_file(chain_id: impl Into<Chain>, block: u64) -> Option<PathBuf> {
@@ -828,14 +951,14 @@ impl From<Config> for Figment {
         // check global foundry.toml file
         if let Some(global_toml) = Config::foundry_dir_toml().filter(|p| p.exists()) {
             figment = figment.merge(BackwardsCompatProvider(ForcedSnakeCaseData(
-                Toml::file(global_toml).nested(),
+                ProfileSectionProvider(Toml::file(global_toml).nested()),
             )))
         }
 
         figment = figment
-            .merge(BackwardsCompatProvider(ForcedSnakeCaseData(
+            .merge(BackwardsCompatProvider(ForcedSnakeCaseData(ProfileSectionProvider(
                 Toml::file(Env::var_or("FOUNDRY_CONFIG", Config::FILE_NAME)).nested(),
-            )))
+            ))))
             .merge(Env::prefixed("DAPP_").ignore(&["REMAPPINGS", "LIBRARIES"]).global())
             .merge(Env::prefixed("DAPP_TEST_").ignore(&["CACHE"]).global())
             .merge(DappEnvCompatProvider)
@@ -992,6 +1115,9 @@ impl Default for Config {
             solc: None,
             auto_detect_solc: true,
             offline: false,
+            solc_jobs: None,
+            vyper: None,
+            vyper_optimize: true,
             optimizer: true,
             optimizer_runs: 200,
             optimizer_details: None,
@@ -1009,11 +1135,19 @@ impl Default for Config {
             fuzz_max_local_rejects: 1024,
             fuzz_max_global_rejects: 65536,
             ffi: false,
+            fs_permissions: Default::default(),
+            sandbox: Default::default(),
             sender: "00a329c0648769A73afAc7F9381E08FB43dBEA72".parse().unwrap(),
             tx_origin: "00a329c0648769A73afAc7F9381E08FB43dBEA72".parse().unwrap(),
             initial_balance: U256::from(0xffffffffffffffffffffffffu128),
             block_number: 0,
             fork_block_number: None,
+            fork_retry_backoff: None,
+            allocs: None,
+            deterministic_addresses: false,
+            isolate: false,
+            deny_reserved_addresses: false,
+            differential: Default::default(),
             chain_id: None,
             gas_limit: i64::MAX.into(),
             gas_price: 0,
@@ -1024,6 +1158,7 @@ impl Default for Config {
             block_gas_limit: None,
             memory_limit: 2u64.pow(25),
             eth_rpc_url: None,
+            rpc_endpoints: Default::default(),
             etherscan_api_key: None,
             verbosity: 0,
             remappings: vec![],
@@ -1036,6 +1171,7 @@ impl Default for Config {
             bytecode_hash: BytecodeHash::Ipfs,
             revert_strings: None,
             sparse_mode: false,
+            fmt: Default::default(),
         }
     }
 }
@@ -1177,10 +1313,45 @@ impl<T: AsRef<str>> From<T> for SolcReq {
     }
 }
 
+/// A Provider that unwraps profiles declared as `[profile.<name>]` tables.
+///
+/// A `[profile.ci]` table is treated the same way as a top-level `[ci]` table, if both exist the
+/// values of `[profile.ci]` take precedence.
+struct ProfileSectionProvider<P>(P);
+
+impl<P: Provider> Provider for ProfileSectionProvider<P> {
+    fn metadata(&self) -> Metadata {
+        self.0.metadata()
+    }
+
+    fn data(&self) -> Result<Map<Profile, Dict>, Error> {
+        let mut data = self.0.data()?;
+        let section = Profile::new(Config::PROFILE_SECTION);
+        if let Some(profiles) = data.remove(&section) {
+            for (name, value) in profiles {
+                let dict = match value {
+                    Value::Dict(_, dict) => dict,
+                    _ => {
+                        return Err(format!(
+                            "Invalid profile `{}`: `[{}.{}]` must be a table",
+                            name,
+                            Config::PROFILE_SECTION,
+                            name
+                        )
+                        .into())
+                    }
+                };
+                data.entry(Profile::new(&name)).or_default().extend(dict);
+            }
+        }
+        Ok(data)
+    }
+}
+
 /// A Provider that ensures all keys are snake case
-struct ForcedSnakeCaseData<F: Format>(Data<F>);
+struct ForcedSnakeCaseData<P>(P);
 
-impl<F: Format> Provider for ForcedSnakeCaseData<F> {
+impl<P: Provider> Provider for ForcedSnakeCaseData<P> {
     fn metadata(&self) -> Metadata {
         Metadata::named("Snake Case toml provider")
     }
@@ -1391,6 +1562,12 @@ impl<'a> RemappingsProvider<'a> {
         new_remappings.sort_by(|a, b| a.name.cmp(&b.name));
         new_remappings.dedup_by(|a, b| a.name.eq(&b.name));
 
+        // resolve `${ENV_VAR}` placeholders in the paths
+        for remapping in &mut new_remappings {
+            remapping.path =
+                interpolate(&remapping.path).map_err::<Error, _>(|err| err.to_string().into())?;
+        }
+
         Ok(new_remappings)
     }
 }
@@ -1562,6 +1739,176 @@ mod tests {
         });
     }
 
+    #[test]
+    fn test_profile_section() {
+        figment::Jail::expect_with(|jail| {
+            jail.create_file(
+                "foundry.toml",
+                r#"
+                [profile.default]
+                libs = ['lib']
+                optimizer_runs = 1000
+
+                [profile.ci]
+                fuzz_runs = 10000
+                libs = ['modules']
+            "#,
+            )?;
+            let config = Config::load();
+            assert_eq!(config.libs, vec![PathBuf::from("lib")]);
+            assert_eq!(config.fuzz_runs, Config::default().fuzz_runs);
+
+            jail.set_env("FOUNDRY_PROFILE", "ci");
+            let config = Config::load();
+            assert_eq!(config.profile, Profile::new("ci"));
+            assert_eq!(config.libs, vec![PathBuf::from("modules")]);
+            assert_eq!(config.fuzz_runs, 10000);
+            // values of the default profile are inherited
+            assert_eq!(config.optimizer_runs, 1000);
+
+            Ok(())
+        });
+    }
+
+    #[test]
+    fn test_rpc_endpoints() {
+        figment::Jail::expect_with(|jail| {
+            jail.create_file(
+                "foundry.toml",
+                r#"
+                [default]
+                eth_rpc_url = "mainnet"
+
+                [default.rpc_endpoints]
+                mainnet = "https://eth-mainnet.alchemyapi.io/v2/${_CONFIG_API_KEY}"
+                local = "http://localhost:8545"
+            "#,
+            )?;
+            let config = Config::load();
+            assert_eq!(config.get_rpc_url("local").unwrap().unwrap(), "http://localhost:8545");
+            assert!(config.get_rpc_url("mainnet").unwrap().is_err());
+            assert!(config.get_rpc_url("goerli").is_none());
+
+            jail.set_env("_CONFIG_API_KEY", "123456");
+            assert_eq!(
+                config.resolve_rpc_url(config.eth_rpc_url.as_ref().unwrap()).unwrap(),
+                "https://eth-mainnet.alchemyapi.io/v2/123456"
+            );
+            assert_eq!(
+                config.resolve_rpc_url("http://127.0.0.1:8545").unwrap(),
+                "http://127.0.0.1:8545"
+            );
+
+            // placeholders are not resolved when serializing the config
+            assert!(config.to_string_pretty().unwrap().contains("${_CONFIG_API_KEY}"));
+
+            Ok(())
+        });
+    }
+
+    #[test]
+    fn test_resolve_env_vars() {
+        figment::Jail::expect_with(|jail| {
+            jail.create_file(
+                "foundry.toml",
+                r#"
+                [default]
+                eth_rpc_url = "${_CONFIG_RPC_URL:-http://localhost:8545}"
+                etherscan_api_key = "${_CONFIG_ETHERSCAN_KEY}"
+                remappings = ["ds-test/=${_CONFIG_LIB_DIR}/ds-test/src/"]
+            "#,
+            )?;
+            jail.set_env("_CONFIG_ETHERSCAN_KEY", "ABCDEF");
+            jail.set_env("_CONFIG_LIB_DIR", "modules");
+
+            let config = Config::load();
+            assert_eq!(config.get_rpc_url_or_localhost_http().unwrap(), "http://localhost:8545");
+            assert_eq!(config.get_etherscan_api_key().unwrap(), Some("ABCDEF".to_string()));
+            assert_eq!(
+                config.remappings,
+                vec![Remapping::from_str("ds-test/=modules/ds-test/src/").unwrap().into()]
+            );
+
+            // placeholders are not resolved when serializing the config
+            assert!(config.to_string_pretty().unwrap().contains("${_CONFIG_ETHERSCAN_KEY}"));
+
+            jail.set_env("_CONFIG_RPC_URL", "https://rpc.example");
+            assert_eq!(
+                Config::load().get_rpc_url_or_localhost_http().unwrap(),
+                "https://rpc.example"
+            );
+
+            jail.create_file(
+                "foundry.toml",
+                r#"
+                [default]
+                etherscan_api_key = "${_CONFIG_MISSING_KEY}"
+            "#,
+            )?;
+            // a missing variable only fails when the value is used
+            let err = Config::load().get_etherscan_api_key().unwrap_err();
+            assert!(err.to_string().contains("_CONFIG_MISSING_KEY"));
+
+            Ok(())
+        });
+    }
+
+    #[test]
+    fn test_fs_permissions() {
+        figment::Jail::expect_with(|jail| {
+            jail.create_file(
+                "foundry.toml",
+                r#"
+                [default]
+                fs_permissions = [{ access = "read", path = "./fixtures" }, { access = "read-write", path = "/tmp/out" }]
+            "#,
+            )?;
+            let config = Config::load().canonic();
+            let root = canonic(jail.directory());
+            assert_eq!(
+                config.fs_permissions,
+                FsPermissions::new([
+                    PathPermission::new(FsAccess::Read, root.join("fixtures")),
+                    PathPermission::new(FsAccess::ReadWrite, "/tmp/out"),
+                ])
+            );
+            assert!(config
+                .fs_permissions
+                .is_allowed(&root.join("fixtures/a.json"), FsAccess::Read));
+            assert!(!config.fs_permissions.is_allowed(&root.join("src/A.sol"), FsAccess::Read));
+
+            Ok(())
+        });
+    }
+
+    #[test]
+    fn test_invalid_profile_section() {
+        figment::Jail::expect_with(|jail| {
+            jail.create_file(
+                "foundry.toml",
+                r#"
+                [profile]
+                ci = 1
+            "#,
+            )?;
+            let err = Config::try_from(Config::figment()).unwrap_err();
+            assert!(err.to_string().contains("`[profile.ci]`"), "{}", err);
+
+            jail.create_file(
+                "foundry.toml",
+                r#"
+                [profile.ci]
+                fuzz_runs = "many"
+            "#,
+            )?;
+            jail.set_env("FOUNDRY_PROFILE", "ci");
+            let err = Config::try_from(Config::figment()).unwrap_err();
+            assert!(err.to_string().contains("fuzz_runs"), "{}", err);
+
+            Ok(())
+        });
+    }
+
     #[test]
     fn test_default_test_path() {
         figment::Jail::expect_with(|_| {
@@ -1819,6 +2166,26 @@ mod tests {
         });
     }
 
+    #[test]
+    fn test_offline_does_not_install_pinned_solc() {
+        figment::Jail::expect_with(|jail| {
+            // a version that is never installed
+            jail.create_file(
+                "foundry.toml",
+                r#"
+                [default]
+                solc_version = "0.0.1"
+                offline = true
+            "#,
+            )?;
+
+            let config = Config::load();
+            let err = config.ensure_solc().unwrap_err();
+            assert_eq!(err.to_string(), "can't install missing solc 0.0.1 in offline mode");
+            Ok(())
+        });
+    }
+
     #[test]
     fn test_toml_casing_file() {
         figment::Jail::expect_with(|jail| {
@@ -0,0 +1,81 @@
+//! Interpolation of `${ENV_VAR}` placeholders in config values
+
+use once_cell::sync::Lazy;
+use regex::{Captures, Regex};
+use std::fmt;
+
+/// A `${ENV_VAR}` or `${ENV_VAR:-default}` placeholder
+static PLACEHOLDER: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(r"\$\{\s*(?P<var>[[:alnum:]_]+)\s*(?::-(?P<default>[^}]*))?\}").unwrap()
+});
+
+/// Replaces all `${ENV_VAR}` placeholders in `input` with the value of the environment variable.
+///
+/// Like in shells, `${ENV_VAR:-default}` falls back to `default` if the variable is not set or
+/// empty. A placeholder without a default for a variable that is not set is an error.
+pub fn interpolate(input: &str) -> Result<String, UnresolvedEnvVarError> {
+    let mut unresolved = None;
+    let resolved = PLACEHOLDER.replace_all(input, |caps: &Captures| {
+        let var = &caps["var"];
+        match (std::env::var(var), caps.name("default")) {
+            (Ok(value), _) if !value.is_empty() => value,
+            (_, Some(default)) => default.as_str().to_string(),
+            (Ok(value), None) => value,
+            (Err(_), None) => {
+                unresolved.get_or_insert_with(|| var.to_string());
+                String::new()
+            }
+        }
+    });
+    match unresolved {
+        Some(var) => Err(UnresolvedEnvVarError { unresolved: input.to_string(), var }),
+        None => Ok(resolved.into_owned()),
+    }
+}
+
+/// Error returned when an `${ENV_VAR}` placeholder references a variable that is not set
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct UnresolvedEnvVarError {
+    /// The unresolved input string
+    pub unresolved: String,
+    /// The environment variable that is not set
+    pub var: String,
+}
+
+impl fmt::Display for UnresolvedEnvVarError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "Failed to resolve env var `{}` in `{}`: the variable is not set",
+            self.var, self.unresolved
+        )
+    }
+}
+
+impl std::error::Error for UnresolvedEnvVarError {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn can_interpolate_with_defaults() {
+        figment::Jail::expect_with(|jail| {
+            jail.set_env("_FOUNDRY_SET", "set");
+            jail.set_env("_FOUNDRY_EMPTY", "");
+
+            assert_eq!(interpolate("${_FOUNDRY_SET:-default}").unwrap(), "set");
+            assert_eq!(interpolate("${_FOUNDRY_EMPTY:-default}").unwrap(), "default");
+            assert_eq!(interpolate("${_FOUNDRY_EMPTY}").unwrap(), "");
+            assert_eq!(
+                interpolate("http://${_FOUNDRY_UNSET:-localhost:8545}/${_FOUNDRY_SET}").unwrap(),
+                "http://localhost:8545/set"
+            );
+            assert_eq!(interpolate("no placeholders").unwrap(), "no placeholders");
+
+            let err = interpolate("a/${_FOUNDRY_UNSET}/${_FOUNDRY_OTHER_UNSET}").unwrap_err();
+            assert_eq!(err.var, "_FOUNDRY_UNSET");
+            Ok(())
+        });
+    }
+}
@@ -0,0 +1,109 @@
+//! Support for restricting the cheatcodes of third-party tests
+
+use serde::{Deserialize, Serialize};
+use std::{
+    fmt,
+    path::{Path, PathBuf},
+};
+
+/// Restricts the cheatcodes available to tests whose sources live in certain directories, e.g.
+/// the tests of dependencies:
+///
+/// ```toml
+/// [default.sandbox]
+/// paths = ["lib"]
+/// allow = ["lib/trusted"]
+/// deny = ["ffi", "fs", "env"]
+/// ```
+///
+/// Paths are relative to the project root. No test is sandboxed by default.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Sandbox {
+    /// Tests whose sources are in these directories are sandboxed
+    pub paths: Vec<PathBuf>,
+    /// Tests whose sources are in these directories are not sandboxed, even if they are inside
+    /// one of the sandboxed `paths`
+    pub allow: Vec<PathBuf>,
+    /// The cheatcodes sandboxed tests can not use
+    pub deny: Vec<CheatcodeGroup>,
+}
+
+impl Default for Sandbox {
+    fn default() -> Self {
+        Self {
+            paths: Vec::new(),
+            allow: Vec::new(),
+            deny: vec![CheatcodeGroup::Ffi, CheatcodeGroup::Fs, CheatcodeGroup::Env],
+        }
+    }
+}
+
+impl Sandbox {
+    /// Returns `true` if tests in the source file at `path`, relative to the project root, are
+    /// sandboxed
+    pub fn is_sandboxed(&self, path: impl AsRef<Path>) -> bool {
+        let path = normalize(path.as_ref());
+        let contains = |dir: &PathBuf| path.starts_with(normalize(dir));
+        self.paths.iter().any(contains) && !self.allow.iter().any(contains)
+    }
+
+    /// Returns the cheatcodes that tests in the source file at `path` can not use
+    pub fn denied(&self, path: impl AsRef<Path>) -> Vec<CheatcodeGroup> {
+        if self.is_sandboxed(path) {
+            self.deny.clone()
+        } else {
+            Vec::new()
+        }
+    }
+}
+
+/// Strips a leading `./`
+fn normalize(path: &Path) -> &Path {
+    path.strip_prefix(".").unwrap_or(path)
+}
+
+/// Cheatcodes that interact with the machine running the tests
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum CheatcodeGroup {
+    /// `ffi`
+    Ffi,
+    /// `readFile`, `writeFile`, `getCode`, `loadAllocs` and `tempDir`
+    Fs,
+    /// `envUint`, `envOr` and the other environment variable cheatcodes
+    Env,
+}
+
+impl fmt::Display for CheatcodeGroup {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            CheatcodeGroup::Ffi => f.write_str("ffi"),
+            CheatcodeGroup::Fs => f.write_str("fs"),
+            CheatcodeGroup::Env => f.write_str("env"),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn can_match_sandboxed_paths() {
+        let sandbox = Sandbox {
+            paths: vec!["lib".into()],
+            allow: vec!["./lib/trusted".into()],
+            ..Default::default()
+        };
+
+        assert!(sandbox.is_sandboxed("lib/dep/test/Dep.t.sol"));
+        assert!(sandbox.is_sandboxed("./lib/dep/test/Dep.t.sol"));
+        assert!(!sandbox.is_sandboxed("lib/trusted/test/Trusted.t.sol"));
+        assert!(!sandbox.is_sandboxed("test/Project.t.sol"));
+        assert!(!sandbox.is_sandboxed("library/Lib.t.sol"));
+        assert!(!Sandbox::default().is_sandboxed("lib/dep/test/Dep.t.sol"));
+        assert_eq!(sandbox.denied("test/Project.t.sol"), vec![]);
+        assert_eq!(sandbox.denied("lib/dep/test/Dep.t.sol").len(), 3);
+    }
+}
@@ -1,7 +1,7 @@
 use crate::{abi::HEVM_ABI, CallKind};
 use ethers::types::{Address, U256};
-use revm::{Memory, OpCode};
-use std::fmt::Display;
+use revm::{opcode, Memory, OpCode};
+use std::{collections::BTreeMap, fmt::Display};
 
 /// An arena of [DebugNode]s
 #[derive(Default, Debug, Clone)]
@@ -64,18 +64,103 @@ impl DebugArena {
     ///
     /// This makes it easy to pretty print the execution steps.
     pub fn flatten(&self, entry: usize) -> Vec<(Address, Vec<DebugStep>, CallKind)> {
+        self.flattened_nodes(entry)
+            .into_iter()
+            .map(|idx| {
+                let node = &self.arena[idx];
+                (node.address, node.steps.clone(), node.kind)
+            })
+            .collect()
+    }
+
+    /// Returns the location of the revert that ended the execution starting at `entry`, as the
+    /// index of the call in the [flattened](Self::flatten) arena and the index of the step in that
+    /// call.
+    ///
+    /// A revert that bubbled up through its callers is traced back to the call it originated in.
+    /// Returns `None` if the execution did not revert.
+    pub fn revert_location(&self, entry: usize) -> Option<(usize, usize)> {
+        let nodes = self.flattened_nodes(entry);
+        let reverted = |node: &DebugNode| {
+            node.steps.last().map_or(false, |step| {
+                matches!(
+                    step.instruction,
+                    Instruction::OpCode(op) if op == opcode::REVERT || op == opcode::INVALID
+                )
+            })
+        };
+
+        let mut call = nodes.len().checked_sub(1)?;
+        if !reverted(&self.arena[nodes[call]]) {
+            return None
+        }
+        // The caller continues in a new node right after the call returned, so a revert that is
+        // bubbled up is preceded by the revert of a call one level deeper
+        while call > 0 {
+            let (prev, current) = (&self.arena[nodes[call - 1]], &self.arena[nodes[call]]);
+            if prev.depth == current.depth + 1 && reverted(prev) {
+                call -= 1;
+            } else {
+                break
+            }
+        }
+        Some((call, self.arena[nodes[call]].steps.len() - 1))
+    }
+
+    /// Returns the indices of the nodes with steps in the order they were executed
+    fn flattened_nodes(&self, entry: usize) -> Vec<usize> {
         let node = &self.arena[entry];
 
         let mut flattened = vec![];
         if !node.steps.is_empty() {
-            flattened.push((node.address, node.steps.clone(), node.kind));
+            flattened.push(entry);
         }
-        flattened.extend(node.children.iter().flat_map(|child| self.flatten(*child)));
+        flattened.extend(node.children.iter().flat_map(|child| self.flattened_nodes(*child)));
 
         flattened
     }
 }
 
+/// Replays the storage accesses in the [flattened](DebugArena::flatten) calls up to the given step
+/// of the given call, returning the last value that was read from or written to each slot of the
+/// contract executing that call.
+///
+/// Only slots that were accessed are known, and since the calls are keyed by the address of the
+/// code, storage accessed through a `DELEGATECALL` is attributed to the library.
+pub fn storage_at(
+    calls: &[(Address, Vec<DebugStep>, CallKind)],
+    call: usize,
+    step: usize,
+) -> BTreeMap<U256, U256> {
+    let address = calls[call].0;
+    let mut storage = BTreeMap::new();
+    for (idx, (_, steps, _)) in calls[..=call].iter().enumerate().filter(|(_, c)| c.0 == address) {
+        // the current step and the steps after it have not been executed yet
+        let executed = if idx == call { &steps[..step] } else { &steps[..] };
+        for (i, current) in executed.iter().enumerate() {
+            let slot = match current.stack.last() {
+                Some(slot) => *slot,
+                None => continue,
+            };
+            match current.instruction {
+                Instruction::OpCode(opcode::SSTORE) => {
+                    if let Some(value) = current.stack.iter().rev().nth(1) {
+                        storage.insert(slot, *value);
+                    }
+                }
+                // the loaded value is on top of the stack of the next step
+                Instruction::OpCode(opcode::SLOAD) => {
+                    if let Some(value) = steps.get(i + 1).and_then(|next| next.stack.last()) {
+                        storage.insert(slot, *value);
+                    }
+                }
+                _ => {}
+            }
+        }
+    }
+    storage
+}
+
 /// A node in the arena
 #[derive(Default, Debug, Clone)]
 pub struct DebugNode {
@@ -132,7 +217,7 @@ impl Default for DebugStep {
             pc: 0,
             stack: vec![],
             memory: Memory::new(),
-            instruction: Instruction::OpCode(revm::opcode::INVALID),
+            instruction: Instruction::OpCode(opcode::INVALID),
             push_bytes: None,
             ic: 0,
             total_gas_used: 0,
@@ -187,3 +272,72 @@ impl Display for Instruction {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn node(depth: usize, ops: &[u8]) -> DebugNode {
+        let steps = ops
+            .iter()
+            .map(|op| DebugStep { instruction: Instruction::OpCode(*op), ..Default::default() })
+            .collect();
+        DebugNode::new(Address::zero(), depth, steps)
+    }
+
+    #[test]
+    fn can_find_origin_of_bubbled_up_revert() {
+        let mut arena = DebugArena::default();
+        arena.push_node(node(0, &[opcode::PUSH1, opcode::CALL]));
+        // a reverted call that was caught
+        arena.push_node(node(1, &[opcode::PUSH1, opcode::REVERT]));
+        arena.push_node(node(0, &[opcode::POP, opcode::CALL]));
+        arena.push_node(node(1, &[opcode::CALL]));
+        arena.push_node(node(2, &[opcode::PUSH1, opcode::PUSH1, opcode::REVERT]));
+        arena.push_node(node(1, &[opcode::RETURNDATACOPY, opcode::REVERT]));
+        arena.push_node(node(0, &[opcode::RETURNDATACOPY, opcode::REVERT]));
+        assert_eq!(arena.revert_location(0), Some((4, 2)));
+
+        let mut arena = DebugArena::default();
+        arena.push_node(node(0, &[opcode::PUSH1, opcode::CALL]));
+        arena.push_node(node(1, &[opcode::PUSH1, opcode::REVERT]));
+        arena.push_node(node(0, &[opcode::POP, opcode::STOP]));
+        assert_eq!(arena.revert_location(0), None);
+    }
+
+    #[test]
+    fn can_replay_storage() {
+        let step = |op: u8, stack: &[u64]| DebugStep {
+            instruction: Instruction::OpCode(op),
+            stack: stack.iter().map(|word| U256::from(*word)).collect(),
+            ..Default::default()
+        };
+        let (contract, other) = (Address::from_low_u64_be(1), Address::from_low_u64_be(2));
+        let calls = vec![
+            // store 5 at slot 1, load 7 from slot 2
+            (
+                contract,
+                vec![
+                    step(opcode::SSTORE, &[5, 1]),
+                    step(opcode::SLOAD, &[2]),
+                    step(opcode::CALL, &[7]),
+                ],
+                CallKind::Call,
+            ),
+            (other, vec![step(opcode::SSTORE, &[9, 1])], CallKind::Call),
+            (
+                contract,
+                vec![step(opcode::SSTORE, &[6, 1]), step(opcode::POP, &[0])],
+                CallKind::Call,
+            ),
+        ];
+
+        let expected = |slots: &[(u64, u64)]| -> BTreeMap<U256, U256> {
+            slots.iter().map(|(slot, value)| ((*slot).into(), (*value).into())).collect()
+        };
+        assert_eq!(storage_at(&calls, 0, 1), expected(&[(1, 5)]));
+        assert_eq!(storage_at(&calls, 1, 0), expected(&[]));
+        assert_eq!(storage_at(&calls, 2, 0), expected(&[(1, 5), (2, 7)]));
+        assert_eq!(storage_at(&calls, 2, 1), expected(&[(1, 6), (2, 7)]));
+    }
+}
@@ -1,3 +1,4 @@
+use super::inspector::precompile_name;
 use ethers::types::{Address, Selector, H160};
 use once_cell::sync::Lazy;
 use std::collections::HashMap;
@@ -37,6 +38,9 @@ ethers::contract::abigen!(
             expectEmit(bool,bool,bool,bool)
             expectEmit(bool,bool,bool,bool,address)
             mockCall(address,bytes,bytes)
+            mockCall(address,uint256,bytes,bytes)
+            mockCallRevert(address,bytes,bytes)
+            mockCallRevert(address,uint256,bytes,bytes)
             clearMockedCalls()
             expectCall(address,bytes)
             getCode(string)
@@ -45,6 +49,46 @@ ethers::contract::abigen!(
             setNonce(address,uint64)
             getNonce(address)
             chainId(uint256)
+            broadcast()
+            broadcast(address)
+            startBroadcast()
+            startBroadcast(address)
+            stopBroadcast()
+            tempDir()(string)
+            skip(bool)
+            loadAllocs(string)
+            createFork(string)(uint256)
+            createFork(string,uint256)(uint256)
+            selectFork(uint256)
+            activeFork()(uint256)
+            makePersistent(address)
+            makePersistent(address[])
+            isPersistent(address)(bool)
+            readFile(string)(string)
+            writeFile(string,string)
+            envBool(string)(bool)
+            envUint(string)(uint256)
+            envInt(string)(int256)
+            envAddress(string)(address)
+            envBytes32(string)(bytes32)
+            envString(string)(string)
+            envBytes(string)(bytes)
+            envBool(string,string)(bool[])
+            envUint(string,string)(uint256[])
+            envInt(string,string)(int256[])
+            envAddress(string,string)(address[])
+            envBytes32(string,string)(bytes32[])
+            envString(string,string)(string[])
+            envBytes(string,string)(bytes[])
+            envOr(string,bool)(bool)
+            envOr(string,uint256)(uint256)
+            envOr(string,int256)(int256)
+            envOr(string,address)(address)
+            envOr(string,bytes32)(bytes32)
+            envOr(string,string)(string)
+            envOr(string,bytes)(bytes)
+            startSnapshotGas(string)
+            stopSnapshotGas(string)(uint256)
     ]"#,
 );
 pub use hevm_mod::{HEVMCalls, HEVM_ABI};
@@ -57,6 +101,20 @@ pub static HARDHAT_CONSOLE_ADDRESS: Address = H160([
     0x2e, 0x6c, 0x6f, 0x67,
 ]);
 
+/// Returns what the address is reserved for if tests must not deploy to, prank from or etch it:
+/// the cheatcode handler, the Hardhat console or one of the precompiles
+pub fn reserved_address(address: Address) -> Option<&'static str> {
+    if address == CHEATCODE_ADDRESS {
+        Some("the cheatcode address")
+    } else if address == HARDHAT_CONSOLE_ADDRESS {
+        Some("the console address")
+    } else if precompile_name(address).is_some() {
+        Some("a precompile address")
+    } else {
+        None
+    }
+}
+
 // Bindings for DS-style event logs
 ethers::contract::abigen!(
     Console,
@@ -630,4 +688,15 @@ mod tests {
             assert_eq!(abigen.to_vec(), patched);
         }
     }
+
+    #[test]
+    fn can_detect_reserved_addresses() {
+        assert!(reserved_address(CHEATCODE_ADDRESS).is_some());
+        assert!(reserved_address(HARDHAT_CONSOLE_ADDRESS).is_some());
+        assert!(reserved_address(Address::from_low_u64_be(1)).is_some());
+        assert!(reserved_address(Address::from_low_u64_be(9)).is_some());
+        assert!(reserved_address(Address::zero()).is_none());
+        assert!(reserved_address(Address::from_low_u64_be(10)).is_none());
+        assert!(reserved_address(Address::from_low_u64_be(0x101)).is_none());
+    }
 }
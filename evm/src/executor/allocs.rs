@@ -0,0 +1,66 @@
+use ethers::types::{Address, Bytes, H256, U256, U64};
+use eyre::WrapErr;
+use serde::{Deserialize, Serialize};
+use std::{collections::BTreeMap, path::Path};
+
+/// The state of a single account in an allocs file.
+///
+/// This follows the format of the `alloc` section of a geth genesis file.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct GenesisAccount {
+    #[serde(default)]
+    pub balance: U256,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub nonce: Option<U64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub code: Option<Bytes>,
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub storage: BTreeMap<H256, H256>,
+}
+
+/// A mapping of addresses to the state of their account
+pub type Allocs = BTreeMap<Address, GenesisAccount>;
+
+/// Reads the account allocations from the given JSON file
+pub fn load_allocs(path: impl AsRef<Path>) -> eyre::Result<Allocs> {
+    let path = path.as_ref();
+    let content = std::fs::read_to_string(path)
+        .wrap_err_with(|| format!("Failed to read allocs file {:?}", path))?;
+    serde_json::from_str(&content)
+        .wrap_err_with(|| format!("Failed to parse allocs file {:?}", path))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn can_parse_allocs() {
+        let allocs: Allocs = serde_json::from_str(
+            r#"{
+                "0x0000000000000000000000000000000000000001": {
+                    "balance": "0x64",
+                    "nonce": "0x1",
+                    "code": "0x6080",
+                    "storage": {
+                        "0x0000000000000000000000000000000000000000000000000000000000000000": "0x000000000000000000000000000000000000000000000000000000000000002a"
+                    }
+                },
+                "0x0000000000000000000000000000000000000002": {
+                    "balance": "0x1"
+                }
+            }"#,
+        )
+        .unwrap();
+
+        let account = &allocs[&Address::from_low_u64_be(1)];
+        assert_eq!(account.balance, 100u64.into());
+        assert_eq!(account.nonce, Some(1u64.into()));
+        assert_eq!(account.code.as_ref().map(|code| code.to_vec()), Some(vec![0x60, 0x80]));
+        assert_eq!(account.storage[&H256::zero()], H256::from_low_u64_be(42));
+
+        let account = &allocs[&Address::from_low_u64_be(2)];
+        assert_eq!(account.code, None);
+        assert!(account.storage.is_empty());
+    }
+}
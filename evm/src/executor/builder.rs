@@ -1,4 +1,6 @@
-use ethers::prelude::Provider;
+use ethers::{abi::Abi, prelude::Provider};
+use foundry_config::{CheatcodeGroup, FsPermissions};
+use parking_lot::RwLock;
 use revm::{
     db::{DatabaseRef, EmptyDB},
     Env, SpecId,
@@ -6,7 +8,7 @@ use revm::{
 use std::{path::PathBuf, sync::Arc};
 
 use super::{
-    fork::SharedBackend,
+    fork::{BatchHttp, ForkState, MultiFork, SharedBackend},
     inspector::{Cheatcodes, InspectorStackConfig},
     Executor,
 };
@@ -40,6 +42,9 @@ pub struct Fork {
     pub pin_block: Option<u64>,
     /// chain id retrieved from the endpoint
     pub chain_id: u64,
+    /// The milliseconds to wait before retrying requests the endpoint rejected because of its
+    /// rate limit, see [BatchHttp]
+    pub retry_backoff: Option<u64>,
 }
 
 impl Fork {
@@ -51,10 +56,11 @@ impl Fork {
     /// endpoint via channels and is intended to be cloned when multiple [revm::Database] are
     /// required. See also [crate::executor::fork::SharedBackend]
     pub async fn spawn_backend(self, env: &Env) -> SharedBackend {
-        let Fork { cache_path, url, pin_block, chain_id } = self;
+        let Fork { cache_path, url, pin_block, chain_id, retry_backoff } = self;
 
-        let provider =
-            Arc::new(Provider::try_from(url.clone()).expect("Failed to establish provider"));
+        let provider = Arc::new(Provider::new(
+            BatchHttp::new(&url, retry_backoff).expect("Failed to establish provider"),
+        ));
 
         let mut meta = BlockchainDbMeta::new(env.clone(), url);
 
@@ -137,6 +143,69 @@ impl ExecutorBuilder {
         self
     }
 
+    /// Allows tests to create and select forks with the `createFork` and `selectFork` cheatcodes.
+    ///
+    /// Has no effect if cheatcodes are not enabled.
+    #[must_use]
+    pub fn with_forks(mut self, forks: MultiFork) -> Self {
+        if let Some(ref mut cheatcodes) = self.inspector_config.cheatcodes {
+            cheatcodes.forks = Some(Arc::new(RwLock::new(ForkState::new(forks))));
+        }
+        self
+    }
+
+    /// Sets the paths the `readFile` and `writeFile` cheatcodes may access, relative paths passed
+    /// to the cheatcodes are resolved against `root`.
+    ///
+    /// Has no effect if cheatcodes are not enabled.
+    #[must_use]
+    pub fn with_fs_permissions(
+        mut self,
+        permissions: FsPermissions,
+        root: impl Into<PathBuf>,
+    ) -> Self {
+        if let Some(ref mut cheatcodes) = self.inspector_config.cheatcodes {
+            cheatcodes.fs_permissions = permissions;
+            cheatcodes.root = root.into();
+        }
+        self
+    }
+
+    /// Denies the given cheatcodes, e.g. for tests of dependencies, see [foundry_config::Sandbox].
+    ///
+    /// Has no effect if cheatcodes are not enabled.
+    #[must_use]
+    pub fn with_denied_cheatcodes(mut self, denied: Vec<CheatcodeGroup>) -> Self {
+        if let Some(ref mut cheatcodes) = self.inspector_config.cheatcodes {
+            cheatcodes.denied_cheatcodes = denied;
+        }
+        self
+    }
+
+    /// Fails `prank` and `etch` if they are used with a reserved address, see [reserved_address].
+    ///
+    /// Has no effect if cheatcodes are not enabled.
+    ///
+    /// [reserved_address]: crate::executor::reserved_address
+    #[must_use]
+    pub fn with_reserved_addresses_denied(mut self) -> Self {
+        if let Some(ref mut cheatcodes) = self.inspector_config.cheatcodes {
+            cheatcodes.deny_reserved_addresses = true;
+        }
+        self
+    }
+
+    /// Decodes the reverts in the reason of a failed `expectRevert` with the given errors.
+    ///
+    /// Has no effect if cheatcodes are not enabled.
+    #[must_use]
+    pub fn with_errors(mut self, errors: Arc<Abi>) -> Self {
+        if let Some(ref mut cheatcodes) = self.inspector_config.cheatcodes {
+            cheatcodes.errors = Some(errors);
+        }
+        self
+    }
+
     /// Enables tracing
     #[must_use]
     pub fn with_tracing(mut self) -> Self {
@@ -151,6 +220,38 @@ impl ExecutorBuilder {
         self
     }
 
+    /// Records the gas used by each instruction, see [GasProfiler](super::inspector::GasProfiler)
+    #[must_use]
+    pub fn with_gas_profiling(mut self) -> Self {
+        self.inspector_config.gas_profiling = true;
+        self
+    }
+
+    /// Counts the calls to precompiles and the executed `KECCAK256` opcodes, see
+    /// [PrecompileCounter](super::inspector::PrecompileCounter)
+    #[must_use]
+    pub fn with_precompile_counting(mut self) -> Self {
+        self.inspector_config.precompiles = true;
+        self
+    }
+
+    /// Executes every call made by the test contract as a transaction of its own, which starts
+    /// with cold accounts and storage slots and pays the intrinsic gas of a transaction
+    #[must_use]
+    pub fn with_isolation(mut self) -> Self {
+        self.inspector_config.isolate = true;
+        self
+    }
+
+    /// Moves contracts created with `CREATE` to deterministic addresses, see [AddressAllocator]
+    ///
+    /// [AddressAllocator]: crate::executor::inspector::AddressAllocator
+    #[must_use]
+    pub fn with_deterministic_addresses(mut self) -> Self {
+        self.inspector_config.deterministic_addresses = true;
+        self
+    }
+
     /// Sets the EVM spec to use
     #[must_use]
     pub fn with_spec(mut self, spec: SpecId) -> Self {
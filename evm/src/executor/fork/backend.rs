@@ -530,6 +530,7 @@ mod tests {
             url: ENDPOINT.to_string(),
             pin_block: Some(block_num),
             chain_id: 1,
+            retry_backoff: None,
         };
 
         let backend = runtime.block_on(fork.spawn_backend(&env));
@@ -0,0 +1,481 @@
+//! A JSON-RPC transport for forking endpoints that batches concurrent requests and retries rate
+//! limited ones, endpoints that do not support batches are sent the requests one by one
+use async_trait::async_trait;
+use ethers::providers::{JsonRpcClient, ProviderError};
+use futures::{
+    channel::{
+        mpsc::{unbounded, UnboundedReceiver, UnboundedSender},
+        oneshot,
+    },
+    StreamExt,
+};
+use reqwest::{Client, StatusCode};
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+use serde_json::{json, Value};
+use std::{
+    collections::HashMap,
+    fmt,
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc,
+    },
+    time::Duration,
+};
+use tracing::{trace, warn};
+use url::Url;
+
+/// The default time to wait before retrying a rate limited request, in milliseconds
+pub const DEFAULT_RETRY_BACKOFF: u64 = 1_000;
+
+/// How often a rate limited request is retried, the backoff doubles with every retry
+const MAX_RETRIES: u32 = 8;
+
+/// The maximum number of requests sent in a single batch
+const MAX_BATCH_SIZE: usize = 100;
+
+/// The error object of a JSON-RPC response
+#[derive(Clone, Debug, Deserialize)]
+pub struct RpcError {
+    pub code: i64,
+    pub message: String,
+    pub data: Option<Value>,
+}
+
+impl RpcError {
+    /// Whether the endpoint rejected the request because it exceeded its rate limit
+    fn is_rate_limit(&self) -> bool {
+        // 429 is used by most providers, -32005 ("limit exceeded") by infura
+        if self.code == 429 || self.code == -32005 {
+            return true
+        }
+        let message = self.message.to_lowercase();
+        message.contains("rate limit") || message.contains("too many requests")
+    }
+
+    /// Whether the endpoint rejected a batch because it does not support batch requests
+    fn is_batch_unsupported(&self) -> bool {
+        // -32700 ("parse error") is returned by endpoints that expect a single request object
+        self.code == -32700 || self.message.to_lowercase().contains("batch")
+    }
+}
+
+impl fmt::Display for RpcError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "(code: {}, message: {}, data: {:?})", self.code, self.message, self.data)
+    }
+}
+
+#[derive(Clone, Debug, thiserror::Error)]
+pub enum BatchHttpError {
+    #[error(transparent)]
+    Request(Arc<reqwest::Error>),
+    #[error(transparent)]
+    Serde(Arc<serde_json::Error>),
+    #[error("JSON-RPC error: {0}")]
+    JsonRpc(RpcError),
+    #[error("the endpoint is still rate limiting requests after retrying them")]
+    RateLimited,
+    #[error("invalid response from the endpoint: {0}")]
+    InvalidResponse(String),
+    #[error("the endpoint did not answer the request")]
+    MissingResponse,
+    #[error("the batching task is not running")]
+    Closed,
+}
+
+impl BatchHttpError {
+    fn is_rate_limit(&self) -> bool {
+        match self {
+            BatchHttpError::RateLimited => true,
+            BatchHttpError::JsonRpc(err) => err.is_rate_limit(),
+            _ => false,
+        }
+    }
+
+    fn is_batch_unsupported(&self) -> bool {
+        match self {
+            BatchHttpError::JsonRpc(err) => !err.is_rate_limit() && err.is_batch_unsupported(),
+            _ => false,
+        }
+    }
+}
+
+impl From<reqwest::Error> for BatchHttpError {
+    fn from(err: reqwest::Error) -> Self {
+        BatchHttpError::Request(Arc::new(err))
+    }
+}
+
+impl From<serde_json::Error> for BatchHttpError {
+    fn from(err: serde_json::Error) -> Self {
+        BatchHttpError::Serde(Arc::new(err))
+    }
+}
+
+impl From<BatchHttpError> for ProviderError {
+    fn from(err: BatchHttpError) -> Self {
+        ProviderError::JsonRpcClientError(Box::new(err))
+    }
+}
+
+/// A request waiting to be sent with the next batch
+#[derive(Debug)]
+struct PendingRequest {
+    method: String,
+    params: Value,
+    sender: oneshot::Sender<Result<Value, BatchHttpError>>,
+}
+
+/// A response of a batch, which contains either a `result` or an `error`
+#[derive(Debug, Deserialize)]
+struct Response {
+    id: Option<u64>,
+    result: Option<Value>,
+    error: Option<RpcError>,
+}
+
+/// Endpoints answer a batch with a list of responses, or with a single error response if they
+/// reject the whole batch
+#[derive(Debug, Deserialize)]
+#[serde(untagged)]
+enum BatchResponse {
+    Batch(Vec<Response>),
+    Single(Response),
+}
+
+/// A HTTP transport that sends all requests that are made concurrently, e.g. the storage slots
+/// and accounts the [SharedBackend](super::SharedBackend)s of a test run are waiting for, as a
+/// single JSON-RPC batch.
+///
+/// Requests that are rejected because of the endpoint's rate limit are retried with exponential
+/// backoff. If the endpoint rejects a batch because it does not support batch requests, this and
+/// all following requests are sent one by one instead.
+#[derive(Clone, Debug)]
+pub struct BatchHttp {
+    requests: UnboundedSender<PendingRequest>,
+}
+
+impl BatchHttp {
+    /// Spawns the task that sends the batches to `url`.
+    ///
+    /// Rate limited requests are first retried after `retry_backoff` milliseconds, or
+    /// [DEFAULT_RETRY_BACKOFF] if not set.
+    ///
+    /// **Note:** this must be called from within a tokio runtime
+    pub fn new(url: &str, retry_backoff: Option<u64>) -> Result<Self, url::ParseError> {
+        let url = Url::parse(url)?;
+        let backoff = Duration::from_millis(retry_backoff.unwrap_or(DEFAULT_RETRY_BACKOFF));
+        let (requests, rx) = unbounded();
+        tokio::spawn(run(Client::new(), url, backoff, rx));
+        Ok(Self { requests })
+    }
+}
+
+#[async_trait]
+impl JsonRpcClient for BatchHttp {
+    type Error = BatchHttpError;
+
+    async fn request<T: Serialize + Send + Sync, R: DeserializeOwned>(
+        &self,
+        method: &str,
+        params: T,
+    ) -> Result<R, BatchHttpError> {
+        let params = serde_json::to_value(params)?;
+        let (sender, rx) = oneshot::channel();
+        self.requests
+            .unbounded_send(PendingRequest { method: method.to_string(), params, sender })
+            .map_err(|_| BatchHttpError::Closed)?;
+        let result = rx.await.map_err(|_| BatchHttpError::Closed)??;
+        Ok(serde_json::from_value(result)?)
+    }
+}
+
+/// Collects the incoming requests into batches until the last [BatchHttp] is dropped
+async fn run(
+    client: Client,
+    url: Url,
+    backoff: Duration,
+    mut requests: UnboundedReceiver<PendingRequest>,
+) {
+    // cleared once the endpoint rejected a batch
+    let batching = Arc::new(AtomicBool::new(true));
+    while let Some(request) = requests.next().await {
+        // the requests made in the same poll of the backend are queued right after this one
+        tokio::task::yield_now().await;
+
+        let mut batch = vec![request];
+        while batch.len() < MAX_BATCH_SIZE {
+            match requests.try_next() {
+                Ok(Some(request)) => batch.push(request),
+                _ => break,
+            }
+        }
+        if batching.load(Ordering::Relaxed) {
+            trace!(target: "batchhttp", "sending batch of {} requests", batch.len());
+            tokio::spawn(send_batch(client.clone(), url.clone(), backoff, batch, batching.clone()));
+        } else {
+            for request in batch {
+                tokio::spawn(send_single(client.clone(), url.clone(), backoff, request));
+            }
+        }
+    }
+    trace!(target: "batchhttp", "last client dropped, stopping");
+}
+
+/// Sends the batch and answers its requests, rate limited requests are retried until
+/// [MAX_RETRIES] is reached.
+///
+/// If the endpoint does not support batches, `batching` is cleared and the requests are sent one
+/// by one.
+async fn send_batch(
+    client: Client,
+    url: Url,
+    backoff: Duration,
+    mut batch: Vec<PendingRequest>,
+    batching: Arc<AtomicBool>,
+) {
+    let mut retries = 0;
+    loop {
+        let calls: Vec<_> = batch
+            .iter()
+            .enumerate()
+            .map(|(id, request)| {
+                json!({
+                    "jsonrpc": "2.0",
+                    "id": id,
+                    "method": request.method,
+                    "params": request.params,
+                })
+            })
+            .collect();
+
+        match post(&client, &url, &calls).await.and_then(|body| parse_batch_response(&body)) {
+            Ok(responses) => {
+                let mut responses: HashMap<_, _> = responses
+                    .into_iter()
+                    .filter_map(|response| Some((response.id?, response)))
+                    .collect();
+                let mut rate_limited = Vec::new();
+                for (id, request) in batch.into_iter().enumerate() {
+                    let result = match responses.remove(&(id as u64)) {
+                        Some(Response { error: Some(err), .. }) => {
+                            if err.is_rate_limit() {
+                                rate_limited.push(request);
+                                continue
+                            }
+                            Err(BatchHttpError::JsonRpc(err))
+                        }
+                        Some(Response { result, .. }) => Ok(result.unwrap_or(Value::Null)),
+                        None => Err(BatchHttpError::MissingResponse),
+                    };
+                    let _ = request.sender.send(result);
+                }
+                batch = rate_limited;
+            }
+            Err(err) if err.is_rate_limit() => {}
+            Err(err) if err.is_batch_unsupported() => {
+                if batching.swap(false, Ordering::Relaxed) {
+                    warn!(target: "batchhttp", "{} does not support batch requests ({}), sending requests one by one", url, err);
+                }
+                futures::future::join_all(
+                    batch
+                        .into_iter()
+                        .map(|request| send_single(client.clone(), url.clone(), backoff, request)),
+                )
+                .await;
+                return
+            }
+            Err(err) => {
+                for request in batch {
+                    let _ = request.sender.send(Err(err.clone()));
+                }
+                return
+            }
+        }
+
+        if batch.is_empty() {
+            return
+        }
+        if retries == MAX_RETRIES {
+            for request in batch {
+                let _ = request.sender.send(Err(BatchHttpError::RateLimited));
+            }
+            return
+        }
+
+        let delay = backoff * 2u32.pow(retries);
+        warn!(target: "batchhttp", "rate limited by {}, retrying {} requests in {:?}", url, batch.len(), delay);
+        tokio::time::sleep(delay).await;
+        retries += 1;
+    }
+}
+
+/// Sends a request on its own and answers it, it is retried until [MAX_RETRIES] is reached if it
+/// is rate limited
+async fn send_single(client: Client, url: Url, backoff: Duration, request: PendingRequest) {
+    let call = json!({
+        "jsonrpc": "2.0",
+        "id": 0,
+        "method": request.method,
+        "params": request.params,
+    });
+
+    let mut retries = 0;
+    let result = loop {
+        let result = match post(&client, &url, &call).await {
+            Ok(body) => parse_response(&body),
+            Err(err) => Err(err),
+        };
+        match result {
+            Err(err) if err.is_rate_limit() && retries < MAX_RETRIES => {
+                let delay = backoff * 2u32.pow(retries);
+                warn!(target: "batchhttp", "rate limited by {}, retrying request in {:?}", url, delay);
+                tokio::time::sleep(delay).await;
+                retries += 1;
+            }
+            Err(err) if err.is_rate_limit() => break Err(BatchHttpError::RateLimited),
+            result => break result,
+        }
+    };
+    let _ = request.sender.send(result);
+}
+
+/// Posts the JSON body and returns the body of the response
+async fn post(client: &Client, url: &Url, body: &impl Serialize) -> Result<String, BatchHttpError> {
+    let response = client.post(url.clone()).json(body).send().await?;
+    if response.status() == StatusCode::TOO_MANY_REQUESTS {
+        return Err(BatchHttpError::RateLimited)
+    }
+    Ok(response.text().await?)
+}
+
+fn parse_response(body: &str) -> Result<Value, BatchHttpError> {
+    match serde_json::from_str(body) {
+        Ok(Response { error: Some(err), .. }) => Err(BatchHttpError::JsonRpc(err)),
+        Ok(Response { result, .. }) => Ok(result.unwrap_or(Value::Null)),
+        Err(_) => Err(BatchHttpError::InvalidResponse(body.to_string())),
+    }
+}
+
+fn parse_batch_response(body: &str) -> Result<Vec<Response>, BatchHttpError> {
+    match serde_json::from_str(body) {
+        Ok(BatchResponse::Batch(responses)) => Ok(responses),
+        Ok(BatchResponse::Single(Response { error: Some(err), .. })) => {
+            Err(BatchHttpError::JsonRpc(err))
+        }
+        _ => Err(BatchHttpError::InvalidResponse(body.to_string())),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::{
+        io::{BufRead, BufReader, Read, Write},
+        net::TcpListener,
+        sync::atomic::AtomicUsize,
+    };
+
+    #[test]
+    fn can_parse_batch_responses() {
+        let responses = parse_batch_response(
+            r#"[{"jsonrpc":"2.0","id":1,"result":"0x01"},{"jsonrpc":"2.0","id":0,"error":{"code":429,"message":"Too Many Requests"}}]"#,
+        )
+        .unwrap();
+        assert_eq!(responses.len(), 2);
+        assert_eq!(responses[0].id, Some(1));
+        assert_eq!(responses[0].result, Some(Value::String("0x01".to_string())));
+        assert!(responses[1].error.as_ref().unwrap().is_rate_limit());
+
+        let err = parse_batch_response(
+            r#"{"jsonrpc":"2.0","id":null,"error":{"code":-32005,"message":"limit exceeded"}}"#,
+        )
+        .unwrap_err();
+        assert!(err.is_rate_limit());
+
+        let err = parse_batch_response(
+            r#"{"jsonrpc":"2.0","id":null,"error":{"code":-32600,"message":"batch requests are not supported"}}"#,
+        )
+        .unwrap_err();
+        assert!(!err.is_rate_limit());
+        assert!(err.is_batch_unsupported());
+
+        assert!(parse_batch_response("<html>Bad Gateway</html>").is_err());
+    }
+
+    /// Serves JSON-RPC requests that answer with the name of their method, and rejects batches
+    fn spawn_batchless_endpoint() -> (String, Arc<AtomicUsize>) {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let url = format!("http://{}", listener.local_addr().unwrap());
+        let batches = Arc::new(AtomicUsize::new(0));
+        let counter = batches.clone();
+        std::thread::spawn(move || {
+            for stream in listener.incoming() {
+                let mut stream = stream.unwrap();
+                let batches = counter.clone();
+                std::thread::spawn(move || {
+                    let mut reader = BufReader::new(stream.try_clone().unwrap());
+                    let mut content_length = 0;
+                    loop {
+                        let mut line = String::new();
+                        reader.read_line(&mut line).unwrap();
+                        let line = line.trim().to_lowercase();
+                        if line.is_empty() {
+                            break
+                        }
+                        if let Some(len) = line.strip_prefix("content-length:") {
+                            content_length = len.trim().parse().unwrap();
+                        }
+                    }
+                    let mut body = vec![0; content_length];
+                    reader.read_exact(&mut body).unwrap();
+
+                    let request: Value = serde_json::from_slice(&body).unwrap();
+                    let response = if request.is_array() {
+                        batches.fetch_add(1, Ordering::SeqCst);
+                        json!({"jsonrpc": "2.0", "id": null, "error": {"code": -32600, "message": "batch requests are not supported"}})
+                    } else {
+                        json!({"jsonrpc": "2.0", "id": request["id"], "result": request["method"]})
+                    };
+                    let response = response.to_string();
+                    write!(
+                        stream,
+                        "HTTP/1.1 200 OK\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+                        response.len(),
+                        response
+                    )
+                    .unwrap();
+                });
+            }
+        });
+        (url, batches)
+    }
+
+    #[test]
+    fn falls_back_to_single_requests() {
+        let (url, batches) = spawn_batchless_endpoint();
+        let runtime = foundry_utils::RuntimeOrHandle::new();
+        runtime.block_on(async move {
+            let client = BatchHttp::new(&url, None).unwrap();
+            let request = |method: &'static str| {
+                let client = client.clone();
+                async move { client.request::<_, String>(method, ()).await.unwrap() }
+            };
+
+            // the concurrent requests are sent as a batch first, which is rejected
+            let results = futures::future::join_all(
+                ["eth_chainId", "eth_blockNumber", "eth_gasPrice"].map(request),
+            )
+            .await;
+            assert_eq!(results, ["eth_chainId", "eth_blockNumber", "eth_gasPrice"]);
+            let rejected = batches.load(Ordering::SeqCst);
+            assert!(rejected > 0);
+
+            // all following requests are sent one by one
+            let results =
+                futures::future::join_all(["eth_getBalance", "eth_getCode"].map(request)).await;
+            assert_eq!(results, ["eth_getBalance", "eth_getCode"]);
+            assert_eq!(batches.load(Ordering::SeqCst), rejected);
+        });
+    }
+}
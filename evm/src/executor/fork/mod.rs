@@ -1,8 +1,14 @@
 mod backend;
 pub use backend::SharedBackend;
 
+mod http;
+pub use http::{BatchHttp, BatchHttpError, DEFAULT_RETRY_BACKOFF};
+
 mod init;
 pub use init::environment;
 
 mod cache;
 pub use cache::{BlockchainDb, BlockchainDbMeta, JsonBlockCacheDB};
+
+mod multi;
+pub use multi::{ForkId, ForkState, ForkedDb, ForkedDbMut, MultiFork, SharedForkState};
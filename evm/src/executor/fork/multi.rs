@@ -0,0 +1,294 @@
+use super::{environment, BatchHttp, SharedBackend};
+use crate::executor::{builder::Fork, StateChangeset};
+use ethers::{
+    providers::Provider,
+    types::{Address, H160, H256, U256},
+};
+use eyre::WrapErr;
+use hashbrown::HashMap;
+use parking_lot::{Mutex, RwLock};
+use revm::{
+    db::{CacheDB, Database, DatabaseCommit, DatabaseRef},
+    Account, AccountInfo, Env,
+};
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    sync::Arc,
+};
+use tokio::runtime::Handle;
+
+/// The identifier of a fork created with the `createFork` cheatcode
+pub type ForkId = usize;
+
+/// Spawns the backends of the forks created with the `createFork` cheatcode.
+///
+/// A [MultiFork] is shared by all executors of a test run, so forks of the same endpoint at the
+/// same block are only spawned once and share their cached state.
+#[derive(Clone, Debug)]
+pub struct MultiFork {
+    /// The runtime the backends are spawned on
+    handle: Handle,
+    /// All spawned backends and their environment, by endpoint and block number
+    backends: Arc<Mutex<BTreeMap<(String, u64), (SharedBackend, Env)>>>,
+    /// The backoff of rate limited requests, see [BatchHttp]
+    retry_backoff: Option<u64>,
+}
+
+impl MultiFork {
+    pub fn new(handle: Handle, retry_backoff: Option<u64>) -> Self {
+        Self { handle, backends: Default::default(), retry_backoff }
+    }
+
+    /// Registers an already spawned backend of a fork of `url` at the given block, so forks of the
+    /// same endpoint and block created by tests share its cached state
+    pub fn register(&self, url: impl Into<String>, block: u64, backend: SharedBackend, env: Env) {
+        self.backends.lock().entry((url.into(), block)).or_insert((backend, env));
+    }
+
+    /// Returns the backend and environment of a fork of `url` at the given block, or the latest
+    /// block, spawning the backend if necessary
+    pub fn get_or_spawn(
+        &self,
+        url: &str,
+        block: Option<u64>,
+        env: &Env,
+    ) -> eyre::Result<(SharedBackend, Env)> {
+        let fut = async {
+            let provider = Provider::new(
+                BatchHttp::new(url, self.retry_backoff)
+                    .wrap_err_with(|| format!("Invalid fork url {}", url))?,
+            );
+            let fork_env =
+                environment(&provider, env.cfg.memory_limit, None, block, env.tx.caller).await?;
+            let key = (url.to_string(), fork_env.block.number.as_u64());
+            if let Some(existing) = self.backends.lock().get(&key) {
+                return Ok::<_, eyre::Report>(existing.clone())
+            }
+
+            let fork = Fork {
+                cache_path: None,
+                url: url.to_string(),
+                pin_block: Some(key.1),
+                chain_id: fork_env.cfg.chain_id.as_u64(),
+                retry_backoff: self.retry_backoff,
+            };
+            let backend = fork.spawn_backend(&fork_env).await;
+            Ok(self.backends.lock().entry(key).or_insert((backend, fork_env)).clone())
+        };
+        tokio::task::block_in_place(|| self.handle.block_on(fut))
+    }
+}
+
+/// A fork created by a test
+#[derive(Clone, Debug)]
+struct CreatedFork {
+    backend: SharedBackend,
+    env: Env,
+    /// The changes made to non-persistent accounts while the fork was selected
+    changes: StateChangeset,
+}
+
+/// The forks created by a test and the fork that is currently selected.
+///
+/// While a fork is selected, all accounts except the persistent ones are read from it. Persistent
+/// accounts, e.g. the test contract itself, keep their state across forks.
+#[derive(Clone, Debug)]
+pub struct ForkState {
+    multi: MultiFork,
+    forks: Vec<CreatedFork>,
+    active: Option<ForkId>,
+    persistent: BTreeSet<Address>,
+}
+
+/// The [ForkState] of a call, shared by the cheatcodes and the database of the call
+pub type SharedForkState = Arc<RwLock<ForkState>>;
+
+impl ForkState {
+    pub fn new(multi: MultiFork) -> Self {
+        Self { multi, forks: Vec::new(), active: None, persistent: BTreeSet::new() }
+    }
+
+    /// Creates a fork of `url` at the given block, or the latest block, without selecting it
+    pub fn create_fork(
+        &mut self,
+        url: &str,
+        block: Option<u64>,
+        env: &Env,
+    ) -> eyre::Result<ForkId> {
+        let (backend, env) = self.multi.get_or_spawn(url, block, env)?;
+        self.forks.push(CreatedFork { backend, env, changes: Default::default() });
+        Ok(self.forks.len() - 1)
+    }
+
+    /// Selects the fork with the given id and returns its environment
+    pub fn select_fork(&mut self, id: ForkId) -> eyre::Result<&Env> {
+        let fork = self.forks.get(id).ok_or_else(|| eyre::eyre!("Fork {} does not exist", id))?;
+        self.active = Some(id);
+        Ok(&fork.env)
+    }
+
+    /// The id of the selected fork
+    pub fn active(&self) -> Option<ForkId> {
+        self.active
+    }
+
+    /// The environment of the selected fork
+    pub fn active_env(&self) -> Option<&Env> {
+        self.active.map(|id| &self.forks[id].env)
+    }
+
+    /// Marks the account as persistent, i.e. it keeps its state when switching forks
+    pub fn make_persistent(&mut self, address: Address) {
+        self.persistent.insert(address);
+    }
+
+    pub fn is_persistent(&self, address: &Address) -> bool {
+        self.persistent.contains(address)
+    }
+
+    /// Stores the changes to non-persistent accounts in the selected fork and returns the changes
+    /// that belong to the underlying database
+    pub fn commit(&mut self, changes: StateChangeset) -> StateChangeset {
+        let id = match self.active {
+            Some(id) => id,
+            None => return changes,
+        };
+
+        let (persistent, forked): (StateChangeset, StateChangeset) =
+            changes.into_iter().partition(|(address, _)| self.persistent.contains(address));
+        let fork_changes = &mut self.forks[id].changes;
+        for (address, account) in forked {
+            match fork_changes.get_mut(&address) {
+                Some(existing) => {
+                    existing.info = account.info;
+                    existing.storage.extend(account.storage);
+                }
+                None => {
+                    fork_changes.insert(address, account);
+                }
+            }
+        }
+        persistent
+    }
+
+    /// Returns the selected fork if the account is read from it
+    fn source(&self, address: &Address) -> Option<&CreatedFork> {
+        let id = self.active?;
+        (!self.persistent.contains(address)).then(|| &self.forks[id])
+    }
+
+    fn basic(&self, address: Address) -> Option<AccountInfo> {
+        let fork = self.source(&address)?;
+        Some(match fork.changes.get(&address) {
+            Some(account) => account.info.clone(),
+            None => fork.backend.basic(address),
+        })
+    }
+
+    fn storage(&self, address: Address, index: U256) -> Option<U256> {
+        let fork = self.source(&address)?;
+        Some(
+            fork.changes
+                .get(&address)
+                .and_then(|account| account.storage.get(&index).copied())
+                .unwrap_or_else(|| fork.backend.storage(address, index)),
+        )
+    }
+
+    fn block_hash(&self, number: U256) -> Option<H256> {
+        self.active.map(|id| self.forks[id].backend.block_hash(number))
+    }
+}
+
+/// A database that reads non-persistent accounts from the selected fork, if any, and everything
+/// else from the executor's database
+pub struct ForkedDb<'a, DB: DatabaseRef> {
+    db: &'a CacheDB<DB>,
+    forks: Option<SharedForkState>,
+}
+
+impl<'a, DB: DatabaseRef> ForkedDb<'a, DB> {
+    pub fn new(db: &'a CacheDB<DB>, forks: Option<SharedForkState>) -> Self {
+        Self { db, forks }
+    }
+}
+
+impl<'a, DB: DatabaseRef> DatabaseRef for ForkedDb<'a, DB> {
+    fn basic(&self, address: H160) -> AccountInfo {
+        self.forks
+            .as_ref()
+            .and_then(|forks| forks.read().basic(address))
+            .unwrap_or_else(|| DatabaseRef::basic(self.db, address))
+    }
+
+    fn code_by_hash(&self, code_hash: H256) -> bytes::Bytes {
+        // the code of forked accounts is always loaded with the account
+        DatabaseRef::code_by_hash(self.db, code_hash)
+    }
+
+    fn storage(&self, address: H160, index: U256) -> U256 {
+        self.forks
+            .as_ref()
+            .and_then(|forks| forks.read().storage(address, index))
+            .unwrap_or_else(|| DatabaseRef::storage(self.db, address, index))
+    }
+
+    fn block_hash(&self, number: U256) -> H256 {
+        self.forks
+            .as_ref()
+            .and_then(|forks| forks.read().block_hash(number))
+            .unwrap_or_else(|| DatabaseRef::block_hash(self.db, number))
+    }
+}
+
+/// The committing counterpart of [ForkedDb].
+///
+/// Changes to non-persistent accounts made while a fork is selected are kept in the fork, all other
+/// changes are committed to the executor's database.
+pub struct ForkedDbMut<'a, DB: DatabaseRef> {
+    db: &'a mut CacheDB<DB>,
+    forks: Option<SharedForkState>,
+}
+
+impl<'a, DB: DatabaseRef> ForkedDbMut<'a, DB> {
+    pub fn new(db: &'a mut CacheDB<DB>, forks: Option<SharedForkState>) -> Self {
+        Self { db, forks }
+    }
+}
+
+impl<'a, DB: DatabaseRef> Database for ForkedDbMut<'a, DB> {
+    fn basic(&mut self, address: H160) -> AccountInfo {
+        match self.forks.as_ref().and_then(|forks| forks.read().basic(address)) {
+            Some(info) => info,
+            None => Database::basic(&mut *self.db, address),
+        }
+    }
+
+    fn code_by_hash(&mut self, code_hash: H256) -> bytes::Bytes {
+        Database::code_by_hash(&mut *self.db, code_hash)
+    }
+
+    fn storage(&mut self, address: H160, index: U256) -> U256 {
+        match self.forks.as_ref().and_then(|forks| forks.read().storage(address, index)) {
+            Some(value) => value,
+            None => Database::storage(&mut *self.db, address, index),
+        }
+    }
+
+    fn block_hash(&mut self, number: U256) -> H256 {
+        match self.forks.as_ref().and_then(|forks| forks.read().block_hash(number)) {
+            Some(hash) => hash,
+            None => Database::block_hash(&mut *self.db, number),
+        }
+    }
+}
+
+impl<'a, DB: DatabaseRef> DatabaseCommit for ForkedDbMut<'a, DB> {
+    fn commit(&mut self, changes: HashMap<H160, Account>) {
+        let changes = match self.forks {
+            Some(ref forks) => forks.write().commit(changes),
+            None => changes,
+        };
+        self.db.commit(changes)
+    }
+}
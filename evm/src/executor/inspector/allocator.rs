@@ -0,0 +1,93 @@
+use bytes::Bytes;
+use ethers::{
+    abi::AbiEncode,
+    types::{Address, H256, U256},
+    utils::keccak256,
+};
+use revm::{return_ok, CreateInputs, CreateScheme, Database, EVMData, Gas, Inspector, Return};
+
+/// The offset of the first allocated address, chosen to stay clear of the precompiles
+pub const ALLOCATED_ADDRESS_OFFSET: u64 = 0x10000;
+
+/// An inspector that moves contracts created with `CREATE` to deterministic addresses.
+///
+/// Every created contract is moved to the lowest unused address after
+/// [ALLOCATED_ADDRESS_OFFSET], i.e. `0x...10001`, `0x...10002`, ... so that addresses stay
+/// stable if deployments are added or removed elsewhere. `CREATE2` deployments are left untouched.
+///
+/// Note that constructors still observe the nonce-derived address, so contracts that store
+/// `address(this)` during construction will see a different address afterwards.
+#[derive(Default, Debug, Clone, Copy)]
+pub struct AddressAllocator;
+
+impl AddressAllocator {
+    /// Returns the lowest address after the offset that has neither code nor a nonce
+    fn next_free_address<DB: Database>(data: &mut EVMData<'_, DB>) -> Option<Address> {
+        let empty_code_hash = H256::from_slice(&keccak256(&[]));
+        (ALLOCATED_ADDRESS_OFFSET + 1..=u64::MAX).map(Address::from_low_u64_be).find(|address| {
+            data.subroutine.load_account(*address, data.db);
+            let info = &data.subroutine.account(*address).info;
+            info.nonce == 0 && info.code_hash == empty_code_hash
+        })
+    }
+}
+
+impl<DB> Inspector<DB> for AddressAllocator
+where
+    DB: Database,
+{
+    fn create_end(
+        &mut self,
+        data: &mut EVMData<'_, DB>,
+        call: &CreateInputs,
+        status: Return,
+        address: Option<Address>,
+        gas: Gas,
+        retdata: Bytes,
+    ) -> (Return, Option<Address>, Gas, Bytes) {
+        let created = match (status, address, &call.scheme) {
+            (return_ok!(), Some(address), CreateScheme::Create) => address,
+            _ => return (status, address, gas, retdata),
+        };
+        let allocated = match Self::next_free_address(data) {
+            Some(allocated) => allocated,
+            None => {
+                return (
+                    Return::Revert,
+                    None,
+                    gas,
+                    "No free address left to move the created contract to"
+                        .to_string()
+                        .encode()
+                        .into(),
+                )
+            }
+        };
+
+        // Move the created account over with journaled operations, so the move is undone if the
+        // surrounding call reverts
+        let account = data.subroutine.account(created).clone();
+        data.subroutine.balance_add(allocated, account.info.balance);
+        data.subroutine.set_code(
+            allocated,
+            account.info.code.clone().unwrap_or_default(),
+            account.info.code_hash,
+        );
+        for (slot, value) in &account.storage {
+            data.subroutine.sstore(allocated, *slot, *value, data.db);
+        }
+        // there is no journaled operation for nonces, they are set directly like in `setNonce`
+        data.subroutine.state().get_mut(&allocated).expect("account was loaded").info.nonce =
+            account.info.nonce;
+
+        // Empty the nonce-derived account
+        data.subroutine.balance_sub(created, account.info.balance);
+        data.subroutine.set_code(created, Bytes::new(), H256::from_slice(&keccak256(&[])));
+        for slot in account.storage.keys() {
+            data.subroutine.sstore(created, *slot, U256::zero(), data.db);
+        }
+        data.subroutine.state().get_mut(&created).expect("account was created").info.nonce = 0;
+
+        (status, Some(allocated), gas, retdata)
+    }
+}
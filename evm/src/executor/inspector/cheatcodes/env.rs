@@ -1,13 +1,14 @@
 use std::collections::BTreeMap;
 
-use super::Cheatcodes;
-use crate::abi::HEVMCalls;
+use super::{ext::permitted_path, Cheatcodes};
+use crate::abi::{reserved_address, HEVMCalls};
 use bytes::Bytes;
 use ethers::{
     abi::{self, AbiEncode, Token, Tokenize},
     types::{Address, H256, U256},
     utils::keccak256,
 };
+use foundry_config::FsAccess;
 use revm::{Database, EVMData};
 
 #[derive(Clone, Debug, Default)]
@@ -37,14 +38,101 @@ fn prank(
 ) -> Result<Bytes, Bytes> {
     let prank = Prank { prank_caller, prank_origin, new_caller, new_origin, depth, single_call };
 
+    if state.broadcast.is_some() {
+        return Err("You cannot `prank` for a broadcasted transaction. Pass the desired tx.origin into the broadcast cheatcode call".to_string().encode().into())
+    }
+
     if state.prank.is_some() {
         return Err("You have an active prank already.".to_string().encode().into())
     }
 
+    if let Some(reserved) = reserved_address(new_caller).filter(|_| state.deny_reserved_addresses) {
+        return Err(format!("You cannot `prank` from {}.", reserved).encode().into())
+    }
+
     state.prank = Some(prank);
     Ok(Bytes::new())
 }
 
+#[derive(Clone, Debug, Default)]
+pub struct Broadcast {
+    /// Address of the transaction origin
+    pub new_origin: Address,
+    /// Original caller
+    pub original_caller: Address,
+    /// Original `tx.origin`
+    pub original_origin: Address,
+    /// Depth of the broadcast
+    pub depth: u64,
+    /// Whether or not the broadcast stops by itself after the next call
+    pub single_call: bool,
+}
+
+fn broadcast(
+    state: &mut Cheatcodes,
+    new_origin: Address,
+    original_caller: Address,
+    original_origin: Address,
+    depth: u64,
+    single_call: bool,
+) -> Result<Bytes, Bytes> {
+    let broadcast = Broadcast { new_origin, original_caller, original_origin, depth, single_call };
+
+    if state.prank.is_some() {
+        return Err("You have an active prank. Broadcasting and pranks are not compatible. \
+             Disable one or the other"
+            .to_string()
+            .encode()
+            .into())
+    }
+
+    if state.broadcast.is_some() {
+        return Err("You have an active broadcast already.".to_string().encode().into())
+    }
+
+    state.broadcast = Some(broadcast);
+    Ok(Bytes::new())
+}
+
+/// Inserts the accounts of the allocs file at `path` into the state
+///
+/// The file must be readable according to the `fs_permissions` of the config.
+fn load_allocs<DB: Database>(
+    state: &Cheatcodes,
+    data: &mut EVMData<'_, DB>,
+    path: &str,
+) -> Result<Bytes, Bytes> {
+    let path = permitted_path(state, path, FsAccess::Read)?;
+    let allocs = crate::executor::load_allocs(path).map_err(|err| err.to_string().encode())?;
+
+    // TODO: Does this increase gas usage?
+    for (address, account) in allocs {
+        data.subroutine.load_account(address, data.db);
+
+        let balance = data.subroutine.account(address).info.balance;
+        if balance < account.balance {
+            data.subroutine.balance_add(address, account.balance - balance);
+        } else {
+            data.subroutine.balance_sub(address, balance - account.balance);
+        }
+
+        if let Some(nonce) = account.nonce {
+            // we can safely unwrap because `load_account` inserted the account
+            data.subroutine.state().get_mut(&address).unwrap().info.nonce = nonce.as_u64();
+        }
+
+        if let Some(code) = account.code {
+            let hash = H256::from_slice(&keccak256(&code));
+            data.subroutine.set_code(address, code.0, hash);
+        }
+
+        for (slot, value) in account.storage {
+            data.subroutine.sstore(address, slot.0.into(), value.0.into(), data.db);
+        }
+    }
+    Ok(Bytes::new())
+}
+
 #[derive(Clone, Debug, Default)]
 pub struct RecordAccess {
     pub reads: BTreeMap<Address, Vec<U256>>,
@@ -73,6 +161,7 @@ pub fn apply<DB: Database>(
     caller: Address,
     call: &HEVMCalls,
 ) -> Option<Result<Bytes, Bytes>> {
+    let depth = state.depth(data);
     Some(match call {
         HEVMCalls::Warp(inner) => {
             data.env.block.timestamp = inner.0;
@@ -99,6 +188,11 @@ pub fn apply<DB: Database>(
             Ok(val.encode().into())
         }
         HEVMCalls::Etch(inner) => {
+            if let Some(reserved) =
+                reserved_address(inner.0).filter(|_| state.deny_reserved_addresses)
+            {
+                return Err(format!("You cannot `etch` {}.", reserved).encode().into())
+            }
             let code = inner.1.clone();
             let hash = H256::from_slice(&keccak256(&code));
 
@@ -124,29 +218,17 @@ pub fn apply<DB: Database>(
             Ok(Bytes::new())
         }
         HEVMCalls::Prank0(inner) => {
-            prank(state, caller, data.env.tx.caller, inner.0, None, data.subroutine.depth(), true)
-        }
-        HEVMCalls::Prank1(inner) => prank(
-            state,
-            caller,
-            data.env.tx.caller,
-            inner.0,
-            Some(inner.1),
-            data.subroutine.depth(),
-            true,
-        ),
+            prank(state, caller, data.env.tx.caller, inner.0, None, depth, true)
+        }
+        HEVMCalls::Prank1(inner) => {
+            prank(state, caller, data.env.tx.caller, inner.0, Some(inner.1), depth, true)
+        }
         HEVMCalls::StartPrank0(inner) => {
-            prank(state, caller, data.env.tx.caller, inner.0, None, data.subroutine.depth(), false)
-        }
-        HEVMCalls::StartPrank1(inner) => prank(
-            state,
-            caller,
-            data.env.tx.caller,
-            inner.0,
-            Some(inner.1),
-            data.subroutine.depth(),
-            false,
-        ),
+            prank(state, caller, data.env.tx.caller, inner.0, None, depth, false)
+        }
+        HEVMCalls::StartPrank1(inner) => {
+            prank(state, caller, data.env.tx.caller, inner.0, Some(inner.1), depth, false)
+        }
         HEVMCalls::StopPrank(_) => {
             state.prank = None;
             Ok(Bytes::new())
@@ -184,6 +266,23 @@ pub fn apply<DB: Database>(
             data.env.cfg.chain_id = inner.0;
             Ok(Bytes::new())
         }
+        HEVMCalls::Broadcast0(_) => {
+            broadcast(state, data.env.tx.caller, caller, data.env.tx.caller, depth, true)
+        }
+        HEVMCalls::Broadcast1(inner) => {
+            broadcast(state, inner.0, caller, data.env.tx.caller, depth, true)
+        }
+        HEVMCalls::StartBroadcast0(_) => {
+            broadcast(state, data.env.tx.caller, caller, data.env.tx.caller, depth, false)
+        }
+        HEVMCalls::StartBroadcast1(inner) => {
+            broadcast(state, inner.0, caller, data.env.tx.caller, depth, false)
+        }
+        HEVMCalls::StopBroadcast(_) => {
+            state.broadcast = None;
+            Ok(Bytes::new())
+        }
+        HEVMCalls::LoadAllocs(inner) => load_allocs(state, data, &inner.0),
         _ => return None,
     })
 }
@@ -2,10 +2,11 @@ use super::Cheatcodes;
 use crate::abi::HEVMCalls;
 use bytes::Bytes;
 use ethers::{
-    abi::{AbiEncode, RawLog},
-    types::{Address, H160},
+    abi::{Abi, AbiEncode, RawLog},
+    types::{Address, H160, U256},
 };
 use revm::{return_ok, Database, EVMData, Return};
+use std::collections::BTreeMap;
 
 /// For some cheatcodes we may internally change the status of the call, i.e. in `expectRevert`.
 /// Solidity will see a successful call and attempt to decode the return data. Therefore, we need
@@ -44,6 +45,7 @@ pub fn handle_expect_revert(
     expected_revert: &Bytes,
     status: Return,
     retdata: Bytes,
+    errors: Option<&Abi>,
 ) -> Result<(Option<Address>, Bytes), Bytes> {
     if matches!(status, return_ok!()) {
         return Err("Call did not revert as expected".to_string().encode().into())
@@ -53,8 +55,8 @@ pub fn handle_expect_revert(
         return Err("Call reverted as expected, but without data".to_string().encode().into())
     }
 
-    let (err, actual_revert): (_, Bytes) = match retdata {
-        _ if retdata.len() >= 4 && retdata[0..4] == [8, 195, 121, 160] => {
+    let (actual, actual_revert): (_, Bytes) = match retdata {
+        _ if retdata.len() >= 4 && retdata[0..4] == ERROR_SELECTOR => {
             // It's a revert string, so we do some conversion to perform the check
             let decoded_data: Bytes =
                 ethers::abi::decode(&[ethers::abi::ParamType::Bytes], &retdata[4..])
@@ -66,29 +68,15 @@ pub fn handle_expect_revert(
 
             (
                 format!(
-                    "Error != expected error: '{}' != '{}'",
+                    "'{}'",
                     String::from_utf8(decoded_data.to_vec())
                         .ok()
-                        .unwrap_or_else(|| hex::encode(&decoded_data)),
-                    String::from_utf8(expected_revert.to_vec())
-                        .ok()
-                        .unwrap_or_else(|| hex::encode(&expected_revert))
-                )
-                .encode()
-                .into(),
+                        .unwrap_or_else(|| hex::encode(&decoded_data))
+                ),
                 decoded_data,
             )
         }
-        _ => (
-            format!(
-                "Error != expected error: 0x{} != 0x{}",
-                hex::encode(&retdata),
-                hex::encode(&expected_revert)
-            )
-            .encode()
-            .into(),
-            retdata,
-        ),
+        _ => (format_revert(&retdata, errors), retdata),
     };
 
     if actual_revert == expected_revert {
@@ -98,7 +86,48 @@ pub fn handle_expect_revert(
             (None, DUMMY_CALL_OUTPUT.to_vec().into())
         })
     } else {
-        Err(err)
+        Err(format!(
+            "Error != expected error: {} != {}",
+            actual,
+            format_revert(expected_revert, errors)
+        )
+        .encode()
+        .into())
+    }
+}
+
+/// The selector of `Error(string)`
+const ERROR_SELECTOR: [u8; 4] = [8, 195, 121, 160];
+
+/// The selector of `Panic(uint256)`
+const PANIC_SELECTOR: [u8; 4] = [78, 72, 123, 113];
+
+/// Renders revert data for the mismatch reason of `expectRevert`.
+///
+/// Builtin errors and the errors in `errors` are decoded into their name and arguments, a lone
+/// selector is rendered as the signature of its error. Other data is rendered as a string if it
+/// is one, like the reasons passed to `expectRevert`, or as hex.
+fn format_revert(data: &[u8], errors: Option<&Abi>) -> String {
+    if data.len() >= 4 {
+        let selector = &data[..4];
+        let error = errors
+            .into_iter()
+            .flat_map(|abi| abi.errors())
+            .find(|error| error.signature()[..4] == *selector);
+        if let (4, Some(error)) = (data.len(), error) {
+            let types = error.inputs.iter().map(|input| input.kind.to_string()).collect::<Vec<_>>();
+            return format!("{}({})", error.name, types.join(","))
+        }
+        if error.is_some() || selector == ERROR_SELECTOR || selector == PANIC_SELECTOR {
+            if let Ok(decoded) = foundry_utils::decode_revert(data, errors) {
+                return decoded
+            }
+        }
+    }
+
+    match std::str::from_utf8(data) {
+        Ok(reason) if !reason.is_empty() => format!("'{}'", reason),
+        _ => format!("0x{}", hex::encode(data)),
     }
 }
 
@@ -120,6 +149,83 @@ pub struct ExpectedEmit {
     pub found: bool,
 }
 
+impl ExpectedEmit {
+    /// Returns the revert reason if the expected log was not emitted, describing the parts of the
+    /// log that were checked
+    pub fn not_found_reason(&self) -> String {
+        let log = match self.log {
+            Some(ref log) => log,
+            None => return "Expected an emit, but no logs were emitted afterward".to_string(),
+        };
+
+        let mut checked = log
+            .topics
+            .iter()
+            .enumerate()
+            .filter(|(i, _)| *i == 0 || self.checks[i - 1])
+            .map(|(i, topic)| format!("topic {i} {topic:?}"))
+            .collect::<Vec<_>>();
+        if self.checks[3] {
+            checked.push(format!("data 0x{}", hex::encode(&log.data)));
+        }
+        if let Some(address) = self.address {
+            checked.push(format!("emitter {address:?}"));
+        }
+        format!("Log != expected log: no log matched {}", checked.join(", "))
+    }
+}
+
+/// The calls a mock applies to
+#[derive(Clone, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub struct MockCallDataContext {
+    /// The calldata, or a prefix of it
+    pub calldata: Bytes,
+    /// The value of the call, `None` matches any value
+    pub value: Option<U256>,
+}
+
+/// What a mocked call returns
+#[derive(Clone, Debug)]
+pub struct MockCallReturnData {
+    /// Whether the call returns or reverts
+    pub ret_type: Return,
+    /// The returned data or revert data
+    pub data: Bytes,
+}
+
+/// Returns the mock that applies to a call with the given calldata and value.
+///
+/// The mock with the longest matching calldata wins, and a mock for the exact value of the call
+/// takes precedence over a mock for any value.
+pub fn find_mock<'a>(
+    mocks: &'a BTreeMap<MockCallDataContext, MockCallReturnData>,
+    input: &[u8],
+    value: U256,
+) -> Option<&'a MockCallReturnData> {
+    mocks
+        .iter()
+        .filter(|(mock, _)| {
+            input.starts_with(&mock.calldata) && mock.value.map_or(true, |v| v == value)
+        })
+        .max_by_key(|(mock, _)| (mock.calldata.len(), mock.value.is_some()))
+        .map(|(_, ret)| ret)
+}
+
+fn mock_call(
+    state: &mut Cheatcodes,
+    callee: Address,
+    calldata: &[u8],
+    value: Option<U256>,
+    ret_type: Return,
+    data: &[u8],
+) -> Result<Bytes, Bytes> {
+    state.mocked_calls.entry(callee).or_default().insert(
+        MockCallDataContext { calldata: calldata.to_vec().into(), value },
+        MockCallReturnData { ret_type, data: data.to_vec().into() },
+    );
+    Ok(Bytes::new())
+}
+
 pub fn handle_expect_emit(state: &mut Cheatcodes, log: RawLog, address: &Address) {
     // Fill or check the expected emits
     if let Some(next_expect_to_fill) =
@@ -132,7 +238,7 @@ pub fn handle_expect_emit(state: &mut Cheatcodes, log: RawLog, address: &Address
         // log that we expect
         let expected =
             next_expect.log.as_ref().expect("we should have a log to compare against here");
-        if expected.topics[0] == log.topics[0] {
+        if expected.topics.first() == log.topics.first() {
             // Topic 0 can match, but the amount of topics can differ.
             if expected.topics.len() != log.topics.len() {
                 next_expect.found = false;
@@ -165,17 +271,14 @@ pub fn apply<DB: Database>(
     data: &mut EVMData<'_, DB>,
     call: &HEVMCalls,
 ) -> Option<Result<Bytes, Bytes>> {
+    let depth = state.depth(data);
     Some(match call {
-        HEVMCalls::ExpectRevert0(_) => expect_revert(state, Bytes::new(), data.subroutine.depth()),
-        HEVMCalls::ExpectRevert1(inner) => {
-            expect_revert(state, inner.0.to_vec().into(), data.subroutine.depth())
-        }
-        HEVMCalls::ExpectRevert2(inner) => {
-            expect_revert(state, inner.0.to_vec().into(), data.subroutine.depth())
-        }
+        HEVMCalls::ExpectRevert0(_) => expect_revert(state, Bytes::new(), depth),
+        HEVMCalls::ExpectRevert1(inner) => expect_revert(state, inner.0.to_vec().into(), depth),
+        HEVMCalls::ExpectRevert2(inner) => expect_revert(state, inner.0.to_vec().into(), depth),
         HEVMCalls::ExpectEmit0(inner) => {
             state.expected_emits.push(ExpectedEmit {
-                depth: data.subroutine.depth() - 1,
+                depth: depth - 1,
                 checks: [inner.0, inner.1, inner.2, inner.3],
                 ..Default::default()
             });
@@ -183,7 +286,7 @@ pub fn apply<DB: Database>(
         }
         HEVMCalls::ExpectEmit1(inner) => {
             state.expected_emits.push(ExpectedEmit {
-                depth: data.subroutine.depth() - 1,
+                depth: depth - 1,
                 checks: [inner.0, inner.1, inner.2, inner.3],
                 address: Some(inner.4),
                 ..Default::default()
@@ -194,13 +297,17 @@ pub fn apply<DB: Database>(
             state.expected_calls.entry(inner.0).or_default().push(inner.1.to_vec().into());
             Ok(Bytes::new())
         }
-        HEVMCalls::MockCall(inner) => {
-            state
-                .mocked_calls
-                .entry(inner.0)
-                .or_default()
-                .insert(inner.1.to_vec().into(), inner.2.to_vec().into());
-            Ok(Bytes::new())
+        HEVMCalls::MockCall0(inner) => {
+            mock_call(state, inner.0, &inner.1, None, Return::Return, &inner.2)
+        }
+        HEVMCalls::MockCall1(inner) => {
+            mock_call(state, inner.0, &inner.2, Some(inner.1), Return::Return, &inner.3)
+        }
+        HEVMCalls::MockCallRevert0(inner) => {
+            mock_call(state, inner.0, &inner.1, None, Return::Revert, &inner.2)
+        }
+        HEVMCalls::MockCallRevert1(inner) => {
+            mock_call(state, inner.0, &inner.2, Some(inner.1), Return::Revert, &inner.3)
         }
         HEVMCalls::ClearMockedCalls(_) => {
             state.mocked_calls = Default::default();
@@ -209,3 +316,47 @@ pub fn apply<DB: Database>(
         _ => return None,
     })
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ethers::types::H256;
+
+    #[test]
+    fn describes_missing_emit() {
+        let expected = ExpectedEmit::default();
+        assert_eq!(
+            expected.not_found_reason(),
+            "Expected an emit, but no logs were emitted afterward"
+        );
+    }
+
+    #[test]
+    fn describes_checked_parts_of_missing_log() {
+        let topic = |n| H256::from_low_u64_be(n);
+        let mut expected = ExpectedEmit {
+            log: Some(RawLog { topics: vec![topic(1), topic(2), topic(3)], data: vec![0xab] }),
+            checks: [false, true, false, true],
+            ..Default::default()
+        };
+        assert_eq!(
+            expected.not_found_reason(),
+            format!(
+                "Log != expected log: no log matched topic 0 {:?}, topic 2 {:?}, data 0xab",
+                topic(1),
+                topic(3)
+            )
+        );
+
+        expected.checks = [false; 4];
+        expected.address = Some(Address::from_low_u64_be(4));
+        assert_eq!(
+            expected.not_found_reason(),
+            format!(
+                "Log != expected log: no log matched topic 0 {:?}, emitter {:?}",
+                topic(1),
+                Address::from_low_u64_be(4)
+            )
+        );
+    }
+}
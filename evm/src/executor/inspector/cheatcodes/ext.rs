@@ -1,15 +1,80 @@
+use super::Cheatcodes;
 use crate::abi::HEVMCalls;
 use bytes::Bytes;
 use ethers::{
-    abi::{self, AbiEncode, Token},
+    abi::{self, AbiEncode, ParamType, Token},
     prelude::{artifacts::CompactContractBytecode, ProjectPathsConfig},
+    solc::utils::canonicalize,
 };
+use foundry_config::{CheatcodeGroup, FsAccess};
 use serde::Deserialize;
-use std::{fs::File, io::Read, path::Path, process::Command};
+use std::{
+    fs::{self, File},
+    io::Read,
+    path::{Path, PathBuf},
+    process::Command,
+    sync::Arc,
+};
+
+/// Returns the group of a cheatcode that can be denied for sandboxed tests
+fn cheatcode_group(call: &HEVMCalls) -> Option<CheatcodeGroup> {
+    Some(match call {
+        HEVMCalls::Ffi(_) => CheatcodeGroup::Ffi,
+        HEVMCalls::ReadFile(_) |
+        HEVMCalls::WriteFile(_) |
+        HEVMCalls::GetCode(_) |
+        HEVMCalls::LoadAllocs(_) |
+        HEVMCalls::TempDir(_) => CheatcodeGroup::Fs,
+        HEVMCalls::EnvBool0(_) |
+        HEVMCalls::EnvUint0(_) |
+        HEVMCalls::EnvInt0(_) |
+        HEVMCalls::EnvAddress0(_) |
+        HEVMCalls::EnvBytes320(_) |
+        HEVMCalls::EnvString0(_) |
+        HEVMCalls::EnvBytes0(_) |
+        HEVMCalls::EnvBool1(_) |
+        HEVMCalls::EnvUint1(_) |
+        HEVMCalls::EnvInt1(_) |
+        HEVMCalls::EnvAddress1(_) |
+        HEVMCalls::EnvBytes321(_) |
+        HEVMCalls::EnvString1(_) |
+        HEVMCalls::EnvBytes1(_) |
+        HEVMCalls::EnvOr0(_) |
+        HEVMCalls::EnvOr1(_) |
+        HEVMCalls::EnvOr2(_) |
+        HEVMCalls::EnvOr3(_) |
+        HEVMCalls::EnvOr4(_) |
+        HEVMCalls::EnvOr5(_) |
+        HEVMCalls::EnvOr6(_) => CheatcodeGroup::Env,
+        _ => return None,
+    })
+}
+
+/// The error of running an external command while FFI is disabled
+pub(crate) const FFI_DISABLED: &str =
+    "FFI disabled: run again with `--ffi` if you want to allow tests to call external scripts.";
+
+/// The error of calling a cheatcode of a group that is denied to the test
+pub(crate) fn denied(group: CheatcodeGroup) -> String {
+    format!("The `{}` cheatcodes are not allowed in this test, see `sandbox` in the config", group)
+}
+
+/// Returns the scratch directory of the current test, creating it if necessary
+fn temp_dir(state: &mut Cheatcodes) -> Result<PathBuf, Bytes> {
+    if state.temp_dir.is_none() {
+        let dir = tempfile::Builder::new()
+            .prefix("foundry-test-")
+            .tempdir()
+            .map_err(|err| err.to_string().encode())?;
+        state.temp_dir = Some(Arc::new(dir));
+    }
+    Ok(state.temp_dir.as_ref().expect("temp dir was just created").path().to_path_buf())
+}
 
-fn ffi(args: &[String]) -> Result<Bytes, Bytes> {
+fn ffi(args: &[String], temp_dir: &Path) -> Result<Bytes, Bytes> {
     let output = Command::new(&args[0])
         .args(&args[1..])
+        .env("FOUNDRY_TEST_DIR", temp_dir)
         .output()
         .map_err(|err| err.to_string().encode())?
         .stdout;
@@ -48,14 +113,18 @@ struct HardhatArtifact {
     bytecode: ethers::types::Bytes,
 }
 
-fn get_code(path: &str) -> Result<Bytes, Bytes> {
+fn get_code(state: &Cheatcodes, path: &str) -> Result<Bytes, Bytes> {
     let path = if path.ends_with(".json") {
-        Path::new(&path).to_path_buf()
+        // artifacts can be read from anywhere the test may read files
+        permitted_path(state, path, FsAccess::Read)?
     } else {
         let parts: Vec<&str> = path.split(':').collect();
         let file = parts[0];
-        let contract_name =
-            if parts.len() == 1 { parts[0].replace(".sol", "") } else { parts[1].to_string() };
+        let contract_name = if parts.len() == 1 {
+            file.trim_end_matches(".sol").trim_end_matches(".vy").to_string()
+        } else {
+            parts[1].to_string()
+        };
         let out_dir = ProjectPathsConfig::find_artifacts_dir(Path::new("./"));
         out_dir.join(format!("{file}/{contract_name}.json"))
     };
@@ -76,16 +145,158 @@ fn get_code(path: &str) -> Result<Bytes, Bytes> {
     }
 }
 
-pub fn apply(ffi_enabled: bool, call: &HEVMCalls) -> Option<Result<Bytes, Bytes>> {
+/// Resolves `path` relative to the project root and checks that it may be accessed.
+///
+/// Paths are canonicalized before they are checked, so that neither `..` components nor symlinks
+/// can be used to escape the permitted directories. Files that do not exist yet are resolved via
+/// their parent directory.
+pub(crate) fn permitted_path(
+    state: &Cheatcodes,
+    path: &str,
+    access: FsAccess,
+) -> Result<PathBuf, Bytes> {
+    let display = path;
+    let path = state.root.join(path);
+    let path = path.as_path();
+    let resolved = match canonicalize(path) {
+        Ok(resolved) => resolved,
+        Err(_) if access != FsAccess::Read => {
+            let parent = match path.parent() {
+                Some(parent) if !parent.as_os_str().is_empty() => parent,
+                _ => Path::new("."),
+            };
+            let file_name = path
+                .file_name()
+                .ok_or_else(|| format!("Invalid file path {}", display).encode())?;
+            canonicalize(parent).map_err(|err| err.to_string().encode())?.join(file_name)
+        }
+        Err(err) => return Err(err.to_string().encode().into()),
+    };
+
+    if !state.fs_permissions.is_allowed(&resolved, access) {
+        let kind = if access == FsAccess::Read { "read" } else { "written" };
+        return Err(format!(
+            "The path {} is not allowed to be {}, see `fs_permissions` in the config",
+            display, kind
+        )
+        .encode()
+        .into())
+    }
+    Ok(resolved)
+}
+
+fn read_file(state: &Cheatcodes, path: &str) -> Result<Bytes, Bytes> {
+    let path = permitted_path(state, path, FsAccess::Read)?;
+    let content = fs::read_to_string(path).map_err(|err| err.to_string().encode())?;
+    Ok(abi::encode(&[Token::String(content)]).into())
+}
+
+fn write_file(state: &Cheatcodes, path: &str, content: &str) -> Result<Bytes, Bytes> {
+    let path = permitted_path(state, path, FsAccess::Write)?;
+    fs::write(path, content).map_err(|err| err.to_string().encode())?;
+    Ok(Bytes::new())
+}
+
+/// Reads the environment variable `key` and parses it as `ty`
+fn env(key: &str, ty: ParamType) -> Result<Bytes, Bytes> {
+    let value = env_var(key)?;
+    let token = parse_env(key, &value, &ty)?;
+    Ok(abi::encode(&[token]).into())
+}
+
+/// Reads the environment variable `key` and parses it as an array of `ty`, separated by `delim`
+fn env_array(key: &str, delim: &str, ty: ParamType) -> Result<Bytes, Bytes> {
+    let value = env_var(key)?;
+    let tokens = if value.trim().is_empty() {
+        Vec::new()
+    } else {
+        value
+            .split(delim)
+            .map(|item| parse_env(key, item.trim(), &ty))
+            .collect::<Result<Vec<_>, _>>()?
+    };
+    Ok(abi::encode(&[Token::Array(tokens)]).into())
+}
+
+/// Reads the environment variable `key` as the type of `default`, or returns `default` if the
+/// variable is not set
+fn env_or(key: &str, default: Token, ty: ParamType) -> Result<Bytes, Bytes> {
+    match std::env::var(key) {
+        Ok(value) => parse_env(key, &value, &ty).map(|token| abi::encode(&[token]).into()),
+        Err(std::env::VarError::NotPresent) => Ok(abi::encode(&[default]).into()),
+        Err(err) => {
+            Err(format!("Failed to read environment variable `{}`: {}", key, err).encode().into())
+        }
+    }
+}
+
+fn env_var(key: &str) -> Result<String, Bytes> {
+    std::env::var(key).map_err(|err| {
+        format!("Failed to read environment variable `{}`: {}", key, err).encode().into()
+    })
+}
+
+fn parse_env(key: &str, value: &str, ty: &ParamType) -> Result<Token, Bytes> {
+    let value = if *ty == ParamType::Bool { value.to_lowercase() } else { value.to_string() };
+    foundry_utils::parse_tokens(std::iter::once((ty, value.as_str())), true)
+        .ok()
+        .and_then(|mut tokens| tokens.pop())
+        .ok_or_else(|| {
+            format!("Failed to parse environment variable `{}` as {}: `{}`", key, ty, value)
+                .encode()
+                .into()
+        })
+}
+
+/// Returns an error if the cheatcode belongs to a group that is denied to the test
+pub(crate) fn check_denied(state: &Cheatcodes, call: &HEVMCalls) -> Result<(), Bytes> {
+    match cheatcode_group(call).filter(|group| state.denied_cheatcodes.contains(group)) {
+        Some(group) => Err(denied(group).encode().into()),
+        None => Ok(()),
+    }
+}
+
+pub fn apply(state: &mut Cheatcodes, call: &HEVMCalls) -> Option<Result<Bytes, Bytes>> {
     Some(match call {
-        HEVMCalls::Ffi(inner) => {
-            if !ffi_enabled {
-                Err("FFI disabled: run again with `--ffi` if you want to allow tests to call external scripts.".to_string().encode().into())
-            } else {
-                ffi(&inner.0)
-            }
+        HEVMCalls::Ffi(inner) => state
+            .check_ffi()
+            .map_err(|err| err.encode().into())
+            .and_then(|_| temp_dir(state))
+            .and_then(|dir| ffi(&inner.0, &dir)),
+        HEVMCalls::GetCode(inner) => get_code(state, &inner.0),
+        HEVMCalls::TempDir(_) => temp_dir(state)
+            .map(|dir| abi::encode(&[Token::String(dir.to_string_lossy().into_owned())]).into()),
+        HEVMCalls::ReadFile(inner) => read_file(state, &inner.0),
+        HEVMCalls::WriteFile(inner) => write_file(state, &inner.0, &inner.1),
+        HEVMCalls::EnvBool0(inner) => env(&inner.0, ParamType::Bool),
+        HEVMCalls::EnvUint0(inner) => env(&inner.0, ParamType::Uint(256)),
+        HEVMCalls::EnvInt0(inner) => env(&inner.0, ParamType::Int(256)),
+        HEVMCalls::EnvAddress0(inner) => env(&inner.0, ParamType::Address),
+        HEVMCalls::EnvBytes320(inner) => env(&inner.0, ParamType::FixedBytes(32)),
+        HEVMCalls::EnvString0(inner) => env(&inner.0, ParamType::String),
+        HEVMCalls::EnvBytes0(inner) => env(&inner.0, ParamType::Bytes),
+        HEVMCalls::EnvBool1(inner) => env_array(&inner.0, &inner.1, ParamType::Bool),
+        HEVMCalls::EnvUint1(inner) => env_array(&inner.0, &inner.1, ParamType::Uint(256)),
+        HEVMCalls::EnvInt1(inner) => env_array(&inner.0, &inner.1, ParamType::Int(256)),
+        HEVMCalls::EnvAddress1(inner) => env_array(&inner.0, &inner.1, ParamType::Address),
+        HEVMCalls::EnvBytes321(inner) => env_array(&inner.0, &inner.1, ParamType::FixedBytes(32)),
+        HEVMCalls::EnvString1(inner) => env_array(&inner.0, &inner.1, ParamType::String),
+        HEVMCalls::EnvBytes1(inner) => env_array(&inner.0, &inner.1, ParamType::Bytes),
+        HEVMCalls::EnvOr0(inner) => env_or(&inner.0, Token::Bool(inner.1), ParamType::Bool),
+        HEVMCalls::EnvOr1(inner) => env_or(&inner.0, Token::Uint(inner.1), ParamType::Uint(256)),
+        HEVMCalls::EnvOr2(inner) => {
+            env_or(&inner.0, Token::Int(inner.1.into_raw()), ParamType::Int(256))
+        }
+        HEVMCalls::EnvOr3(inner) => env_or(&inner.0, Token::Address(inner.1), ParamType::Address),
+        HEVMCalls::EnvOr4(inner) => {
+            env_or(&inner.0, Token::FixedBytes(inner.1.to_vec()), ParamType::FixedBytes(32))
+        }
+        HEVMCalls::EnvOr5(inner) => {
+            env_or(&inner.0, Token::String(inner.1.clone()), ParamType::String)
+        }
+        HEVMCalls::EnvOr6(inner) => {
+            env_or(&inner.0, Token::Bytes(inner.1.to_vec()), ParamType::Bytes)
         }
-        HEVMCalls::GetCode(inner) => get_code(&inner.0),
         _ => return None,
     })
 }
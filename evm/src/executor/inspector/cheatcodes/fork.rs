@@ -0,0 +1,120 @@
+use super::Cheatcodes;
+use crate::{abi::HEVMCalls, executor::CHEATCODE_ADDRESS, CALLER};
+use bytes::Bytes;
+use ethers::{
+    abi::AbiEncode,
+    types::{Address, U256},
+};
+use revm::{Database, EVMData};
+
+/// Returns an error if forks can not be created in this context
+fn not_enabled() -> Bytes {
+    "Forks can not be created here, forking cheatcodes are only supported in tests"
+        .to_string()
+        .encode()
+        .into()
+}
+
+fn create_fork<DB: Database>(
+    state: &mut Cheatcodes,
+    data: &EVMData<'_, DB>,
+    caller: Address,
+    url: &str,
+    block: Option<U256>,
+) -> Result<Bytes, Bytes> {
+    let forks = state.forks.as_ref().ok_or_else(not_enabled)?;
+    let block = block
+        .map(|block| {
+            u64::try_from(block).map_err(|_| format!("Invalid block number {}", block).encode())
+        })
+        .transpose()?;
+    let mut forks = forks.write();
+
+    // the test contract and the accounts driving the test keep their state across forks
+    for address in [caller, *CALLER, CHEATCODE_ADDRESS, data.env.tx.caller] {
+        forks.make_persistent(address);
+    }
+
+    let id = forks.create_fork(url, block, &*data.env).map_err(|err| err.to_string().encode())?;
+    Ok(U256::from(id).encode().into())
+}
+
+fn select_fork<DB: Database>(
+    state: &mut Cheatcodes,
+    data: &mut EVMData<'_, DB>,
+    id: U256,
+) -> Result<Bytes, Bytes> {
+    let forks = state.forks.as_ref().ok_or_else(not_enabled)?;
+    let id = usize::try_from(id).map_err(|_| format!("Fork {} does not exist", id).encode())?;
+    let reloaded = {
+        let mut forks = forks.write();
+
+        // keep the changes made on the current fork for when it is selected again
+        let changed = data
+            .subroutine
+            .state()
+            .iter()
+            .filter(|(address, _)| !forks.is_persistent(address))
+            .map(|(address, account)| (*address, account.clone()))
+            .collect();
+        forks.commit(changed);
+
+        let env = forks.select_fork(id).map_err(|err| err.to_string().encode())?.clone();
+        data.env.block = env.block;
+        data.env.cfg.chain_id = env.cfg.chain_id;
+
+        data.subroutine
+            .state()
+            .keys()
+            .filter(|address| !forks.is_persistent(address))
+            .copied()
+            .collect::<Vec<_>>()
+    };
+
+    // accounts that were already loaded in this call are reloaded from the selected fork
+    for address in reloaded {
+        let info = data.db.basic(address);
+        let account = data.subroutine.state().get_mut(&address).expect("account is loaded");
+        account.info = info;
+        account.storage.clear();
+    }
+    Ok(Bytes::new())
+}
+
+fn make_persistent(state: &mut Cheatcodes, addresses: &[Address]) -> Result<Bytes, Bytes> {
+    let forks = state.forks.as_ref().ok_or_else(not_enabled)?;
+    let mut forks = forks.write();
+    addresses.iter().for_each(|address| forks.make_persistent(*address));
+    Ok(Bytes::new())
+}
+
+pub fn apply<DB: Database>(
+    state: &mut Cheatcodes,
+    data: &mut EVMData<'_, DB>,
+    caller: Address,
+    call: &HEVMCalls,
+) -> Option<Result<Bytes, Bytes>> {
+    Some(match call {
+        HEVMCalls::CreateFork0(inner) => create_fork(state, data, caller, &inner.0, None),
+        HEVMCalls::CreateFork1(inner) => create_fork(state, data, caller, &inner.0, Some(inner.1)),
+        HEVMCalls::SelectFork(inner) => select_fork(state, data, inner.0),
+        HEVMCalls::ActiveFork(_) => {
+            state.forks.as_ref().ok_or_else(not_enabled).and_then(|forks| {
+                match forks.read().active() {
+                    Some(id) => Ok(U256::from(id).encode().into()),
+                    None => Err("No fork is selected".to_string().encode().into()),
+                }
+            })
+        }
+        HEVMCalls::MakePersistent0(inner) => make_persistent(state, &[inner.0]),
+        HEVMCalls::MakePersistent1(inner) => make_persistent(state, &inner.0),
+        HEVMCalls::IsPersistent(inner) => Ok(state
+            .forks
+            .as_ref()
+            .map(|forks| forks.read().is_persistent(&inner.0))
+            .unwrap_or_default()
+            .encode()
+            .into()),
+        _ => return None,
+    })
+}
@@ -0,0 +1,49 @@
+use super::Cheatcodes;
+use crate::abi::HEVMCalls;
+use bytes::Bytes;
+use ethers::{abi::AbiEncode, types::U256};
+use std::collections::BTreeMap;
+
+/// Gas measurements of named code regions, started with `startSnapshotGas` and stopped with
+/// `stopSnapshotGas`
+#[derive(Clone, Debug, Default)]
+pub struct GasSnapshots {
+    /// The depth and the gas spent by the caller when the last cheatcode was called
+    pub last_call: (u64, u64),
+    /// The depth and the gas spent when the regions that are still measured were started, by name
+    pub active: BTreeMap<String, (u64, u64)>,
+    /// The gas used by the finished regions, by name
+    pub finished: BTreeMap<String, u64>,
+}
+
+pub fn apply(state: &mut Cheatcodes, call: &HEVMCalls) -> Option<Result<Bytes, Bytes>> {
+    let snapshots = &mut state.gas_snapshots;
+    Some(match call {
+        HEVMCalls::StartSnapshotGas(inner) => {
+            if snapshots.active.contains_key(&inner.0) {
+                Err(format!("Gas snapshot `{}` was already started", inner.0).encode().into())
+            } else {
+                snapshots.active.insert(inner.0.clone(), snapshots.last_call);
+                Ok(Bytes::new())
+            }
+        }
+        HEVMCalls::StopSnapshotGas(inner) => {
+            let (depth, last_spent) = snapshots.last_call;
+            match snapshots.active.remove(&inner.0) {
+                None => Err(format!("Gas snapshot `{}` was not started", inner.0).encode().into()),
+                Some((start_depth, _)) if start_depth != depth => Err(format!(
+                    "Gas snapshot `{}` must be stopped in the call that started it",
+                    inner.0
+                )
+                .encode()
+                .into()),
+                Some((_, start_spent)) => {
+                    let gas_used = last_spent.saturating_sub(start_spent);
+                    snapshots.finished.insert(inner.0.clone(), gas_used);
+                    Ok(U256::from(gas_used).encode().into())
+                }
+            }
+        }
+        _ => return None,
+    })
+}
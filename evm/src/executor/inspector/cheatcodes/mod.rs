@@ -1,31 +1,47 @@
 /// Cheatcodes related to the execution environment.
 mod env;
-pub use env::{Prank, RecordAccess};
+pub use env::{Broadcast, Prank, RecordAccess};
 /// Assertion helpers (such as `expectEmit`)
 mod expect;
-pub use expect::{ExpectedEmit, ExpectedRevert};
+pub use expect::{ExpectedEmit, ExpectedRevert, MockCallDataContext, MockCallReturnData};
 /// Cheatcodes that interact with the external environment (FFI etc.)
 mod ext;
+/// Cheatcodes that create and select forks
+mod fork;
 /// Cheatcodes that configure the fuzzer
 mod fuzz;
+/// Cheatcodes that measure the gas of code regions (`startSnapshotGas` etc.)
+mod gas;
+pub use gas::GasSnapshots;
 /// Utility cheatcodes (`sign` etc.)
 mod util;
 
-use self::expect::{handle_expect_emit, handle_expect_revert};
+use self::expect::{find_mock, handle_expect_emit, handle_expect_revert};
 use crate::{
     abi::HEVMCalls,
-    executor::{CHEATCODE_ADDRESS, HARDHAT_CONSOLE_ADDRESS},
+    executor::{fork::SharedForkState, CHEATCODE_ADDRESS, HARDHAT_CONSOLE_ADDRESS},
 };
 use bytes::Bytes;
 use ethers::{
-    abi::{AbiDecode, AbiEncode, RawLog},
-    types::{Address, H256},
+    abi::{Abi, AbiDecode, AbiEncode, RawLog},
+    types::{
+        transaction::eip2718::TypedTransaction, Address, NameOrAddress, TransactionRequest, H256,
+    },
 };
+use foundry_config::{CheatcodeGroup, FsPermissions};
 use revm::{
-    opcode, BlockEnv, CallInputs, CreateInputs, Database, EVMData, Gas, Inspector, Interpreter,
-    Return,
+    opcode, BlockEnv, CallInputs, CallScheme, CreateInputs, CreateScheme, Database, EVMData, Gas,
+    Inspector, Interpreter, Return,
 };
-use std::collections::BTreeMap;
+use std::{
+    collections::{BTreeMap, VecDeque},
+    path::PathBuf,
+    sync::Arc,
+};
+use tempfile::TempDir;
+
+/// The revert reason of a test that was skipped with the `skip` cheatcode
+pub const MAGIC_SKIP_REASON: &str = "FOUNDRY::SKIP";
 
 /// An inspector that handles calls to various cheatcodes, each with their own behavior.
 ///
@@ -36,6 +52,21 @@ pub struct Cheatcodes {
     /// Whether FFI is enabled or not
     pub ffi: bool,
 
+    /// The paths the `readFile`, `writeFile`, `loadAllocs` and `getCode` cheatcodes may access
+    pub fs_permissions: FsPermissions,
+
+    /// The root of the project, relative paths passed to cheatcodes are resolved against it
+    pub root: PathBuf,
+
+    /// The cheatcodes that are denied because the test is sandboxed
+    pub denied_cheatcodes: Vec<CheatcodeGroup>,
+
+    /// Whether `prank` and `etch` fail if they are used with a reserved address
+    pub deny_reserved_addresses: bool,
+
+    /// All known errors, used to decode the reverts in the reason of a failed `expectRevert`
+    pub errors: Option<Arc<Abi>>,
+
     /// The block environment
     ///
     /// Used in the cheatcode handler to overwrite the block environment separately from the
@@ -55,13 +86,35 @@ pub struct Cheatcodes {
     pub accesses: Option<RecordAccess>,
 
     /// Mocked calls
-    pub mocked_calls: BTreeMap<Address, BTreeMap<Bytes, Bytes>>,
+    pub mocked_calls: BTreeMap<Address, BTreeMap<MockCallDataContext, MockCallReturnData>>,
 
     /// Expected calls
     pub expected_calls: BTreeMap<Address, Vec<Bytes>>,
 
     /// Expected emits
     pub expected_emits: Vec<ExpectedEmit>,
+
+    /// Current broadcasting information
+    pub broadcast: Option<Broadcast>,
+
+    /// Transactions recorded via `broadcast` and `startBroadcast` that can later be signed and
+    /// sent onchain
+    pub broadcastable_transactions: VecDeque<TypedTransaction>,
+
+    /// The scratch directory of the current test, created on first use.
+    ///
+    /// The directory is removed once the last reference to it is dropped.
+    pub temp_dir: Option<Arc<TempDir>>,
+
+    /// The forks created by the current test, if forks can be created
+    pub forks: Option<SharedForkState>,
+
+    /// Gas measurements of named code regions
+    pub gas_snapshots: GasSnapshots,
+
+    /// The depth of the call that is executed as an isolated transaction, whose calls start at
+    /// depth 0 again, see [InspectorStack](crate::executor::inspector::InspectorStack)
+    pub(crate) depth_offset: u64,
 }
 
 impl Cheatcodes {
@@ -69,6 +122,23 @@ impl Cheatcodes {
         Self { ffi, block: Some(block), ..Default::default() }
     }
 
+    /// Returns an error if the test can not run external commands, because FFI is disabled or
+    /// denied by the sandbox
+    pub fn check_ffi(&self) -> Result<(), String> {
+        if self.denied_cheatcodes.contains(&CheatcodeGroup::Ffi) {
+            Err(ext::denied(CheatcodeGroup::Ffi))
+        } else if !self.ffi {
+            Err(ext::FFI_DISABLED.to_string())
+        } else {
+            Ok(())
+        }
+    }
+
+    /// The depth of the current call in the test transaction
+    pub(crate) fn depth<DB: Database>(&self, data: &EVMData<'_, DB>) -> u64 {
+        self.depth(data) + self.depth_offset
+    }
+
     fn apply_cheatcode<DB: Database>(
         &mut self,
         data: &mut EVMData<'_, DB>,
@@ -77,13 +147,16 @@ impl Cheatcodes {
     ) -> Result<Bytes, Bytes> {
         // Decode the cheatcode call
         let decoded = HEVMCalls::decode(&call.input).map_err(|err| err.to_string().encode())?;
+        ext::check_denied(self, &decoded)?;
 
         // TODO: Log the opcode for the debugger
         env::apply(self, data, caller, &decoded)
             .or_else(|| util::apply(self, data, &decoded))
             .or_else(|| expect::apply(self, data, &decoded))
             .or_else(|| fuzz::apply(data, &decoded))
-            .or_else(|| ext::apply(self.ffi, &decoded))
+            .or_else(|| ext::apply(self, &decoded))
+            .or_else(|| fork::apply(self, data, caller, &decoded))
+            .or_else(|| gas::apply(self, &decoded))
             .ok_or_else(|| "Cheatcode was unhandled. This is a bug.".to_string().encode())?
     }
 }
@@ -115,22 +188,16 @@ where
 
             // Handle mocked calls
             if let Some(mocks) = self.mocked_calls.get(&call.contract) {
-                if let Some(mock_retdata) = mocks.get(&call.input) {
-                    return (Return::Return, Gas::new(call.gas_limit), mock_retdata.clone())
-                } else if let Some((_, mock_retdata)) =
-                    mocks.iter().find(|(mock, _)| *mock == &call.input[..mock.len()])
-                {
-                    return (Return::Return, Gas::new(call.gas_limit), mock_retdata.clone())
+                if let Some(mock) = find_mock(mocks, &call.input, call.transfer.value) {
+                    return (mock.ret_type, Gas::new(call.gas_limit), mock.data.clone())
                 }
             }
 
             // Apply our prank
             if let Some(prank) = &self.prank {
-                if data.subroutine.depth() >= prank.depth &&
-                    call.context.caller == prank.prank_caller
-                {
+                if self.depth(data) >= prank.depth && call.context.caller == prank.prank_caller {
                     // At the target depth we set `msg.sender`
-                    if data.subroutine.depth() == prank.depth {
+                    if self.depth(data) == prank.depth {
                         call.context.caller = prank.new_caller;
                         call.transfer.source = prank.new_caller;
                     }
@@ -142,6 +209,56 @@ where
                 }
             }
 
+            // Record the call as a transaction if we are broadcasting
+            if let Some(broadcast) = &self.broadcast {
+                if self.depth(data) == broadcast.depth &&
+                    call.context.caller == broadcast.original_caller
+                {
+                    if call.context.scheme == CallScheme::StaticCall {
+                        // Static calls can't be sent as transactions, so they are only an error if
+                        // they would consume a single-call broadcast
+                        if broadcast.single_call {
+                            return (
+                                Return::Revert,
+                                Gas::new(call.gas_limit),
+                                "Staticcalls are not allowed after `broadcast`. Either remove it, or use `startBroadcast` instead."
+                                    .to_string()
+                                    .encode()
+                                    .into(),
+                            )
+                        }
+                    } else {
+                        // At the target depth we set `msg.sender` and `tx.origin`
+                        call.context.caller = broadcast.new_origin;
+                        call.transfer.source = broadcast.new_origin;
+                        data.env.tx.caller = broadcast.new_origin;
+
+                        data.subroutine.load_account(broadcast.new_origin, data.db);
+                        // we can safely unwrap because `load_account` inserts the account
+                        let account =
+                            data.subroutine.state().get_mut(&broadcast.new_origin).unwrap();
+
+                        // We record a legacy transaction here since we only need `from`, `to`,
+                        // `value` and `data`. It can be converted to the required transaction
+                        // type once the target chain is known.
+                        self.broadcastable_transactions.push_back(TypedTransaction::Legacy(
+                            TransactionRequest {
+                                from: Some(broadcast.new_origin),
+                                to: Some(NameOrAddress::Address(call.contract)),
+                                value: Some(call.transfer.value),
+                                data: Some(call.input.clone().into()),
+                                nonce: Some(account.info.nonce.into()),
+                                ..Default::default()
+                            },
+                        ));
+
+                        // Calls do not increase the nonce of the caller in the EVM, but the
+                        // transaction will, so we do it ourselves
+                        account.info.nonce += 1;
+                    }
+                }
+            }
+
             (Return::Continue, Gas::new(call.gas_limit), Bytes::new())
         } else {
             (Return::Continue, Gas::new(call.gas_limit), Bytes::new())
@@ -158,12 +275,35 @@ where
         // so we apply our actual block data with the correct fees and all.
         if let Some(block) = self.block.take() {
             data.env.block = block;
+
+            // a fork selected in an earlier call, e.g. in `setUp`, also determines the chain id
+            if let Some(ref forks) = self.forks {
+                if let Some(env) = forks.read().active_env() {
+                    data.env.cfg.chain_id = env.cfg.chain_id;
+                }
+            }
         }
 
         Return::Continue
     }
 
-    fn step(&mut self, interpreter: &mut Interpreter, _: &mut EVMData<'_, DB>, _: bool) -> Return {
+    fn step(
+        &mut self,
+        interpreter: &mut Interpreter,
+        data: &mut EVMData<'_, DB>,
+        _: bool,
+    ) -> Return {
+        // Record the gas spent before cheatcode calls, so `startSnapshotGas` and `stopSnapshotGas`
+        // can measure the gas used in between
+        let op = interpreter.contract.code[interpreter.program_counter()];
+        if op == opcode::CALL || op == opcode::STATICCALL {
+            let mut to = [0u8; 32];
+            try_or_continue!(interpreter.stack().peek(1)).to_big_endian(&mut to);
+            if Address::from_slice(&to[12..]) == CHEATCODE_ADDRESS {
+                self.gas_snapshots.last_call = (self.depth(data), interpreter.gas().spend());
+            }
+        }
+
         // Record writes and reads if `record` has been called
         if let Some(storage_accesses) = &mut self.accesses {
             match interpreter.contract.code[interpreter.program_counter()] {
@@ -221,21 +361,37 @@ where
             return (status, remaining_gas, retdata)
         }
 
-        // Clean up pranks
+        // Clean up pranks once the pranked call returns, nested calls must not end the prank
         if let Some(prank) = &self.prank {
-            if data.subroutine.depth() == prank.depth {
+            if self.depth(data) <= prank.depth {
                 data.env.tx.caller = prank.prank_origin;
+                if prank.single_call {
+                    std::mem::take(&mut self.prank);
+                }
             }
-            if prank.single_call {
-                std::mem::take(&mut self.prank);
+        }
+
+        // Clean up broadcasts
+        if let Some(broadcast) = &self.broadcast {
+            if self.depth(data) == broadcast.depth {
+                data.env.tx.caller = broadcast.original_origin;
+                if broadcast.single_call {
+                    std::mem::take(&mut self.broadcast);
+                }
             }
         }
 
         // Handle expected reverts
         if let Some(expected_revert) = &self.expected_revert {
-            if data.subroutine.depth() <= expected_revert.depth {
+            if self.depth(data) <= expected_revert.depth {
                 let expected_revert = std::mem::take(&mut self.expected_revert).unwrap();
-                return match handle_expect_revert(false, &expected_revert.reason, status, retdata) {
+                return match handle_expect_revert(
+                    false,
+                    &expected_revert.reason,
+                    status,
+                    retdata,
+                    self.errors.as_deref(),
+                ) {
                     Err(retdata) => (Return::Revert, remaining_gas, retdata),
                     Ok((_, retdata)) => (Return::Return, remaining_gas, retdata),
                 }
@@ -243,24 +399,19 @@ where
         }
 
         // Handle expected emits at current depth
-        if !self
+        if let Some(expected) = self
             .expected_emits
             .iter()
-            .filter(|expected| expected.depth == data.subroutine.depth())
-            .all(|expected| expected.found)
+            .find(|expected| expected.depth == self.depth(data) && !expected.found)
         {
-            return (
-                Return::Revert,
-                remaining_gas,
-                "Log != expected log".to_string().encode().into(),
-            )
+            return (Return::Revert, remaining_gas, expected.not_found_reason().encode().into())
         } else {
             // Clear the emits we expected at this depth that have been found
             self.expected_emits.retain(|expected| !expected.found)
         }
 
         // If the depth is 0, then this is the root call terminating
-        if data.subroutine.depth() == 0 {
+        if self.depth(data) == 0 {
             // Handle expected calls that were not fulfilled
             if let Some((address, expecteds)) =
                 self.expected_calls.iter().find(|(_, expecteds)| !expecteds.is_empty())
@@ -279,15 +430,8 @@ where
             }
 
             // Check if we have any leftover expected emits
-            if !self.expected_emits.is_empty() {
-                return (
-                    Return::Revert,
-                    remaining_gas,
-                    "Expected an emit, but no logs were emitted afterward"
-                        .to_string()
-                        .encode()
-                        .into(),
-                )
+            if let Some(expected) = self.expected_emits.first() {
+                return (Return::Revert, remaining_gas, expected.not_found_reason().encode().into())
             }
         }
 
@@ -301,9 +445,9 @@ where
     ) -> (Return, Option<Address>, Gas, Bytes) {
         // Apply our prank
         if let Some(prank) = &self.prank {
-            if data.subroutine.depth() >= prank.depth && call.caller == prank.prank_caller {
+            if self.depth(data) >= prank.depth && call.caller == prank.prank_caller {
                 // At the target depth we set `msg.sender`
-                if data.subroutine.depth() == prank.depth {
+                if self.depth(data) == prank.depth {
                     call.caller = prank.new_caller;
                 }
 
@@ -314,6 +458,42 @@ where
             }
         }
 
+        // Record the create as a transaction if we are broadcasting
+        if let Some(broadcast) = &self.broadcast {
+            if self.depth(data) == broadcast.depth && call.caller == broadcast.original_caller {
+                if let CreateScheme::Create2 { .. } = call.scheme {
+                    return (
+                        Return::Revert,
+                        None,
+                        Gas::new(call.gas_limit),
+                        "CREATE2 is not supported for broadcasted transactions."
+                            .to_string()
+                            .encode()
+                            .into(),
+                    )
+                }
+
+                // At the target depth we set `msg.sender` and `tx.origin`
+                call.caller = broadcast.new_origin;
+                data.env.tx.caller = broadcast.new_origin;
+
+                data.subroutine.load_account(broadcast.new_origin, data.db);
+                // we can safely unwrap because `load_account` inserts the account
+                let nonce = data.subroutine.state().get(&broadcast.new_origin).unwrap().info.nonce;
+
+                self.broadcastable_transactions.push_back(TypedTransaction::Legacy(
+                    TransactionRequest {
+                        from: Some(broadcast.new_origin),
+                        to: None,
+                        value: Some(call.value),
+                        data: Some(call.init_code.clone().into()),
+                        nonce: Some(nonce.into()),
+                        ..Default::default()
+                    },
+                ));
+            }
+        }
+
         (Return::Continue, None, Gas::new(call.gas_limit), Bytes::new())
     }
 
@@ -326,21 +506,37 @@ where
         remaining_gas: Gas,
         retdata: Bytes,
     ) -> (Return, Option<Address>, Gas, Bytes) {
-        // Clean up pranks
+        // Clean up pranks once the pranked call returns, nested calls must not end the prank
         if let Some(prank) = &self.prank {
-            if data.subroutine.depth() == prank.depth {
+            if self.depth(data) <= prank.depth {
                 data.env.tx.caller = prank.prank_origin;
+                if prank.single_call {
+                    std::mem::take(&mut self.prank);
+                }
             }
-            if prank.single_call {
-                std::mem::take(&mut self.prank);
+        }
+
+        // Clean up broadcasts
+        if let Some(broadcast) = &self.broadcast {
+            if self.depth(data) == broadcast.depth {
+                data.env.tx.caller = broadcast.original_origin;
+                if broadcast.single_call {
+                    std::mem::take(&mut self.broadcast);
+                }
             }
         }
 
         // Handle expected reverts
         if let Some(expected_revert) = &self.expected_revert {
-            if data.subroutine.depth() <= expected_revert.depth {
+            if self.depth(data) <= expected_revert.depth {
                 let expected_revert = std::mem::take(&mut self.expected_revert).unwrap();
-                return match handle_expect_revert(true, &expected_revert.reason, status, retdata) {
+                return match handle_expect_revert(
+                    true,
+                    &expected_revert.reason,
+                    status,
+                    retdata,
+                    self.errors.as_deref(),
+                ) {
                     Err(retdata) => (Return::Revert, None, remaining_gas, retdata),
                     Ok((address, retdata)) => (Return::Return, address, remaining_gas, retdata),
                 }
@@ -8,7 +8,7 @@ use ethers::{
 };
 use revm::{Database, EVMData};
 
-use super::Cheatcodes;
+use super::{Cheatcodes, MAGIC_SKIP_REASON};
 
 fn addr(private_key: U256) -> Result<Bytes, Bytes> {
     if private_key.is_zero() {
@@ -60,6 +60,13 @@ pub fn apply<DB: Database>(
             state.labels.insert(inner.0, inner.1.clone());
             Ok(Bytes::new())
         }
+        HEVMCalls::Skip(inner) => {
+            if inner.0 {
+                Err(MAGIC_SKIP_REASON.to_string().encode().into())
+            } else {
+                Ok(Bytes::new())
+            }
+        }
         _ => return None,
     })
 }
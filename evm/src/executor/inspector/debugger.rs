@@ -45,6 +45,8 @@ pub struct Debugger {
     ///
     /// For more information on gas blocks, see [current_gas_block].
     pub previous_gas_block: u64,
+    /// Added to the depth of the calls of an isolated transaction, which start at depth 0 again
+    pub(crate) depth_offset: usize,
 }
 
 impl Debugger {
@@ -101,7 +103,7 @@ where
         _: bool,
     ) -> (Return, Gas, Bytes) {
         self.enter(
-            data.subroutine.depth() as usize,
+            data.subroutine.depth() as usize + self.depth_offset,
             call.context.code_address,
             call.context.scheme.into(),
         );
@@ -207,7 +209,7 @@ where
         data.subroutine.load_account(call.caller, data.db);
         let nonce = data.subroutine.account(call.caller).info.nonce;
         self.enter(
-            data.subroutine.depth() as usize,
+            data.subroutine.depth() as usize + self.depth_offset,
             get_create_address(call, nonce),
             CallKind::Create,
         );
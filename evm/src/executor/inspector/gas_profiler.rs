@@ -0,0 +1,232 @@
+use bytes::Bytes;
+use ethers::types::Address;
+use revm::{
+    opcode, spec_opcode_gas, CallInputs, CreateInputs, Database, EVMData, Gas, Inspector,
+    Interpreter, Return,
+};
+use std::collections::BTreeMap;
+
+/// The gas used by the instructions of a contract's runtime code
+#[derive(Clone, Debug, Default)]
+pub struct ContractGas {
+    /// The runtime code of the contract
+    pub code: Bytes,
+    /// The gas used by the instruction at each program counter, excluding the gas recorded for
+    /// the contracts it calls
+    pub gas: BTreeMap<usize, u64>,
+}
+
+impl ContractGas {
+    /// Returns the gas used by the instruction at each instruction counter, which is the index
+    /// of the instruction in the source map
+    pub fn gas_by_ic(&self) -> BTreeMap<usize, u64> {
+        let mut gas_by_ic = BTreeMap::new();
+        let mut ic = 0;
+        let mut pc = 0;
+        while pc < self.code.len() {
+            if let Some(gas) = self.gas.get(&pc) {
+                gas_by_ic.insert(ic, *gas);
+            }
+            let op = self.code[pc];
+            if (opcode::PUSH1..=opcode::PUSH32).contains(&op) {
+                // skip the pushed bytes
+                pc += (op - opcode::PUSH1 + 1) as usize;
+            }
+            pc += 1;
+            ic += 1;
+        }
+        gas_by_ic
+    }
+}
+
+/// The gas used by the instructions of all called contracts, by code address
+pub type GasProfile = BTreeMap<Address, ContractGas>;
+
+/// The execution context of a call or create
+#[derive(Debug, Default)]
+struct Frame {
+    /// The address of the executed code, `None` for init code
+    address: Option<Address>,
+    /// See [Debugger::current_gas_block](super::Debugger::current_gas_block)
+    current_gas_block: u64,
+    /// See [Debugger::previous_gas_block](super::Debugger::previous_gas_block)
+    previous_gas_block: u64,
+    /// The program counter of the last executed instruction and the gas used before it
+    last_step: Option<(usize, u64)>,
+    /// Whether the runtime code of `address` is executed
+    executed: bool,
+    /// The recorded gas of the calls made by the last executed instruction
+    child_gas: u64,
+    /// The recorded gas of all calls made in the frame
+    recorded_children: u64,
+}
+
+/// An inspector that records the gas used by every instruction of the runtime code of the called
+/// contracts.
+///
+/// The gas used by init code is not recorded separately, it's part of the gas of the instruction
+/// that created the contract.
+#[derive(Default, Debug)]
+pub struct GasProfiler {
+    /// The recorded gas usage
+    pub profile: GasProfile,
+    /// The stack of execution contexts
+    frames: Vec<Frame>,
+}
+
+impl GasProfiler {
+    /// Attributes the gas used since the last step of the current frame to that step
+    fn record_last_step(&mut self, gas_used: u64) {
+        let frame = match self.frames.last_mut() {
+            Some(frame) => frame,
+            None => return,
+        };
+        if let (Some(address), Some((pc, last_gas_used))) = (frame.address, frame.last_step) {
+            let gas = gas_used.saturating_sub(last_gas_used).saturating_sub(frame.child_gas);
+            *self.profile.entry(address).or_default().gas.entry(pc).or_default() += gas;
+        }
+        frame.last_step = None;
+        frame.child_gas = 0;
+    }
+
+    /// Exits the current frame, which used `gas` in total
+    fn exit(&mut self, gas: &Gas) {
+        self.record_last_step(gas.spend());
+        let frame = match self.frames.pop() {
+            Some(frame) => frame,
+            None => return,
+        };
+        // the gas of init code, precompiles and accounts without code is not recorded in the
+        // frame, so it's attributed to the calling instruction instead
+        let recorded = if frame.address.is_some() && frame.executed {
+            gas.spend()
+        } else {
+            frame.recorded_children
+        };
+        if let Some(parent) = self.frames.last_mut() {
+            parent.child_gas += recorded;
+            parent.recorded_children += recorded;
+        }
+    }
+}
+
+impl<DB> Inspector<DB> for GasProfiler
+where
+    DB: Database,
+{
+    fn initialize_interp(
+        &mut self,
+        interp: &mut Interpreter,
+        data: &mut EVMData<'_, DB>,
+        _: bool,
+    ) -> Return {
+        if let Some(frame) = self.frames.last_mut() {
+            frame.previous_gas_block = interp.contract.first_gas_block();
+            frame.executed = true;
+            if let Some(address) = frame.address {
+                let contract = self.profile.entry(address).or_default();
+                if contract.code.is_empty() {
+                    // the code of the interpreter is padded, so the code of the account is used
+                    // to identify the contract
+                    contract.code =
+                        data.subroutine.account(address).info.code.clone().unwrap_or_default();
+                }
+            }
+        }
+
+        Return::Continue
+    }
+
+    fn step(&mut self, interp: &mut Interpreter, data: &mut EVMData<'_, DB>, _: bool) -> Return {
+        let pc = interp.program_counter();
+        let op = interp.contract.code[pc];
+        let opcode_info = &spec_opcode_gas(data.env.cfg.spec_id)[op as usize];
+
+        let gas_used = match self.frames.last_mut() {
+            Some(frame) => {
+                // Same as in the debugger, the fixed costs are charged per gas block
+                let gas_used =
+                    interp.gas().spend() - frame.previous_gas_block + frame.current_gas_block;
+                if opcode_info.is_gas_block_end {
+                    frame.previous_gas_block = interp.contract.gas_block(pc);
+                    frame.current_gas_block = 0;
+                } else {
+                    frame.current_gas_block += opcode_info.gas;
+                }
+                gas_used
+            }
+            None => return Return::Continue,
+        };
+
+        self.record_last_step(gas_used);
+        if let Some(frame) = self.frames.last_mut() {
+            frame.last_step = Some((pc, gas_used));
+        }
+
+        Return::Continue
+    }
+
+    fn call(
+        &mut self,
+        _: &mut EVMData<'_, DB>,
+        call: &mut CallInputs,
+        _: bool,
+    ) -> (Return, Gas, Bytes) {
+        self.frames.push(Frame { address: Some(call.context.code_address), ..Default::default() });
+
+        (Return::Continue, Gas::new(call.gas_limit), Bytes::new())
+    }
+
+    fn call_end(
+        &mut self,
+        _: &mut EVMData<'_, DB>,
+        _: &CallInputs,
+        gas: Gas,
+        status: Return,
+        retdata: Bytes,
+        _: bool,
+    ) -> (Return, Gas, Bytes) {
+        self.exit(&gas);
+
+        (status, gas, retdata)
+    }
+
+    fn create(
+        &mut self,
+        _: &mut EVMData<'_, DB>,
+        call: &mut CreateInputs,
+    ) -> (Return, Option<Address>, Gas, Bytes) {
+        self.frames.push(Frame::default());
+
+        (Return::Continue, None, Gas::new(call.gas_limit), Bytes::new())
+    }
+
+    fn create_end(
+        &mut self,
+        _: &mut EVMData<'_, DB>,
+        _: &CreateInputs,
+        status: Return,
+        address: Option<Address>,
+        gas: Gas,
+        retdata: Bytes,
+    ) -> (Return, Option<Address>, Gas, Bytes) {
+        self.exit(&gas);
+
+        (status, address, gas, retdata)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn can_map_gas_to_instruction_counters() {
+        // PUSH1 0x80 PUSH2 0x0102 ADD STOP
+        let contract = ContractGas {
+            code: Bytes::from_static(&[0x60, 0x80, 0x61, 0x01, 0x02, 0x01, 0x00]),
+            gas: BTreeMap::from([(0, 3), (2, 3), (5, 3), (6, 0)]),
+        };
+        assert_eq!(contract.gas_by_ic(), BTreeMap::from([(0, 3), (1, 3), (2, 3), (3, 0)]));
+    }
+}
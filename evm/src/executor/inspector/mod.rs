@@ -14,9 +14,20 @@ mod stack;
 pub use stack::{InspectorData, InspectorStack};
 
 mod cheatcodes;
-pub use cheatcodes::Cheatcodes;
+pub use cheatcodes::{Cheatcodes, MAGIC_SKIP_REASON};
 
+mod allocator;
+pub use allocator::{AddressAllocator, ALLOCATED_ADDRESS_OFFSET};
+
+mod gas_profiler;
+pub use gas_profiler::{ContractGas, GasProfile, GasProfiler};
+
+mod precompiles;
+pub use precompiles::{precompile_name, PrecompileCounter};
+
+use parking_lot::RwLock;
 use revm::BlockEnv;
+use std::sync::Arc;
 
 #[derive(Default, Clone, Debug)]
 pub struct InspectorStackConfig {
@@ -32,6 +43,14 @@ pub struct InspectorStackConfig {
     pub tracing: bool,
     /// Whether or not the debugger is enabled
     pub debugger: bool,
+    /// Whether or not the gas used by each instruction is recorded
+    pub gas_profiling: bool,
+    /// Whether or not the calls to precompiles and the executed `KECCAK256` opcodes are counted
+    pub precompiles: bool,
+    /// Whether or not created contracts are moved to deterministic addresses
+    pub deterministic_addresses: bool,
+    /// Whether or not the calls of the test contract are executed as separate transactions
+    pub isolate: bool,
 }
 
 impl InspectorStackConfig {
@@ -42,6 +61,11 @@ impl InspectorStackConfig {
         stack.cheatcodes = self.cheatcodes.clone();
         if let Some(ref mut cheatcodes) = stack.cheatcodes {
             cheatcodes.block = Some(self.block.clone());
+            // every call gets its own scratch directory so tests sharing a `setUp` are isolated
+            cheatcodes.temp_dir = None;
+            // forks created or selected during a call must not leak into other calls
+            cheatcodes.forks =
+                cheatcodes.forks.as_ref().map(|forks| Arc::new(RwLock::new(forks.read().clone())));
         }
 
         if self.tracing {
@@ -50,6 +74,16 @@ impl InspectorStackConfig {
         if self.debugger {
             stack.debugger = Some(Debugger::default());
         }
+        if self.gas_profiling {
+            stack.gas_profiler = Some(GasProfiler::default());
+        }
+        if self.precompiles {
+            stack.precompiles = Some(PrecompileCounter::default());
+        }
+        if self.deterministic_addresses {
+            stack.allocator = Some(AddressAllocator);
+        }
+        stack.isolate = self.isolate;
         stack
     }
 }
@@ -0,0 +1,80 @@
+use bytes::Bytes;
+use ethers::types::Address;
+use revm::{db::Database, opcode, CallInputs, EVMData, Gas, Inspector, Interpreter, Return};
+use std::collections::BTreeMap;
+
+/// The names of the precompiles at the addresses `0x01..=0x09`
+const PRECOMPILES: [&str; 9] = [
+    "ecrecover",
+    "sha256",
+    "ripemd160",
+    "identity",
+    "modexp",
+    "ecadd",
+    "ecmul",
+    "ecpairing",
+    "blake2f",
+];
+
+/// Returns the name of the precompile at `address`, if any
+pub fn precompile_name(address: Address) -> Option<&'static str> {
+    let bytes = address.as_bytes();
+    if bytes[..19].iter().any(|byte| *byte != 0) {
+        return None
+    }
+    PRECOMPILES.get((bytes[19] as usize).checked_sub(1)?).copied()
+}
+
+/// An inspector that counts the calls to precompiles and the executed `KECCAK256` opcodes.
+#[derive(Default, Debug)]
+pub struct PrecompileCounter {
+    /// The number of calls, by precompile name or `keccak256`
+    pub calls: BTreeMap<&'static str, u64>,
+}
+
+impl PrecompileCounter {
+    /// Returns the counts with owned names
+    pub fn counts(&self) -> BTreeMap<String, u64> {
+        self.calls.iter().map(|(name, count)| (name.to_string(), *count)).collect()
+    }
+}
+
+impl<DB> Inspector<DB> for PrecompileCounter
+where
+    DB: Database,
+{
+    fn step(&mut self, interpreter: &mut Interpreter, _: &mut EVMData<'_, DB>, _: bool) -> Return {
+        if interpreter.contract.code[interpreter.program_counter()] == opcode::SHA3 {
+            *self.calls.entry("keccak256").or_default() += 1;
+        }
+
+        Return::Continue
+    }
+
+    fn call(
+        &mut self,
+        _: &mut EVMData<'_, DB>,
+        call: &mut CallInputs,
+        _: bool,
+    ) -> (Return, Gas, Bytes) {
+        if let Some(name) = precompile_name(call.contract) {
+            *self.calls.entry(name).or_default() += 1;
+        }
+
+        (Return::Continue, Gas::new(call.gas_limit), Bytes::new())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn can_name_precompiles() {
+        assert_eq!(precompile_name(Address::from_low_u64_be(1)), Some("ecrecover"));
+        assert_eq!(precompile_name(Address::from_low_u64_be(9)), Some("blake2f"));
+        assert_eq!(precompile_name(Address::zero()), None);
+        assert_eq!(precompile_name(Address::from_low_u64_be(10)), None);
+        assert_eq!(precompile_name(Address::from_low_u64_be(0x101)), None);
+    }
+}
@@ -1,12 +1,22 @@
-use super::{Cheatcodes, Debugger, LogCollector, Tracer};
-use crate::{debug::DebugArena, trace::CallTraceArena};
+use super::{
+    AddressAllocator, Cheatcodes, Debugger, GasProfile, GasProfiler, LogCollector,
+    PrecompileCounter, Tracer,
+};
+use crate::{
+    debug::DebugArena,
+    executor::{fork::SharedForkState, StateChangeset},
+    trace::CallTraceArena,
+};
 use bytes::Bytes;
 use ethers::{
     abi::RawLog,
-    types::{Address, H256},
+    types::{transaction::eip2718::TypedTransaction, Address, H160, H256, U256},
 };
-use revm::{db::Database, CallInputs, CreateInputs, EVMData, Gas, Inspector, Interpreter, Return};
-use std::collections::BTreeMap;
+use revm::{
+    db::Database, AccountInfo, CallInputs, CallScheme, CreateInputs, EVMData, Gas, Inspector,
+    Interpreter, Return, TransactOut, TransactTo, EVM,
+};
+use std::collections::{BTreeMap, VecDeque};
 
 /// Helper macro to call the same method on multiple inspectors without resorting to dynamic
 /// dispatch
@@ -26,6 +36,10 @@ pub struct InspectorData {
     pub traces: Option<CallTraceArena>,
     pub debug: Option<DebugArena>,
     pub cheatcodes: Option<Cheatcodes>,
+    pub transactions: Option<VecDeque<TypedTransaction>>,
+    pub gas_snapshots: BTreeMap<String, u64>,
+    pub precompile_calls: BTreeMap<String, u64>,
+    pub gas_profile: Option<GasProfile>,
 }
 
 /// An inspector that calls multiple inspectors in sequence.
@@ -38,9 +52,21 @@ pub struct InspectorStack {
     pub logs: Option<LogCollector>,
     pub cheatcodes: Option<Cheatcodes>,
     pub debugger: Option<Debugger>,
+    pub allocator: Option<AddressAllocator>,
+    pub precompiles: Option<PrecompileCounter>,
+    pub gas_profiler: Option<GasProfiler>,
+    /// Whether the calls made by the test contract are executed as transactions of their own
+    pub isolate: bool,
+    /// Whether an isolated call is being executed
+    pub(crate) in_inner_context: bool,
 }
 
 impl InspectorStack {
+    /// The fork state of the call, if cheatcodes are enabled and forks can be created
+    pub fn forks(&self) -> Option<SharedForkState> {
+        self.cheatcodes.as_ref().and_then(|cheatcodes| cheatcodes.forks.clone())
+    }
+
     pub fn collect_inspector_states(self) -> InspectorData {
         InspectorData {
             logs: self.logs.map(|logs| logs.logs).unwrap_or_default(),
@@ -51,11 +77,143 @@ impl InspectorStack {
                 .unwrap_or_default(),
             traces: self.tracer.map(|tracer| tracer.traces),
             debug: self.debugger.map(|debugger| debugger.arena),
+            transactions: self.cheatcodes.as_ref().and_then(|cheatcodes| {
+                (!cheatcodes.broadcastable_transactions.is_empty())
+                    .then(|| cheatcodes.broadcastable_transactions.clone())
+            }),
+            gas_snapshots: self
+                .cheatcodes
+                .as_ref()
+                .map(|cheatcodes| cheatcodes.gas_snapshots.finished.clone())
+                .unwrap_or_default(),
+            precompile_calls: self
+                .precompiles
+                .map(|precompiles| precompiles.counts())
+                .unwrap_or_default(),
+            gas_profile: self.gas_profiler.map(|profiler| profiler.profile),
             cheatcodes: self.cheatcodes,
         }
     }
 }
 
+impl InspectorStack {
+    /// Executes the call as a transaction of its own, which starts with cold accounts and storage
+    /// slots and pays the intrinsic gas of a transaction, and applies its changes to the state of
+    /// the current transaction.
+    ///
+    /// All inspectors of the stack inspect the transaction, except for its top level call, which
+    /// they already saw as the call of the current transaction. The logs of the transaction are
+    /// added to the current transaction.
+    fn isolated_call<DB: Database>(
+        &mut self,
+        data: &mut EVMData<'_, DB>,
+        call: &CallInputs,
+    ) -> (Return, Gas, Bytes) {
+        let caller = call.context.caller;
+        let mut env = data.env.clone();
+        env.tx.caller = caller;
+        env.tx.transact_to = TransactTo::Call(call.contract);
+        env.tx.data = call.input.clone();
+        env.tx.value = call.transfer.value;
+        env.tx.gas_limit = call.gas_limit;
+
+        let mut evm = EVM::new();
+        evm.env = env;
+        evm.database(IsolatedDb { state: data.subroutine.state(), db: &mut *data.db });
+
+        // the calls of the transaction start at depth 0 again, so the inspectors that track the
+        // depth are told where the transaction is nested in the current one
+        self.set_depth_offset(data.subroutine.depth());
+        self.in_inner_context = true;
+        let (status, out, gas_used, changes, logs) = evm.inspect(&mut *self);
+        self.in_inner_context = false;
+        self.set_depth_offset(0);
+
+        // cheatcodes like `warp` change the block of the inner transaction
+        data.env.block = evm.env.block;
+
+        for (address, account) in changes {
+            data.subroutine.load_account(address, data.db);
+            let current = data.subroutine.account(address).info.clone();
+
+            if current.balance < account.info.balance {
+                data.subroutine.balance_add(address, account.info.balance - current.balance);
+            } else {
+                data.subroutine.balance_sub(address, current.balance - account.info.balance);
+            }
+            // the transaction increased the nonce of the caller, which a call does not
+            if address != caller {
+                // we can safely unwrap because `load_account` inserted the account
+                data.subroutine.state().get_mut(&address).unwrap().info.nonce = account.info.nonce;
+            }
+            if current.code_hash != account.info.code_hash {
+                data.subroutine.set_code(
+                    address,
+                    account.info.code.unwrap_or_default(),
+                    account.info.code_hash,
+                );
+            }
+            for (slot, value) in account.storage {
+                data.subroutine.sstore(address, slot, value, data.db);
+            }
+        }
+        for log in logs {
+            data.subroutine.log(log);
+        }
+
+        let mut gas = Gas::new(call.gas_limit);
+        gas.record_cost(gas_used);
+        let retdata = match out {
+            TransactOut::Call(data) => data,
+            _ => Bytes::new(),
+        };
+        (status, gas, retdata)
+    }
+
+    fn set_depth_offset(&mut self, depth: u64) {
+        if let Some(ref mut cheatcodes) = self.cheatcodes {
+            cheatcodes.depth_offset = depth;
+        }
+        if let Some(ref mut tracer) = self.tracer {
+            tracer.depth_offset = depth as usize;
+        }
+        if let Some(ref mut debugger) = self.debugger {
+            debugger.depth_offset = depth as usize;
+        }
+    }
+}
+
+/// The database of an isolated call, which reads the accounts that were loaded by the current
+/// transaction from its state
+struct IsolatedDb<'a, DB> {
+    state: &'a StateChangeset,
+    db: &'a mut DB,
+}
+
+impl<'a, DB: Database> Database for IsolatedDb<'a, DB> {
+    fn basic(&mut self, address: H160) -> AccountInfo {
+        match self.state.get(&address) {
+            Some(account) => account.info.clone(),
+            None => self.db.basic(address),
+        }
+    }
+
+    fn code_by_hash(&mut self, code_hash: H256) -> Bytes {
+        self.db.code_by_hash(code_hash)
+    }
+
+    fn storage(&mut self, address: H160, index: U256) -> U256 {
+        match self.state.get(&address).and_then(|account| account.storage.get(&index)) {
+            Some(value) => *value,
+            None => self.db.storage(address, index),
+        }
+    }
+
+    fn block_hash(&mut self, number: U256) -> H256 {
+        self.db.block_hash(number)
+    }
+}
+
 impl<DB> Inspector<DB> for InspectorStack
 where
     DB: Database,
@@ -68,7 +226,13 @@ where
     ) -> Return {
         call_inspectors!(
             inspector,
-            [&mut self.debugger, &mut self.tracer, &mut self.logs, &mut self.cheatcodes],
+            [
+                &mut self.debugger,
+                &mut self.gas_profiler,
+                &mut self.tracer,
+                &mut self.logs,
+                &mut self.cheatcodes
+            ],
             {
                 let status = inspector.initialize_interp(interpreter, data, is_static);
 
@@ -90,7 +254,14 @@ where
     ) -> Return {
         call_inspectors!(
             inspector,
-            [&mut self.debugger, &mut self.tracer, &mut self.logs, &mut self.cheatcodes],
+            [
+                &mut self.debugger,
+                &mut self.gas_profiler,
+                &mut self.tracer,
+                &mut self.logs,
+                &mut self.cheatcodes,
+                &mut self.precompiles
+            ],
             {
                 let status = inspector.step(interpreter, data, is_static);
 
@@ -145,9 +316,21 @@ where
         call: &mut CallInputs,
         is_static: bool,
     ) -> (Return, Gas, Bytes) {
+        // the top level call of an isolated call was already inspected
+        if self.in_inner_context && data.subroutine.depth() == 0 {
+            return (Return::Continue, Gas::new(call.gas_limit), Bytes::new())
+        }
+
         call_inspectors!(
             inspector,
-            [&mut self.debugger, &mut self.tracer, &mut self.logs, &mut self.cheatcodes],
+            [
+                &mut self.debugger,
+                &mut self.gas_profiler,
+                &mut self.tracer,
+                &mut self.logs,
+                &mut self.cheatcodes,
+                &mut self.precompiles
+            ],
             {
                 let (status, gas, retdata) = inspector.call(data, call, is_static);
 
@@ -158,6 +341,15 @@ where
             }
         );
 
+        if self.isolate &&
+            !self.in_inner_context &&
+            !is_static &&
+            data.subroutine.depth() == 1 &&
+            call.context.scheme == CallScheme::Call
+        {
+            return self.isolated_call(data, call)
+        }
+
         (Return::Continue, Gas::new(call.gas_limit), Bytes::new())
     }
 
@@ -170,9 +362,19 @@ where
         retdata: Bytes,
         is_static: bool,
     ) -> (Return, Gas, Bytes) {
+        if self.in_inner_context && data.subroutine.depth() == 0 {
+            return (status, remaining_gas, retdata)
+        }
+
         call_inspectors!(
             inspector,
-            [&mut self.debugger, &mut self.tracer, &mut self.logs, &mut self.cheatcodes],
+            [
+                &mut self.debugger,
+                &mut self.gas_profiler,
+                &mut self.tracer,
+                &mut self.logs,
+                &mut self.cheatcodes
+            ],
             {
                 let (new_status, new_gas, new_retdata) = inspector.call_end(
                     data,
@@ -201,7 +403,13 @@ where
     ) -> (Return, Option<Address>, Gas, Bytes) {
         call_inspectors!(
             inspector,
-            [&mut self.debugger, &mut self.tracer, &mut self.logs, &mut self.cheatcodes],
+            [
+                &mut self.debugger,
+                &mut self.gas_profiler,
+                &mut self.tracer,
+                &mut self.logs,
+                &mut self.cheatcodes
+            ],
             {
                 let (status, addr, gas, retdata) = inspector.create(data, call);
 
@@ -224,9 +432,23 @@ where
         remaining_gas: Gas,
         retdata: Bytes,
     ) -> (Return, Option<Address>, Gas, Bytes) {
+        // The allocator runs first so the other inspectors see the allocated address
+        let (status, address, remaining_gas, retdata) = match self.allocator {
+            Some(ref mut allocator) => {
+                allocator.create_end(data, call, status, address, remaining_gas, retdata)
+            }
+            None => (status, address, remaining_gas, retdata),
+        };
+
         call_inspectors!(
             inspector,
-            [&mut self.debugger, &mut self.tracer, &mut self.logs, &mut self.cheatcodes],
+            [
+                &mut self.debugger,
+                &mut self.gas_profiler,
+                &mut self.tracer,
+                &mut self.logs,
+                &mut self.cheatcodes
+            ],
             {
                 let (new_status, new_address, new_gas, new_retdata) = inspector.create_end(
                     data,
@@ -18,6 +18,8 @@ use revm::{return_ok, CallInputs, CreateInputs, Database, EVMData, Gas, Inspecto
 pub struct Tracer {
     pub trace_stack: Vec<usize>,
     pub traces: CallTraceArena,
+    /// Added to the depth of the calls of an isolated transaction, which start at depth 0 again
+    pub(crate) depth_offset: usize,
 }
 
 impl Tracer {
@@ -73,7 +75,7 @@ where
         _: bool,
     ) -> (Return, Gas, Bytes) {
         self.start_trace(
-            data.subroutine.depth() as usize,
+            data.subroutine.depth() as usize + self.depth_offset,
             call.context.code_address,
             call.input.to_vec(),
             call.transfer.value,
@@ -118,7 +120,7 @@ where
         data.subroutine.load_account(call.caller, data.db);
         let nonce = data.subroutine.account(call.caller).info.nonce;
         self.start_trace(
-            data.subroutine.depth() as usize,
+            data.subroutine.depth() as usize + self.depth_offset,
             get_create_address(call, nonce),
             call.init_code.to_vec(),
             call.value,
@@ -1,8 +1,8 @@
 /// ABIs used internally in the executor
 pub mod abi;
 pub use abi::{
-    patch_hardhat_console_selector, HardhatConsoleCalls, CHEATCODE_ADDRESS, CONSOLE_ABI,
-    HARDHAT_CONSOLE_ABI, HARDHAT_CONSOLE_ADDRESS,
+    patch_hardhat_console_selector, reserved_address, HardhatConsoleCalls, CHEATCODE_ADDRESS,
+    CONSOLE_ABI, HARDHAT_CONSOLE_ABI, HARDHAT_CONSOLE_ADDRESS,
 };
 
 /// Executor configuration
@@ -14,6 +14,10 @@ pub mod inspector;
 /// Forking provider
 pub mod fork;
 
+/// Account allocations loaded into the executor state
+pub mod allocs;
+pub use allocs::{load_allocs, Allocs, GenesisAccount};
+
 /// Executor builder
 pub mod builder;
 pub use builder::{ExecutorBuilder, Fork};
@@ -26,12 +30,17 @@ pub use revm::db::DatabaseRef;
 
 pub use revm::Env;
 
-use self::inspector::{InspectorData, InspectorStackConfig};
+use self::{
+    fork::{ForkedDb, ForkedDbMut},
+    inspector::{GasProfile, InspectorData, InspectorStackConfig},
+};
 use crate::{debug::DebugArena, trace::CallTraceArena, CALLER};
 use bytes::Bytes;
 use ethers::{
     abi::{Abi, Detokenize, RawLog, Tokenize},
     prelude::{decode_function_data, encode_function_data, Address, U256},
+    types::{transaction::eip2718::TypedTransaction, H256},
+    utils::keccak256,
 };
 use eyre::Result;
 use foundry_utils::IntoFunction;
@@ -40,7 +49,7 @@ use revm::{
     db::{CacheDB, DatabaseCommit, EmptyDB},
     return_ok, Account, BlockEnv, CreateScheme, Return, TransactOut, TransactTo, TxEnv, EVM,
 };
-use std::collections::BTreeMap;
+use std::collections::{BTreeMap, VecDeque};
 
 /// A mapping of addresses to their changed state.
 pub type StateChangeset = HashMap<Address, Account>;
@@ -113,7 +122,7 @@ pub struct CallResult<D: Detokenize> {
 #[derive(Debug)]
 pub struct RawCallResult {
     /// The status of the call
-    status: Return,
+    pub status: Return,
     /// Whether the call reverted or not
     pub reverted: bool,
     /// The raw result of the call
@@ -130,6 +139,15 @@ pub struct RawCallResult {
     pub traces: Option<CallTraceArena>,
     /// The debug nodes of the call
     pub debug: Option<DebugArena>,
+    /// Scripted transactions generated from this call
+    pub transactions: Option<VecDeque<TypedTransaction>>,
+    /// The gas used by the code regions measured with `startSnapshotGas` and `stopSnapshotGas`,
+    /// by name
+    pub gas_snapshots: BTreeMap<String, u64>,
+    /// The number of calls to each precompile and of executed `KECCAK256` opcodes, by name
+    pub precompile_calls: BTreeMap<String, u64>,
+    /// The gas used by each instruction of the called contracts, if gas profiling is enabled
+    pub gas_profile: Option<GasProfile>,
     /// The changeset of the state.
     ///
     /// This is only present if the changed state was not committed to the database (i.e. if you
@@ -149,6 +167,10 @@ impl Default for RawCallResult {
             labels: BTreeMap::new(),
             traces: None,
             debug: None,
+            transactions: None,
+            gas_snapshots: BTreeMap::new(),
+            precompile_calls: BTreeMap::new(),
+            gas_profile: None,
             state_changeset: None,
         }
     }
@@ -208,6 +230,11 @@ where
         self.db.basic(address).balance
     }
 
+    /// Gets the nonce of an account
+    pub fn get_nonce(&self, address: Address) -> u64 {
+        self.db.basic(address).nonce
+    }
+
     /// Set the nonce of an account.
     pub fn set_nonce(&mut self, address: Address, nonce: u64) -> &mut Self {
         let mut account = self.db.basic(address);
@@ -217,11 +244,42 @@ where
         self
     }
 
+    /// Inserts the balance, nonce, code and storage of the given accounts into the state.
+    pub fn load_allocs(&mut self, allocs: &Allocs) -> &mut Self {
+        for (address, account) in allocs {
+            let mut info = self.db.basic(*address);
+            info.balance = account.balance;
+            if let Some(nonce) = account.nonce {
+                info.nonce = nonce.as_u64();
+            }
+            if let Some(ref code) = account.code {
+                info.code_hash = H256::from_slice(&keccak256(code));
+                info.code = Some(code.0.clone());
+            }
+            self.db.insert_cache(*address, info);
+
+            for (slot, value) in &account.storage {
+                self.db.insert_cache_storage(*address, slot.0.into(), value.0.into());
+            }
+        }
+        self
+    }
+
     pub fn set_tracing(&mut self, tracing: bool) -> &mut Self {
         self.inspector_config.tracing = tracing;
         self
     }
 
+    /// Returns an error if the tests of this executor can not run external commands, see
+    /// [Cheatcodes::check_ffi](inspector::Cheatcodes::check_ffi)
+    pub fn check_ffi(&self) -> Result<(), String> {
+        match self.inspector_config.cheatcodes {
+            Some(ref cheatcodes) => cheatcodes.check_ffi(),
+            // FFI is disabled without cheatcodes
+            None => inspector::Cheatcodes::default().check_ffi(),
+        }
+    }
+
     pub fn set_debugger(&mut self, debugger: bool) -> &mut Self {
         self.inspector_config.debugger = debugger;
         self
@@ -262,6 +320,7 @@ where
             traces,
             debug,
             state_changeset,
+            ..
         } = self.call_raw_committing(from, to, calldata, value)?;
         match status {
             return_ok!() => {
@@ -311,18 +370,27 @@ where
         // Build VM
         let mut evm = EVM::new();
         evm.env = self.build_env(from, TransactTo::Call(to), calldata, value);
-        evm.database(&mut self.db);
+        let mut inspector = self.inspector_config.stack();
+        evm.database(ForkedDbMut::new(&mut self.db, inspector.forks()));
 
         // Run the call
-        let mut inspector = self.inspector_config.stack();
         let (status, out, gas, _) = evm.inspect_commit(&mut inspector);
         let result = match out {
             TransactOut::Call(data) => data,
             _ => Bytes::default(),
         };
 
-        let InspectorData { logs, labels, traces, debug, cheatcodes } =
-            inspector.collect_inspector_states();
+        let InspectorData {
+            logs,
+            labels,
+            traces,
+            debug,
+            cheatcodes,
+            transactions,
+            gas_snapshots,
+            precompile_calls,
+            gas_profile,
+        } = inspector.collect_inspector_states();
 
         // Persist the changed block environment
         self.inspector_config.block = evm.env.block.clone();
@@ -340,6 +408,10 @@ where
             labels,
             traces,
             debug,
+            transactions,
+            gas_snapshots,
+            precompile_calls,
+            gas_profile,
             state_changeset: None,
         })
     }
@@ -369,6 +441,7 @@ where
             traces,
             debug,
             state_changeset,
+            ..
         } = self.call_raw(from, to, calldata, value)?;
         match status {
             return_ok!() => {
@@ -418,18 +491,27 @@ where
         // Build VM
         let mut evm = EVM::new();
         evm.env = self.build_env(from, TransactTo::Call(to), calldata, value);
-        evm.database(&self.db);
+        let mut inspector = self.inspector_config.stack();
+        evm.database(ForkedDb::new(&self.db, inspector.forks()));
 
         // Run the call
-        let mut inspector = self.inspector_config.stack();
         let (status, out, gas, state_changeset, _) = evm.inspect_ref(&mut inspector);
         let result = match out {
             TransactOut::Call(data) => data,
             _ => Bytes::default(),
         };
 
-        let InspectorData { logs, labels, traces, debug, .. } =
-            inspector.collect_inspector_states();
+        let InspectorData {
+            logs,
+            labels,
+            traces,
+            debug,
+            transactions,
+            gas_snapshots,
+            precompile_calls,
+            gas_profile,
+            ..
+        } = inspector.collect_inspector_states();
         Ok(RawCallResult {
             status,
             reverted: !matches!(status, return_ok!()),
@@ -440,18 +522,26 @@ where
             labels,
             traces,
             debug,
+            transactions,
+            gas_snapshots,
+            precompile_calls,
+            gas_profile,
             state_changeset: Some(state_changeset),
         })
     }
 
     /// Deploys a contract and commits the new state to the underlying database.
     pub fn deploy(&mut self, from: Address, code: Bytes, value: U256) -> Result<DeployResult> {
+        if let Some(reserved) = reserved_address(from) {
+            eyre::bail!("cannot deploy from {} ({:?})", reserved, from)
+        }
         let mut evm = EVM::new();
         evm.env = self.build_env(from, TransactTo::Create(CreateScheme::Create), code, value);
-        evm.database(&mut self.db);
-
         let mut inspector = self.inspector_config.stack();
-        let (status, out, gas, _) = evm.inspect_commit(&mut inspector);
+        evm.database(ForkedDbMut::new(&mut self.db, inspector.forks()));
+
+        // the deployed address is checked before the state is committed
+        let (status, out, gas, state_changeset, _) = evm.inspect(&mut inspector);
         let address = match status {
             return_ok!() => {
                 if let TransactOut::Create(_, Some(addr)) = out {
@@ -464,11 +554,17 @@ where
             // regarding deployments in general
             _ => eyre::bail!("deployment failed: {:?}", status),
         };
+        if let Some(reserved) = reserved_address(address) {
+            eyre::bail!("contract deployed to {} ({:?})", reserved, address)
+        }
+        let block = evm.env.block.clone();
+        ForkedDbMut::new(&mut self.db, inspector.forks()).commit(state_changeset);
+
         let InspectorData { logs, traces, debug, cheatcodes, .. } =
             inspector.collect_inspector_states();
 
         // Persist the changed block environment
-        self.inspector_config.block = evm.env.block.clone();
+        self.inspector_config.block = block;
 
         // Persist cheatcode state
         self.inspector_config.cheatcodes = cheatcodes;
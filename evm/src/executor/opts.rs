@@ -2,8 +2,10 @@ use ethers::{
     providers::{Middleware, Provider},
     types::{Address, Chain, U256},
 };
+use foundry_config::{FsPermissions, Sandbox};
 use revm::{BlockEnv, CfgEnv, SpecId, TxEnv};
 use serde::{Deserialize, Deserializer, Serialize};
+use std::path::PathBuf;
 
 use super::fork::environment;
 
@@ -19,6 +21,10 @@ pub struct EvmOpts {
     /// pins the block number for the state fork
     pub fork_block_number: Option<u64>,
 
+    /// The milliseconds to wait before retrying requests the fork endpoint rejected because of
+    /// its rate limit, doubled with every retry
+    pub fork_retry_backoff: Option<u64>,
+
     /// Disables storage caching entirely.
     pub no_storage_caching: bool,
 
@@ -31,11 +37,29 @@ pub struct EvmOpts {
     /// enables the FFI cheatcode
     pub ffi: bool,
 
+    /// The paths the `readFile` and `writeFile` cheatcodes may access
+    pub fs_permissions: FsPermissions,
+
+    /// The cheatcodes tests in third-party sources can not use
+    pub sandbox: Sandbox,
+
     /// Verbosity mode of EVM output as number of occurences
     pub verbosity: u8,
 
     /// The memory limit of the EVM in bytes.
     pub memory_limit: u64,
+
+    /// A JSON file of account allocations that is loaded into the state before tests are deployed
+    pub allocs: Option<PathBuf>,
+
+    /// Moves created contracts to deterministic, incrementing addresses
+    pub deterministic_addresses: bool,
+
+    /// Executes every call made by the test contract as a transaction of its own
+    pub isolate: bool,
+
+    /// Fails `prank` and `etch` if they are used with a reserved address
+    pub deny_reserved_addresses: bool,
 }
 
 impl EvmOpts {
@@ -0,0 +1,99 @@
+use ethers::abi::{self, Function, Token};
+use eyre::WrapErr;
+use serde::{Deserialize, Serialize};
+use std::{fmt, process::Command, sync::Arc};
+
+/// The signature of a Rust reference implementation
+pub type ReferenceFn = dyn Fn(&[Token]) -> eyre::Result<Vec<Token>> + Send + Sync;
+
+/// A reference implementation a function of a test contract is compared against in differential
+/// fuzz tests
+#[derive(Clone)]
+pub enum DifferentialReference {
+    /// An external command.
+    ///
+    /// The ABI-encoded inputs are appended to the command as a `0x` prefixed hex string, and the
+    /// command is expected to print the ABI-encoded outputs as a hex string, like commands called
+    /// with the `ffi` cheatcode. A command that exits with an error is treated like a revert.
+    ///
+    /// Commands are only run if the test could use the `ffi` cheatcode.
+    Command(Vec<String>),
+    /// A Rust implementation, returning an error is treated like a revert
+    Callback(Arc<ReferenceFn>),
+}
+
+impl DifferentialReference {
+    pub fn command(command: impl IntoIterator<Item = impl Into<String>>) -> Self {
+        DifferentialReference::Command(command.into_iter().map(Into::into).collect())
+    }
+
+    pub fn callback(
+        callback: impl Fn(&[Token]) -> eyre::Result<Vec<Token>> + Send + Sync + 'static,
+    ) -> Self {
+        DifferentialReference::Callback(Arc::new(callback))
+    }
+
+    /// Returns the outputs of the reference implementation of `func` for the given inputs
+    pub fn outputs(&self, func: &Function, inputs: &[Token]) -> eyre::Result<Vec<Token>> {
+        match self {
+            DifferentialReference::Command(command) => {
+                let (program, args) =
+                    command.split_first().ok_or_else(|| eyre::eyre!("Empty reference command"))?;
+                let output = Command::new(program)
+                    .args(args)
+                    .arg(format!("0x{}", hex::encode(abi::encode(inputs))))
+                    .output()
+                    .wrap_err_with(|| format!("Failed to run `{}`", command.join(" ")))?;
+                if !output.status.success() {
+                    eyre::bail!(
+                        "`{}` failed with {}: {}",
+                        command.join(" "),
+                        output.status,
+                        String::from_utf8_lossy(&output.stderr).trim()
+                    )
+                }
+
+                let stdout = String::from_utf8_lossy(&output.stdout);
+                let stdout = stdout.trim();
+                let encoded = hex::decode(stdout.strip_prefix("0x").unwrap_or(stdout))
+                    .wrap_err("The reference command did not print a hex string")?;
+                let types = func.outputs.iter().map(|param| param.kind.clone()).collect::<Vec<_>>();
+                Ok(abi::decode(&types, &encoded)?)
+            }
+            DifferentialReference::Callback(callback) => callback(inputs),
+        }
+    }
+}
+
+impl fmt::Debug for DifferentialReference {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            DifferentialReference::Command(command) => {
+                f.debug_tuple("Command").field(command).finish()
+            }
+            DifferentialReference::Callback(_) => f.write_str("Callback"),
+        }
+    }
+}
+
+/// How often a differential fuzz test agreed with its reference implementation
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct DifferentialStats {
+    /// The number of inputs both implementations were run with
+    pub runs: usize,
+    /// The number of inputs both implementations reverted on
+    pub reverts: usize,
+    /// The number of inputs the implementations disagreed on, including the inputs that were
+    /// tried while shrinking a counterexample
+    pub mismatches: usize,
+}
+
+impl fmt::Display for DifferentialStats {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "(compared: {}, both reverted: {}, diverged: {})",
+            self.runs, self.reverts, self.mismatches
+        )
+    }
+}
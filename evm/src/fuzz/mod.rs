@@ -1,5 +1,8 @@
+mod differential;
 mod strategies;
 
+pub use differential::{DifferentialReference, DifferentialStats, ReferenceFn};
+
 pub use proptest::test_runner::{Config as FuzzConfig, Reason};
 
 use crate::{
@@ -101,6 +104,7 @@ where
                     calldata,
                     gas: call.gas,
                     stipend: call.stipend,
+                    precompile_calls: call.precompile_calls,
                 });
                 Ok(())
             } else {
@@ -121,37 +125,160 @@ where
             }
         });
 
-        let (calldata, call) = counterexample.into_inner();
-        let mut result = FuzzTestResult {
-            cases: FuzzedCases::new(cases.into_inner()),
-            success: run_result.is_ok(),
-            reason: None,
-            counterexample: None,
-            logs: call.logs,
-            traces: call.traces,
-            labeled_addresses: call.labels,
-        };
-
-        match run_result {
-            Err(TestError::Abort(reason)) => {
-                result.reason = Some(reason.to_string());
-            }
-            Err(TestError::Fail(reason, _)) => {
-                let reason = reason.to_string();
-                result.reason = if reason.is_empty() { None } else { Some(reason) };
-
-                let args = func
-                    .decode_input(&calldata.as_ref()[4..])
-                    .expect("could not decode fuzzer inputs");
-                result.counterexample = Some(CounterExample { calldata, args });
+        fuzz_result(func, cases.into_inner(), counterexample.into_inner(), run_result)
+    }
+
+    /// Fuzzes the provided function like [Self::fuzz], but instead of checking whether the calls
+    /// succeed, compares the outputs of every call with the outputs of `reference` for the same
+    /// inputs.
+    ///
+    /// An input the function reverts on must also make the reference fail.
+    pub fn fuzz_differential(
+        &self,
+        func: &Function,
+        address: Address,
+        reference: &DifferentialReference,
+        errors: Option<&Abi>,
+    ) -> FuzzTestResult {
+        // reference commands are external programs, so they need the permissions of `ffi`
+        if let DifferentialReference::Command(_) = reference {
+            if let Err(reason) = self.executor.check_ffi() {
+                return FuzzTestResult {
+                    cases: FuzzedCases::new(vec![]),
+                    success: false,
+                    reason: Some(reason),
+                    counterexample: None,
+                    logs: vec![],
+                    traces: None,
+                    labeled_addresses: BTreeMap::new(),
+                    differential: None,
+                }
             }
-            _ => (),
         }
 
+        let cases: RefCell<Vec<FuzzCase>> = RefCell::new(Default::default());
+        let counterexample: RefCell<(Bytes, RawCallResult)> = RefCell::new(Default::default());
+        let stats: RefCell<DifferentialStats> = RefCell::new(Default::default());
+
+        let state: EvmFuzzState = build_initial_state(&self.executor.db);
+        let strat = proptest::strategy::Union::new_weighted(vec![
+            (60, fuzz_calldata(func.clone())),
+            (40, fuzz_calldata_from_state(func.clone(), state.clone())),
+        ]);
+        tracing::debug!(func = ?func.name, "differential fuzzing");
+        let run_result = self.runner.clone().run(&strat, |calldata| {
+            let call = self
+                .executor
+                .call_raw(self.sender, address, calldata.0.clone(), 0.into())
+                .expect("could not make raw evm call");
+            let state_changeset =
+                call.state_changeset.as_ref().expect("we should have a state changeset");
+            collect_state_from_call(&call.logs, state_changeset, state.clone());
+
+            if call.result.as_ref() == ASSUME_MAGIC_RETURN_CODE {
+                return Err(TestCaseError::reject("ASSUME: Too many rejects"))
+            }
+
+            let inputs =
+                func.decode_input(&calldata.as_ref()[4..]).expect("could not decode fuzzer inputs");
+            let expected = reference.outputs(func, &inputs);
+            let actual = if call.reverted {
+                Err(foundry_utils::decode_revert(call.result.as_ref(), errors)
+                    .unwrap_or_else(|_| "unknown revert".to_string()))
+            } else {
+                func.decode_output(call.result.as_ref()).map_err(|err| err.to_string())
+            };
+
+            let mut stats = stats.borrow_mut();
+            stats.runs += 1;
+            let divergence = match (actual, expected) {
+                (Ok(actual), Ok(expected)) if actual == expected => None,
+                (Err(_), Err(_)) => {
+                    stats.reverts += 1;
+                    None
+                }
+                (Ok(actual), Ok(expected)) => Some(format!(
+                    "Outputs differ: [{}] != reference [{}]",
+                    format_tokens(&actual),
+                    format_tokens(&expected)
+                )),
+                (Err(reason), Ok(expected)) => Some(format!(
+                    "Reverted with `{}`, but the reference returned [{}]",
+                    reason,
+                    format_tokens(&expected)
+                )),
+                (Ok(actual), Err(err)) => Some(format!(
+                    "Returned [{}], but the reference failed: {}",
+                    format_tokens(&actual),
+                    err
+                )),
+            };
+
+            match divergence {
+                None => {
+                    cases.borrow_mut().push(FuzzCase {
+                        calldata,
+                        gas: call.gas,
+                        stipend: call.stipend,
+                        precompile_calls: call.precompile_calls,
+                    });
+                    Ok(())
+                }
+                Some(reason) => {
+                    stats.mismatches += 1;
+                    *counterexample.borrow_mut() = (calldata, call);
+                    Err(TestCaseError::fail(reason))
+                }
+            }
+        });
+
+        let mut result =
+            fuzz_result(func, cases.into_inner(), counterexample.into_inner(), run_result);
+        result.differential = Some(stats.into_inner());
         result
     }
 }
 
+/// Builds the result of a fuzz run from the successful cases and the last failing case
+fn fuzz_result(
+    func: &Function,
+    cases: Vec<FuzzCase>,
+    (calldata, call): (Bytes, RawCallResult),
+    run_result: Result<(), TestError<Bytes>>,
+) -> FuzzTestResult {
+    let mut result = FuzzTestResult {
+        cases: FuzzedCases::new(cases),
+        success: run_result.is_ok(),
+        reason: None,
+        counterexample: None,
+        logs: call.logs,
+        traces: call.traces,
+        labeled_addresses: call.labels,
+        differential: None,
+    };
+
+    match run_result {
+        Err(TestError::Abort(reason)) => {
+            result.reason = Some(reason.to_string());
+        }
+        Err(TestError::Fail(reason, _)) => {
+            let reason = reason.to_string();
+            result.reason = if reason.is_empty() { None } else { Some(reason) };
+
+            let args =
+                func.decode_input(&calldata.as_ref()[4..]).expect("could not decode fuzzer inputs");
+            result.counterexample = Some(CounterExample { calldata, args });
+        }
+        _ => (),
+    }
+
+    result
+}
+
+fn format_tokens(tokens: &[Token]) -> String {
+    foundry_utils::format_tokens(tokens).collect::<Vec<_>>().join(", ")
+}
+
 #[derive(Clone, Debug, Serialize, Deserialize)]
 pub struct CounterExample {
     pub calldata: Bytes,
@@ -194,6 +321,9 @@ pub struct FuzzTestResult {
 
     /// Labeled addresses
     pub labeled_addresses: BTreeMap<Address, String>,
+
+    /// How often the outputs matched the reference implementation, for differential fuzz tests
+    pub differential: Option<DifferentialStats>,
 }
 
 /// Container type for all successful test cases
@@ -261,6 +391,18 @@ impl FuzzedCases {
     pub fn lowest_gas(&self) -> u64 {
         self.lowest().map(|c| c.gas).unwrap_or_default()
     }
+
+    /// Returns how often the cases called each precompile or executed `KECCAK256`, by name
+    pub fn precompile_stats(&self) -> BTreeMap<&str, PrecompileStats> {
+        let mut stats: BTreeMap<&str, PrecompileStats> = BTreeMap::new();
+        for (name, calls) in self.cases.iter().flat_map(|case| case.precompile_calls.iter()) {
+            let entry = stats.entry(name.as_str()).or_default();
+            entry.calls += calls;
+            entry.runs += 1;
+            entry.max = entry.max.max(*calls);
+        }
+        stats
+    }
 }
 
 /// Data of a single fuzz test case
@@ -272,4 +414,24 @@ pub struct FuzzCase {
     pub gas: u64,
     /// The initial gas stipend for the transaction
     pub stipend: u64,
+    /// The number of calls to each precompile and of executed `KECCAK256` opcodes, by name
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub precompile_calls: BTreeMap<String, u64>,
+}
+
+/// How often the cases of a fuzz test called a precompile
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PrecompileStats {
+    /// The number of calls in all cases
+    pub calls: u64,
+    /// The number of cases that made any calls
+    pub runs: usize,
+    /// The highest number of calls in a single case
+    pub max: u64,
+}
+
+impl fmt::Display for PrecompileStats {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} calls in {} runs (max: {})", self.calls, self.runs, self.max)
+    }
 }
@@ -0,0 +1,152 @@
+use super::{CallTrace, CallTraceArena, RawOrDecodedCall};
+use serde::Serialize;
+use std::collections::BTreeMap;
+
+/// Call traces in the [Chrome trace event format](https://docs.google.com/document/d/1CvAClvFfyA5R-PhYUmn5OOQtYMH4h6I0nSsKchNAySU),
+/// which can be explored in `chrome://tracing`, [Perfetto](https://ui.perfetto.dev) or
+/// [speedscope](https://www.speedscope.app).
+///
+/// Call traces have no timing information, so gas is used as the time axis: one unit of gas is
+/// displayed as one microsecond. The gas a call spends in its own code is not known relative to its
+/// subcalls, so the subcalls are laid out back to back from the start of the call.
+#[derive(Debug, Clone, Default, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ChromeTrace {
+    trace_events: Vec<ChromeTraceEvent>,
+    /// The number of tracks, every arena is displayed on its own track
+    #[serde(skip)]
+    tracks: u64,
+}
+
+/// A single event of a [`ChromeTrace`]
+#[derive(Debug, Clone, Serialize)]
+pub struct ChromeTraceEvent {
+    pub name: String,
+    /// The phase of the event, `X` for calls and `M` for the names of tracks
+    pub ph: &'static str,
+    pub ts: u64,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub dur: Option<u64>,
+    pub pid: u64,
+    pub tid: u64,
+    pub args: BTreeMap<&'static str, String>,
+}
+
+impl ChromeTrace {
+    /// Adds the calls of the arena on a new track with the given name, e.g. `setUp` or the name of
+    /// the test
+    pub fn add_arena(&mut self, name: impl Into<String>, arena: &CallTraceArena) {
+        let tid = self.tracks;
+        self.tracks += 1;
+        self.trace_events.push(ChromeTraceEvent {
+            name: "thread_name".to_string(),
+            ph: "M",
+            ts: 0,
+            dur: None,
+            pid: 0,
+            tid,
+            args: [("name", name.into())].into(),
+        });
+        if !arena.arena.is_empty() {
+            self.add_node(arena, 0, 0, tid);
+        }
+    }
+
+    /// Adds the call at `idx` starting at `ts`, followed by its subcalls
+    fn add_node(&mut self, arena: &CallTraceArena, idx: usize, ts: u64, tid: u64) {
+        let node = &arena.arena[idx];
+        let trace = &node.trace;
+        let mut args: BTreeMap<_, _> = [
+            ("address", format!("{:?}", trace.address)),
+            ("kind", format!("{:?}", trace.kind)),
+            ("success", trace.success.to_string()),
+            ("gas", trace.gas_cost.to_string()),
+        ]
+        .into();
+        if !trace.value.is_zero() {
+            args.insert("value", trace.value.to_string());
+        }
+        self.trace_events.push(ChromeTraceEvent {
+            name: frame_name(trace),
+            ph: "X",
+            ts,
+            dur: Some(trace.gas_cost),
+            pid: 0,
+            tid,
+            args,
+        });
+
+        let mut child_ts = ts;
+        for child in &node.children {
+            self.add_node(arena, *child, child_ts, tid);
+            child_ts += arena.arena[*child].trace.gas_cost;
+        }
+    }
+
+    pub fn events(&self) -> &[ChromeTraceEvent] {
+        &self.trace_events
+    }
+
+    /// Returns the trace as JSON, ready to be written to a file
+    pub fn to_json(&self) -> serde_json::Result<String> {
+        serde_json::to_string(self)
+    }
+}
+
+/// Returns the name of the call in the timeline, like `Token::transfer` or `new Token`
+fn frame_name(trace: &CallTrace) -> String {
+    let contract = trace.label.clone().unwrap_or_else(|| format!("{:?}", trace.address));
+    if trace.created() {
+        return format!("new {}", contract)
+    }
+    let func = match &trace.data {
+        RawOrDecodedCall::Decoded(func, _) => func.clone(),
+        RawOrDecodedCall::Raw(bytes) if bytes.len() >= 4 => {
+            format!("0x{}", hex::encode(&bytes[..4]))
+        }
+        RawOrDecodedCall::Raw(_) => "fallback".to_string(),
+    };
+    format!("{}::{}", contract, func)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::CallKind;
+
+    #[test]
+    fn can_lay_out_calls_by_gas() {
+        let mut arena = CallTraceArena::default();
+        let call = |depth, label: &str, gas_cost| CallTrace {
+            depth,
+            label: Some(label.to_string()),
+            kind: CallKind::Call,
+            data: RawOrDecodedCall::Decoded("run".to_string(), vec![]),
+            gas_cost,
+            ..Default::default()
+        };
+        arena.push_trace(0, call(0, "Test", 1000));
+        arena.push_trace(0, call(1, "A", 300));
+        arena.push_trace(0, call(1, "B", 200));
+        arena.push_trace(0, call(2, "C", 50));
+
+        let mut trace = ChromeTrace::default();
+        trace.add_arena("test", &arena);
+        let calls = trace
+            .events()
+            .iter()
+            .filter(|event| event.ph == "X")
+            .map(|event| (event.name.as_str(), event.ts, event.dur.unwrap()))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            calls,
+            vec![
+                ("Test::run", 0, 1000),
+                ("A::run", 0, 300),
+                ("B::run", 300, 200),
+                ("C::run", 300, 50)
+            ]
+        );
+        assert!(trace.to_json().unwrap().starts_with(r#"{"traceEvents":[{"name":"thread_name""#));
+    }
+}
@@ -10,7 +10,26 @@ use ethers::{
     abi::{Abi, Address, Event, Function, Param, ParamType, Token},
     types::H256,
 };
-use std::collections::{BTreeMap, HashMap};
+use foundry_utils::SignaturesCache;
+use std::{
+    collections::{BTreeMap, HashMap},
+    sync::Arc,
+};
+
+/// The functions of the local signatures cache by selector
+pub type CachedFunctions = Arc<BTreeMap<[u8; 4], Vec<Function>>>;
+
+/// Parses the functions of the local signatures cache, so that they can be shared by all
+/// decoders instead of being parsed for each of them
+pub fn parse_signatures(signatures: &SignaturesCache) -> CachedFunctions {
+    let mut functions: BTreeMap<_, Vec<_>> = BTreeMap::new();
+    for sig in signatures.all_functions() {
+        if let Ok(func) = foundry_utils::get_func(&format!("function {}", sig)) {
+            functions.entry(func.short_signature()).or_default().push(func);
+        }
+    }
+    Arc::new(functions)
+}
 
 /// Build a new [CallTraceDecoder].
 #[derive(Default)]
@@ -42,6 +61,14 @@ impl CallTraceDecoderBuilder {
         self
     }
 
+    /// Add function signatures from the local signatures cache, see [parse_signatures].
+    ///
+    /// These are only used to decode calls to functions that could not be identified otherwise.
+    pub fn with_signatures(mut self, functions: CachedFunctions) -> Self {
+        self.decoder.cached_functions = functions;
+        self
+    }
+
     /// Build the decoder.
     pub fn build(self) -> CallTraceDecoder {
         self.decoder
@@ -67,6 +94,8 @@ pub struct CallTraceDecoder {
     pub labels: HashMap<Address, String>,
     /// A mapping of addresses to their known functions
     pub functions: BTreeMap<[u8; 4], Vec<Function>>,
+    /// Functions from the local signatures cache, used if a function is not otherwise known
+    pub cached_functions: CachedFunctions,
     /// All known events
     pub events: BTreeMap<(H256, usize), Vec<Event>>,
     /// All known errors
@@ -165,6 +194,7 @@ impl CallTraceDecoder {
             ]
             .into(),
             functions,
+            cached_functions: Default::default(),
             events: CONSOLE_ABI
                 .events()
                 .map(|event| ((event.signature(), indexed_inputs(event)), vec![event.clone()]))
@@ -239,7 +269,11 @@ impl CallTraceDecoder {
                 node.decode_precompile(precompile_fn, &self.labels);
             } else if let RawOrDecodedCall::Raw(ref bytes) = node.trace.data {
                 if bytes.len() >= 4 {
-                    if let Some(funcs) = self.functions.get(&bytes[0..4]) {
+                    if let Some(funcs) = self
+                        .functions
+                        .get(&bytes[0..4])
+                        .or_else(|| self.cached_functions.get(&bytes[0..4]))
+                    {
                         node.decode_function(funcs, &self.labels, &self.errors);
                     }
                 } else {
@@ -1,4 +1,5 @@
 use super::{AddressIdentity, TraceIdentifier};
+use crate::executor::{CHEATCODE_ADDRESS, HARDHAT_CONSOLE_ADDRESS};
 use ethers::{
     abi::{Abi, Address},
     etherscan,
@@ -10,7 +11,13 @@ use futures::{
     stream::{FuturesUnordered, Stream, StreamExt},
     task::{Context, Poll},
 };
-use std::{borrow::Cow, path::PathBuf, pin::Pin};
+use std::{
+    borrow::Cow,
+    collections::{BTreeMap, BTreeSet},
+    path::PathBuf,
+    pin::Pin,
+    sync::RwLock,
+};
 use tokio::time::{Duration, Interval};
 use tracing::{trace, warn};
 
@@ -18,6 +25,11 @@ use tracing::{trace, warn};
 pub struct EtherscanIdentifier {
     /// The Etherscan client
     client: Option<etherscan::Client>,
+    /// The addresses that were already looked up, `None` if the address is not a verified
+    /// contract.
+    ///
+    /// This avoids querying Etherscan for the same addresses in the traces of every test.
+    contracts: RwLock<BTreeMap<Address, Option<(String, Abi)>>>,
 }
 
 impl EtherscanIdentifier {
@@ -42,8 +54,48 @@ impl EtherscanIdentifier {
                     etherscan::Client::new_cached(chain.into(), key, cache_path, ttl).ok()
                 })
             }),
+            contracts: Default::default(),
         }
     }
+
+    /// Identifies the addresses from the cache, looking up the addresses that are not cached yet
+    /// with `fetch`.
+    ///
+    /// `fetch` only returns the addresses Etherscan answered for, addresses that could not be
+    /// looked up, e.g. because of the rate limit, are not cached and are looked up again next
+    /// time.
+    fn identify_with<F>(&self, addresses: BTreeSet<Address>, fetch: F) -> Vec<AddressIdentity>
+    where
+        F: FnOnce(Vec<Address>) -> Vec<(Address, Option<(String, Abi)>)>,
+    {
+        let unknown = {
+            let contracts = self.contracts.read().expect("lock is not poisoned");
+            addresses
+                .iter()
+                .filter(|address| !contracts.contains_key(address))
+                .copied()
+                .collect::<Vec<_>>()
+        };
+        // the lock is not held while fetching, so that other traces can be identified meanwhile
+        if !unknown.is_empty() {
+            let fetched = fetch(unknown);
+            self.contracts.write().expect("lock is not poisoned").extend(fetched);
+        }
+
+        let contracts = self.contracts.read().expect("lock is not poisoned");
+        addresses
+            .into_iter()
+            .filter_map(|address| {
+                let (label, abi) = contracts.get(&address)?.as_ref()?;
+                Some(AddressIdentity {
+                    address,
+                    label: Some(label.clone()),
+                    contract: Some(label.clone()),
+                    abi: Some(Cow::Owned(abi.clone())),
+                })
+            })
+            .collect()
+    }
 }
 
 impl TraceIdentifier for EtherscanIdentifier {
@@ -51,33 +103,44 @@ impl TraceIdentifier for EtherscanIdentifier {
         &self,
         addresses: Vec<(&Address, Option<&Vec<u8>>)>,
     ) -> Vec<AddressIdentity> {
-        self.client.as_ref().map_or(Default::default(), |client| {
-            let mut fetcher = EtherscanFetcher::new(client.clone(), Duration::from_secs(1), 5);
+        let client = match self.client {
+            Some(ref client) => client,
+            None => return Default::default(),
+        };
+        let addresses = addresses
+            .into_iter()
+            .map(|(address, _)| *address)
+            .filter(|address| !is_builtin_address(address))
+            .collect::<BTreeSet<_>>();
 
-            for (addr, _) in addresses {
-                fetcher.push(*addr);
+        self.identify_with(addresses, |unknown| {
+            let mut fetcher = EtherscanFetcher::new(client.clone(), Duration::from_secs(1), 5);
+            for addr in unknown {
+                fetcher.push(addr);
             }
-
-            let fut = fetcher
-                .map(|(address, label, abi)| AddressIdentity {
-                    address,
-                    label: Some(label.clone()),
-                    contract: Some(label),
-                    abi: Some(Cow::Owned(abi)),
-                })
-                .collect();
-
-            foundry_utils::RuntimeOrHandle::new().block_on(fut)
+            foundry_utils::RuntimeOrHandle::new().block_on(fetcher.collect())
         })
     }
 }
 
+/// Returns `true` for addresses that are never contracts verified on Etherscan, like the
+/// precompiles and the cheatcode address
+fn is_builtin_address(address: &Address) -> bool {
+    *address <= Address::from_low_u64_be(9) ||
+        *address == CHEATCODE_ADDRESS ||
+        *address == HARDHAT_CONSOLE_ADDRESS
+}
+
 type EtherscanFuture =
     Pin<Box<dyn Future<Output = (Address, Result<ContractMetadata, EtherscanError>)>>>;
 
 /// A rate limit aware Etherscan client.
 ///
 /// Fetches information about multiple addresses concurrently, while respecting rate limits.
+///
+/// As a [Stream], it yields the name and ABI of every address that is a verified contract and
+/// `None` for the addresses Etherscan has no verified source code for. Addresses that could not
+/// be looked up are skipped.
 pub struct EtherscanFetcher {
     /// The Etherscan client
     client: etherscan::Client,
@@ -126,7 +189,7 @@ impl EtherscanFetcher {
 }
 
 impl Stream for EtherscanFetcher {
-    type Item = (Address, String, Abi);
+    type Item = (Address, Option<(String, Abi)>);
 
     fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
         let pin = self.get_mut();
@@ -149,11 +212,13 @@ impl Stream for EtherscanFetcher {
                     made_progress_this_iter = true;
                     match res {
                         Ok(mut metadata) => {
-                            if let Some(item) = metadata.items.pop() {
-                                if let Ok(abi) = serde_json::from_str(&item.abi) {
-                                    return Poll::Ready(Some((addr, item.contract_name, abi)))
-                                }
-                            }
+                            // unverified contracts are returned with the ABI "Contract source
+                            // code not verified"
+                            let contract = metadata.items.pop().and_then(|item| {
+                                let abi = serde_json::from_str(&item.abi).ok()?;
+                                Some((item.contract_name, abi))
+                            });
+                            return Poll::Ready(Some((addr, contract)))
                         }
                         Err(etherscan::errors::EtherscanError::RateLimitExceeded) => {
                             warn!(target: "etherscanidentifier", "rate limit exceeded on attempt");
@@ -173,3 +238,49 @@ impl Stream for EtherscanFetcher {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::Mutex;
+
+    #[test]
+    fn caches_only_answered_lookups() {
+        let identifier = EtherscanIdentifier { client: None, contracts: Default::default() };
+        let verified = Address::from_low_u64_be(100);
+        let unverified = Address::from_low_u64_be(101);
+        let failed = Address::from_low_u64_be(102);
+        let addresses = BTreeSet::from([verified, unverified, failed]);
+
+        let requested = Mutex::new(Vec::new());
+        let fetch = |unknown: Vec<Address>| {
+            // the cache is not locked while fetching
+            assert!(identifier.contracts.try_write().is_ok());
+            requested.lock().unwrap().push(unknown.clone());
+            unknown
+                .into_iter()
+                .filter_map(|address| {
+                    if address == verified {
+                        Some((address, Some(("Token".to_string(), Abi::default()))))
+                    } else if address == unverified {
+                        Some((address, None))
+                    } else {
+                        None
+                    }
+                })
+                .collect()
+        };
+
+        let identities = identifier.identify_with(addresses.clone(), fetch);
+        assert_eq!(identities.len(), 1);
+        assert_eq!(identities[0].address, verified);
+        assert_eq!(identities[0].label.as_deref(), Some("Token"));
+
+        // only the address that could not be looked up is requested again
+        identifier.identify_with(addresses, fetch);
+        assert_eq!(
+            requested.into_inner().unwrap(),
+            vec![vec![verified, unverified, failed], vec![failed]]
+        );
+    }
+}
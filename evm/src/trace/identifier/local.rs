@@ -2,12 +2,19 @@ use super::{AddressIdentity, TraceIdentifier};
 use ethers::{
     abi::{Abi, Address, Event},
     prelude::ArtifactId,
+    types::H256,
+    utils::keccak256,
 };
 use std::{borrow::Cow, collections::BTreeMap};
 
 /// A trace identifier that tries to identify addresses using local contracts.
+///
+/// Contracts are first looked up by the hash of their runtime code. If no contract matches
+/// exactly, e.g. because of immutable variables, the most similar local contract is used.
 pub struct LocalTraceIdentifier {
-    local_contracts: BTreeMap<Vec<u8>, (String, Abi)>,
+    local_contracts: BTreeMap<Vec<u8>, (ArtifactId, Abi)>,
+    /// The runtime code of the local contracts by its hash
+    code_hashes: BTreeMap<H256, Vec<u8>>,
 }
 
 impl LocalTraceIdentifier {
@@ -15,9 +22,11 @@ impl LocalTraceIdentifier {
         Self {
             local_contracts: known_contracts
                 .iter()
-                .map(|(id, (abi, runtime_code))| {
-                    (runtime_code.clone(), (id.name.clone(), abi.clone()))
-                })
+                .map(|(id, (abi, runtime_code))| (runtime_code.clone(), (id.clone(), abi.clone())))
+                .collect(),
+            code_hashes: known_contracts
+                .values()
+                .map(|(_, runtime_code)| (keccak256(runtime_code).into(), runtime_code.clone()))
                 .collect(),
         }
     }
@@ -26,6 +35,27 @@ impl LocalTraceIdentifier {
     pub fn events(&self) -> Vec<Event> {
         self.local_contracts.iter().flat_map(|(_, (_, abi))| abi.events().cloned()).collect()
     }
+
+    /// Returns the artifact of the local contract with the given runtime code, which unlike its
+    /// name tells apart contracts with the same name in different sources
+    pub fn identify_artifact(&self, code: &[u8]) -> Option<&ArtifactId> {
+        self.identify_code(code).map(|(id, _)| id)
+    }
+
+    /// Returns the artifact and ABI of the local contract with the given runtime code
+    fn identify_code(&self, code: &[u8]) -> Option<&(ArtifactId, Abi)> {
+        let hash: H256 = keccak256(code).into();
+        if let Some(known_code) = self.code_hashes.get(&hash) {
+            return self.local_contracts.get(known_code)
+        }
+
+        self.local_contracts
+            .iter()
+            .map(|(known_code, contract)| (diff_score(known_code, code), contract))
+            .filter(|(score, _)| *score < 0.1)
+            .min_by(|(a, _), (b, _)| a.partial_cmp(b).expect("scores are never NaN"))
+            .map(|(_, contract)| contract)
+    }
 }
 
 impl TraceIdentifier for LocalTraceIdentifier {
@@ -36,16 +66,12 @@ impl TraceIdentifier for LocalTraceIdentifier {
         addresses
             .into_iter()
             .filter_map(|(address, code)| {
-                let code = code?;
-                let (_, (name, abi)) = self
-                    .local_contracts
-                    .iter()
-                    .find(|(known_code, _)| diff_score(known_code, code) < 0.1)?;
+                let (id, abi) = self.identify_code(code?)?;
 
                 Some(AddressIdentity {
                     address: *address,
-                    contract: Some(name.clone()),
-                    label: Some(name.clone()),
+                    contract: Some(id.name.clone()),
+                    label: Some(id.name.clone()),
                     abi: Some(Cow::Borrowed(abi)),
                 })
             })
@@ -55,20 +81,54 @@ impl TraceIdentifier for LocalTraceIdentifier {
 
 /// Very simple fuzzy matching of contract bytecode.
 ///
+/// Bytes beyond the length of the shorter code count as differences, so a small contract does
+/// not match a larger contract that happens to start with the same bytes.
+///
 /// Will fail for small contracts that are essentially all immutable variables.
 fn diff_score(a: &[u8], b: &[u8]) -> f64 {
-    let cutoff_len = usize::min(a.len(), b.len());
-    if cutoff_len == 0 {
+    let max_len = usize::max(a.len(), b.len());
+    if max_len == 0 || a.is_empty() || b.is_empty() {
         return 1.0
     }
 
-    let a = &a[..cutoff_len];
-    let b = &b[..cutoff_len];
-    let mut diff_chars = 0;
-    for i in 0..cutoff_len {
-        if a[i] != b[i] {
-            diff_chars += 1;
+    let diff_chars =
+        a.iter().zip(b.iter()).filter(|(a, b)| a != b).count() + max_len - a.len().min(b.len());
+    diff_chars as f64 / max_len as f64
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::path::PathBuf;
+
+    fn artifact_id(name: &str) -> ArtifactId {
+        ArtifactId {
+            path: PathBuf::from(format!("out/{name}.sol/{name}.json")),
+            name: name.to_string(),
+            source: PathBuf::from(format!("src/{name}.sol")),
+            version: "0.8.13".parse().unwrap(),
         }
     }
-    diff_chars as f64 / cutoff_len as f64
+
+    #[test]
+    fn identifies_best_match() {
+        let small = vec![0x60, 0x80, 0x60, 0x40];
+        let large = [small.clone(), vec![0u8; 100]].concat();
+        let mut similar = large.clone();
+        similar[50] = 1;
+
+        let known_contracts = BTreeMap::from([
+            (artifact_id("Small"), (Abi::default(), small.clone())),
+            (artifact_id("Large"), (Abi::default(), large.clone())),
+        ]);
+        let identifier = LocalTraceIdentifier::new(&known_contracts);
+
+        assert_eq!(identifier.identify_code(&small).map(|(id, _)| id.name.as_str()), Some("Small"));
+        assert_eq!(identifier.identify_code(&large).map(|(id, _)| id.name.as_str()), Some("Large"));
+        assert_eq!(
+            identifier.identify_code(&similar).map(|(id, _)| id.name.as_str()),
+            Some("Large")
+        );
+        assert!(identifier.identify_code(&[0xff; 64]).is_none());
+    }
 }
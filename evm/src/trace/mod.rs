@@ -3,11 +3,13 @@
 /// Identifiers figure out what ABIs and labels belong to all the addresses of the trace.
 pub mod identifier;
 
+mod chrome;
 mod decoder;
 mod node;
 mod utils;
 
-pub use decoder::{CallTraceDecoder, CallTraceDecoderBuilder};
+pub use chrome::{ChromeTrace, ChromeTraceEvent};
+pub use decoder::{parse_signatures, CachedFunctions, CallTraceDecoder, CallTraceDecoderBuilder};
 
 use crate::{abi::CHEATCODE_ADDRESS, CallKind};
 use ansi_term::Colour;
@@ -11,22 +11,7 @@ use crate::{
     visit::{ParameterList, VResult, Visitable, Visitor},
 };
 
-/// Contains the config and rule set
-#[derive(Debug, Clone)]
-pub struct FormatterConfig {
-    /// Maximum line length where formatter will try to wrap the line
-    pub line_length: usize,
-    /// Number of spaces per indentation level
-    pub tab_width: usize,
-    /// Print spaces between brackets
-    pub bracket_spacing: bool,
-}
-
-impl Default for FormatterConfig {
-    fn default() -> Self {
-        FormatterConfig { line_length: 80, tab_width: 4, bracket_spacing: false }
-    }
-}
+pub use foundry_config::fmt::{FormatterConfig, QuoteStyle};
 
 // TODO: use it inside Formatter since they're sharing same fields
 #[derive(Default)]
@@ -93,6 +78,12 @@ impl<'a, W: Write> Formatter<'a, W> {
         write!(self, "{}", if self.config.bracket_spacing { " }" } else { "}" })
     }
 
+    /// Returns the string quoted with respect to `config.quote_style` setting
+    fn quote(&self, string: &str) -> String {
+        let quote = self.config.quote_style.quote();
+        format!("{quote}{string}{quote}")
+    }
+
     /// Write empty brackets with respect to `config.bracket_spacing` setting:
     /// `"{ }"` if `true`, `"{}"` if `false`
     fn write_empty_brackets(&mut self) -> std::fmt::Result {
@@ -386,7 +377,8 @@ impl<'a, W: Write> Visitor for Formatter<'a, W> {
     }
 
     fn visit_import_plain(&mut self, import: &mut StringLiteral) -> VResult {
-        write!(self, "import \"{}\";", &import.string)?;
+        let import = self.quote(&import.string);
+        write!(self, "import {};", import)?;
 
         Ok(())
     }
@@ -396,7 +388,8 @@ impl<'a, W: Write> Visitor for Formatter<'a, W> {
         global: &mut StringLiteral,
         alias: &mut Identifier,
     ) -> VResult {
-        write!(self, "import \"{}\" as {};", global.string, alias.name)?;
+        let global = self.quote(&global.string);
+        write!(self, "import {} as {};", global, alias.name)?;
 
         Ok(())
     }
@@ -438,7 +431,8 @@ impl<'a, W: Write> Visitor for Formatter<'a, W> {
             self.write_closing_bracket()?;
         }
 
-        write!(self, " from \"{}\";", from.string)?;
+        let from = self.quote(&from.string);
+        write!(self, " from {};", from)?;
 
         Ok(())
     }
@@ -873,6 +867,13 @@ mod tests {
                                 "bracket-spacing" => {
                                     config.bracket_spacing = value.parse().unwrap()
                                 }
+                                "quote-style" => {
+                                    config.quote_style = match value {
+                                        "double" => QuoteStyle::Double,
+                                        "single" => QuoteStyle::Single,
+                                        _ => panic!("Unknown quote style: {value}"),
+                                    }
+                                }
                                 _ => panic!("Unknown config key: {key}"),
                             }
                         }
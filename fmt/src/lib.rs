@@ -4,5 +4,5 @@ mod formatter;
 mod loc;
 mod visit;
 
-pub use formatter::{Formatter, FormatterConfig};
+pub use formatter::{Formatter, FormatterConfig, QuoteStyle};
 pub use visit::Visitable;
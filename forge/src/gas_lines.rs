@@ -0,0 +1,231 @@
+use crate::{executor::inspector::GasProfile, trace::identifier::LocalTraceIdentifier};
+use comfy_table::{modifiers::UTF8_ROUND_CORNERS, presets::UTF8_FULL, *};
+use ethers::{prelude::ArtifactId, solc::sourcemap::SourceMap};
+use std::{collections::BTreeMap, fmt::Display};
+
+/// The keywords that start the declarations lines are grouped by
+const DECLARATIONS: [&str; 5] = ["function ", "modifier ", "constructor", "fallback", "receive"];
+
+/// A source file with the offsets of its lines
+#[derive(Debug)]
+struct Source {
+    path: String,
+    content: String,
+    /// The offsets of the first characters of the lines
+    line_starts: Vec<usize>,
+}
+
+impl Source {
+    fn new(path: String, content: String) -> Self {
+        let line_starts =
+            std::iter::once(0).chain(content.match_indices('\n').map(|(i, _)| i + 1)).collect();
+        Self { path, content, line_starts }
+    }
+
+    /// Returns the number of the line that contains the character at `offset`, starting at 1
+    fn line(&self, offset: usize) -> usize {
+        self.line_starts.partition_point(|start| *start <= offset)
+    }
+
+    /// Returns the trimmed text of the line with the given number
+    fn text(&self, line: usize) -> &str {
+        let start = self.line_starts[line - 1];
+        let end = self.line_starts.get(line).copied().unwrap_or(self.content.len());
+        self.content[start..end].trim()
+    }
+
+    /// Returns the line of the nearest function, modifier, constructor, fallback or receive
+    /// declaration at or before `line`
+    fn declaration(&self, line: usize) -> Option<usize> {
+        (1..=line).rev().find(|line| {
+            let text = self.text(*line);
+            DECLARATIONS.iter().any(|keyword| text.starts_with(keyword))
+        })
+    }
+}
+
+/// Attributes the gas used by the instructions of the called contracts to the source lines they
+/// were compiled from
+#[derive(Debug, Default)]
+pub struct LineGasReport {
+    /// The runtime source maps, by artifact
+    source_maps: BTreeMap<ArtifactId, SourceMap>,
+    /// The sources, by source id
+    sources: BTreeMap<u32, Source>,
+    /// The gas used by each line, by source id and line number
+    gas: BTreeMap<u32, BTreeMap<usize, u64>>,
+}
+
+impl LineGasReport {
+    /// Creates a report for the contracts with the given runtime source maps, which refer to the
+    /// given `(id, path, content)` sources
+    pub fn new(
+        source_maps: BTreeMap<ArtifactId, SourceMap>,
+        sources: impl IntoIterator<Item = (u32, String, String)>,
+    ) -> Self {
+        Self {
+            source_maps,
+            sources: sources
+                .into_iter()
+                .map(|(id, path, content)| (id, Source::new(path, content)))
+                .collect(),
+            gas: BTreeMap::new(),
+        }
+    }
+
+    /// Adds the gas used in `profile` by the contracts that can be identified
+    pub fn analyze(&mut self, profile: &GasProfile, identifier: &LocalTraceIdentifier) {
+        for contract in profile.values() {
+            let source_map = match identifier
+                .identify_artifact(&contract.code)
+                .and_then(|id| self.source_maps.get(id))
+            {
+                Some(source_map) => source_map,
+                None => continue,
+            };
+            for (ic, gas) in contract.gas_by_ic() {
+                let element = match source_map.get(ic) {
+                    Some(element) => element,
+                    None => continue,
+                };
+                // instructions generated by the compiler have no source
+                let (id, source) =
+                    match element.index.and_then(|id| Some((id, self.sources.get(&id)?))) {
+                        Some(source) => source,
+                        None => continue,
+                    };
+                *self.gas.entry(id).or_default().entry(source.line(element.offset)).or_default() +=
+                    gas;
+            }
+        }
+    }
+
+    /// Returns the gas used by each line, by source path and line number
+    pub fn lines(&self) -> BTreeMap<&str, &BTreeMap<usize, u64>> {
+        self.gas.iter().map(|(id, lines)| (self.sources[id].path.as_str(), lines)).collect()
+    }
+
+    /// Returns a table for every source, which lists the `limit` most expensive lines of every
+    /// function
+    pub fn tables(&self, limit: usize) -> Vec<Table> {
+        let mut tables = Vec::new();
+        for (id, lines) in self.gas.iter() {
+            let source = &self.sources[id];
+
+            // lines outside of functions are grouped under the declaration `None`
+            let mut declarations: BTreeMap<Option<usize>, (u64, Vec<(usize, u64)>)> =
+                BTreeMap::new();
+            for (line, gas) in lines {
+                let declaration = declarations.entry(source.declaration(*line)).or_default();
+                declaration.0 += gas;
+                declaration.1.push((*line, *gas));
+            }
+            let mut declarations: Vec<_> = declarations.into_iter().collect();
+            declarations.sort_by(|(_, (a, _)), (_, (b, _))| b.cmp(a));
+
+            let mut table = Table::new();
+            table.load_preset(UTF8_FULL).apply_modifier(UTF8_ROUND_CORNERS);
+            table.set_header(vec![
+                Cell::new(&source.path).add_attribute(Attribute::Bold).fg(Color::Green),
+                Cell::new("Gas").add_attribute(Attribute::Bold).fg(Color::Cyan),
+                Cell::new("Source").add_attribute(Attribute::Bold).fg(Color::Cyan),
+            ]);
+            for (declaration, (total, mut lines)) in declarations {
+                let name = match declaration {
+                    Some(line) => source.text(line).trim_end_matches('{').trim_end().to_string(),
+                    None => "(outside of functions)".to_string(),
+                };
+                table.add_row(vec![
+                    Cell::new(""),
+                    Cell::new(total.to_string()).add_attribute(Attribute::Bold),
+                    Cell::new(name).add_attribute(Attribute::Bold).fg(Color::Magenta),
+                ]);
+                lines.sort_by(|(_, a), (_, b)| b.cmp(a));
+                for (line, gas) in lines.into_iter().take(limit) {
+                    table.add_row(vec![
+                        Cell::new(format!("L{line}")),
+                        Cell::new(gas.to_string()),
+                        Cell::new(source.text(line)),
+                    ]);
+                }
+            }
+            tables.push(table);
+        }
+        tables
+    }
+}
+
+impl Display for LineGasReport {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> Result<(), std::fmt::Error> {
+        for table in self.tables(10) {
+            writeln!(f, "{table}")?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::executor::inspector::ContractGas;
+    use ethers::types::Address;
+
+    #[test]
+    fn can_find_lines_and_declarations() {
+        let source = Source::new(
+            "src/Counter.sol".to_string(),
+            "contract Counter {\n    uint256 count;\n\n    function increment() public {\n        count += 1;\n    }\n}\n"
+                .to_string(),
+        );
+        assert_eq!(source.line(0), 1);
+        assert_eq!(source.line(18), 1);
+        assert_eq!(source.line(19), 2);
+        assert_eq!(source.text(5), "count += 1;");
+        assert_eq!(source.declaration(5), Some(4));
+        assert_eq!(source.declaration(2), None);
+    }
+
+    #[test]
+    fn tells_apart_contracts_with_the_same_name() {
+        let artifact_id = |dir: &str| ArtifactId {
+            path: format!("out/{dir}/Counter.sol/Counter.json").into(),
+            name: "Counter".to_string(),
+            source: format!("src/{dir}/Counter.sol").into(),
+            version: "0.8.13".parse().unwrap(),
+        };
+        let (a, b) = (artifact_id("a"), artifact_id("b"));
+        // `ADD ADD` and `MUL MUL`, compiled from the first and second source
+        let (code_a, code_b) = (vec![0x01, 0x01], vec![0x02, 0x02]);
+        let known_contracts = BTreeMap::from([
+            (a.clone(), (Default::default(), code_a.clone())),
+            (b.clone(), (Default::default(), code_b.clone())),
+        ]);
+        let identifier = LocalTraceIdentifier::new(&known_contracts);
+
+        let source_maps = BTreeMap::from([
+            (a, ethers::solc::sourcemap::parse("0:1:0:-;").unwrap()),
+            (b, ethers::solc::sourcemap::parse("0:1:1:-;").unwrap()),
+        ]);
+        let sources = [
+            (0, "src/a/Counter.sol".to_string(), "contract Counter {}\n".to_string()),
+            (1, "src/b/Counter.sol".to_string(), "contract Counter {}\n".to_string()),
+        ];
+        let mut report = LineGasReport::new(source_maps, sources);
+
+        let profile = GasProfile::from([
+            (
+                Address::from_low_u64_be(1),
+                ContractGas { code: code_a.into(), gas: BTreeMap::from([(0, 3)]) },
+            ),
+            (
+                Address::from_low_u64_be(2),
+                ContractGas { code: code_b.into(), gas: BTreeMap::from([(1, 5)]) },
+            ),
+        ]);
+        report.analyze(&profile, &identifier);
+
+        let lines = report.lines();
+        assert_eq!(lines["src/a/Counter.sol"], &BTreeMap::from([(1, 3)]));
+        assert_eq!(lines["src/b/Counter.sol"], &BTreeMap::from([(1, 5)]));
+    }
+}
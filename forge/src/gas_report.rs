@@ -1,9 +1,9 @@
 use crate::{
     executor::{CHEATCODE_ADDRESS, HARDHAT_CONSOLE_ADDRESS},
-    trace::{CallTraceArena, RawOrDecodedCall, TraceKind},
+    trace::{CallTraceArena, RawOrDecodedCall, RawOrDecodedReturnData, TraceKind},
 };
 use comfy_table::{modifiers::UTF8_ROUND_CORNERS, presets::UTF8_FULL, *};
-use ethers::types::U256;
+use ethers::types::{Address, U256};
 use serde::{Deserialize, Serialize};
 use std::{collections::BTreeMap, fmt::Display};
 
@@ -36,16 +36,34 @@ impl GasReport {
 
     pub fn analyze(&mut self, traces: &[(TraceKind, CallTraceArena)]) {
         let report_for_all = self.report_for.is_empty() || self.report_for.iter().any(|s| s == "*");
+        // The test contract is the target of the setup and execution traces, it is not part of
+        // the report
+        let test_contracts: Vec<_> = traces
+            .iter()
+            .filter(|(kind, _)| *kind != TraceKind::Deployment)
+            .map(|(_, trace)| trace.arena[0].trace.address)
+            .collect();
         traces.iter().for_each(|(_, trace)| {
-            self.analyze_trace(trace, report_for_all);
+            self.analyze_trace(trace, report_for_all, &test_contracts);
         });
     }
 
-    fn analyze_trace(&mut self, trace: &CallTraceArena, report_for_all: bool) {
-        self.analyze_node(0, trace, report_for_all);
+    fn analyze_trace(
+        &mut self,
+        trace: &CallTraceArena,
+        report_for_all: bool,
+        test_contracts: &[Address],
+    ) {
+        self.analyze_node(0, trace, report_for_all, test_contracts);
     }
 
-    fn analyze_node(&mut self, node_index: usize, arena: &CallTraceArena, report_for_all: bool) {
+    fn analyze_node(
+        &mut self,
+        node_index: usize,
+        arena: &CallTraceArena,
+        report_for_all: bool,
+        test_contracts: &[Address],
+    ) {
         let node = &arena.arena[node_index];
         let trace = &node.trace;
 
@@ -53,6 +71,13 @@ impl GasReport {
             return
         }
 
+        if trace.depth == 0 && test_contracts.contains(&trace.address) {
+            node.children.iter().for_each(|index| {
+                self.analyze_node(*index, arena, report_for_all, test_contracts);
+            });
+            return
+        }
+
         if let Some(name) = &trace.contract {
             let report_for = self.report_for.iter().any(|s| s == name);
             if report_for || report_for_all {
@@ -60,14 +85,14 @@ impl GasReport {
                     self.contracts.entry(name.to_string()).or_insert_with(Default::default);
 
                 match &trace.data {
-                    RawOrDecodedCall::Raw(bytes) if trace.created() => {
+                    RawOrDecodedCall::Raw(_) if trace.created() => {
                         contract_report.gas = trace.gas_cost.into();
-                        contract_report.size = bytes.len().into();
+                        // the output of a creation is the deployed runtime code
+                        if let RawOrDecodedReturnData::Raw(code) = &trace.output {
+                            contract_report.size = code.len().into();
+                        }
                     }
-                    // TODO: More robust test contract filtering
-                    RawOrDecodedCall::Decoded(func, _)
-                        if !func.starts_with("test") && func != "setUp" =>
-                    {
+                    RawOrDecodedCall::Decoded(func, _) => {
                         let function_report = contract_report
                             .functions
                             .entry(func.clone())
@@ -80,7 +105,7 @@ impl GasReport {
         }
 
         node.children.iter().for_each(|index| {
-            self.analyze_node(*index, arena, report_for_all);
+            self.analyze_node(*index, arena, report_for_all, test_contracts);
         });
     }
 
@@ -91,19 +116,16 @@ impl GasReport {
                 func.calls.sort();
                 func.min = func.calls.first().cloned().unwrap_or_default();
                 func.max = func.calls.last().cloned().unwrap_or_default();
-                func.mean =
-                    func.calls.iter().fold(U256::zero(), |acc, x| acc + x) / func.calls.len();
 
                 let len = func.calls.len();
-                func.median = if len > 0 {
-                    if len % 2 == 0 {
+                if len > 0 {
+                    func.mean = func.calls.iter().fold(U256::zero(), |acc, x| acc + x) / len;
+                    func.median = if len % 2 == 0 {
                         (func.calls[len / 2 - 1] + func.calls[len / 2]) / 2
                     } else {
                         func.calls[len / 2]
-                    }
-                } else {
-                    0.into()
-                };
+                    };
+                }
             });
         });
         self
@@ -113,10 +135,6 @@ impl GasReport {
 impl Display for GasReport {
     fn fmt(&self, f: &mut std::fmt::Formatter) -> Result<(), std::fmt::Error> {
         for (name, contract) in self.contracts.iter() {
-            if contract.functions.is_empty() {
-                continue
-            }
-
             let mut table = Table::new();
             table.load_preset(UTF8_FULL).apply_modifier(UTF8_ROUND_CORNERS);
             table.set_header(vec![Cell::new(format!("{name} contract"))
@@ -128,6 +146,11 @@ impl Display for GasReport {
             ]);
             table.add_row(vec![contract.gas.to_string(), contract.size.to_string()]);
 
+            if contract.functions.is_empty() {
+                writeln!(f, "{}", table)?;
+                continue
+            }
+
             table.add_row(vec![
                 Cell::new("Function Name").add_attribute(Attribute::Bold).fg(Color::Magenta),
                 Cell::new("min").add_attribute(Attribute::Bold).fg(Color::Green),
@@ -151,3 +174,82 @@ impl Display for GasReport {
         Ok(())
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{trace::CallTrace, CallKind};
+
+    #[test]
+    fn skips_only_the_test_contract() {
+        let test_contract = Address::from_low_u64_be(1);
+        let library = Address::from_low_u64_be(2);
+        let counter = Address::from_low_u64_be(3);
+
+        let deployment = |address, name: &str| {
+            let mut arena = CallTraceArena::default();
+            arena.arena[0].trace = CallTrace {
+                address,
+                contract: Some(name.to_string()),
+                kind: CallKind::Create,
+                output: RawOrDecodedReturnData::Raw(vec![0; 3]),
+                gas_cost: 100,
+                ..Default::default()
+            };
+            (TraceKind::Deployment, arena)
+        };
+
+        let mut execution = CallTraceArena::default();
+        execution.arena[0].trace = CallTrace {
+            address: test_contract,
+            contract: Some("CounterTest".to_string()),
+            data: RawOrDecodedCall::Decoded("testIncrement".to_string(), vec![]),
+            ..Default::default()
+        };
+        execution.push_trace(
+            0,
+            CallTrace {
+                depth: 1,
+                address: counter,
+                contract: Some("Counter".to_string()),
+                data: RawOrDecodedCall::Decoded("increment".to_string(), vec![]),
+                gas_cost: 50,
+                ..Default::default()
+            },
+        );
+
+        let mut report = GasReport::default();
+        report.analyze(&[
+            deployment(library, "Lib"),
+            deployment(test_contract, "CounterTest"),
+            (TraceKind::Execution, execution),
+        ]);
+
+        assert!(!report.contracts.contains_key("CounterTest"));
+        assert_eq!(report.contracts["Lib"].gas, 100.into());
+        assert_eq!(report.contracts["Lib"].size, 3.into());
+        assert_eq!(report.contracts["Counter"].functions["increment"].calls, vec![50.into()]);
+    }
+
+    #[test]
+    fn finalizes_function_stats() {
+        let mut report = GasReport::default();
+        let contract = report.contracts.entry("Counter".to_string()).or_default();
+        contract.functions.entry("increment".to_string()).or_default().calls =
+            vec![300.into(), 100.into(), 400.into(), 200.into()];
+        contract.functions.entry("reset".to_string()).or_default();
+
+        let report = report.finalize();
+        let contract = &report.contracts["Counter"];
+
+        let increment = &contract.functions["increment"];
+        assert_eq!(increment.min, 100.into());
+        assert_eq!(increment.max, 400.into());
+        assert_eq!(increment.mean, 250.into());
+        assert_eq!(increment.median, 250.into());
+
+        let reset = &contract.functions["reset"];
+        assert_eq!(reset.mean, U256::zero());
+        assert_eq!(reset.median, U256::zero());
+    }
+}
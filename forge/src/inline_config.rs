@@ -0,0 +1,258 @@
+use std::collections::BTreeMap;
+
+/// The marker of a configuration comment
+const CONFIG_PREFIX: &str = "forge-config:";
+
+/// The configuration of a single test, read from `forge-config` comments above the test function:
+///
+/// ```solidity
+/// /// forge-config: max-gas = 50_000
+/// function testTransfer() public {}
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct InlineConfig {
+    /// The test fails if it uses less gas
+    pub min_gas: Option<u64>,
+    /// The test fails if it uses more gas
+    pub max_gas: Option<u64>,
+}
+
+/// The `forge-config` comments of a source file
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct SourceInlineConfig {
+    /// The configuration of the annotated functions, by contract and function name
+    pub configs: BTreeMap<(String, String), InlineConfig>,
+    /// The comments that are invalid or misplaced, by the contract they are in. Comments outside
+    /// of a contract have an empty contract name
+    pub errors: Vec<(String, String)>,
+}
+
+impl SourceInlineConfig {
+    /// Returns the configuration of the functions of the contract, by function name
+    pub fn contract_configs(&self, contract: &str) -> BTreeMap<String, InlineConfig> {
+        self.configs
+            .iter()
+            .filter(|((name, _), _)| name == contract)
+            .map(|((_, function), config)| (function.clone(), config.clone()))
+            .collect()
+    }
+
+    /// Returns the errors of the comments in the contract or outside of any contract
+    pub fn contract_errors(&self, contract: &str) -> Vec<String> {
+        self.errors
+            .iter()
+            .filter(|(name, _)| name.is_empty() || name == contract)
+            .map(|(_, err)| err.clone())
+            .collect()
+    }
+}
+
+impl InlineConfig {
+    /// Parses the inline configuration of all functions in a source file.
+    ///
+    /// Invalid entries and comments that are not placed above a function are ignored and
+    /// collected as errors, so they don't affect the other tests of the file.
+    pub fn parse_source(source: &str) -> SourceInlineConfig {
+        let mut parsed = SourceInlineConfig::default();
+        let mut pending: Option<(usize, &str, InlineConfig)> = None;
+        let mut contract = "";
+
+        for (idx, line) in source.lines().enumerate() {
+            let line = line.trim();
+            if line.starts_with("//") || line.starts_with("/*") || line.starts_with('*') {
+                if let Some(pos) = line.find(CONFIG_PREFIX) {
+                    let (_, _, config) =
+                        pending.get_or_insert_with(|| (idx, contract, Default::default()));
+                    if let Err(err) = config.set(&line[pos + CONFIG_PREFIX.len()..]) {
+                        parsed
+                            .errors
+                            .push((contract.to_string(), format!("line {}: {}", idx + 1, err)));
+                    }
+                }
+                continue
+            }
+            if line.is_empty() {
+                continue
+            }
+            if let Some(name) = contract_name(line) {
+                contract = name;
+            }
+
+            if let Some((start, start_contract, config)) = pending.take() {
+                match function_name(line) {
+                    Some(name) => {
+                        parsed.configs.insert((contract.to_string(), name.to_string()), config);
+                    }
+                    None => parsed.errors.push((start_contract.to_string(), misplaced(start))),
+                }
+            }
+        }
+
+        if let Some((start, start_contract, _)) = pending {
+            parsed.errors.push((start_contract.to_string(), misplaced(start)));
+        }
+        parsed
+    }
+
+    /// Sets a value from a `key = value` entry
+    fn set(&mut self, entry: &str) -> eyre::Result<()> {
+        let entry = entry.trim().trim_end_matches("*/").trim_end();
+        let (key, value) = entry
+            .split_once('=')
+            .ok_or_else(|| eyre::eyre!("expected `key = value`, found `{}`", entry))?;
+        let (key, value) = (key.trim(), value.trim());
+
+        let parse_gas = || {
+            value
+                .replace('_', "")
+                .parse::<u64>()
+                .map_err(|_| eyre::eyre!("invalid gas value `{}` for `{}`", value, key))
+        };
+        match key {
+            "min-gas" => self.min_gas = Some(parse_gas()?),
+            "max-gas" => self.max_gas = Some(parse_gas()?),
+            _ => eyre::bail!("unknown key `{}`, expected `min-gas` or `max-gas`", key),
+        }
+        Ok(())
+    }
+
+    /// Returns why the test fails if the gas it used is out of the configured bounds
+    pub fn check_gas(&self, gas: u64) -> Option<String> {
+        match (self.min_gas, self.max_gas) {
+            (_, Some(max)) if gas > max => {
+                Some(format!("Gas usage {} exceeds the maximum of {}", gas, max))
+            }
+            (Some(min), _) if gas < min => {
+                Some(format!("Gas usage {} is below the minimum of {}", gas, min))
+            }
+            _ => None,
+        }
+    }
+}
+
+/// Returns the error of a comment that is not placed above a function
+fn misplaced(line: usize) -> String {
+    format!(
+        "line {}: `{}` comments must be placed directly above a function",
+        line + 1,
+        CONFIG_PREFIX
+    )
+}
+
+/// Returns the name of the function declared on the line, if any
+fn function_name(line: &str) -> Option<&str> {
+    identifier(line.strip_prefix("function")?)
+}
+
+/// Returns the name of the contract, library or interface declared on the line, if any
+fn contract_name(line: &str) -> Option<&str> {
+    let line = line.strip_prefix("abstract ").map(str::trim_start).unwrap_or(line);
+    ["contract", "library", "interface"]
+        .iter()
+        .find_map(|kind| identifier(line.strip_prefix(kind)?))
+}
+
+/// Returns the identifier at the start of `rest`, which must be separated from the preceding
+/// keyword by whitespace
+fn identifier(rest: &str) -> Option<&str> {
+    let trimmed = rest.trim_start();
+    if trimmed.len() == rest.len() {
+        return None
+    }
+    let end =
+        trimmed.find(|c: char| !(c.is_ascii_alphanumeric() || c == '_')).unwrap_or(trimmed.len());
+    (end > 0).then(|| &trimmed[..end])
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn can_parse_inline_config() {
+        let source = r#"
+contract GasTest {
+    /// forge-config: max-gas = 50_000
+    /// forge-config: min-gas = 100
+    function testBounded() public {}
+
+    /**
+     * Some docs
+     * forge-config: max-gas = 10
+     */
+    function testDocBlock() public {}
+
+    function testPlain() public {}
+}
+
+abstract contract OtherGasTest {
+    /// forge-config: max-gas = 20
+    function testBounded() public {}
+}
+"#;
+        let configs = InlineConfig::parse_source(source);
+        assert!(configs.errors.is_empty());
+        let key = |contract: &str, function: &str| (contract.to_string(), function.to_string());
+        assert_eq!(
+            configs.configs,
+            BTreeMap::from([
+                (
+                    key("GasTest", "testBounded"),
+                    InlineConfig { min_gas: Some(100), max_gas: Some(50_000) }
+                ),
+                (key("GasTest", "testDocBlock"), InlineConfig { min_gas: None, max_gas: Some(10) }),
+                (
+                    key("OtherGasTest", "testBounded"),
+                    InlineConfig { min_gas: None, max_gas: Some(20) }
+                ),
+            ])
+        );
+    }
+
+    #[test]
+    fn rejects_invalid_inline_config() {
+        let unknown_key = "/// forge-config: max-gass = 1\nfunction testA() public {}";
+        assert_eq!(InlineConfig::parse_source(unknown_key).errors.len(), 1);
+
+        let invalid_value = "/// forge-config: max-gas = lots\nfunction testA() public {}";
+        assert_eq!(InlineConfig::parse_source(invalid_value).errors.len(), 1);
+
+        let misplaced = "/// forge-config: max-gas = 1\nuint256 a;";
+        assert_eq!(InlineConfig::parse_source(misplaced).errors.len(), 1);
+    }
+
+    #[test]
+    fn invalid_inline_config_only_affects_its_contract() {
+        let source = r#"
+contract A {
+    /// forge-config: max-gas = lots
+    function testA() public {}
+
+    /// forge-config: max-gas = 1
+}
+
+contract B {
+    /// forge-config: max-gas = 10
+    function testB() public {}
+}
+"#;
+        let configs = InlineConfig::parse_source(source);
+        assert_eq!(configs.contract_errors("A").len(), 2);
+        assert!(configs.contract_errors("B").is_empty());
+        assert_eq!(
+            configs.contract_configs("B"),
+            BTreeMap::from([(
+                "testB".to_string(),
+                InlineConfig { min_gas: None, max_gas: Some(10) }
+            )])
+        );
+    }
+
+    #[test]
+    fn can_check_gas() {
+        let config = InlineConfig { min_gas: Some(10), max_gas: Some(20) };
+        assert!(config.check_gas(15).is_none());
+        assert!(config.check_gas(21).unwrap().contains("exceeds the maximum of 20"));
+        assert!(config.check_gas(9).unwrap().contains("below the minimum of 10"));
+    }
+}
@@ -1,6 +1,12 @@
 /// Gas reports
 pub mod gas_report;
 
+/// Gas usage by source line
+pub mod gas_lines;
+
+/// Per-test configuration from comments in the test sources
+pub mod inline_config;
+
 /// The Forge test runner
 mod runner;
 pub use runner::{ContractRunner, SuiteResult, TestKind, TestKindGas, TestResult};
@@ -9,6 +15,10 @@ pub use runner::{ContractRunner, SuiteResult, TestKind, TestKindGas, TestResult}
 mod multi_runner;
 pub use multi_runner::{MultiContractRunner, MultiContractRunnerBuilder};
 
+/// Live progress of test runs
+mod progress;
+pub use progress::{ProgressFn, TestEvent, TestProgress};
+
 pub trait TestFilter {
     fn matches_test(&self, test_name: impl AsRef<str>) -> bool;
     fn matches_contract(&self, contract_name: impl AsRef<str>) -> bool;
@@ -23,9 +33,10 @@ pub mod test_helpers {
     use crate::TestFilter;
     use ethers::{
         prelude::{artifacts::Settings, Lazy, ProjectCompileOutput, SolcConfig},
-        solc::{Project, ProjectPathsConfig},
+        solc::{utils::canonicalize, Project, ProjectPathsConfig},
         types::{Address, U256},
     };
+    use foundry_config::{FsAccess, FsPermissions, PathPermission};
     use foundry_evm::{
         executor::{
             builder::Backend,
@@ -86,6 +97,10 @@ pub mod test_helpers {
         sender: Address::from_str("00a329c0648769a73afac7f9381e08fb43dbea72").unwrap(),
         initial_balance: U256::MAX,
         ffi: true,
+        fs_permissions: FsPermissions::new([
+            PathPermission::new(FsAccess::Read, canonicalize("../testdata/fixtures").unwrap()),
+            PathPermission::new(FsAccess::ReadWrite, canonicalize(std::env::temp_dir()).unwrap()),
+        ]),
         memory_limit: 2u64.pow(24),
         ..Default::default()
     });
@@ -1,4 +1,6 @@
-use crate::{ContractRunner, SuiteResult, TestFilter};
+use crate::{
+    inline_config::InlineConfig, ContractRunner, SuiteResult, TestEvent, TestFilter, TestProgress,
+};
 use ethers::{
     abi::Abi,
     prelude::{artifacts::CompactContractBytecode, ArtifactId, ArtifactOutput},
@@ -6,13 +8,22 @@ use ethers::{
     types::{Address, Bytes, U256},
 };
 use eyre::Result;
-use foundry_evm::executor::{
-    builder::Backend, opts::EvmOpts, DatabaseRef, Executor, ExecutorBuilder, Fork, SpecId,
+use foundry_evm::{
+    executor::{
+        builder::Backend, fork::MultiFork, load_allocs, opts::EvmOpts, DatabaseRef, Executor,
+        ExecutorBuilder, Fork, SpecId,
+    },
+    fuzz::DifferentialReference,
 };
 use foundry_utils::{PostLinkInput, RuntimeOrHandle};
 use proptest::test_runner::TestRunner;
 use rayon::prelude::*;
-use std::{collections::BTreeMap, marker::Sync, path::Path, sync::mpsc::Sender};
+use std::{
+    collections::BTreeMap,
+    marker::Sync,
+    path::{Path, PathBuf},
+    sync::{mpsc::Sender, Arc},
+};
 
 /// Builder used for instantiating the multi-contract runner
 #[derive(Debug, Default)]
@@ -28,6 +39,18 @@ pub struct MultiContractRunnerBuilder {
     pub evm_spec: Option<SpecId>,
     /// The fork config
     pub fork: Option<Fork>,
+    /// The reference implementations of functions that are fuzzed differentially, by function
+    /// name
+    pub differential: BTreeMap<String, DifferentialReference>,
+    /// Reports the test suites and tests as they start and finish
+    pub progress: Option<TestProgress>,
+    /// The total number of worker threads the test contracts and their tests share, defaults to
+    /// the number of CPUs
+    pub jobs: Option<usize>,
+    /// Whether the gas used by each instruction of the called contracts is recorded
+    pub gas_profiling: bool,
+    /// Whether the calls to precompiles and the executed `KECCAK256` opcodes are counted
+    pub count_precompiles: bool,
 }
 
 pub type DeployableContracts = BTreeMap<ArtifactId, (Abi, Bytes, Vec<Bytes>)>;
@@ -44,10 +67,11 @@ impl MultiContractRunnerBuilder {
     where
         A: ArtifactOutput,
     {
+        let root = root.as_ref().to_path_buf();
         // This is just the contracts compiled, but we need to merge this with the read cached
         // artifacts
         let contracts = output
-            .with_stripped_file_prefixes(root)
+            .with_stripped_file_prefixes(&root)
             .into_artifacts()
             .map(|(i, c)| (i, c.into_contract_bytecode()))
             .collect::<Vec<(ArtifactId, CompactContractBytecode)>>();
@@ -87,7 +111,9 @@ impl MultiContractRunnerBuilder {
                 let abi = contract.abi.expect("We should have an abi by now");
                 // if its a test, add it to deployable contracts
                 if abi.constructor.as_ref().map(|c| c.inputs.is_empty()).unwrap_or(true) &&
-                    abi.functions().any(|func| func.name.starts_with("test"))
+                    abi.functions().any(|func| {
+                        func.name.starts_with("test") || self.differential.contains_key(&func.name)
+                    })
                 {
                     deployable_contracts
                         .insert(id.clone(), (abi.clone(), bytecode, dependencies.to_vec()));
@@ -112,7 +138,13 @@ impl MultiContractRunnerBuilder {
             fuzzer: self.fuzzer,
             errors: Some(execution_info.2),
             source_paths,
+            root,
             fork: self.fork,
+            differential: self.differential,
+            progress: self.progress,
+            jobs: self.jobs,
+            gas_profiling: self.gas_profiling,
+            count_precompiles: self.count_precompiles,
         })
     }
 
@@ -145,6 +177,46 @@ impl MultiContractRunnerBuilder {
         self.fork = fork;
         self
     }
+
+    /// Fuzzes the functions with the given name in all test contracts differentially, comparing
+    /// their outputs with the outputs of the reference implementation
+    #[must_use]
+    pub fn differential(
+        mut self,
+        function: impl Into<String>,
+        reference: DifferentialReference,
+    ) -> Self {
+        self.differential.insert(function.into(), reference);
+        self
+    }
+
+    /// Reports the progress of the test run to the given callback
+    #[must_use]
+    pub fn progress(mut self, progress: TestProgress) -> Self {
+        self.progress = Some(progress);
+        self
+    }
+
+    /// Runs the test contracts and their tests on at most `jobs` worker threads in total
+    #[must_use]
+    pub fn jobs(mut self, jobs: usize) -> Self {
+        self.jobs = Some(jobs.max(1));
+        self
+    }
+
+    /// Records the gas used by each instruction in the results of the standard tests
+    #[must_use]
+    pub fn gas_profiling(mut self, enable: bool) -> Self {
+        self.gas_profiling = enable;
+        self
+    }
+
+    /// Counts the calls to precompiles and the executed `KECCAK256` opcodes of the fuzz cases
+    #[must_use]
+    pub fn count_precompiles(mut self, enable: bool) -> Self {
+        self.count_precompiles = enable;
+        self
+    }
 }
 
 /// A multi contract runner receives a set of contracts deployed in an EVM instance and proceeds
@@ -167,8 +239,21 @@ pub struct MultiContractRunner {
     sender: Option<Address>,
     /// A map of contract names to absolute source file paths
     pub source_paths: BTreeMap<String, String>,
+    /// The root of the project, the sources of the contracts are relative to it
+    pub root: PathBuf,
     /// The fork config
     pub fork: Option<Fork>,
+    /// The reference implementations of functions that are fuzzed differentially
+    pub differential: BTreeMap<String, DifferentialReference>,
+    /// Reports the test suites and tests as they start and finish
+    pub progress: Option<TestProgress>,
+    /// The total number of worker threads the test contracts and their tests share, defaults to
+    /// the number of CPUs
+    pub jobs: Option<usize>,
+    /// Whether the gas used by each instruction of the called contracts is recorded
+    pub gas_profiling: bool,
+    /// Whether the calls to precompiles and the executed `KECCAK256` opcodes are counted
+    pub count_precompiles: bool,
 }
 
 impl MultiContractRunner {
@@ -195,47 +280,130 @@ impl MultiContractRunner {
         let env = runtime.block_on(self.evm_opts.evm_env());
 
         // the db backend that serves all the data
-        let db = runtime.block_on(Backend::new(self.fork.take(), &env));
+        let fork = self.fork.take();
+        let pinned_fork = fork.as_ref().and_then(|fork| Some((fork.url.clone(), fork.pin_block?)));
+        let db = runtime.block_on(Backend::new(fork, &env));
 
-        let results = self
-            .contracts
-            .par_iter()
-            .filter(|(id, _)| {
-                filter.matches_path(id.source.to_string_lossy()) &&
-                    filter.matches_contract(&id.name)
-            })
-            .filter(|(_, (abi, _, _))| abi.functions().any(|func| filter.matches_test(&func.name)))
-            .map(|(id, (abi, deploy_code, libs))| {
-                let mut builder = ExecutorBuilder::new()
-                    .with_cheatcodes(self.evm_opts.ffi)
-                    .with_config(env.clone())
-                    .with_spec(self.evm_spec)
-                    .with_gas_limit(self.evm_opts.gas_limit());
-
-                if self.evm_opts.verbosity >= 3 {
-                    builder = builder.with_tracing();
-                }
+        // the `forge-config` comments of the test sources
+        let mut inline_configs = BTreeMap::new();
+        for (id, _) in self.contracts.iter().filter(|(id, _)| {
+            filter.matches_path(id.source.to_string_lossy()) && filter.matches_contract(&id.name)
+        }) {
+            if inline_configs.contains_key(&id.source) {
+                continue
+            }
+            // the sources are relative to the project root
+            let path = self.root.join(&id.source);
+            let source = std::fs::read_to_string(&path)
+                .map_err(|err| eyre::eyre!("Failed to read {}: {}", path.display(), err))?;
+            inline_configs.insert(id.source.clone(), InlineConfig::parse_source(&source));
+        }
 
-                let executor = builder.build(db.clone());
-                let result = self.run_tests(
-                    &id.identifier(),
-                    abi,
-                    executor,
-                    deploy_code.clone(),
-                    libs,
-                    (filter, include_fuzz_tests),
-                )?;
-                Ok((id.identifier(), result))
-            })
-            .filter_map(Result::<_>::ok)
-            .filter(|(_, results)| !results.is_empty())
-            .map_with(stream_result, |stream_result, (name, result)| {
-                if let Some(stream_result) = stream_result.as_ref() {
-                    stream_result.send((name.clone(), result.clone())).unwrap();
-                }
-                (name, result)
-            })
-            .collect::<BTreeMap<_, _>>();
+        // spawns the forks created by tests via cheatcodes
+        let forks = MultiFork::new(runtime.handle(), self.evm_opts.fork_retry_backoff);
+        // forks of the pinned block of the forked endpoint share the cached state of all test
+        // contracts instead of fetching it again
+        if let (Some((url, block)), Backend::Forked(backend)) = (pinned_fork, &db) {
+            forks.register(url, block, backend.clone(), env.clone());
+        }
+
+        // the accounts that are loaded into the state of every test contract
+        let allocs = self.evm_opts.allocs.as_ref().map(load_allocs).transpose()?;
+
+        // the known errors, used to decode the reverts of failed `expectRevert`s
+        let errors = self.errors.clone().map(Arc::new);
+
+        // every test contract gets its own executor, so they are run in parallel
+        let run = || {
+            self.contracts
+                .par_iter()
+                .filter(|(id, _)| {
+                    filter.matches_path(id.source.to_string_lossy()) &&
+                        filter.matches_contract(&id.name)
+                })
+                .filter(|(_, (abi, _, _))| {
+                    abi.functions().any(|func| filter.matches_test(&func.name))
+                })
+                .map(|(id, (abi, deploy_code, libs))| {
+                    let mut builder = ExecutorBuilder::new()
+                        .with_cheatcodes(self.evm_opts.ffi)
+                        .with_fs_permissions(self.evm_opts.fs_permissions.clone(), &self.root)
+                        .with_denied_cheatcodes(self.evm_opts.sandbox.denied(&id.source))
+                        .with_config(env.clone())
+                        .with_spec(self.evm_spec)
+                        .with_gas_limit(self.evm_opts.gas_limit())
+                        .with_forks(forks.clone());
+
+                    if let Some(ref errors) = errors {
+                        builder = builder.with_errors(errors.clone());
+                    }
+                    if self.evm_opts.verbosity >= 3 {
+                        builder = builder.with_tracing();
+                    }
+                    if self.evm_opts.deterministic_addresses {
+                        builder = builder.with_deterministic_addresses();
+                    }
+                    if self.evm_opts.isolate {
+                        builder = builder.with_isolation();
+                    }
+                    if self.evm_opts.deny_reserved_addresses {
+                        builder = builder.with_reserved_addresses_denied();
+                    }
+                    if self.gas_profiling {
+                        builder = builder.with_gas_profiling();
+                    }
+                    if self.count_precompiles {
+                        builder = builder.with_precompile_counting();
+                    }
+
+                    let mut executor = builder.build(db.clone());
+                    if let Some(ref allocs) = allocs {
+                        executor.load_allocs(allocs);
+                    }
+
+                    let progress =
+                        self.progress.as_ref().map(|progress| progress.suite(id.identifier()));
+                    if let Some(ref progress) = progress {
+                        progress.emit(TestEvent::SuiteStarted);
+                    }
+
+                    let inline_config = inline_configs.get(&id.source).cloned().unwrap_or_default();
+                    let result = self.run_tests(
+                        &id.identifier(),
+                        abi,
+                        executor,
+                        deploy_code.clone(),
+                        libs,
+                        inline_config.contract_configs(&id.name),
+                        inline_config
+                            .contract_errors(&id.name)
+                            .into_iter()
+                            .map(|err| {
+                                format!("Invalid inline config in {}: {}", id.source.display(), err)
+                            })
+                            .collect(),
+                        progress.clone(),
+                        (filter, include_fuzz_tests),
+                    )?;
+                    if let Some(ref progress) = progress {
+                        progress.emit(TestEvent::SuiteFinished { result: &result });
+                    }
+                    Ok((id.identifier(), result))
+                })
+                .filter_map(Result::<_>::ok)
+                .filter(|(_, results)| !results.is_empty())
+                .map_with(stream_result, |stream_result, (name, result)| {
+                    if let Some(stream_result) = stream_result.as_ref() {
+                        stream_result.send((name.clone(), result.clone())).unwrap();
+                    }
+                    (name, result)
+                })
+                .collect::<BTreeMap<_, _>>()
+        };
+        let results = match self.jobs {
+            Some(jobs) => rayon::ThreadPoolBuilder::new().num_threads(jobs).build()?.install(run),
+            None => run(),
+        };
         Ok(results)
     }
 
@@ -246,6 +414,7 @@ impl MultiContractRunner {
         err,
         fields(name = %_name)
     )]
+    #[allow(clippy::too_many_arguments)]
     fn run_tests<DB: DatabaseRef + Send + Sync>(
         &self,
         _name: &str,
@@ -253,6 +422,9 @@ impl MultiContractRunner {
         executor: Executor<DB>,
         deploy_code: Bytes,
         libs: &[Bytes],
+        inline_config: BTreeMap<String, InlineConfig>,
+        warnings: Vec<String>,
+        progress: Option<TestProgress>,
         (filter, include_fuzz_tests): (&impl TestFilter, bool),
     ) -> Result<SuiteResult> {
         let mut runner = ContractRunner::new(
@@ -264,6 +436,10 @@ impl MultiContractRunner {
             self.errors.as_ref(),
             libs,
         );
+        runner.inline_config = inline_config;
+        runner.warnings = warnings;
+        runner.differential = self.differential.clone();
+        runner.progress = progress;
         runner.run_tests(filter, self.fuzzer.clone(), include_fuzz_tests)
     }
 }
@@ -276,8 +452,11 @@ mod tests {
         test_helpers::{
             filter::Filter, COMPILED, COMPILED_WITH_LIBS, EVM_OPTS, LIBS_PROJECT, PROJECT,
         },
+        TestKind,
     };
+    use ethers::abi::Token;
     use foundry_evm::trace::TraceKind;
+    use std::sync::Mutex;
 
     /// Builds a base runner
     fn base_runner() -> MultiContractRunnerBuilder {
@@ -304,7 +483,13 @@ mod tests {
         opts.fork_url = Some(rpc.to_string());
         let chain_id = opts.get_chain_id();
 
-        let fork = Some(Fork { cache_path: None, url: rpc.to_string(), pin_block: None, chain_id });
+        let fork = Some(Fork {
+            cache_path: None,
+            url: rpc.to_string(),
+            pin_block: None,
+            chain_id,
+            retry_backoff: None,
+        });
         base_runner()
             .with_fork(fork)
             .build(&(*LIBS_PROJECT).paths.root, (*COMPILED_WITH_LIBS).clone(), opts)
@@ -532,6 +717,31 @@ mod tests {
 
     #[test]
     fn test_cheats() {
+        // read by the environment cheatcode tests in `cheats/Env.t.sol`
+        for (key, value) in [
+            ("_FOUNDRY_TEST_ENV_BOOL", "true"),
+            (
+                "_FOUNDRY_TEST_ENV_UINT",
+                "115792089237316195423570985008687907853269984665640564039457584007913129639935",
+            ),
+            ("_FOUNDRY_TEST_ENV_UINT_HEX", "0xff"),
+            ("_FOUNDRY_TEST_ENV_INT", "-42"),
+            ("_FOUNDRY_TEST_ENV_ADDRESS", "0x7109709ECfa91a80626fF3989D68f67F5b1DD12D"),
+            (
+                "_FOUNDRY_TEST_ENV_BYTES32",
+                "0x0000000000000000000000000000000000000000000000000000000000000001",
+            ),
+            ("_FOUNDRY_TEST_ENV_STRING", "hello, world"),
+            ("_FOUNDRY_TEST_ENV_BYTES", "0xdeadbeef"),
+            ("_FOUNDRY_TEST_ENV_UINT_ARRAY", "1, 2,0x10"),
+            (
+                "_FOUNDRY_TEST_ENV_ADDRESS_ARRAY",
+                "0x7109709ECfa91a80626fF3989D68f67F5b1DD12D,0x0000000000000000000000000000000000000000",
+            ),
+        ] {
+            std::env::set_var(key, value);
+        }
+
         let mut runner = runner();
         let suite_result = runner.test(&Filter::new(".*", ".*", ".*cheats"), None, true).unwrap();
 
@@ -549,6 +759,133 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_skip() {
+        let mut runner = runner();
+        let suite_result =
+            runner.test(&Filter::new(".*", "SkipTest", ".*cheats"), None, true).unwrap();
+
+        let SuiteResult { test_results, .. } =
+            suite_result.get("cheats/Skip.t.sol:SkipTest").expect("skip tests were not run");
+        for (test_name, result) in test_results {
+            let should_skip = test_name != "testFailNotSkip()";
+            assert!(result.success, "Test {} did not pass as expected.", test_name);
+            assert_eq!(result.skipped, should_skip, "Test {} has the wrong skip status", test_name);
+            assert!(result.reason.is_none() || !should_skip);
+        }
+    }
+
+    #[test]
+    fn test_deterministic_addresses() {
+        let mut opts = EVM_OPTS.clone();
+        opts.deterministic_addresses = true;
+        let mut runner =
+            base_runner().build(&(*PROJECT).paths.root, (*COMPILED).clone(), opts).unwrap();
+        let suite_result =
+            runner.test(&Filter::new(".*", ".*", ".*addresses"), None, true).unwrap();
+        assert!(!suite_result.is_empty(), "no tests were run");
+
+        for (_, SuiteResult { test_results, .. }) in suite_result {
+            for (test_name, result) in test_results {
+                assert!(
+                    result.success,
+                    "Test {} did not pass as expected.\nReason: {:?}",
+                    test_name, result.reason
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn test_deny_reserved_addresses() {
+        let mut opts = EVM_OPTS.clone();
+        opts.deny_reserved_addresses = true;
+        let mut runner =
+            base_runner().build(&(*PROJECT).paths.root, (*COMPILED).clone(), opts).unwrap();
+        let suite_result = runner.test(&Filter::new(".*", ".*", ".*reserved"), None, true).unwrap();
+        assert!(!suite_result.is_empty(), "no tests were run");
+
+        for (_, SuiteResult { test_results, .. }) in suite_result {
+            for (test_name, result) in test_results {
+                assert!(
+                    result.success,
+                    "Test {} did not pass as expected.\nReason: {:?}",
+                    test_name, result.reason
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn test_isolate() {
+        let mut opts = EVM_OPTS.clone();
+        opts.isolate = true;
+        let mut runner =
+            base_runner().build(&(*PROJECT).paths.root, (*COMPILED).clone(), opts).unwrap();
+        let suite_result = runner.test(&Filter::new(".*", ".*", ".*isolate"), None, true).unwrap();
+        assert!(!suite_result.is_empty(), "no tests were run");
+
+        for (_, SuiteResult { test_results, .. }) in suite_result {
+            for (test_name, result) in test_results {
+                assert!(
+                    result.success,
+                    "Test {} did not pass as expected.\nReason: {:?}",
+                    test_name, result.reason
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn test_table() {
+        let mut runner = runner();
+        let suite_result = runner.test(&Filter::new(".*", ".*", ".*table"), None, false).unwrap();
+
+        let results = suite_result
+            .into_values()
+            .flat_map(|SuiteResult { test_results, .. }| test_results)
+            .map(|(test_name, result)| (test_name, result.success))
+            .collect::<BTreeMap<_, _>>();
+        assert_eq!(
+            results,
+            BTreeMap::from([
+                ("testFailFixture(uint256)[0]".to_string(), true),
+                ("testFailFixture(uint256)[1]".to_string(), true),
+                ("testFailFixture(uint256)[2]".to_string(), true),
+                ("testMismatchedFixtures(uint256,uint256)".to_string(), false),
+                ("testMultipleFixtures(uint256,address)[0]".to_string(), true),
+                ("testMultipleFixtures(uint256,address)[1]".to_string(), true),
+                ("testMultipleFixtures(uint256,address)[2]".to_string(), true),
+                ("testSingleFixture(uint256)[0]".to_string(), true),
+                ("testSingleFixture(uint256)[1]".to_string(), true),
+                ("testSingleFixture(uint256)[2]".to_string(), true),
+            ])
+        );
+    }
+
+    #[test]
+    fn test_inline_gas_config() {
+        let mut runner = runner();
+        let suite_result = runner.test(&Filter::new(".*", ".*", ".*inline"), None, true).unwrap();
+
+        let results = suite_result
+            .into_values()
+            .flat_map(|SuiteResult { test_results, .. }| test_results)
+            .map(|(test_name, result)| (test_name, (result.success, result.reason)))
+            .collect::<BTreeMap<_, _>>();
+        assert_eq!(results.len(), 4);
+        assert_eq!(results["testWithinBounds()"], (true, None));
+        assert_eq!(results["testUnbounded()"], (true, None));
+
+        let (success, reason) = &results["testAboveMaximum()"];
+        assert!(!success);
+        assert!(reason.as_ref().unwrap().contains("exceeds the maximum of 1000"));
+
+        let (success, reason) = &results["testBelowMinimum()"];
+        assert!(!success);
+        assert!(reason.as_ref().unwrap().contains("is below the minimum of 1000000"));
+    }
+
     #[test]
     fn test_fuzz() {
         let mut runner = runner();
@@ -578,6 +915,242 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_differential() {
+        let average = |inputs: &[Token]| -> eyre::Result<Vec<Token>> {
+            let (a, b) =
+                (inputs[0].clone().into_uint().unwrap(), inputs[1].clone().into_uint().unwrap());
+            Ok(vec![Token::Uint((a + b) / 2)])
+        };
+        let fuzzer = TestRunner::new(proptest::test_runner::Config {
+            failure_persistence: None,
+            ..Default::default()
+        });
+        let mut runner = base_runner()
+            .fuzzer(fuzzer)
+            .differential("average", DifferentialReference::callback(average))
+            .differential("averageBroken", DifferentialReference::callback(average))
+            .differential(
+                "checkedDiv",
+                DifferentialReference::callback(|inputs| {
+                    let (a, b) = (
+                        inputs[0].clone().into_uint().unwrap(),
+                        inputs[1].clone().into_uint().unwrap(),
+                    );
+                    a.checked_div(b)
+                        .map(|quotient| vec![Token::Uint(quotient)])
+                        .ok_or_else(|| eyre::eyre!("division by zero"))
+                }),
+            )
+            .differential(
+                "answer",
+                DifferentialReference::command([
+                    "sh",
+                    "-c",
+                    "printf 0x000000000000000000000000000000000000000000000000000000000000002a",
+                    "sh",
+                ]),
+            )
+            .build(&(*PROJECT).paths.root, (*COMPILED).clone(), EVM_OPTS.clone())
+            .unwrap();
+        let suite_result =
+            runner.test(&Filter::new(".*", ".*", ".*differential"), None, true).unwrap();
+        let results =
+            &suite_result["differential/Differential.t.sol:DifferentialTest"].test_results;
+
+        for test in ["average(uint128,uint128)", "checkedDiv(uint256,uint256)", "answer(uint8)"] {
+            let result = &results[test];
+            assert!(result.success, "{} did not pass: {:?}", test, result.reason);
+            let stats = result.differential.as_ref().unwrap();
+            assert_eq!(stats.mismatches, 0);
+            assert!(stats.runs > 0);
+        }
+
+        let broken = &results["averageBroken(uint128,uint128)"];
+        assert!(!broken.success);
+        assert!(broken.reason.as_ref().unwrap().starts_with("Outputs differ"));
+        assert!(broken.counterexample.is_some());
+        assert!(broken.differential.as_ref().unwrap().mismatches > 0);
+    }
+
+    #[test]
+    fn test_jobs() {
+        let filter = Filter::new(".*", ".*", ".*core");
+        let outcome = |mut runner: MultiContractRunner| {
+            runner
+                .test(&filter, None, true)
+                .unwrap()
+                .into_iter()
+                .map(|(name, suite)| {
+                    let results = suite
+                        .test_results
+                        .into_iter()
+                        .map(|(test, result)| (test, result.success))
+                        .collect::<BTreeMap<_, _>>();
+                    (name, results)
+                })
+                .collect::<BTreeMap<_, _>>()
+        };
+
+        let sequential = base_runner()
+            .jobs(1)
+            .build(&(*PROJECT).paths.root, (*COMPILED).clone(), EVM_OPTS.clone())
+            .unwrap();
+        assert_eq!(outcome(sequential), outcome(runner()));
+    }
+
+    #[test]
+    fn test_precompile_calls() {
+        let mut runner = base_runner()
+            .count_precompiles(true)
+            .build(&(*PROJECT).paths.root, (*COMPILED).clone(), EVM_OPTS.clone())
+            .unwrap();
+        let suite_result =
+            runner.test(&Filter::new(".*", ".*", ".*precompiles"), None, true).unwrap();
+        let result = &suite_result["precompiles/PrecompileCalls.t.sol:PrecompileCallsTest"]
+            .test_results["testHashAndRecover(bytes32,uint8,bytes32,bytes32)"];
+        assert!(result.success);
+
+        let cases = match result.kind {
+            TestKind::Fuzz(ref cases) => cases,
+            _ => panic!("expected a fuzz test"),
+        };
+        let stats = cases.precompile_stats();
+        let ecrecover = &stats["ecrecover"];
+        assert_eq!(ecrecover.runs, cases.cases().len());
+        assert_eq!(ecrecover.calls, 2 * cases.cases().len() as u64);
+        assert_eq!(ecrecover.max, 2);
+        assert!(stats["keccak256"].calls >= cases.cases().len() as u64);
+    }
+
+    #[test]
+    fn test_gas_profiling() {
+        let mut runner = base_runner()
+            .gas_profiling(true)
+            .build(&(*PROJECT).paths.root, (*COMPILED).clone(), EVM_OPTS.clone())
+            .unwrap();
+        let suite_result =
+            runner.test(&Filter::new("testNested", ".*", ".*core"), None, true).unwrap();
+        let result = &suite_result["core/LibraryLinking.t.sol:LibraryLinkingTest"].test_results
+            ["testNested()"];
+        assert!(result.success);
+
+        // the test contract, the consumer and both libraries are called
+        let profile = result.gas_profile.as_ref().unwrap();
+        let called = profile.values().filter(|contract| !contract.gas.is_empty()).count();
+        assert_eq!(called, 4);
+        let total: u64 = profile.values().flat_map(|contract| contract.gas.values()).sum();
+        assert!(total > 0);
+        assert!(total <= result.kind.gas_used().gas());
+    }
+
+    #[test]
+    fn test_differential_commands_require_ffi() {
+        let answer = || {
+            DifferentialReference::command([
+                "sh",
+                "-c",
+                "printf 0x000000000000000000000000000000000000000000000000000000000000002a",
+                "sh",
+            ])
+        };
+        let reason = |opts: EvmOpts| {
+            let fuzzer = TestRunner::new(proptest::test_runner::Config {
+                failure_persistence: None,
+                ..Default::default()
+            });
+            let mut runner = base_runner()
+                .fuzzer(fuzzer)
+                .differential("answer", answer())
+                .build(&(*PROJECT).paths.root, (*COMPILED).clone(), opts)
+                .unwrap();
+            let suite_result =
+                runner.test(&Filter::new("answer", ".*", ".*differential"), None, true).unwrap();
+            let result = &suite_result["differential/Differential.t.sol:DifferentialTest"]
+                .test_results["answer(uint8)"];
+            assert!(!result.success);
+            assert!(result.differential.is_none());
+            result.reason.clone().unwrap()
+        };
+
+        let mut opts = EVM_OPTS.clone();
+        opts.ffi = false;
+        assert!(reason(opts).starts_with("FFI disabled"));
+
+        let mut opts = EVM_OPTS.clone();
+        opts.sandbox.paths = vec!["differential".into()];
+        assert_eq!(
+            reason(opts),
+            "The `ffi` cheatcodes are not allowed in this test, see `sandbox` in the config"
+        );
+    }
+
+    #[test]
+    fn test_sandbox() {
+        let mut opts = EVM_OPTS.clone();
+        opts.sandbox.paths = vec!["sandbox".into()];
+        let mut runner =
+            base_runner().build(&(*PROJECT).paths.root, (*COMPILED).clone(), opts).unwrap();
+        let suite_result = runner.test(&Filter::new(".*", ".*", ".*sandbox"), None, true).unwrap();
+
+        // every denied cheatcode reverts with the sandbox error, which the tests check
+        let results = &suite_result["sandbox/Sandbox.t.sol:SandboxTest"].test_results;
+        let tests: Vec<_> = results.keys().map(String::as_str).collect();
+        assert_eq!(
+            tests,
+            [
+                "testEnvIsDenied()",
+                "testFfiIsDenied()",
+                "testFsIsDenied()",
+                "testGetCodeIsDenied()",
+                "testLoadAllocsIsDenied()",
+                "testOtherCheatcodesAreAllowed()",
+                "testTempDirIsDenied()",
+            ]
+        );
+        for (test_name, result) in results {
+            assert!(
+                result.success,
+                "Test {} did not pass as expected.\nReason: {:?}",
+                test_name, result.reason
+            );
+        }
+    }
+
+    #[test]
+    fn test_progress() {
+        let events = Arc::new(Mutex::new(Vec::new()));
+        let recorded = events.clone();
+        let mut runner = base_runner()
+            .progress(TestProgress::new(move |suite, event| {
+                let event = match event {
+                    TestEvent::SuiteStarted => "suite started".to_string(),
+                    TestEvent::TestStarted { test } => format!("started {}", test),
+                    TestEvent::TestFinished { test, result } => {
+                        format!("finished {} {}", test, result.success)
+                    }
+                    TestEvent::SuiteFinished { result } => {
+                        format!("suite finished {}", result.len())
+                    }
+                };
+                recorded.lock().unwrap().push((suite.to_string(), event));
+            }))
+            .build(&(*PROJECT).paths.root, (*COMPILED).clone(), EVM_OPTS.clone())
+            .unwrap();
+        runner.test(&Filter::new(".*", ".*", ".*core/DSStyle"), None, true).unwrap();
+
+        let suite = "core/DSStyle.t.sol:DSStyleTest".to_string();
+        assert_eq!(
+            *events.lock().unwrap(),
+            vec![
+                (suite.clone(), "suite started".to_string()),
+                (suite.clone(), "started testFailingAssertions()".to_string()),
+                (suite.clone(), "finished testFailingAssertions() false".to_string()),
+                (suite, "suite finished 1".to_string()),
+            ]
+        );
+    }
+
     #[test]
     fn test_trace() {
         let mut runner = tracing_runner();
@@ -0,0 +1,50 @@
+use crate::{SuiteResult, TestResult};
+use std::{fmt, sync::Arc};
+
+/// The signature of a [`TestProgress`] callback, called with the name of the test suite and the
+/// event
+pub type ProgressFn = dyn Fn(&str, TestEvent<'_>) + Send + Sync;
+
+/// An event emitted while tests are running
+#[derive(Clone, Copy)]
+pub enum TestEvent<'a> {
+    /// The test contract is about to be deployed and set up
+    SuiteStarted,
+    /// A test function is about to run
+    TestStarted { test: &'a str },
+    /// A test finished, table tests finish once for every row of their fixtures
+    TestFinished { test: &'a str, result: &'a TestResult },
+    /// All tests of the test contract finished
+    SuiteFinished { result: &'a SuiteResult },
+}
+
+/// Reports the progress of a test run as it happens.
+///
+/// Test suites and the tests inside them run in parallel, so the callback is called from multiple
+/// threads at once.
+#[derive(Clone)]
+pub struct TestProgress {
+    callback: Arc<ProgressFn>,
+    suite: String,
+}
+
+impl TestProgress {
+    pub fn new(callback: impl Fn(&str, TestEvent<'_>) + Send + Sync + 'static) -> Self {
+        Self { callback: Arc::new(callback), suite: String::new() }
+    }
+
+    /// Returns a handle that reports the events of the given test suite
+    pub fn suite(&self, suite: impl Into<String>) -> Self {
+        Self { callback: self.callback.clone(), suite: suite.into() }
+    }
+
+    pub fn emit(&self, event: TestEvent<'_>) {
+        (self.callback)(&self.suite, event)
+    }
+}
+
+impl fmt::Debug for TestProgress {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("TestProgress").field("suite", &self.suite).finish_non_exhaustive()
+    }
+}
@@ -1,12 +1,22 @@
-use crate::TestFilter;
+use crate::{
+    inline_config::InlineConfig,
+    progress::{TestEvent, TestProgress},
+    TestFilter,
+};
 use ethers::{
-    abi::{Abi, Function, RawLog},
+    abi::{Abi, Function, RawLog, Token},
     types::{Address, Bytes, U256},
 };
 use eyre::Result;
 use foundry_evm::{
-    executor::{CallResult, DatabaseRef, DeployResult, EvmError, Executor},
-    fuzz::{CounterExample, FuzzedCases, FuzzedExecutor},
+    executor::{
+        inspector::{GasProfile, MAGIC_SKIP_REASON},
+        CallResult, DatabaseRef, DeployResult, EvmError, Executor, RawCallResult,
+    },
+    fuzz::{
+        CounterExample, DifferentialReference, DifferentialStats, FuzzTestResult, FuzzedCases,
+        FuzzedExecutor,
+    },
     trace::{CallTraceArena, TraceKind},
     CALLER,
 };
@@ -56,6 +66,11 @@ pub struct TestResult {
     /// (prefixed with `testFail`)
     pub success: bool,
 
+    /// Whether the test was skipped with the `skip` cheatcode.
+    ///
+    /// Skipped tests are not considered failures.
+    pub skipped: bool,
+
     /// If there was a revert, this field will be populated. Note that the test can
     /// still be successful (i.e self.success == true) when it's expected to fail.
     pub reason: Option<String>,
@@ -76,6 +91,23 @@ pub struct TestResult {
 
     /// Labeled addresses
     pub labeled_addresses: BTreeMap<Address, String>,
+
+    /// The time it took to run the test
+    pub duration: Duration,
+
+    /// How often the outputs matched the reference implementation, for differential fuzz tests
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub differential: Option<DifferentialStats>,
+
+    /// The gas used by the code regions measured with `startSnapshotGas` and `stopSnapshotGas`,
+    /// by name
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub gas_snapshots: BTreeMap<String, u64>,
+
+    /// The gas used by each instruction of the called contracts, for standard tests run with gas
+    /// profiling
+    #[serde(skip)]
+    pub gas_profile: Option<GasProfile>,
 }
 
 impl TestResult {
@@ -155,6 +187,8 @@ pub struct TestSetup {
     pub setup_failed: bool,
     /// The reason the setup failed
     pub reason: Option<String>,
+    /// Whether the setup was skipped with the `skip` cheatcode
+    pub skipped: bool,
 }
 
 pub struct ContractRunner<'a, DB: DatabaseRef> {
@@ -174,6 +208,15 @@ pub struct ContractRunner<'a, DB: DatabaseRef> {
     pub initial_balance: U256,
     /// The address which will be used as the `from` field in all EVM calls
     pub sender: Address,
+    /// The inline configuration of the tests, by function name
+    pub inline_config: BTreeMap<String, InlineConfig>,
+    /// Warnings about the contract that are reported with the results of its tests
+    pub warnings: Vec<String>,
+    /// The reference implementations of functions that are fuzzed differentially, by function
+    /// name
+    pub differential: BTreeMap<String, DifferentialReference>,
+    /// Reports the tests as they start and finish
+    pub progress: Option<TestProgress>,
 }
 
 impl<'a, DB: DatabaseRef> ContractRunner<'a, DB> {
@@ -195,6 +238,10 @@ impl<'a, DB: DatabaseRef> ContractRunner<'a, DB> {
             sender: sender.unwrap_or_default(),
             errors,
             predeploy_libs,
+            inline_config: BTreeMap::new(),
+            warnings: Vec::new(),
+            differential: BTreeMap::new(),
+            progress: None,
         }
     }
 }
@@ -245,6 +292,11 @@ impl<'a, DB: DatabaseRef + Send + Sync> ContractRunner<'a, DB> {
                 .setup(address)
             {
                 Ok(CallResult { traces, labels, logs, .. }) => (false, logs, traces, labels, None),
+                Err(EvmError::Execution { traces, labels, logs, reason, .. })
+                    if reason == MAGIC_SKIP_REASON =>
+                {
+                    (true, logs, traces, labels, None)
+                }
                 Err(EvmError::Execution { traces, labels, logs, reason, .. }) => {
                     (true, logs, traces, labels, Some(format!("Setup failed: {reason}")))
                 }
@@ -259,7 +311,8 @@ impl<'a, DB: DatabaseRef + Send + Sync> ContractRunner<'a, DB> {
             traces.extend(setup_traces.map(|traces| (TraceKind::Setup, traces)).into_iter());
             logs.extend_from_slice(&setup_logs);
 
-            TestSetup { address, logs, traces, labeled_addresses, setup_failed, reason }
+            let skipped = setup_failed && reason.is_none();
+            TestSetup { address, logs, traces, labeled_addresses, setup_failed, reason, skipped }
         } else {
             TestSetup { address, logs, traces, ..Default::default() }
         })
@@ -274,7 +327,7 @@ impl<'a, DB: DatabaseRef + Send + Sync> ContractRunner<'a, DB> {
     ) -> Result<SuiteResult> {
         tracing::info!("starting tests");
         let start = Instant::now();
-        let mut warnings = Vec::new();
+        let mut warnings = self.warnings.clone();
 
         let setup_fns: Vec<_> =
             self.contract.functions().filter(|func| func.name.to_lowercase() == "setup").collect();
@@ -299,12 +352,17 @@ impl<'a, DB: DatabaseRef + Send + Sync> ContractRunner<'a, DB> {
                     "setUp()".to_string(),
                     TestResult {
                         success: false,
+                        skipped: false,
                         reason: Some("Multiple setUp functions".to_string()),
                         counterexample: None,
                         logs: vec![],
                         kind: TestKind::Standard(0),
                         traces: vec![],
                         labeled_addresses: BTreeMap::new(),
+                        duration: start.elapsed(),
+                        differential: None,
+                        gas_snapshots: BTreeMap::new(),
+                        gas_profile: None,
                     },
                 )]
                 .into(),
@@ -314,19 +372,24 @@ impl<'a, DB: DatabaseRef + Send + Sync> ContractRunner<'a, DB> {
 
         let setup = self.setup(needs_setup)?;
         if setup.setup_failed {
-            // The setup failed, so we return a single test result for `setUp`
+            // The setup failed or was skipped, so we return a single test result for `setUp`
             return Ok(SuiteResult::new(
                 start.elapsed(),
                 [(
                     "setUp()".to_string(),
                     TestResult {
-                        success: false,
+                        success: setup.skipped,
+                        skipped: setup.skipped,
                         reason: setup.reason,
                         counterexample: None,
                         logs: setup.logs,
                         kind: TestKind::Standard(0),
                         traces: setup.traces,
                         labeled_addresses: setup.labeled_addresses,
+                        duration: start.elapsed(),
+                        differential: None,
+                        gas_snapshots: BTreeMap::new(),
+                        gas_profile: None,
                     },
                 )]
                 .into(),
@@ -340,25 +403,60 @@ impl<'a, DB: DatabaseRef + Send + Sync> ContractRunner<'a, DB> {
             .functions()
             .into_iter()
             .filter(|func| {
-                func.name.starts_with("test") &&
+                (func.name.starts_with("test") || self.differential.contains_key(&func.name)) &&
                     filter.matches_test(func.signature()) &&
-                    (include_fuzz_tests || func.inputs.is_empty())
+                    (include_fuzz_tests ||
+                        func.inputs.is_empty() ||
+                        self.fixture_functions(func).is_some())
             })
             .map(|func| (func, func.name.starts_with("testFail")))
             .collect();
 
         let test_results = tests
             .par_iter()
-            .filter_map(|(func, should_fail)| {
-                let result = if func.inputs.is_empty() {
-                    Some(self.run_test(func, *should_fail, setup.clone()))
+            .flat_map(|(func, should_fail)| {
+                let signature = func.signature();
+                if let Some(ref progress) = self.progress {
+                    progress.emit(TestEvent::TestStarted { test: &signature });
+                }
+
+                let results: Vec<_> = if let Some(reference) = self.differential.get(&func.name) {
+                    fuzzer
+                        .as_ref()
+                        .map(|fuzzer| {
+                            self.run_differential_test(
+                                func,
+                                reference,
+                                fuzzer.clone(),
+                                setup.clone(),
+                            )
+                            .map(|result| (func.signature(), result))
+                        })
+                        .into_iter()
+                        .collect()
+                } else if func.inputs.is_empty() {
+                    vec![self
+                        .run_test(func, *should_fail, setup.clone())
+                        .map(|result| (func.signature(), result))]
+                } else if let Some(fixtures) = self.fixture_functions(func) {
+                    self.run_table_test(func, &fixtures, *should_fail, setup.clone())
                 } else {
-                    fuzzer.as_ref().map(|fuzzer| {
-                        self.run_fuzz_test(func, *should_fail, fuzzer.clone(), setup.clone())
-                    })
+                    fuzzer
+                        .as_ref()
+                        .map(|fuzzer| {
+                            self.run_fuzz_test(func, *should_fail, fuzzer.clone(), setup.clone())
+                                .map(|result| (func.signature(), result))
+                        })
+                        .into_iter()
+                        .collect()
                 };
 
-                result.map(|result| Ok((func.signature(), result?)))
+                if let Some(ref progress) = self.progress {
+                    for (test, result) in results.iter().flatten() {
+                        progress.emit(TestEvent::TestFinished { test, result });
+                    }
+                }
+                results
             })
             .collect::<Result<BTreeMap<_, _>>>()?;
 
@@ -381,73 +479,216 @@ impl<'a, DB: DatabaseRef + Send + Sync> ContractRunner<'a, DB> {
         func: &Function,
         should_fail: bool,
         setup: TestSetup,
+    ) -> Result<TestResult> {
+        self.run_test_case(func, &[], should_fail, setup)
+    }
+
+    /// Returns the fixture functions for the inputs of a test, if every input has one.
+    ///
+    /// The fixture of an input named `amount` is the function `fixtureAmount()`, which returns an
+    /// array of values for that input.
+    pub fn fixture_functions(&self, func: &Function) -> Option<Vec<&'a Function>> {
+        if func.inputs.is_empty() {
+            return None
+        }
+        func.inputs
+            .iter()
+            .map(|input| {
+                let mut chars = input.name.chars();
+                let first = chars.next()?;
+                let name = format!("fixture{}{}", first.to_uppercase(), chars.as_str());
+                self.contract
+                    .functions()
+                    .find(|fixture| fixture.name == name && fixture.inputs.is_empty())
+            })
+            .collect()
+    }
+
+    /// Runs a test once for every row of its fixtures, the `n`th run is called with the `n`th
+    /// value of every fixture and is reported as `<signature>[n]`
+    #[tracing::instrument(name = "table-test", skip_all, fields(name = %func.signature(), %should_fail))]
+    pub fn run_table_test(
+        &self,
+        func: &Function,
+        fixtures: &[&Function],
+        should_fail: bool,
+        setup: TestSetup,
+    ) -> Vec<Result<(String, TestResult)>> {
+        let columns = match self.load_fixtures(func, fixtures, setup.address) {
+            Ok(columns) => columns,
+            Err(err) => {
+                return vec![Ok((
+                    func.signature(),
+                    TestResult {
+                        success: false,
+                        skipped: false,
+                        reason: Some(format!("Failed to load fixtures: {}", err)),
+                        counterexample: None,
+                        logs: setup.logs,
+                        kind: TestKind::Standard(0),
+                        traces: setup.traces,
+                        labeled_addresses: setup.labeled_addresses,
+                        duration: Duration::default(),
+                        differential: None,
+                        gas_snapshots: BTreeMap::new(),
+                        gas_profile: None,
+                    },
+                ))]
+            }
+        };
+
+        let rows = columns.first().map(Vec::len).unwrap_or_default();
+        (0..rows)
+            .map(|row| {
+                let args: Vec<_> = columns.iter().map(|column| column[row].clone()).collect();
+                let result = self.run_test_case(func, &args, should_fail, setup.clone())?;
+                Ok((format!("{}[{}]", func.signature(), row), result))
+            })
+            .collect()
+    }
+
+    /// Calls the fixture functions and returns their values, one column per input of the test
+    fn load_fixtures(
+        &self,
+        func: &Function,
+        fixtures: &[&Function],
+        address: Address,
+    ) -> Result<Vec<Vec<Token>>> {
+        let mut columns = Vec::with_capacity(fixtures.len());
+        for (fixture, input) in fixtures.iter().zip(&func.inputs) {
+            let RawCallResult { reverted, result, .. } = self.executor.call_raw(
+                self.sender,
+                address,
+                fixture.encode_input(&[])?.into(),
+                0.into(),
+            )?;
+            if reverted {
+                eyre::bail!("`{}` reverted", fixture.signature())
+            }
+
+            let values = match fixture.decode_output(&result)?.pop() {
+                Some(Token::Array(values) | Token::FixedArray(values)) => values,
+                _ => eyre::bail!("`{}` does not return an array", fixture.signature()),
+            };
+            if let Some(value) = values.iter().find(|value| !value.type_check(&input.kind)) {
+                eyre::bail!(
+                    "`{}` returned {:?}, which is not a valid `{}`",
+                    fixture.signature(),
+                    value,
+                    input.kind
+                )
+            }
+            columns.push(values);
+        }
+
+        if columns.windows(2).any(|pair| pair[0].len() != pair[1].len()) {
+            eyre::bail!("The fixtures of `{}` have different lengths", func.signature())
+        }
+        Ok(columns)
+    }
+
+    /// Runs the test with the given arguments
+    fn run_test_case(
+        &self,
+        func: &Function,
+        args: &[Token],
+        should_fail: bool,
+        setup: TestSetup,
     ) -> Result<TestResult> {
         let TestSetup { address, mut logs, mut traces, mut labeled_addresses, .. } = setup;
 
         // Run unit test
         let start = Instant::now();
-        let (reverted, reason, gas, stipend, execution_traces, state_changeset) = match self
-            .executor
-            .call::<(), _, _>(self.sender, address, func.clone(), (), 0.into(), self.errors)
-        {
-            Ok(CallResult {
-                reverted,
-                gas,
-                stipend,
-                logs: execution_logs,
-                traces: execution_trace,
-                labels: new_labels,
-                state_changeset,
-                ..
-            }) => {
-                labeled_addresses.extend(new_labels);
-                logs.extend(execution_logs);
-                (reverted, None, gas, stipend, execution_trace, state_changeset)
-            }
-            Err(EvmError::Execution {
+        let calldata = func.encode_input(args)?;
+        let (
+            reverted,
+            reason,
+            gas,
+            stipend,
+            execution_traces,
+            state_changeset,
+            gas_snapshots,
+            gas_profile,
+        ) = match self.executor.call_raw(self.sender, address, calldata.into(), 0.into()) {
+            Ok(RawCallResult {
+                status,
                 reverted,
-                reason,
+                result,
                 gas,
                 stipend,
                 logs: execution_logs,
                 traces: execution_trace,
                 labels: new_labels,
                 state_changeset,
+                gas_snapshots,
+                gas_profile,
                 ..
             }) => {
                 labeled_addresses.extend(new_labels);
                 logs.extend(execution_logs);
-                (reverted, Some(reason), gas, stipend, execution_trace, state_changeset)
+                let reason = reverted.then(|| {
+                    foundry_utils::decode_revert(result.as_ref(), self.errors)
+                        .unwrap_or_else(|_| format!("{:?}", status))
+                });
+                (
+                    reverted,
+                    reason,
+                    gas,
+                    stipend,
+                    execution_trace,
+                    state_changeset,
+                    gas_snapshots,
+                    gas_profile,
+                )
             }
             Err(err) => {
                 tracing::error!(?err);
-                return Err(err.into())
+                return Err(err)
             }
         };
         traces.extend(execution_traces.map(|traces| (TraceKind::Execution, traces)).into_iter());
 
-        let success = self.executor.is_success(
-            setup.address,
-            reverted,
-            state_changeset.expect("we should have a state changeset"),
-            should_fail,
-        );
+        let skipped = reason.as_deref() == Some(MAGIC_SKIP_REASON);
+        let reason = if skipped { None } else { reason };
+        let success = skipped ||
+            self.executor.is_success(
+                setup.address,
+                reverted,
+                state_changeset.expect("we should have a state changeset"),
+                should_fail,
+            );
+
+        // Passing tests still fail if their gas usage is out of the configured bounds
+        let gas_used = gas.overflowing_sub(stipend).0;
+        let gas_failure = (success && !skipped)
+            .then(|| self.inline_config.get(&func.name)?.check_gas(gas_used))
+            .flatten();
+        let (success, reason) = match gas_failure {
+            Some(gas_failure) => (false, Some(gas_failure)),
+            None => (success, reason),
+        };
 
         // Record test execution time
+        let duration = start.elapsed();
         tracing::debug!(
-            duration = ?start.elapsed(),
+            ?duration,
             %success,
             %gas
         );
 
         Ok(TestResult {
             success,
+            skipped,
             reason,
             counterexample: None,
             logs,
-            kind: TestKind::Standard(gas.overflowing_sub(stipend).0),
+            kind: TestKind::Standard(gas_used),
             traces,
             labeled_addresses,
+            duration,
+            differential: None,
+            gas_snapshots,
+            gas_profile,
         })
     }
 
@@ -459,36 +700,73 @@ impl<'a, DB: DatabaseRef + Send + Sync> ContractRunner<'a, DB> {
         runner: TestRunner,
         setup: TestSetup,
     ) -> Result<TestResult> {
-        let TestSetup { address, mut logs, mut traces, mut labeled_addresses, .. } = setup;
-
         // Run fuzz test
         let start = Instant::now();
-        let mut result = FuzzedExecutor::new(&self.executor, runner, self.sender).fuzz(
+        let result = FuzzedExecutor::new(&self.executor, runner, self.sender).fuzz(
             func,
-            address,
+            setup.address,
             should_fail,
             self.errors,
         );
+        Ok(fuzz_test_result(result, setup, start))
+    }
 
-        // Record logs, labels and traces
-        logs.append(&mut result.logs);
-        labeled_addresses.append(&mut result.labeled_addresses);
-        traces.extend(result.traces.map(|traces| (TraceKind::Execution, traces)).into_iter());
-
-        // Record test execution time
-        tracing::debug!(
-            duration = ?start.elapsed(),
-            success = %result.success
+    /// Fuzzes a function of the test contract and compares its outputs with the outputs of the
+    /// reference implementation
+    #[tracing::instrument(name = "differential-test", skip_all, fields(name = %func.signature()))]
+    pub fn run_differential_test(
+        &self,
+        func: &Function,
+        reference: &DifferentialReference,
+        runner: TestRunner,
+        setup: TestSetup,
+    ) -> Result<TestResult> {
+        let start = Instant::now();
+        let result = FuzzedExecutor::new(&self.executor, runner, self.sender).fuzz_differential(
+            func,
+            setup.address,
+            reference,
+            self.errors,
         );
+        Ok(fuzz_test_result(result, setup, start))
+    }
+}
 
-        Ok(TestResult {
-            success: result.success,
-            reason: result.reason,
-            counterexample: result.counterexample,
-            logs,
-            kind: TestKind::Fuzz(result.cases),
-            traces,
-            labeled_addresses,
-        })
+/// Converts the result of a fuzz run that started at `start` into a [TestResult]
+fn fuzz_test_result(mut result: FuzzTestResult, setup: TestSetup, start: Instant) -> TestResult {
+    let TestSetup { mut logs, mut traces, mut labeled_addresses, .. } = setup;
+
+    // Record logs, labels and traces
+    logs.append(&mut result.logs);
+    labeled_addresses.append(&mut result.labeled_addresses);
+    traces.extend(result.traces.map(|traces| (TraceKind::Execution, traces)).into_iter());
+
+    // Record test execution time
+    let duration = start.elapsed();
+    tracing::debug!(
+        ?duration,
+        success = %result.success
+    );
+
+    // A skipped fuzz test reverts on every run
+    let skipped = result.reason.as_deref() == Some(MAGIC_SKIP_REASON);
+    if skipped {
+        result.reason = None;
+        result.counterexample = None;
+    }
+
+    TestResult {
+        success: skipped || result.success,
+        skipped,
+        reason: result.reason,
+        counterexample: result.counterexample,
+        logs,
+        kind: TestKind::Fuzz(result.cases),
+        traces,
+        labeled_addresses,
+        duration,
+        differential: result.differential,
+        gas_snapshots: BTreeMap::new(),
+        gas_profile: None,
     }
 }
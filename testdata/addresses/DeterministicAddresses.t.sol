@@ -0,0 +1,64 @@
+// SPDX-License-Identifier: Unlicense
+pragma solidity >=0.8.0;
+
+import "ds-test/test.sol";
+import "../cheats/Cheats.sol";
+
+contract Dummy {}
+
+contract Stored {
+    uint256 public value = 42;
+}
+
+contract DeterministicAddressesTest is DSTest {
+    Cheats constant cheats = Cheats(HEVM_ADDRESS);
+    Dummy dummy;
+
+    function setUp() public {
+        dummy = new Dummy();
+    }
+
+    function testTestContractAddress() public {
+        assertEq(address(this), address(0x10001));
+    }
+
+    function testCreatedAddressesIncrement() public {
+        assertEq(address(dummy), address(0x10002));
+        assertEq(address(new Dummy()), address(0x10003));
+        assertEq(address(new Dummy()), address(0x10004));
+    }
+
+    function testCreate2IsNotMoved() public {
+        Dummy create2 = new Dummy{salt: bytes32(uint256(1))}();
+        assertTrue(uint160(address(create2)) > 0x10004);
+    }
+
+    function testStorageIsMoved() public {
+        Stored stored = new Stored();
+        assertEq(address(stored), address(0x10003));
+        assertEq(stored.value(), 42);
+        assertEq(cheats.getNonce(address(stored)), 1);
+    }
+
+    function testNonceDerivedAddressIsEmptied() public {
+        uint64 nonce = cheats.getNonce(address(this));
+        address derived = address(
+            uint160(uint256(keccak256(abi.encodePacked(bytes1(0xd6), bytes1(0x94), address(this), uint8(nonce)))))
+        );
+        new Stored();
+
+        assertEq(derived.code.length, 0);
+        assertEq(cheats.getNonce(derived), 0);
+        assertEq(cheats.load(derived, bytes32(0)), bytes32(0));
+    }
+
+    function testMoveIsUndoneOnRevert() public {
+        try this.createAndRevert() {} catch {}
+        assertEq(address(new Dummy()), address(0x10003));
+    }
+
+    function createAndRevert() external {
+        new Dummy();
+        revert();
+    }
+}
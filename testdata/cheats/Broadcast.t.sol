@@ -0,0 +1,73 @@
+// SPDX-License-Identifier: Unlicense
+pragma solidity >=0.8.0;
+
+import "ds-test/test.sol";
+import "./Cheats.sol";
+
+contract Counter {
+    uint256 public count;
+    address public lastSender;
+    address public lastOrigin;
+
+    function increment() public {
+        count++;
+        lastSender = msg.sender;
+        lastOrigin = tx.origin;
+    }
+}
+
+contract Deployed {
+    address public deployer;
+
+    constructor() {
+        deployer = msg.sender;
+    }
+}
+
+contract BroadcastTest is DSTest {
+    Cheats constant cheats = Cheats(HEVM_ADDRESS);
+
+    address constant ACCOUNT_A = address(0x1337);
+    address constant ACCOUNT_B = address(0x1338);
+
+    function testBroadcastSetsCallerAndOrigin() public {
+        Counter counter = new Counter();
+
+        cheats.broadcast(ACCOUNT_A);
+        counter.increment();
+        assertEq(counter.lastSender(), ACCOUNT_A);
+        assertEq(counter.lastOrigin(), ACCOUNT_A);
+
+        // `broadcast` only applies to the next call
+        counter.increment();
+        assertEq(counter.lastSender(), address(this));
+        assertEq(counter.count(), 2);
+    }
+
+    function testStartBroadcast() public {
+        Counter counter = new Counter();
+
+        cheats.startBroadcast(ACCOUNT_B);
+        counter.increment();
+        Deployed deployed = new Deployed();
+        counter.increment();
+        cheats.stopBroadcast();
+
+        assertEq(counter.lastSender(), ACCOUNT_B);
+        assertEq(deployed.deployer(), ACCOUNT_B);
+        assertEq(counter.count(), 2);
+
+        counter.increment();
+        assertEq(counter.lastSender(), address(this));
+    }
+
+    function testFailBroadcastTwice() public {
+        cheats.startBroadcast(ACCOUNT_A);
+        cheats.startBroadcast(ACCOUNT_B);
+    }
+
+    function testFailBroadcastWithPrank() public {
+        cheats.prank(ACCOUNT_A);
+        cheats.broadcast(ACCOUNT_B);
+    }
+}
@@ -51,6 +51,12 @@ interface Cheats {
     // pass a Solidity selector to the expected calldata, then the entire Solidity
     // function will be mocked.
     function mockCall(address,bytes calldata,bytes calldata) external;
+    // Mocks a call with the given msg.value to an address, returning specified data.
+    // A mock for an exact msg.value takes precedence over a mock without one.
+    function mockCall(address,uint256,bytes calldata,bytes calldata) external;
+    // Mocks a call to an address, reverting with the specified data
+    function mockCallRevert(address,bytes calldata,bytes calldata) external;
+    function mockCallRevert(address,uint256,bytes calldata,bytes calldata) external;
     // Clears all mocked calls
     function clearMockedCalls() external;
     // Expect a call to an address with the specified calldata.
@@ -68,4 +74,66 @@ interface Cheats {
     function getNonce(address) external returns(uint64);
     // Set block.chainid (newChainId)
     function chainId(uint256) external;
+    // Using the address that calls the test contract, has the next call (at this call depth only) create a transaction that can later be signed and sent onchain
+    function broadcast() external;
+    // Has the next call (at this call depth only) create a transaction with the address provided as the sender that can later be signed and sent onchain
+    function broadcast(address) external;
+    // Using the address that calls the test contract, has all subsequent calls (at this call depth only) create transactions that can later be signed and sent onchain
+    function startBroadcast() external;
+    // Has all subsequent calls (at this call depth only) create transactions that can later be signed and sent onchain
+    function startBroadcast(address) external;
+    // Stops collecting onchain transactions
+    function stopBroadcast() external;
+    // Returns the path of a scratch directory that is unique to the current test and removed after it ran.
+    // The path is also passed to `ffi` commands as the `FOUNDRY_TEST_DIR` environment variable
+    function tempDir() external returns (string memory);
+    // If the condition is true, stops the execution of the current test and marks it as skipped
+    function skip(bool) external;
+    // Loads the balance, nonce, code and storage of the accounts in a geth-style allocs JSON file into the state
+    function loadAllocs(string calldata) external;
+    // Creates a fork of the given endpoint at the latest block and returns its identifier, the fork is not selected
+    function createFork(string calldata) external returns (uint256);
+    // Creates a fork of the given endpoint at the given block and returns its identifier, the fork is not selected
+    function createFork(string calldata, uint256) external returns (uint256);
+    // Selects the fork with the given identifier, all accounts except the persistent ones are read from it afterwards
+    function selectFork(uint256) external;
+    // Returns the identifier of the selected fork
+    function activeFork() external returns (uint256);
+    // Keeps the state of the account(s) when switching forks, the test contract is always persistent
+    function makePersistent(address) external;
+    function makePersistent(address[] calldata) external;
+    // Returns whether the account keeps its state when switching forks
+    function isPersistent(address) external returns (bool);
+    // Reads the file at the given path, the path must be readable according to `fs_permissions`
+    function readFile(string calldata) external returns (string memory);
+    // Writes the data to the file at the given path, creating or replacing it, the path must be writable according to `fs_permissions`
+    function writeFile(string calldata, string calldata) external;
+    // Reads an environment variable as the given type, (name) => (value)
+    function envBool(string calldata) external returns (bool);
+    function envUint(string calldata) external returns (uint256);
+    function envInt(string calldata) external returns (int256);
+    function envAddress(string calldata) external returns (address);
+    function envBytes32(string calldata) external returns (bytes32);
+    function envString(string calldata) external returns (string memory);
+    function envBytes(string calldata) external returns (bytes memory);
+    // Reads an environment variable as an array of the given type, (name, delimiter) => (values)
+    function envBool(string calldata, string calldata) external returns (bool[] memory);
+    function envUint(string calldata, string calldata) external returns (uint256[] memory);
+    function envInt(string calldata, string calldata) external returns (int256[] memory);
+    function envAddress(string calldata, string calldata) external returns (address[] memory);
+    function envBytes32(string calldata, string calldata) external returns (bytes32[] memory);
+    function envString(string calldata, string calldata) external returns (string[] memory);
+    function envBytes(string calldata, string calldata) external returns (bytes[] memory);
+    // Reads an environment variable, or returns the default if it is not set, (name, default) => (value)
+    function envOr(string calldata, bool) external returns (bool);
+    function envOr(string calldata, uint256) external returns (uint256);
+    function envOr(string calldata, int256) external returns (int256);
+    function envOr(string calldata, address) external returns (address);
+    function envOr(string calldata, bytes32) external returns (bytes32);
+    function envOr(string calldata, string calldata) external returns (string memory);
+    function envOr(string calldata, bytes calldata) external returns (bytes memory);
+    // Starts measuring the gas used by a named code region, the result is written to the gas snapshot
+    function startSnapshotGas(string calldata) external;
+    // Stops measuring the gas used by a named code region and returns it
+    function stopSnapshotGas(string calldata) external returns (uint256);
 }
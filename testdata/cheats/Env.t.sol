@@ -0,0 +1,60 @@
+// SPDX-License-Identifier: Unlicense
+pragma solidity >=0.8.0;
+
+import "ds-test/test.sol";
+import "./Cheats.sol";
+
+// The environment variables are set by the test runner
+contract EnvTest is DSTest {
+    Cheats constant cheats = Cheats(HEVM_ADDRESS);
+
+    function testEnvValues() public {
+        assertTrue(cheats.envBool("_FOUNDRY_TEST_ENV_BOOL"));
+        assertEq(cheats.envUint("_FOUNDRY_TEST_ENV_UINT"), 115792089237316195423570985008687907853269984665640564039457584007913129639935);
+        assertEq(cheats.envUint("_FOUNDRY_TEST_ENV_UINT_HEX"), 0xff);
+        assertEq(cheats.envInt("_FOUNDRY_TEST_ENV_INT"), -42);
+        assertEq(cheats.envAddress("_FOUNDRY_TEST_ENV_ADDRESS"), 0x7109709ECfa91a80626fF3989D68f67F5b1DD12D);
+        assertEq(cheats.envBytes32("_FOUNDRY_TEST_ENV_BYTES32"), bytes32(uint256(1)));
+        assertEq(cheats.envString("_FOUNDRY_TEST_ENV_STRING"), "hello, world");
+        assertEq(keccak256(cheats.envBytes("_FOUNDRY_TEST_ENV_BYTES")), keccak256(hex"deadbeef"));
+    }
+
+    function testEnvArrays() public {
+        uint256[] memory values = cheats.envUint("_FOUNDRY_TEST_ENV_UINT_ARRAY", ",");
+        assertEq(values.length, 3);
+        assertEq(values[0], 1);
+        assertEq(values[1], 2);
+        assertEq(values[2], 0x10);
+
+        address[] memory addresses = cheats.envAddress("_FOUNDRY_TEST_ENV_ADDRESS_ARRAY", ",");
+        assertEq(addresses.length, 2);
+        assertEq(addresses[0], 0x7109709ECfa91a80626fF3989D68f67F5b1DD12D);
+        assertEq(addresses[1], address(0));
+
+        string[] memory strings = cheats.envString("_FOUNDRY_TEST_ENV_STRING", ",");
+        assertEq(strings.length, 2);
+        assertEq(strings[0], "hello");
+        assertEq(strings[1], "world");
+    }
+
+    function testEnvOr() public {
+        assertEq(cheats.envOr("_FOUNDRY_TEST_ENV_UINT_HEX", uint256(1)), 0xff);
+        assertEq(cheats.envOr("_FOUNDRY_TEST_ENV_UNSET", uint256(1)), 1);
+        assertEq(cheats.envOr("_FOUNDRY_TEST_ENV_UNSET", int256(-1)), -1);
+        assertTrue(cheats.envOr("_FOUNDRY_TEST_ENV_UNSET", true));
+        assertEq(cheats.envOr("_FOUNDRY_TEST_ENV_UNSET", address(1)), address(1));
+        assertEq(cheats.envOr("_FOUNDRY_TEST_ENV_UNSET", bytes32(uint256(2))), bytes32(uint256(2)));
+        assertEq(cheats.envOr("_FOUNDRY_TEST_ENV_UNSET", string("default")), "default");
+        assertEq(cheats.envOr("_FOUNDRY_TEST_ENV_STRING", string("default")), "hello, world");
+    }
+
+    function testEnvUnsetOrInvalid() public {
+        try cheats.envUint("_FOUNDRY_TEST_ENV_UNSET") returns (uint256) {
+            fail();
+        } catch {}
+
+        try cheats.envUint("_FOUNDRY_TEST_ENV_STRING") returns (uint256) {
+            fail();
+        } catch {}
+    }
+}
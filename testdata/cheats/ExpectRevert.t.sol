@@ -6,6 +6,7 @@ import "./Cheats.sol";
 
 contract Reverter {
     error CustomError();
+    error InsufficientBalance(uint256 available, uint256 required);
 
     function revertWithMessage(string memory message) public pure {
         require(false, message);
@@ -21,6 +22,10 @@ contract Reverter {
         revert CustomError();
     }
 
+    function revertWithInsufficientBalance(uint256 available, uint256 required) public pure {
+        revert InsufficientBalance(available, required);
+    }
+
     function nestedRevert(Reverter inner, string memory message) public pure {
         inner.revertWithMessage(message);
     }
@@ -107,6 +112,47 @@ contract ExpectRevertTest is DSTest {
         reverter.revertWithoutReason();
     }
 
+    function testExpectRevertMismatchDecodesErrors() public {
+        Reverter reverter = new Reverter();
+
+        cheats.expectRevert(abi.encodeWithSelector(Reverter.InsufficientBalance.selector, 1, 3));
+        try reverter.revertWithInsufficientBalance(1, 2) {
+            fail();
+        } catch (bytes memory data) {
+            assertEq(
+                abi.decode(data, (string)),
+                "Error != expected error: InsufficientBalance(1, 2) != InsufficientBalance(1, 3)"
+            );
+        }
+
+        cheats.expectRevert(abi.encodePacked(Reverter.InsufficientBalance.selector));
+        try reverter.revertWithCustomError() {
+            fail();
+        } catch (bytes memory data) {
+            assertEq(
+                abi.decode(data, (string)),
+                "Error != expected error: CustomError() != InsufficientBalance(uint256,uint256)"
+            );
+        }
+
+        cheats.expectRevert("custom error");
+        try reverter.revertWithCustomError() {
+            fail();
+        } catch (bytes memory data) {
+            assertEq(abi.decode(data, (string)), "Error != expected error: CustomError() != 'custom error'");
+        }
+
+        cheats.expectRevert("arithmetic");
+        try reverter.panic() {
+            fail();
+        } catch (bytes memory data) {
+            assertEq(
+                abi.decode(data, (string)),
+                "Error != expected error: Arithmetic over/underflow != 'arithmetic'"
+            );
+        }
+    }
+
     function testFailExpectRevertDangling() public {
         cheats.expectRevert("dangling");
     }
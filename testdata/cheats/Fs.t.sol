@@ -0,0 +1,41 @@
+// SPDX-License-Identifier: Unlicense
+pragma solidity >=0.8.0;
+
+import "ds-test/test.sol";
+import "./Cheats.sol";
+
+contract FsTest is DSTest {
+    Cheats constant cheats = Cheats(HEVM_ADDRESS);
+
+    function testReadFile() public {
+        string memory content = cheats.readFile("fixtures/File/read.txt");
+        assertEq(content, "hello readable world");
+    }
+
+    function testWriteFile() public {
+        string memory path = string(abi.encodePacked(cheats.tempDir(), "/write.txt"));
+        cheats.writeFile(path, "hello writable world");
+        assertEq(cheats.readFile(path), "hello writable world");
+
+        cheats.writeFile(path, "replaced");
+        assertEq(cheats.readFile(path), "replaced");
+    }
+
+    function testReadFileOutsidePermissions() public {
+        try cheats.readFile("cheats/Cheats.sol") returns (string memory) {
+            fail();
+        } catch {}
+
+        try cheats.readFile("fixtures/../cheats/Cheats.sol") returns (string memory) {
+            fail();
+        } catch {}
+    }
+
+    function testWriteFileOutsidePermissions() public {
+        try cheats.writeFile("fixtures/File/read.txt", "overwritten") {
+            fail();
+        } catch {}
+
+        assertEq(cheats.readFile("fixtures/File/read.txt"), "hello readable world");
+    }
+}
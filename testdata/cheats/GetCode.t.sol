@@ -32,6 +32,12 @@ contract GetCodeTest is DSTest {
         //);
     }
 
+    function testGetCodeOutsidePermissions() public {
+        try cheats.getCode("../evm/abi/console.json") returns (bytes memory) {
+            fail();
+        } catch {}
+    }
+
     function testFailGetUnlinked() public {
         cheats.getCode("UnlinkedContract.sol");
     }
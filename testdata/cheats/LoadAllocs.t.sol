@@ -0,0 +1,37 @@
+// SPDX-License-Identifier: Unlicense
+pragma solidity >=0.8.0;
+
+import "ds-test/test.sol";
+import "./Cheats.sol";
+
+contract LoadAllocsTest is DSTest {
+    Cheats constant cheats = Cheats(HEVM_ADDRESS);
+
+    address constant ALLOC = address(0xa110c);
+
+    function setUp() public {
+        cheats.loadAllocs("fixtures/LoadAllocs/allocs.json");
+    }
+
+    function testLoadAllocs() public {
+        assertEq(ALLOC.balance, 100, "balance was not loaded");
+        assertEq(cheats.getNonce(ALLOC), 5, "nonce was not loaded");
+        assertEq(
+            cheats.load(ALLOC, bytes32(uint256(1))),
+            bytes32(uint256(42)),
+            "storage was not loaded"
+        );
+
+        (bool success, bytes memory data) = ALLOC.call("");
+        assertTrue(success, "code was not loaded");
+        assertEq(abi.decode(data, (uint256)), 42, "code was not loaded");
+    }
+
+    function testFailLoadAllocsMissingFile() public {
+        cheats.loadAllocs("fixtures/LoadAllocs/missing.json");
+    }
+
+    function testFailLoadAllocsOutsideFsPermissions() public {
+        cheats.loadAllocs("cheats/Cheats.sol");
+    }
+}
@@ -16,6 +16,10 @@ contract Mock {
     function add(uint256 a, uint256 b) public pure returns (uint256) {
         return a + b;
     }
+
+    function pay(uint256 a) public payable returns (uint256) {
+        return a;
+    }
 }
 
 contract NestedMock {
@@ -113,4 +117,84 @@ contract MockCallTest is DSTest {
         assertEq(target.numberA(), 1);
         assertEq(target.numberB(), 2);
     }
+
+    function testMockLongestPrefix() public {
+        Mock target = new Mock();
+
+        cheats.mockCall(
+            address(target),
+            abi.encodeWithSelector(target.add.selector, 5, 5),
+            abi.encode(11)
+        );
+        cheats.mockCall(
+            address(target),
+            abi.encodeWithSelector(target.add.selector),
+            abi.encode(12)
+        );
+
+        assertEq(target.add(5, 5), 11);
+        assertEq(target.add(6, 4), 12);
+    }
+
+    function testMockShorterCalldata() public {
+        Mock target = new Mock();
+
+        cheats.mockCall(
+            address(target),
+            abi.encodeWithSelector(target.add.selector, 5, 5),
+            abi.encode(11)
+        );
+
+        // the mock is longer than the calldata, so it does not apply
+        assertEq(target.numberA(), 1);
+    }
+
+    function testMockValue() public {
+        Mock target = new Mock();
+
+        cheats.mockCall(
+            address(target),
+            abi.encodeWithSelector(target.pay.selector),
+            abi.encode(10)
+        );
+        cheats.mockCall(
+            address(target),
+            1 ether,
+            abi.encodeWithSelector(target.pay.selector),
+            abi.encode(20)
+        );
+
+        assertEq(target.pay(1), 10);
+        assertEq(target.pay{value: 0.5 ether}(1), 10);
+        assertEq(target.pay{value: 1 ether}(1), 20);
+    }
+
+    function testMockCallRevert() public {
+        Mock target = new Mock();
+
+        cheats.mockCallRevert(
+            address(target),
+            abi.encodeWithSelector(target.numberB.selector),
+            "numberB mocked to revert"
+        );
+
+        assertEq(target.numberA(), 1);
+        cheats.expectRevert("numberB mocked to revert");
+        target.numberB();
+    }
+
+    function testMockCallRevertWithValue() public {
+        Mock target = new Mock();
+
+        cheats.mockCallRevert(
+            address(target),
+            1 ether,
+            abi.encodeWithSelector(target.pay.selector),
+            "paid too much"
+        );
+
+        assertEq(target.pay{value: 0.5 ether}(1), 1);
+        cheats.expectRevert("paid too much");
+        target.pay{value: 1 ether}(1);
+    }
 }
@@ -323,4 +323,29 @@ contract PrankTest is DSTest {
             "tx.origin was not set correctly"
         );
     }
+
+    /// Checks that a `prank` is not ended by the subcalls of the pranked call, which would leave
+    /// `tx.origin` altered once the pranked call returns.
+    function testPrankOriginCleanedUpAfterNestedCalls(address sender, address origin) public {
+        address oldOrigin = tx.origin;
+
+        Victim innerVictim = new Victim();
+        NestedVictim victim = new NestedVictim(innerVictim);
+
+        cheats.prank(sender, origin);
+        victim.assertCallerAndOrigin(
+            sender,
+            "msg.sender was not set during prank",
+            origin,
+            "tx.origin was not set during prank"
+        );
+
+        // Ensure we cleaned up correctly
+        innerVictim.assertCallerAndOrigin(
+            address(this),
+            "msg.sender was not cleaned up",
+            oldOrigin,
+            "tx.origin was not cleaned up"
+        );
+    }
 }
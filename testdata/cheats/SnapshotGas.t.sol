@@ -0,0 +1,37 @@
+// SPDX-License-Identifier: Unlicense
+pragma solidity >=0.8.0;
+
+import "ds-test/test.sol";
+import "./Cheats.sol";
+
+contract SnapshotGasTest is DSTest {
+    Cheats constant cheats = Cheats(HEVM_ADDRESS);
+
+    uint256 slot;
+
+    function testSnapshotGas() public {
+        cheats.startSnapshotGas("sstore");
+        slot = 1;
+        uint256 gasUsed = cheats.stopSnapshotGas("sstore");
+        assertGt(gasUsed, 20000);
+    }
+
+    function testSnapshotGasMultipleRegions() public {
+        cheats.startSnapshotGas("outer");
+        cheats.startSnapshotGas("inner");
+        slot = 2;
+        uint256 inner = cheats.stopSnapshotGas("inner");
+        slot = 3;
+        uint256 outer = cheats.stopSnapshotGas("outer");
+        assertGt(outer, inner);
+    }
+
+    function testFailStopUnknownSnapshotGas() public {
+        cheats.stopSnapshotGas("unknown");
+    }
+
+    function testFailStartSnapshotGasTwice() public {
+        cheats.startSnapshotGas("twice");
+        cheats.startSnapshotGas("twice");
+    }
+}
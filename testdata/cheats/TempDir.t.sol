@@ -0,0 +1,38 @@
+// SPDX-License-Identifier: Unlicense
+pragma solidity >=0.8.0;
+
+import "ds-test/test.sol";
+import "./Cheats.sol";
+
+contract TempDirTest is DSTest {
+    Cheats constant cheats = Cheats(HEVM_ADDRESS);
+
+    string setUpDir;
+
+    function setUp() public {
+        setUpDir = cheats.tempDir();
+    }
+
+    function testTempDirIsStable() public {
+        string memory dir = cheats.tempDir();
+        assertTrue(bytes(dir).length > 0, "temp dir is empty");
+        assertEq(dir, cheats.tempDir(), "temp dir changed within a test");
+    }
+
+    function testTempDirIsIsolatedFromSetUp() public {
+        assertTrue(
+            keccak256(bytes(setUpDir)) != keccak256(bytes(cheats.tempDir())),
+            "temp dir shared with setUp"
+        );
+    }
+
+    function testTempDirIsPassedToFfi() public {
+        string[] memory inputs = new string[](3);
+        inputs[0] = "sh";
+        inputs[1] = "-c";
+        inputs[2] = "printf '%s' \"$FOUNDRY_TEST_DIR\" | od -An -tx1 | tr -d ' \\n'";
+
+        bytes memory res = cheats.ffi(inputs);
+        assertEq(string(res), cheats.tempDir(), "ffi did not receive the temp dir");
+    }
+}
@@ -0,0 +1,26 @@
+// SPDX-License-Identifier: Unlicense
+pragma solidity >=0.8.0;
+
+import "ds-test/test.sol";
+
+// The reference implementations of these functions are registered by the test runner
+contract DifferentialTest is DSTest {
+    function average(uint128 a, uint128 b) public pure returns (uint256) {
+        return (uint256(a) + b) / 2;
+    }
+
+    function averageBroken(uint128 a, uint128 b) public pure returns (uint256) {
+        if (a > 100) {
+            return 0;
+        }
+        return (uint256(a) + b) / 2;
+    }
+
+    function checkedDiv(uint256 a, uint256 b) public pure returns (uint256) {
+        return a / b;
+    }
+
+    function answer(uint8) public pure returns (uint256) {
+        return 42;
+    }
+}
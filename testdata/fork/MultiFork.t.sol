@@ -0,0 +1,99 @@
+// SPDX-License-Identifier: Unlicense
+pragma solidity >=0.8.0;
+
+import "ds-test/test.sol";
+import "../cheats/Cheats.sol";
+
+interface IERC20 {
+    function totalSupply() external view returns (uint256);
+}
+
+contract Counter {
+    uint256 public count;
+
+    function increment() public {
+        count++;
+    }
+}
+
+contract MultiForkTest is DSTest {
+    Cheats constant cheats = Cheats(HEVM_ADDRESS);
+    address constant DAI = 0x6B175474E89094C44Da98b954EedeAC495271d0F;
+
+    uint256 early;
+    uint256 late;
+
+    // the tests run against the endpoint of the forked runner, `ETH_RPC_URL`
+    function rpcUrl() internal returns (string memory) {
+        string[] memory inputs = new string[](3);
+        inputs[0] = "sh";
+        inputs[1] = "-c";
+        inputs[2] = "printf %s \"$ETH_RPC_URL\" | od -An -tx1 | tr -d ' \\n'";
+        return string(cheats.ffi(inputs));
+    }
+
+    function setUp() public {
+        string memory url = rpcUrl();
+        early = cheats.createFork(url, 14_000_000);
+        late = cheats.createFork(url, 15_000_000);
+    }
+
+    function testSelectFork() public {
+        cheats.selectFork(early);
+        assertEq(block.number, 14_000_000);
+        assertEq(cheats.activeFork(), early);
+
+        cheats.selectFork(late);
+        assertEq(block.number, 15_000_000);
+        assertEq(cheats.activeFork(), late);
+    }
+
+    function testReadsStateOfSelectedFork() public {
+        cheats.selectFork(early);
+        uint256 earlySupply = IERC20(DAI).totalSupply();
+
+        cheats.selectFork(late);
+        uint256 lateSupply = IERC20(DAI).totalSupply();
+
+        assertTrue(earlySupply != lateSupply);
+    }
+
+    function testChangesStayOnTheirFork() public {
+        bytes32 slot = bytes32(uint256(1337));
+
+        cheats.selectFork(early);
+        cheats.store(DAI, slot, bytes32(uint256(1)));
+
+        cheats.selectFork(late);
+        assertEq(cheats.load(DAI, slot), bytes32(0));
+
+        cheats.selectFork(early);
+        assertEq(cheats.load(DAI, slot), bytes32(uint256(1)));
+    }
+
+    function testPersistentAccountsKeepTheirState() public {
+        assertTrue(cheats.isPersistent(address(this)));
+
+        Counter counter = new Counter();
+        cheats.makePersistent(address(counter));
+
+        cheats.selectFork(early);
+        counter.increment();
+
+        cheats.selectFork(late);
+        assertEq(counter.count(), 1);
+    }
+
+    function testOtherAccountsDoNotExistOnOtherForks() public {
+        cheats.selectFork(early);
+        Counter counter = new Counter();
+        assertTrue(!cheats.isPersistent(address(counter)));
+
+        cheats.selectFork(late);
+        assertEq(address(counter).code.length, 0);
+    }
+
+    function testFailCreateForkWithInvalidBlock() public {
+        cheats.createFork(rpcUrl(), uint256(type(uint64).max) + 1);
+    }
+}
@@ -0,0 +1,34 @@
+// SPDX-License-Identifier: Unlicense
+pragma solidity >=0.8.0;
+
+import "ds-test/test.sol";
+
+contract GasBoundsTest is DSTest {
+    uint256[] values;
+
+    function burn(uint256 count) internal {
+        for (uint256 i = 0; i < count; i++) {
+            values.push(i);
+        }
+    }
+
+    /// forge-config: min-gas = 10_000
+    /// forge-config: max-gas = 1_000_000
+    function testWithinBounds() public {
+        burn(5);
+    }
+
+    /// forge-config: max-gas = 1000
+    function testAboveMaximum() public {
+        burn(5);
+    }
+
+    /// forge-config: min-gas = 1_000_000
+    function testBelowMinimum() public {
+        burn(1);
+    }
+
+    function testUnbounded() public {
+        burn(50);
+    }
+}
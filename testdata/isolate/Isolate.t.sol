@@ -0,0 +1,90 @@
+// SPDX-License-Identifier: Unlicense
+pragma solidity >=0.8.0;
+
+import "ds-test/test.sol";
+import "../cheats/Cheats.sol";
+
+contract Counter {
+    event Incremented(uint256 count);
+
+    uint256 public count;
+    address public lastCaller;
+
+    function increment() public {
+        count += 1;
+        lastCaller = msg.sender;
+        emit Incremented(count);
+    }
+
+    function incrementTwice() public {
+        this.increment();
+        this.increment();
+    }
+
+    function incrementAndRevert() public {
+        this.increment();
+        revert("reverted after increment");
+    }
+}
+
+contract IsolateTest is DSTest {
+    Cheats constant cheats = Cheats(HEVM_ADDRESS);
+
+    event Incremented(uint256 count);
+
+    Counter counter;
+
+    function setUp() public {
+        counter = new Counter();
+    }
+
+    function testCallsPayTheBaseCost() public {
+        counter.increment();
+
+        // the storage of the counter is cold again
+        uint256 gasBefore = gasleft();
+        counter.increment();
+        assertGt(gasBefore - gasleft(), 21000 + 2100);
+    }
+
+    function testStateIsKept() public {
+        counter.increment();
+        counter.increment();
+        assertEq(counter.count(), 2);
+    }
+
+    function testExpectRevertAfterNestedCall() public {
+        // the nested call that succeeds must not end the expected revert
+        cheats.expectRevert("reverted after increment");
+        counter.incrementAndRevert();
+        assertEq(counter.count(), 0);
+    }
+
+    function testExpectEmitInNestedCall() public {
+        cheats.expectEmit(false, false, false, true);
+        emit Incremented(1);
+        cheats.expectEmit(false, false, false, true);
+        emit Incremented(2);
+        counter.incrementTwice();
+    }
+
+    function testFailExpectEmitNotEmitted() public {
+        cheats.expectEmit(false, false, false, true);
+        emit Incremented(2);
+        counter.increment();
+    }
+
+    function testStartPrank() public {
+        cheats.startPrank(address(1337));
+        counter.increment();
+        assertEq(counter.lastCaller(), address(1337));
+
+        // nested calls are made by the counter
+        counter.incrementTwice();
+        assertEq(counter.lastCaller(), address(counter));
+        cheats.stopPrank();
+
+        counter.increment();
+        assertEq(counter.lastCaller(), address(this));
+    }
+}
@@ -0,0 +1,12 @@
+// SPDX-License-Identifier: Unlicense
+pragma solidity >=0.8.0;
+
+import "ds-test/test.sol";
+
+contract PrecompileCallsTest is DSTest {
+    function testHashAndRecover(bytes32 digest, uint8 v, bytes32 r, bytes32 s) public {
+        bytes32 hash = keccak256(abi.encodePacked(digest));
+        ecrecover(hash, v, r, s);
+        ecrecover(digest, v, r, s);
+    }
+}
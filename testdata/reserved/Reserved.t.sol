@@ -0,0 +1,33 @@
+// SPDX-License-Identifier: Unlicense
+pragma solidity >=0.8.0;
+
+import "ds-test/test.sol";
+import "../cheats/Cheats.sol";
+
+contract ReservedAddressesTest is DSTest {
+    Cheats constant cheats = Cheats(HEVM_ADDRESS);
+
+    function testFailPrankFromPrecompile() public {
+        cheats.prank(address(1));
+    }
+
+    function testFailPrankFromCheatcodeAddress() public {
+        cheats.startPrank(HEVM_ADDRESS);
+    }
+
+    function testFailPrankFromConsoleAddress() public {
+        cheats.prank(0x000000000000000000636F6e736F6c652e6c6f67);
+    }
+
+    function testFailEtchPrecompile() public {
+        cheats.etch(address(9), hex"1010");
+    }
+
+    function testFailEtchCheatcodeAddress() public {
+        cheats.etch(HEVM_ADDRESS, hex"1010");
+    }
+
+    function testPrankFromOrdinaryAddress() public {
+        cheats.prank(address(10));
+    }
+}
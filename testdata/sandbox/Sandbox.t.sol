@@ -0,0 +1,82 @@
+// SPDX-License-Identifier: Unlicense
+pragma solidity >=0.8.0;
+
+import "ds-test/test.sol";
+import "../cheats/Cheats.sol";
+
+// The test runner sandboxes this directory
+contract SandboxTest is DSTest {
+    Cheats constant cheats = Cheats(HEVM_ADDRESS);
+
+    function assertDenied(bytes memory reason, string memory group) internal {
+        assertEq(
+            keccak256(reason),
+            keccak256(
+                abi.encode(
+                    string(
+                        abi.encodePacked(
+                            "The `", group, "` cheatcodes are not allowed in this test, see `sandbox` in the config"
+                        )
+                    )
+                )
+            )
+        );
+    }
+
+    function testFfiIsDenied() public {
+        string[] memory inputs = new string[](2);
+        inputs[0] = "echo";
+        inputs[1] = "0x01";
+
+        try cheats.ffi(inputs) returns (bytes memory) {
+            fail();
+        } catch (bytes memory reason) {
+            assertDenied(reason, "ffi");
+        }
+    }
+
+    function testFsIsDenied() public {
+        try cheats.readFile("fixtures/File/read.txt") returns (string memory) {
+            fail();
+        } catch (bytes memory reason) {
+            assertDenied(reason, "fs");
+        }
+    }
+
+    function testGetCodeIsDenied() public {
+        try cheats.getCode("fixtures/GetCode/WorkingContract.json") returns (bytes memory) {
+            fail();
+        } catch (bytes memory reason) {
+            assertDenied(reason, "fs");
+        }
+    }
+
+    function testLoadAllocsIsDenied() public {
+        try cheats.loadAllocs("fixtures/LoadAllocs/allocs.json") {
+            fail();
+        } catch (bytes memory reason) {
+            assertDenied(reason, "fs");
+        }
+    }
+
+    function testTempDirIsDenied() public {
+        try cheats.tempDir() returns (string memory) {
+            fail();
+        } catch (bytes memory reason) {
+            assertDenied(reason, "fs");
+        }
+    }
+
+    function testEnvIsDenied() public {
+        try cheats.envOr("_FOUNDRY_TEST_ENV_UNSET", uint256(1)) returns (uint256) {
+            fail();
+        } catch (bytes memory reason) {
+            assertDenied(reason, "env");
+        }
+    }
+
+    function testOtherCheatcodesAreAllowed() public {
+        cheats.warp(10);
+        assertEq(block.timestamp, 10);
+    }
+}
@@ -0,0 +1,37 @@
+// SPDX-License-Identifier: Unlicense
+pragma solidity >=0.8.0;
+
+import "ds-test/test.sol";
+
+contract TableTest is DSTest {
+    uint256[] amounts = [1, 5, 555];
+    address[] owners = [address(0x1), address(0x2), address(0x3)];
+
+    function fixtureAmount() public view returns (uint256[] memory) {
+        return amounts;
+    }
+
+    function fixtureOwner() public view returns (address[] memory) {
+        return owners;
+    }
+
+    function fixtureMismatched() public pure returns (uint256[] memory) {
+        return new uint256[](1);
+    }
+
+    function testSingleFixture(uint256 amount) public {
+        assertTrue(amount == 1 || amount == 5 || amount == 555);
+    }
+
+    function testMultipleFixtures(uint256 amount, address owner) public {
+        if (amount == 1) assertEq(owner, address(0x1));
+        if (amount == 5) assertEq(owner, address(0x2));
+        if (amount == 555) assertEq(owner, address(0x3));
+    }
+
+    function testFailFixture(uint256 amount) public {
+        assertEq(amount, 0);
+    }
+
+    function testMismatchedFixtures(uint256 amount, uint256 mismatched) public {}
+}
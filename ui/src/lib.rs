@@ -6,10 +6,13 @@ use crossterm::{
     execute,
     terminal::{disable_raw_mode, enable_raw_mode, EnterAlternateScreen, LeaveAlternateScreen},
 };
-use ethers::{solc::artifacts::ContractBytecodeSome, types::Address};
+use ethers::{
+    solc::artifacts::ContractBytecodeSome,
+    types::{Address, U256},
+};
 use eyre::Result;
 use forge::{
-    debug::{DebugStep, Instruction},
+    debug::{storage_at, DebugStep, Instruction},
     CallKind,
 };
 use std::{
@@ -52,6 +55,8 @@ pub struct Tui {
     terminal: Terminal<CrosstermBackend<io::Stdout>>,
     /// Buffer for keys prior to execution, i.e. '10' + 'k' => move up 10 operations
     key_buffer: String,
+    /// the call the debugger starts in
+    current_call: usize,
     /// current step in the debug steps
     current_step: usize,
     identified_contracts: HashMap<Address, String>,
@@ -60,10 +65,11 @@ pub struct Tui {
 }
 
 impl Tui {
-    /// Create a tui
+    /// Create a tui that starts at the given step of the given call in the debug arena
     #[allow(unused_must_use)]
     pub fn new(
         debug_arena: Vec<(Address, Vec<DebugStep>, CallKind)>,
+        current_call: usize,
         current_step: usize,
         identified_contracts: HashMap<Address, String>,
         known_contracts: HashMap<String, ContractBytecodeSome>,
@@ -79,6 +85,7 @@ impl Tui {
             debug_arena,
             terminal,
             key_buffer: String::new(),
+            current_call,
             current_step,
             identified_contracts,
             known_contracts,
@@ -114,6 +121,7 @@ impl Tui {
         draw_memory: &mut DrawMemory,
         stack_labels: bool,
         mem_utf: bool,
+        storage: Option<&BTreeMap<U256, U256>>,
     ) {
         let total_size = f.size();
         if total_size.width < 225 {
@@ -130,6 +138,7 @@ impl Tui {
                 draw_memory,
                 stack_labels,
                 mem_utf,
+                storage,
             );
         } else {
             Tui::square_layout(
@@ -145,6 +154,7 @@ impl Tui {
                 draw_memory,
                 stack_labels,
                 mem_utf,
+                storage,
             );
         }
     }
@@ -163,6 +173,7 @@ impl Tui {
         draw_memory: &mut DrawMemory,
         stack_labels: bool,
         mem_utf: bool,
+        storage: Option<&BTreeMap<U256, U256>>,
     ) {
         let total_size = f.size();
         if let [app, footer] = Layout::default()
@@ -211,7 +222,18 @@ impl Tui {
                     stack_labels,
                     draw_memory,
                 );
-                Tui::draw_memory(f, debug_steps, current_step, memory_pane, mem_utf, draw_memory);
+                if let Some(storage) = storage {
+                    Tui::draw_storage(f, storage, debug_steps, current_step, memory_pane);
+                } else {
+                    Tui::draw_memory(
+                        f,
+                        debug_steps,
+                        current_step,
+                        memory_pane,
+                        mem_utf,
+                        draw_memory,
+                    );
+                }
             } else {
                 panic!("unable to create vertical panes")
             }
@@ -234,6 +256,7 @@ impl Tui {
         draw_memory: &mut DrawMemory,
         stack_labels: bool,
         mem_utf: bool,
+        storage: Option<&BTreeMap<U256, U256>>,
     ) {
         let total_size = f.size();
 
@@ -288,14 +311,18 @@ impl Tui {
                             stack_labels,
                             draw_memory,
                         );
-                        Tui::draw_memory(
-                            f,
-                            debug_steps,
-                            current_step,
-                            memory_pane,
-                            mem_utf,
-                            draw_memory,
-                        );
+                        if let Some(storage) = storage {
+                            Tui::draw_storage(f, storage, debug_steps, current_step, memory_pane);
+                        } else {
+                            Tui::draw_memory(
+                                f,
+                                debug_steps,
+                                current_step,
+                                memory_pane,
+                                mem_utf,
+                                draw_memory,
+                            );
+                        }
                     }
                 } else {
                     panic!("Couldn't generate horizontal split layout 1:2.");
@@ -312,7 +339,7 @@ impl Tui {
         let block_controls = Block::default();
 
         let text_output = Text::from(Span::styled(
-            "[q]: quit | [k/j]: prev/next op | [a/s]: prev/next jump | [c/C]: prev/next call | [g/G]: start/end | [t]: toggle stack labels | [m]: toggle memory decoding | [shift + j/k]: scroll stack | [ctrl + j/k]: scroll memory",
+            "[q]: quit | [k/j]: prev/next op | [a/s]: prev/next jump | [c/C]: prev/next call | [g/G]: start/end | [<n>g]: go to step n | [t]: toggle stack labels | [m]: toggle memory decoding | [S]: toggle storage | [shift + j/k]: scroll stack | [ctrl + j/k]: scroll memory",
             Style::default().add_modifier(Modifier::DIM)
         ));
         let paragraph = Paragraph::new(text_output)
@@ -641,7 +668,7 @@ impl Tui {
     ) {
         let block_source_code = Block::default()
             .title(format!(
-                "Address: {} | PC: {} | Gas used in call: {}",
+                "Address: {} | PC: {} | Gas used in call: {} | Step: {}/{}",
                 address,
                 if let Some(step) = debug_steps.get(current_step) {
                     step.pc.to_string()
@@ -649,6 +676,8 @@ impl Tui {
                     "END".to_string()
                 },
                 debug_steps[current_step].total_gas_used,
+                draw_memory.current_global_step,
+                draw_memory.total_steps.saturating_sub(1),
             ))
             .borders(Borders::ALL);
         let mut text_output: Vec<Spans> = Vec::new();
@@ -907,6 +936,63 @@ impl Tui {
         let paragraph = Paragraph::new(text).block(stack_space).wrap(Wrap { trim: true });
         f.render_widget(paragraph, area);
     }
+
+    /// Draw the storage of the current contract in the memory pane
+    fn draw_storage<B: Backend>(
+        f: &mut Frame<B>,
+        storage: &BTreeMap<U256, U256>,
+        debug_steps: &[DebugStep],
+        current_step: usize,
+        area: Rect,
+    ) {
+        let step = &debug_steps[current_step];
+        // the slot the current op reads or writes
+        let accessed = match step.instruction {
+            Instruction::OpCode(opcode::SLOAD | opcode::SSTORE) => step.stack.last(),
+            _ => None,
+        };
+        let block = Block::default()
+            .title(format!("Storage: {} accessed slots", storage.len()))
+            .borders(Borders::ALL);
+        let text: Vec<Spans> = storage
+            .iter()
+            .map(|(slot, value)| {
+                let style = if Some(slot) == accessed {
+                    Style::default().fg(Color::Cyan)
+                } else {
+                    Style::default().fg(Color::White)
+                };
+                Spans::from(Span::styled(format!("{:#066x}: {:#066x}", slot, value), style))
+            })
+            .collect();
+        let paragraph = Paragraph::new(text).block(block).wrap(Wrap { trim: true });
+        f.render_widget(paragraph, area);
+    }
+
+    /// Returns the index of the step in the whole execution
+    fn global_step(
+        debug_call: &[(Address, Vec<DebugStep>, CallKind)],
+        call: usize,
+        step: usize,
+    ) -> usize {
+        debug_call[..call].iter().map(|(_, steps, _)| steps.len()).sum::<usize>() + step
+    }
+
+    /// Returns the call and the step in that call of the given step of the whole execution,
+    /// clamped to the last step
+    fn locate_step(
+        debug_call: &[(Address, Vec<DebugStep>, CallKind)],
+        mut step: usize,
+    ) -> (usize, usize) {
+        for (call, (_, steps, _)) in debug_call.iter().enumerate() {
+            if step < steps.len() {
+                return (call, step)
+            }
+            step -= steps.len();
+        }
+        let last = debug_call.len() - 1;
+        (last, debug_call[last].1.len() - 1)
+    }
 }
 
 impl Ui for Tui {
@@ -952,14 +1038,20 @@ impl Ui for Tui {
 
         self.terminal.clear()?;
         let mut draw_memory: DrawMemory = DrawMemory::default();
+        draw_memory.inner_call_index = self.current_call;
 
         let debug_call: Vec<(Address, Vec<DebugStep>, CallKind)> = self.debug_arena.clone();
-        let mut opcode_list: Vec<String> =
-            debug_call[0].1.iter().map(|step| step.pretty_opcode()).collect();
-        let mut last_index = 0;
+        let mut opcode_list: Vec<String> = debug_call[draw_memory.inner_call_index]
+            .1
+            .iter()
+            .map(|step| step.pretty_opcode())
+            .collect();
+        let mut last_index = draw_memory.inner_call_index;
 
         let mut stack_labels = false;
         let mut mem_utf = false;
+        let mut show_storage = false;
+        draw_memory.total_steps = debug_call.iter().map(|(_, steps, _)| steps.len()).sum();
         // UI thread that manages drawing
         loop {
             if last_index != draw_memory.inner_call_index {
@@ -1043,10 +1135,14 @@ impl Ui for Tui {
                         }
                         self.key_buffer.clear();
                     }
-                    // Go to top of file
+                    // Go to top of file, or to the n-th step of the execution
                     KeyCode::Char('g') => {
-                        draw_memory.inner_call_index = 0;
-                        self.current_step = 0;
+                        let (call, step) = Tui::locate_step(
+                            &debug_call,
+                            Tui::buffer_as_number(&self.key_buffer, 0),
+                        );
+                        draw_memory.inner_call_index = call;
+                        self.current_step = step;
                         self.key_buffer.clear();
                     }
                     // Go to bottom of file
@@ -1131,6 +1227,10 @@ impl Ui for Tui {
                     KeyCode::Char('m') => {
                         mem_utf = !mem_utf;
                     }
+                    // toggle storage view
+                    KeyCode::Char('S') => {
+                        show_storage = !show_storage;
+                    }
                     KeyCode::Char(other) => match other {
                         '0' | '1' | '2' | '3' | '4' | '5' | '6' | '7' | '8' | '9' => {
                             self.key_buffer.push(other);
@@ -1172,6 +1272,10 @@ impl Ui for Tui {
             }
             // Draw
             let current_step = self.current_step;
+            draw_memory.current_global_step =
+                Tui::global_step(&debug_call, draw_memory.inner_call_index, current_step);
+            let storage = show_storage
+                .then(|| storage_at(&debug_call, draw_memory.inner_call_index, current_step));
             self.terminal.draw(|f| {
                 Tui::draw_layout(
                     f,
@@ -1186,6 +1290,7 @@ impl Ui for Tui {
                     &mut draw_memory,
                     stack_labels,
                     mem_utf,
+                    storage.as_ref(),
                 )
             })?;
         }
@@ -1206,6 +1311,9 @@ struct DrawMemory {
     pub inner_call_index: usize,
     pub current_mem_startline: usize,
     pub current_stack_startline: usize,
+    /// The index of the current step in the whole execution
+    pub current_global_step: usize,
+    pub total_steps: usize,
 }
 
 impl DrawMemory {
@@ -1215,6 +1323,8 @@ impl DrawMemory {
             inner_call_index: 0,
             current_mem_startline: 0,
             current_stack_startline: 0,
+            current_global_step: 0,
+            total_steps: 0,
         }
     }
 }
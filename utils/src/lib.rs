@@ -24,6 +24,12 @@ use std::{
 
 use tokio::runtime::{Handle, Runtime};
 
+mod selectors;
+pub use selectors::{
+    error_signature, event_signature, function_signature, import_selectors, SelectorImportResponse,
+    SelectorImportResult, SignaturesCache,
+};
+
 #[allow(clippy::large_enum_variant)]
 #[derive(Debug)]
 pub enum RuntimeOrHandle {
@@ -45,6 +51,14 @@ impl RuntimeOrHandle {
         }
     }
 
+    /// Returns a handle to the runtime
+    pub fn handle(&self) -> Handle {
+        match &self {
+            RuntimeOrHandle::Runtime(runtime) => runtime.handle().clone(),
+            RuntimeOrHandle::Handle(handle) => handle.clone(),
+        }
+    }
+
     pub fn block_on<F: std::future::Future>(&self, f: F) -> F::Output {
         match &self {
             RuntimeOrHandle::Runtime(runtime) => runtime.block_on(f),
@@ -500,6 +514,9 @@ pub async fn get_func_etherscan(
 }
 
 /// Parses string input as Token against the expected ParamType
+///
+/// Tuple (struct) and array parameters can also be given as JSON, e.g. `[1, ["0x..", true]]`
+/// for a `(uint256,(address,bool))` parameter, see [`parse_json_token`]
 pub fn parse_tokens<'a, I: IntoIterator<Item = (&'a ParamType, &'a str)>>(
     params: I,
     lenient: bool,
@@ -507,6 +524,10 @@ pub fn parse_tokens<'a, I: IntoIterator<Item = (&'a ParamType, &'a str)>>(
     params
         .into_iter()
         .map(|(param, value)| {
+            if let Some(json) = as_json_composite(param, value) {
+                return parse_json_token(param, &json, lenient)
+            }
+
             let mut token = if lenient {
                 LenientTokenizer::tokenize(param, value)
             } else {
@@ -525,12 +546,125 @@ pub fn parse_tokens<'a, I: IntoIterator<Item = (&'a ParamType, &'a str)>>(
                     }
                 }
             }
-            token
+            Ok(token?)
         })
-        .collect::<Result<_, _>>()
+        .collect::<Result<_>>()
         .wrap_err("Failed to parse tokens")
 }
 
+/// Returns the parsed JSON value if the param is a tuple or an array and the value is valid JSON.
+///
+/// The numbers of the value are parsed as strings, so that integers that don't fit into `u64` are
+/// tokenized from their exact text instead of being rounded to `f64`.
+fn as_json_composite(param: &ParamType, value: &str) -> Option<serde_json::Value> {
+    match param {
+        ParamType::Tuple(_) | ParamType::Array(_) | ParamType::FixedArray(..) => {
+            serde_json::from_str::<serde_json::Value>(&quote_json_numbers(value.trim()))
+                .ok()
+                .filter(|json| json.is_array() || json.is_object())
+        }
+        _ => None,
+    }
+}
+
+/// Wraps the number literals of a JSON document in quotes
+fn quote_json_numbers(json: &str) -> String {
+    let mut quoted = String::with_capacity(json.len());
+    let mut chars = json.chars().peekable();
+    let mut in_string = false;
+    while let Some(c) = chars.next() {
+        quoted.push(c);
+        if in_string {
+            match c {
+                '\\' => quoted.extend(chars.next()),
+                '"' => in_string = false,
+                _ => {}
+            }
+        } else if c == '"' {
+            in_string = true;
+        } else if c == '-' || c.is_ascii_digit() {
+            quoted.insert(quoted.len() - 1, '"');
+            while let Some(c) =
+                chars.next_if(|c| c.is_ascii_alphanumeric() || matches!(*c, '.' | '+' | '-'))
+            {
+                quoted.push(c);
+            }
+            quoted.push('"');
+        }
+    }
+    quoted
+}
+
+/// Parses a JSON value as Token against the expected ParamType
+///
+/// Tuples are encoded from a JSON array of their components in declaration order and arrays from
+/// a JSON array of their elements, both can be nested arbitrarily. Strings, numbers and booleans
+/// are tokenized like regular string arguments, large integers should be passed as strings.
+///
+/// JSON objects are not supported for tuples: the param types don't know the names of the
+/// components, so the fields could not be matched to them.
+pub fn parse_json_token(
+    param: &ParamType,
+    value: &serde_json::Value,
+    lenient: bool,
+) -> Result<Token> {
+    use serde_json::Value;
+
+    match (param, value) {
+        (ParamType::Tuple(components), Value::Array(values)) => {
+            eyre::ensure!(
+                components.len() == values.len(),
+                "Expected {} components for tuple `{}`, got {}",
+                components.len(),
+                param,
+                values.len()
+            );
+            let tokens = components
+                .iter()
+                .zip(values)
+                .map(|(param, value)| parse_json_token(param, value, lenient))
+                .collect::<Result<_>>()?;
+            Ok(Token::Tuple(tokens))
+        }
+        (ParamType::Array(inner), Value::Array(values)) => {
+            let tokens = values
+                .iter()
+                .map(|value| parse_json_token(inner, value, lenient))
+                .collect::<Result<_>>()?;
+            Ok(Token::Array(tokens))
+        }
+        (ParamType::FixedArray(inner, len), Value::Array(values)) => {
+            eyre::ensure!(
+                *len == values.len(),
+                "Expected {} elements for `{}`, got {}",
+                len,
+                param,
+                values.len()
+            );
+            let tokens = values
+                .iter()
+                .map(|value| parse_json_token(inner, value, lenient))
+                .collect::<Result<_>>()?;
+            Ok(Token::FixedArray(tokens))
+        }
+        (ParamType::Tuple(_), Value::Object(_)) => eyre::bail!(
+            "Expected a JSON array of the components of `{}` in declaration order, objects are not supported",
+            param
+        ),
+        (ParamType::Tuple(_) | ParamType::Array(_) | ParamType::FixedArray(..), _) => {
+            eyre::bail!("Expected a JSON array for `{}`, got `{}`", param, value)
+        }
+        (_, Value::String(s)) => {
+            parse_tokens(std::iter::once((param, s.as_str())), lenient).map(|mut t| t.remove(0))
+        }
+        (_, Value::Number(_) | Value::Bool(_)) => {
+            let s = value.to_string();
+            parse_tokens(std::iter::once((param, s.as_str())), lenient).map(|mut t| t.remove(0))
+        }
+        _ => eyre::bail!("Can not parse `{}` as `{}`", value, param),
+    }
+}
+
 /// Given a function and a vector of string arguments, it proceeds to convert the args to ethabi
 /// Tokens and then ABI encode them.
 pub fn encode_args(func: &Function, args: &[impl AsRef<str>]) -> Result<Vec<u8>> {
@@ -707,7 +841,9 @@ pub fn abi_decode(sig: &str, calldata: &str, input: bool) -> Result<Vec<Token>>
     let calldata = hex::decode(calldata)?;
     let res = if input {
         // need to strip the function selector
-        func.decode_input(&calldata[4..])?
+        let calldata =
+            calldata.get(4..).ok_or_else(|| eyre::eyre!("calldata cannot be less than 4 bytes"))?;
+        func.decode_input(calldata)?
     } else {
         func.decode_output(&calldata)?
     };
@@ -720,6 +856,63 @@ pub fn abi_decode(sig: &str, calldata: &str, input: bool) -> Result<Vec<Token>>
     Ok(res)
 }
 
+/// Formats ABI-encoded data for humans: the function, every parameter with its type and decoded
+/// value, and the 32-byte words of the encoding with their offsets.
+///
+/// If `input` is true, `data` is calldata starting with the selector of `sig`, otherwise it is the
+/// ABI-encoded output of the function.
+pub fn pretty_abi_decode(sig: &str, data: &str, input: bool) -> Result<String> {
+    let func = IntoFunction::into(sig);
+    let tokens = abi_decode(sig, data, input)?;
+    let data = hex::decode(data.strip_prefix("0x").unwrap_or(data))?;
+
+    Ok(if input {
+        format_abi_data(&func, Some(&data[..4]), true, &tokens, &data[4..])
+    } else {
+        format_abi_data(&func, None, false, &tokens, &data)
+    })
+}
+
+/// Formats the ABI-encoded arguments of `sig`, which are not prefixed with a selector, like
+/// [`pretty_abi_decode`] formats calldata.
+pub fn pretty_abi_encoded_args(sig: &str, data: &str) -> Result<String> {
+    let func = IntoFunction::into(sig);
+    let data = hex::decode(data.strip_prefix("0x").unwrap_or(data))?;
+    let tokens = func.decode_input(&data)?;
+    Ok(format_abi_data(&func, None, true, &tokens, &data))
+}
+
+/// Formats the decoded inputs or outputs of `func` and the words of their encoding
+fn format_abi_data(
+    func: &Function,
+    selector: Option<&[u8]>,
+    input: bool,
+    tokens: &[Token],
+    words: &[u8],
+) -> String {
+    let types = func.inputs.iter().map(|param| param.kind.to_string()).collect::<Vec<_>>();
+    let mut out = format!("Method: {}({})\n", func.name, types.join(","));
+    if let Some(selector) = selector {
+        out.push_str(&format!("Selector: 0x{}\n", hex::encode(selector)));
+    }
+    let params = if input {
+        out.push_str("Inputs:\n");
+        &func.inputs
+    } else {
+        out.push_str("Outputs:\n");
+        &func.outputs
+    };
+    for (i, (param, token)) in params.iter().zip(tokens).enumerate() {
+        let name = if param.name.is_empty() { String::new() } else { format!(" {}", param.name) };
+        out.push_str(&format!("  [{i}]{name} ({}): {}\n", param.kind, format_token(token)));
+    }
+    out.push_str("Words:\n");
+    for (i, word) in words.chunks(32).enumerate() {
+        out.push_str(&format!("  [{:#04x}]: {}\n", i * 32, hex::encode(word)));
+    }
+    out.trim_end().to_string()
+}
+
 /// Resolves an input to [`NameOrAddress`]. The input could also be a contract/token name supported
 /// by
 /// [`ethers-addressbook`](https://github.com/gakonst/ethers-rs/tree/master/ethers-addressbook).
@@ -1033,6 +1226,100 @@ mod tests {
         assert_eq!(tokens, vec![Token::Uint(100u64.into())]);
     }
 
+    #[test]
+    fn parse_json_tuple_tokens() {
+        let param = ParamType::Tuple(vec![
+            ParamType::Uint(256),
+            ParamType::Tuple(vec![ParamType::Address, ParamType::Bool]),
+            ParamType::Array(Box::new(ParamType::String)),
+        ]);
+        let value = r#"[1, ["0x00000000000000000000000000000000000000aa", true], ["a", "b"]]"#;
+
+        let tokens = parse_tokens(std::iter::once((&param, value)), true).unwrap();
+        assert_eq!(
+            tokens,
+            vec![Token::Tuple(vec![
+                Token::Uint(1u64.into()),
+                Token::Tuple(vec![
+                    Token::Address(Address::from_low_u64_be(0xaa)),
+                    Token::Bool(true)
+                ]),
+                Token::Array(vec![Token::String("a".to_string()), Token::String("b".to_string())]),
+            ])]
+        );
+
+        let err = parse_tokens(std::iter::once((&param, "[1, 2]")), true).unwrap_err();
+        assert!(err.root_cause().to_string().contains("Expected 3 components"));
+
+        let err = parse_tokens(std::iter::once((&param, r#"{"a": 1}"#)), true).unwrap_err();
+        assert!(err.root_cause().to_string().contains("objects are not supported"));
+    }
+
+    #[test]
+    fn parse_json_large_integers_exactly() {
+        let param = ParamType::Array(Box::new(ParamType::Uint(256)));
+        let max = U256::MAX.to_string();
+        let value = format!("[123456789012345678901, {}, \"{}\"]", max, max);
+
+        let tokens = parse_tokens(std::iter::once((&param, value.as_str())), true).unwrap();
+        assert_eq!(
+            tokens,
+            vec![Token::Array(vec![
+                Token::Uint(U256::from_dec_str("123456789012345678901").unwrap()),
+                Token::Uint(U256::MAX),
+                Token::Uint(U256::MAX),
+            ])]
+        );
+
+        // numbers in strings are left as they are
+        assert_eq!(quote_json_numbers(r#"[-1, "a\"1", 2e3]"#), r#"["-1", "a\"1", "2e3"]"#);
+    }
+
+    #[test]
+    fn can_pretty_print_abi_encoded_data() {
+        let calldata =
+            format!("0xa9059cbb{:0>64}{:0>64}", "00000000000000000000000000000000000000aa", "2a");
+        let pretty = pretty_abi_decode("transfer(address to, uint256)", &calldata, true).unwrap();
+        assert_eq!(
+            pretty,
+            format!(
+                "Method: transfer(address,uint256)
+Selector: 0xa9059cbb
+Inputs:
+  [0] to (address): 0x00000000000000000000000000000000000000aa
+  [1] (uint256): 42
+Words:
+  [0x00]: {:0>64}
+  [0x20]: {:0>64}",
+                "00000000000000000000000000000000000000aa", "2a"
+            )
+        );
+
+        let output =
+            pretty_abi_decode("balanceOf(address)(uint256)", &calldata[10..74], false).unwrap();
+        assert!(output.contains("Outputs:\n  [0] (uint256): 170"));
+        assert!(abi_decode("transfer(address,uint256)", "0xa905", true).is_err());
+    }
+
+    #[test]
+    fn can_pretty_print_abi_encoded_args() {
+        let args = format!("0x{:0>64}{:0>64}", "00000000000000000000000000000000000000aa", "2a");
+        let pretty = pretty_abi_encoded_args("transfer(address to, uint256)", &args).unwrap();
+        assert_eq!(
+            pretty,
+            format!(
+                "Method: transfer(address,uint256)
+Inputs:
+  [0] to (address): 0x00000000000000000000000000000000000000aa
+  [1] (uint256): 42
+Words:
+  [0x00]: {:0>64}
+  [0x20]: {:0>64}",
+                "00000000000000000000000000000000000000aa", "2a"
+            )
+        );
+    }
+
     #[test]
     fn test_linking() {
         let mut contract_names = [
@@ -0,0 +1,204 @@
+//! Local cache of function and event signatures and support for sharing them upstream
+
+use ethers_core::{
+    abi::{Abi, AbiError, Event, Function, Param},
+    utils::{id, keccak256},
+};
+use eyre::{Result, WrapErr};
+use serde::{Deserialize, Serialize};
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    path::Path,
+};
+
+/// The endpoint signatures are imported to, see <https://sig.eth.samczsun.com>
+const SELECTOR_IMPORT_URL: &str = "https://sig.eth.samczsun.com/api/v1/import";
+
+/// A local cache of function and event signatures keyed by their `0x` prefixed selector or topic.
+///
+/// The cache is populated from project builds and remote lookups, and is shared by `cast`'s
+/// selector lookups and `forge`'s trace decoder, see `Config::foundry_signatures_cache_file`.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SignaturesCache {
+    #[serde(default)]
+    functions: BTreeMap<String, BTreeSet<String>>,
+    #[serde(default)]
+    events: BTreeMap<String, BTreeSet<String>>,
+}
+
+impl SignaturesCache {
+    /// Loads the cache from the given file, an unreadable or missing file yields an empty cache
+    pub fn load(path: impl AsRef<Path>) -> Self {
+        std::fs::read_to_string(path.as_ref())
+            .ok()
+            .and_then(|content| serde_json::from_str(&content).ok())
+            .unwrap_or_default()
+    }
+
+    /// Writes the cache to the given file
+    pub fn save(&self, path: impl AsRef<Path>) -> Result<()> {
+        let path = path.as_ref();
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        std::fs::write(path, serde_json::to_string(self)?)
+            .wrap_err_with(|| format!("Failed to write signatures cache {:?}", path))
+    }
+
+    /// Returns `true` if the cache contains no signatures
+    pub fn is_empty(&self) -> bool {
+        self.functions.is_empty() && self.events.is_empty()
+    }
+
+    /// Adds all function, error and event signatures of the ABI
+    pub fn extend_from_abi(&mut self, abi: &Abi) {
+        for func in abi.functions() {
+            self.insert_function(function_signature(func));
+        }
+        for error in abi.errors() {
+            self.insert_function(error_signature(error));
+        }
+        for event in abi.events() {
+            self.insert_event(event_signature(event));
+        }
+    }
+
+    /// Adds a function signature, like `transfer(address,uint256)`
+    pub fn insert_function(&mut self, signature: impl Into<String>) {
+        let signature = signature.into();
+        let selector = format!("0x{}", hex::encode(id(&signature)));
+        self.functions.entry(selector).or_default().insert(signature);
+    }
+
+    /// Adds an event signature, like `Transfer(address,address,uint256)`
+    pub fn insert_event(&mut self, signature: impl Into<String>) {
+        let signature = signature.into();
+        let topic = format!("0x{}", hex::encode(keccak256(&signature)));
+        self.events.entry(topic).or_default().insert(signature);
+    }
+
+    /// Returns all known function signatures for the selector
+    pub fn functions(&self, selector: &str) -> Vec<String> {
+        lookup(&self.functions, selector, 8)
+    }
+
+    /// Returns all known event signatures for the topic
+    pub fn events(&self, topic: &str) -> Vec<String> {
+        lookup(&self.events, topic, 64)
+    }
+
+    /// Returns all function signatures in the cache
+    pub fn all_functions(&self) -> impl Iterator<Item = &str> {
+        self.functions.values().flatten().map(String::as_str)
+    }
+}
+
+fn lookup(map: &BTreeMap<String, BTreeSet<String>>, key: &str, len: usize) -> Vec<String> {
+    let key = key.strip_prefix("0x").unwrap_or(key).to_lowercase();
+    key.get(..len)
+        .and_then(|key| map.get(&format!("0x{}", key)))
+        .map(|sigs| sigs.iter().cloned().collect())
+        .unwrap_or_default()
+}
+
+/// Returns the signature of the function, like `transfer(address,uint256)`
+pub fn function_signature(func: &Function) -> String {
+    signature(&func.name, &func.inputs)
+}
+
+/// Returns the signature of the custom error, like `InsufficientBalance(uint256,uint256)`
+pub fn error_signature(error: &AbiError) -> String {
+    signature(&error.name, &error.inputs)
+}
+
+/// Returns the signature of the event, like `Transfer(address,address,uint256)`
+pub fn event_signature(event: &Event) -> String {
+    let inputs = event.inputs.iter().map(|param| param.kind.to_string()).collect::<Vec<_>>();
+    format!("{}({})", event.name, inputs.join(","))
+}
+
+fn signature(name: &str, inputs: &[Param]) -> String {
+    let inputs = inputs.iter().map(|param| param.kind.to_string()).collect::<Vec<_>>();
+    format!("{}({})", name, inputs.join(","))
+}
+
+/// The result of importing signatures upstream
+#[derive(Debug, Default, Deserialize)]
+pub struct SelectorImportResult {
+    /// The signatures that were newly imported, `signature -> selector`
+    #[serde(default)]
+    pub imported: BTreeMap<String, String>,
+    /// The signatures that were already known, `signature -> selector`
+    #[serde(default)]
+    pub duplicated: BTreeMap<String, String>,
+}
+
+/// The response of an upstream signature import
+#[derive(Debug, Default, Deserialize)]
+pub struct SelectorImportResponse {
+    #[serde(default)]
+    pub function: SelectorImportResult,
+    #[serde(default)]
+    pub event: SelectorImportResult,
+}
+
+/// Uploads the function and event signatures to the public signature database, so that other
+/// users can decode them too
+pub async fn import_selectors(
+    functions: Vec<String>,
+    events: Vec<String>,
+) -> Result<SelectorImportResponse> {
+    #[derive(Deserialize)]
+    struct ApiResponse {
+        ok: bool,
+        result: Option<SelectorImportResponse>,
+        error: Option<String>,
+    }
+
+    let body = serde_json::json!({ "function": functions, "event": events });
+    let res = reqwest::Client::new().post(SELECTOR_IMPORT_URL).json(&body).send().await?;
+    let res = res.text().await?;
+    let api_response = match serde_json::from_str::<ApiResponse>(&res) {
+        Ok(inner) => inner,
+        Err(err) => {
+            eyre::bail!("Could not decode response:\n {res}.\nError: {err}")
+        }
+    };
+
+    if !api_response.ok {
+        eyre::bail!(
+            "Failed to import selectors: {}",
+            api_response.error.unwrap_or_else(|| "unknown error".to_string())
+        )
+    }
+    Ok(api_response.result.unwrap_or_default())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ethers_core::abi::parse_abi;
+
+    #[test]
+    fn can_lookup_abi_signatures() {
+        let abi = parse_abi(&[
+            "function transfer(address to, uint256 amount) external returns (bool)",
+            "event Transfer(address indexed from, address indexed to, uint256 value)",
+        ])
+        .unwrap();
+
+        let mut cache = SignaturesCache::default();
+        cache.extend_from_abi(&abi);
+
+        assert_eq!(cache.functions("0xa9059cbb"), vec!["transfer(address,uint256)".to_string()]);
+        assert_eq!(
+            cache.functions("a9059cbb000000000000000000000000"),
+            vec!["transfer(address,uint256)".to_string()]
+        );
+        assert_eq!(
+            cache.events("0xddf252ad1be2c89b69c2b068fc378daa952ba7f163c4a11628f55a4df523b3ef"),
+            vec!["Transfer(address,address,uint256)".to_string()]
+        );
+        assert!(cache.functions("0x00000000").is_empty());
+    }
+}